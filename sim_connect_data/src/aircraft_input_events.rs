@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The type of an aircraft input event's value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename = "camelCase")]
+pub enum InputEventType {
+    Double,
+    String,
+}
+
+/// The value of an aircraft input event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename = "camelCase")]
+pub enum InputEventValue {
+    Double(f64),
+    String(String),
+}
+
+impl InputEventValue {
+    pub fn event_type(&self) -> InputEventType {
+        match self {
+            Self::Double(_) => InputEventType::Double,
+            Self::String(_) => InputEventType::String,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Double(value) => Some(*value),
+            Self::String(_) => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Double(_) => None,
+            Self::String(value) => Some(value),
+        }
+    }
+}
+
+impl From<f64> for InputEventValue {
+    fn from(value: f64) -> Self {
+        Self::Double(value)
+    }
+}
+
+impl From<&str> for InputEventValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_owned())
+    }
+}
+
+impl From<String> for InputEventValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+/// An input event of the loaded aircraft, such as a knob or switch in its cockpit. Input
+/// events are read, set and subscribed to by `hash`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename = "camelCase")]
+pub struct InputEventDescriptor {
+    pub name: String,
+    pub hash: u64,
+    pub event_type: InputEventType,
+}
+
+/// Every input event of the loaded aircraft, as enumerated by `SimConnect_EnumerateInputEvents`.
+///
+/// Unlike the events in `sim_input_events`, which are the same for every aircraft, input events
+/// depend on the aircraft, so the catalog should be enumerated again once another is loaded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InputEventCatalog {
+    descriptors: Vec<InputEventDescriptor>,
+    by_name: HashMap<String, usize>,
+}
+
+impl InputEventCatalog {
+    /// Looks up an input event by its exact name, e.g. `"FUEL_PUMP_1"`
+    pub fn get(&self, name: &str) -> Option<&InputEventDescriptor> {
+        self.by_name
+            .get(name)
+            .map(|index| &self.descriptors[*index])
+    }
+
+    /// Looks up the hash of the input event called `name`
+    pub fn hash_of(&self, name: &str) -> Option<u64> {
+        self.get(name).map(|descriptor| descriptor.hash)
+    }
+
+    pub fn by_hash(&self, hash: u64) -> Option<&InputEventDescriptor> {
+        self.descriptors
+            .iter()
+            .find(|descriptor| descriptor.hash == hash)
+    }
+
+    /// Iterates over the input events in the order the simulator enumerated them
+    pub fn iter(&self) -> std::slice::Iter<'_, InputEventDescriptor> {
+        self.descriptors.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.descriptors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.descriptors.is_empty()
+    }
+}
+
+impl FromIterator<InputEventDescriptor> for InputEventCatalog {
+    fn from_iter<I: IntoIterator<Item = InputEventDescriptor>>(iter: I) -> Self {
+        let mut catalog = Self::default();
+        catalog.extend(iter);
+        catalog
+    }
+}
+
+impl Extend<InputEventDescriptor> for InputEventCatalog {
+    fn extend<I: IntoIterator<Item = InputEventDescriptor>>(&mut self, iter: I) {
+        for descriptor in iter {
+            self.by_name
+                .insert(descriptor.name.clone(), self.descriptors.len());
+            self.descriptors.push(descriptor);
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a InputEventCatalog {
+    type Item = &'a InputEventDescriptor;
+    type IntoIter = std::slice::Iter<'a, InputEventDescriptor>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for InputEventCatalog {
+    type Item = InputEventDescriptor;
+    type IntoIter = std::vec::IntoIter<InputEventDescriptor>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.descriptors.into_iter()
+    }
+}
@@ -0,0 +1,184 @@
+use sim_connect_sys::bindings;
+use std::marker::PhantomData;
+
+/// A field of a `ClientData` struct, as added to a client data definition with
+/// `SimConnect_AddToClientDataDefinition`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClientDataField {
+    /// Offset of the field in the struct, and in the client data area
+    pub offset: u32,
+    /// One of the `SIMCONNECT_CLIENTDATATYPE_*` values, or the size of the field in bytes
+    pub size_or_type: u32,
+    /// How much the field has to change by to count as changed for
+    /// `SIMCONNECT_CLIENT_DATA_REQUEST_FLAG_CHANGED`. Only used for typed fields.
+    pub epsilon: f32,
+}
+
+impl ClientDataField {
+    /// Size of the field in bytes
+    pub fn size(&self) -> usize {
+        match self.size_or_type {
+            bindings::SIMCONNECT_CLIENTDATATYPE_INT8 => 1,
+            bindings::SIMCONNECT_CLIENTDATATYPE_INT16 => 2,
+            bindings::SIMCONNECT_CLIENTDATATYPE_INT32
+            | bindings::SIMCONNECT_CLIENTDATATYPE_FLOAT32 => 4,
+            bindings::SIMCONNECT_CLIENTDATATYPE_INT64
+            | bindings::SIMCONNECT_CLIENTDATATYPE_FLOAT64 => 8,
+            size => size as usize,
+        }
+    }
+}
+
+/// A type which can be a field of a `ClientData` struct
+///
+/// # Safety
+///
+/// Every bit pattern, including all zeroes, must be a valid value of the type, and
+/// `SIZE_OR_TYPE` must describe exactly `size_of::<Self>()` bytes
+pub unsafe trait ClientDataValue: Copy {
+    /// One of the `SIMCONNECT_CLIENTDATATYPE_*` values, or the size of the type in bytes
+    const SIZE_OR_TYPE: u32;
+}
+
+macro_rules! client_data_value {
+    ($($ty: ty => $size_or_type: expr),* $(,)?) => {
+        $(
+            unsafe impl ClientDataValue for $ty {
+                const SIZE_OR_TYPE: u32 = $size_or_type;
+            }
+        )*
+    };
+}
+
+client_data_value! {
+    i8 => bindings::SIMCONNECT_CLIENTDATATYPE_INT8,
+    i16 => bindings::SIMCONNECT_CLIENTDATATYPE_INT16,
+    i32 => bindings::SIMCONNECT_CLIENTDATATYPE_INT32,
+    i64 => bindings::SIMCONNECT_CLIENTDATATYPE_INT64,
+    f32 => bindings::SIMCONNECT_CLIENTDATATYPE_FLOAT32,
+    f64 => bindings::SIMCONNECT_CLIENTDATATYPE_FLOAT64,
+    // SimConnect has no unsigned types, so these are sent as raw bytes
+    u8 => 1,
+    u16 => 2,
+    u32 => 4,
+    u64 => 8,
+}
+
+unsafe impl<T: ClientDataValue, const N: usize> ClientDataValue for [T; N] {
+    const SIZE_OR_TYPE: u32 = (N * std::mem::size_of::<T>()) as u32;
+}
+
+/// # Description
+/// A `#[repr(C)]` struct shared through a client data area, such as between a WASM gauge and
+/// an external application. Derive it with `#[derive(ClientData)]` rather than implementing
+/// it by hand.
+///
+/// Each field is added to the definition at its offset in the struct, so both sides agree on
+/// the layout as long as they agree on the struct. The simulator sends and receives the fields
+/// packed one after the other, which `to_bytes` and `from_bytes` convert from and to the
+/// struct.
+///
+/// # Example
+///
+/// This is re-exported from `sim_connect_rs`, which this crate can't depend on, so the example
+/// is not compiled here.
+///
+/// ```ignore
+///     use sim_connect_rs::ClientData;
+///
+///     #[derive(Clone, Copy, ClientData)]
+///     #[repr(C)]
+///     struct GaugeState {
+///         #[client_data(epsilon = 0.01)]
+///         needle: f64,
+///         lights: [u8; 4],
+///         mode: i32,
+///     }
+/// ```
+///
+/// # Safety
+///
+/// The struct must be `#[repr(C)]`, `fields` must describe fields which lie within it and don't
+/// overlap, and every field must be valid for any bit pattern, so that bytes written by another
+/// client are always a valid value
+pub unsafe trait ClientData: Copy + Send + 'static {
+    fn fields() -> Vec<ClientDataField>;
+
+    /// The fields of `self`, packed one after the other in definition order, as sent by
+    /// `SimConnect_SetClientData`
+    fn to_bytes(&self) -> Vec<u8> {
+        let base = self as *const Self as *const u8;
+        let mut bytes = Vec::new();
+        for field in Self::fields() {
+            // Only the fields are read, padding between them may be uninitialized
+            bytes.extend_from_slice(unsafe {
+                std::slice::from_raw_parts(base.add(field.offset as usize), field.size())
+            });
+        }
+        bytes
+    }
+
+    /// Rebuilds the struct from its fields packed one after the other, as received in a
+    /// `SIMCONNECT_RECV_CLIENT_DATA`. Returns `None` if `data` is too short.
+    fn from_bytes(data: &[u8]) -> Option<Self> {
+        // Sound because every field is valid for any bit pattern, and padding has no validity
+        let mut value: Self = unsafe { std::mem::zeroed() };
+        let bytes = unsafe {
+            std::slice::from_raw_parts_mut(
+                &mut value as *mut Self as *mut u8,
+                std::mem::size_of::<Self>(),
+            )
+        };
+
+        let mut read = 0;
+        for field in Self::fields() {
+            let offset = field.offset as usize;
+            let size = field.size();
+            bytes[offset..offset + size].copy_from_slice(data.get(read..read + size)?);
+            read += size;
+        }
+        Some(value)
+    }
+}
+
+/// A client data area holding a `T`, as created or mapped by `SimConnect::create_client_data`
+/// and `SimConnect::map_client_data`
+#[derive(Debug)]
+pub struct ClientDataArea<T: ClientData> {
+    name: String,
+    client_data_id: u32,
+    define_id: u32,
+    _data: PhantomData<fn() -> T>,
+}
+
+impl<T: ClientData> ClientDataArea<T> {
+    pub fn new(name: String, client_data_id: u32, define_id: u32) -> Self {
+        Self {
+            name,
+            client_data_id,
+            define_id,
+            _data: PhantomData,
+        }
+    }
+
+    /// The name the area is mapped by, which other clients map to share it
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The id the area's name is mapped to
+    pub fn client_data_id(&self) -> u32 {
+        self.client_data_id
+    }
+
+    /// The id of the client data definition describing `T`
+    pub fn define_id(&self) -> u32 {
+        self.define_id
+    }
+}
+
+impl<T: ClientData> Clone for ClientDataArea<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.name.clone(), self.client_data_id, self.define_id)
+    }
+}
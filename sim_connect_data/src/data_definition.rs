@@ -0,0 +1,419 @@
+use std::{collections::HashMap, fmt::Display};
+
+use serde::{Deserialize, Serialize};
+use sim_connect_sys::bindings;
+
+use crate::{
+    error::{SimConnectError, SimConnectResult},
+    recv_data::RecvSimData,
+    sim_data::ToDatum,
+    sim_units::SimUnit,
+    sim_var_types::SimVarType,
+    sim_vars::SimVar,
+};
+
+/// The value of a datum of a `DataDefinition`, as read from or written to the simulator
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SimValue {
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    String(String),
+    LatLonAlt(LatLonAlt),
+    Xyz(Xyz),
+}
+
+impl SimValue {
+    /// The value of a number of any type, converted to `f64`
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::I32(value) => Some(*value as f64),
+            Self::I64(value) => Some(*value as f64),
+            Self::F32(value) => Some(*value as f64),
+            Self::F64(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The value every datum of `data_type` starts at, or `None` if `SimValue` can't hold it
+    fn zero(data_type: SimVarType) -> Option<Self> {
+        Some(match data_type {
+            SimVarType::I32 => Self::I32(0),
+            SimVarType::I64 => Self::I64(0),
+            SimVarType::F32 => Self::F32(0.0),
+            SimVarType::F64 => Self::F64(0.0),
+            SimVarType::String8
+            | SimVarType::String32
+            | SimVarType::String64
+            | SimVarType::String128
+            | SimVarType::String256
+            | SimVarType::String260
+            | SimVarType::StringV => Self::String(String::new()),
+            SimVarType::LatLongAlt => Self::LatLonAlt(LatLonAlt::default()),
+            SimVarType::XYZ => Self::Xyz(Xyz::default()),
+            SimVarType::InitPos
+            | SimVarType::MarkerState
+            | SimVarType::Waypoint
+            | SimVarType::Invalid => return None,
+        })
+    }
+
+    /// Reads a value of `data_type` from the start of `data`, returning it and the number of
+    /// bytes it took up
+    fn read(data_type: SimVarType, data: &[u8]) -> Result<(Self, usize), String> {
+        let size = match data_type {
+            // Variable length strings are null terminated and padded to a whole dword
+            SimVarType::StringV => {
+                let length = data
+                    .iter()
+                    .position(|b| *b == 0)
+                    .ok_or("Unterminated string")?;
+                (length + 1 + 3) & !3
+            }
+            data_type => data_type
+                .size()
+                .ok_or_else(|| format!("{data_type:?} can't be read as a SimValue"))?,
+        };
+        let bytes = data.get(..size).ok_or("Data ended early")?;
+        let f64_at =
+            |index: usize| f64::from_le_bytes(bytes[index * 8..index * 8 + 8].try_into().unwrap());
+
+        let value = match data_type {
+            SimVarType::I32 => Self::I32(i32::from_le_bytes(bytes.try_into().unwrap())),
+            SimVarType::I64 => Self::I64(i64::from_le_bytes(bytes.try_into().unwrap())),
+            SimVarType::F32 => Self::F32(f32::from_le_bytes(bytes.try_into().unwrap())),
+            SimVarType::F64 => Self::F64(f64_at(0)),
+            SimVarType::LatLongAlt => Self::LatLonAlt(LatLonAlt {
+                latitude: f64_at(0),
+                longitude: f64_at(1),
+                altitude: f64_at(2),
+            }),
+            SimVarType::XYZ => Self::Xyz(Xyz {
+                x: f64_at(0),
+                y: f64_at(1),
+                z: f64_at(2),
+            }),
+            SimVarType::InitPos | SimVarType::MarkerState | SimVarType::Waypoint => {
+                return Err(format!("{data_type:?} can't be read as a SimValue"))
+            }
+            _ => {
+                let length = bytes.iter().position(|b| *b == 0).unwrap_or(size);
+                let text = std::str::from_utf8(&bytes[..length])
+                    .map_err(|e| format!("Invalid string: {e}"))?;
+                Self::String(text.to_owned())
+            }
+        };
+        Ok((value, size))
+    }
+}
+
+impl ToDatum for SimValue {
+    fn write_datum(&self, data_type: SimVarType, out: &mut Vec<u8>) -> SimConnectResult<()> {
+        match self {
+            Self::I32(value) => value.write_datum(data_type, out),
+            Self::I64(value) => value.write_datum(data_type, out),
+            Self::F32(value) => value.write_datum(data_type, out),
+            Self::F64(value) => value.write_datum(data_type, out),
+            Self::String(value) => value.write_datum(data_type, out),
+            Self::LatLonAlt(value) => value.write_datum(data_type, out),
+            Self::Xyz(value) => value.write_datum(data_type, out),
+        }
+    }
+}
+
+macro_rules! sim_value_from {
+    ($($variant: ident($value: ty)),*) => {
+        $(
+            impl From<$value> for SimValue {
+                fn from(value: $value) -> Self {
+                    Self::$variant(value)
+                }
+            }
+        )*
+    };
+}
+
+sim_value_from!(
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    String(String),
+    LatLonAlt(LatLonAlt),
+    Xyz(Xyz)
+);
+
+impl From<&str> for SimValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.to_owned())
+    }
+}
+
+/// A position on the globe, as in `SIMCONNECT_DATA_LATLONALT`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatLonAlt {
+    /// Degrees, north positive
+    pub latitude: f64,
+    /// Degrees, east positive
+    pub longitude: f64,
+    /// Meters above mean sea level
+    pub altitude: f64,
+}
+
+impl From<bindings::SIMCONNECT_DATA_LATLONALT> for LatLonAlt {
+    fn from(value: bindings::SIMCONNECT_DATA_LATLONALT) -> Self {
+        Self {
+            latitude: value.Latitude,
+            longitude: value.Longitude,
+            altitude: value.Altitude,
+        }
+    }
+}
+
+impl ToDatum for LatLonAlt {
+    fn write_datum(&self, data_type: SimVarType, out: &mut Vec<u8>) -> SimConnectResult<()> {
+        write_triple(
+            [self.latitude, self.longitude, self.altitude],
+            SimVarType::LatLongAlt,
+            data_type,
+            out,
+        )
+    }
+}
+
+/// A vector, as in `SIMCONNECT_DATA_XYZ`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Xyz {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl From<bindings::SIMCONNECT_DATA_XYZ> for Xyz {
+    fn from(value: bindings::SIMCONNECT_DATA_XYZ) -> Self {
+        Self {
+            x: value.x,
+            y: value.y,
+            z: value.z,
+        }
+    }
+}
+
+impl ToDatum for Xyz {
+    fn write_datum(&self, data_type: SimVarType, out: &mut Vec<u8>) -> SimConnectResult<()> {
+        write_triple([self.x, self.y, self.z], SimVarType::XYZ, data_type, out)
+    }
+}
+
+fn write_triple(
+    values: [f64; 3],
+    expected: SimVarType,
+    data_type: SimVarType,
+    out: &mut Vec<u8>,
+) -> SimConnectResult<()> {
+    if data_type != expected {
+        return Err(SimConnectError::InvalidData(format!(
+            "Unable to write {expected:?} as {data_type:?}"
+        )));
+    }
+    for value in values {
+        out.extend(value.to_le_bytes());
+    }
+    Ok(())
+}
+
+/// A datum of a `DataDefinition`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DefinitionDatum {
+    /// The simvar as SimConnect names it, e.g. `GENERAL ENG RPM:1`
+    pub name: String,
+    /// Empty for simvars without a unit, such as strings
+    pub unit: String,
+    pub data_type: SimVarType,
+}
+
+/// A data definition put together at runtime, for simvars which aren't known when compiling,
+/// such as those picked by a user. Values are read and written as `SimValue`s, one per datum,
+/// in the order they were added.
+///
+/// The id of each datum is its index in the definition.
+///
+/// # Example
+///
+/// ```
+/// use sim_connect_data::{
+///     data_definition::DataDefinition, sim_units::GPS, sim_var_types::SimVarType,
+///     sim_vars::SimVar,
+/// };
+///
+/// let definition = DataDefinition::new()
+///     .add(SimVar::PlaneLat, Some(&GPS::DegLat), SimVarType::F64)
+///     .add_named("GENERAL ENG RPM:1", "rpm", SimVarType::F64)
+///     .add_named("TITLE", "", SimVarType::String256);
+/// assert_eq!(definition.datums()[1].name, "GENERAL ENG RPM:1");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DataDefinition {
+    datums: Vec<DefinitionDatum>,
+}
+
+impl DataDefinition {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `sim_var`, read in `unit`, as `data_type`
+    pub fn add(self, sim_var: SimVar, unit: Option<&dyn SimUnit>, data_type: SimVarType) -> Self {
+        let unit = unit
+            .map(|unit| unit.sc_string().to_string_lossy().into_owned())
+            .unwrap_or_default();
+        self.add_named(&sim_var.to_string(), &unit, data_type)
+    }
+
+    /// Adds the simvar SimConnect knows as `name`, read in the unit SimConnect knows as `unit`,
+    /// as `data_type`
+    pub fn add_named(mut self, name: &str, unit: &str, data_type: SimVarType) -> Self {
+        self.datums.push(DefinitionDatum {
+            name: name.to_owned(),
+            unit: unit.to_owned(),
+            data_type,
+        });
+        self
+    }
+
+    pub fn datums(&self) -> &[DefinitionDatum] {
+        &self.datums
+    }
+
+    /// Fails if the definition is empty, or has a datum of a type `SimValue` can't hold
+    pub fn check(&self) -> SimConnectResult<()> {
+        if self.datums.is_empty() {
+            return Err(SimConnectError::InvalidData(
+                "A data definition needs at least one datum".to_owned(),
+            ));
+        }
+        match self
+            .datums
+            .iter()
+            .find(|datum| SimValue::zero(datum.data_type).is_none())
+        {
+            Some(datum) => Err(SimConnectError::InvalidData(format!(
+                "{} can't be read as a SimValue from {:?}",
+                datum.name, datum.data_type
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Reads the value of every datum from data answering a request for this definition
+    pub fn read(&self, data: &RecvSimData) -> SimConnectResult<Vec<SimValue>> {
+        let mut bytes = data.get_data();
+        let mut values = Vec::with_capacity(self.datums.len());
+        for datum in &self.datums {
+            let (value, size) = SimValue::read(datum.data_type, bytes)
+                .map_err(|reason| parse_error(data, reason))?;
+            values.push(value);
+            bytes = &bytes[size..];
+        }
+        Ok(values)
+    }
+
+    /// Updates `values` with tagged data answering a request for this definition, which only
+    /// has the datums that changed with `DataRequest::changed`. Datums which were never sent
+    /// start at zero.
+    pub fn read_tagged(
+        &self,
+        data: &RecvSimData,
+        values: &mut Vec<SimValue>,
+    ) -> SimConnectResult<()> {
+        if values.len() != self.datums.len() {
+            *values = self
+                .datums
+                .iter()
+                .map(|datum| SimValue::zero(datum.data_type).unwrap_or(SimValue::I32(0)))
+                .collect();
+        }
+
+        let mut bytes = data.get_data();
+        for _ in 0..data.get_define_count() {
+            let datum_id = bytes
+                .get(..4)
+                .map(|id| u32::from_le_bytes(id.try_into().unwrap()))
+                .ok_or_else(|| parse_error(data, "Tagged data ended early"))?;
+            let datum = self
+                .datums
+                .get(datum_id as usize)
+                .ok_or_else(|| parse_error(data, format!("Unknown datum id {datum_id}")))?;
+            let (value, size) = SimValue::read(datum.data_type, &bytes[4..])
+                .map_err(|reason| parse_error(data, reason))?;
+            values[datum_id as usize] = value;
+            bytes = &bytes[4 + size..];
+        }
+        Ok(())
+    }
+
+    /// Packs `values`, one for each datum, the way `SimConnect_SetDataOnSimObject` expects them
+    pub fn write(&self, values: &[SimValue]) -> SimConnectResult<Vec<u8>> {
+        if values.len() != self.datums.len() {
+            return Err(SimConnectError::InvalidData(format!(
+                "{} values were given for {} datums",
+                values.len(),
+                self.datums.len()
+            )));
+        }
+
+        let mut data = Vec::new();
+        for (datum, value) in self.datums.iter().zip(values) {
+            value
+                .write_datum(datum.data_type, &mut data)
+                .map_err(|e| match e {
+                    SimConnectError::InvalidData(reason) => {
+                        SimConnectError::InvalidData(format!("{}: {reason}", datum.name))
+                    }
+                    e => e,
+                })?;
+        }
+        Ok(data)
+    }
+
+    /// Pairs `values`, as read for this definition, with the names of their datums
+    pub fn to_map(&self, values: Vec<SimValue>) -> HashMap<String, SimValue> {
+        self.datums
+            .iter()
+            .map(|datum| datum.name.clone())
+            .zip(values)
+            .collect()
+    }
+}
+
+impl Display for DataDefinition {
+    /// Lists every datum, e.g. `[Plane Latitude (degree latitude) as F64]`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("[")?;
+        for (index, datum) in self.datums.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            match datum.unit.as_str() {
+                "" => write!(f, "{} as {:?}", datum.name, datum.data_type)?,
+                unit => write!(f, "{} ({unit}) as {:?}", datum.name, datum.data_type)?,
+            }
+        }
+        f.write_str("]")
+    }
+}
+
+fn parse_error(data: &RecvSimData, reason: impl Into<String>) -> SimConnectError {
+    SimConnectError::Parse {
+        recv_id: data.get_recv_id(),
+        reason: reason.into(),
+    }
+}
@@ -0,0 +1,224 @@
+use num_enum::{FromPrimitive, IntoPrimitive};
+use sim_connect_sys::bindings;
+use std::{ffi::NulError, fmt::Display, ptr::NonNull};
+use thiserror::Error;
+
+pub type SimConnectResult<T> = Result<T, SimConnectError>;
+
+/// A failing `HRESULT` returned by one of the `SimConnect_*` functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HResult {
+    /// `E_FAIL`. SimConnect returns this for most failures, including the simulator not running
+    Fail,
+    /// `E_INVALIDARG`
+    InvalidArg,
+    /// `E_OUTOFMEMORY`
+    OutOfMemory,
+    /// `E_NOTIMPL`
+    NotImpl,
+    /// `E_POINTER`
+    Pointer,
+    /// `E_UNEXPECTED`
+    Unexpected,
+    /// Any other failing `HRESULT`
+    Other(i32),
+}
+
+impl HResult {
+    pub fn code(&self) -> i32 {
+        let code: u32 = match self {
+            Self::Fail => 0x8000_4005,
+            Self::InvalidArg => 0x8007_0057,
+            Self::OutOfMemory => 0x8007_000E,
+            Self::NotImpl => 0x8000_4001,
+            Self::Pointer => 0x8000_4003,
+            Self::Unexpected => 0x8000_FFFF,
+            Self::Other(code) => return *code,
+        };
+        code as i32
+    }
+}
+
+impl From<i32> for HResult {
+    fn from(code: i32) -> Self {
+        match code as u32 {
+            0x8000_4005 => Self::Fail,
+            0x8007_0057 => Self::InvalidArg,
+            0x8007_000E => Self::OutOfMemory,
+            0x8000_4001 => Self::NotImpl,
+            0x8000_4003 => Self::Pointer,
+            0x8000_FFFF => Self::Unexpected,
+            _ => Self::Other(code),
+        }
+    }
+}
+
+impl Display for HResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Fail => "E_FAIL",
+            Self::InvalidArg => "E_INVALIDARG",
+            Self::OutOfMemory => "E_OUTOFMEMORY",
+            Self::NotImpl => "E_NOTIMPL",
+            Self::Pointer => "E_POINTER",
+            Self::Unexpected => "E_UNEXPECTED",
+            Self::Other(_) => "HRESULT",
+        };
+        write!(f, "{name} (0x{:08x})", self.code() as u32)
+    }
+}
+
+/// The kind of a `SIMCONNECT_RECV_EXCEPTION` raised by the simulator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive, IntoPrimitive)]
+#[repr(u32)]
+pub enum SimConnectException {
+    None = bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_NONE as u32,
+    Error = bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_ERROR as u32,
+    SizeMismatch = bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_SIZE_MISMATCH as u32,
+    UnrecognizedId = bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_UNRECOGNIZED_ID as u32,
+    Unopened = bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_UNOPENED as u32,
+    VersionMismatch = bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_VERSION_MISMATCH as u32,
+    TooManyGroups = bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_TOO_MANY_GROUPS as u32,
+    NameUnrecognized = bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_NAME_UNRECOGNIZED as u32,
+    TooManyEventNames =
+        bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_TOO_MANY_EVENT_NAMES as u32,
+    EventIdDuplicate =
+        bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_EVENT_ID_DUPLICATE as u32,
+    TooManyMaps = bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_TOO_MANY_MAPS as u32,
+    TooManyObjects = bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_TOO_MANY_OBJECTS as u32,
+    TooManyRequests = bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_TOO_MANY_REQUESTS as u32,
+    WeatherInvalidPort =
+        bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_WEATHER_INVALID_PORT as u32,
+    WeatherInvalidMetar =
+        bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_WEATHER_INVALID_METAR as u32,
+    WeatherUnableToGetObservation =
+        bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_WEATHER_UNABLE_TO_GET_OBSERVATION
+            as u32,
+    WeatherUnableToCreateStation =
+        bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_WEATHER_UNABLE_TO_CREATE_STATION as u32,
+    WeatherUnableToRemoveStation =
+        bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_WEATHER_UNABLE_TO_REMOVE_STATION as u32,
+    InvalidDataType = bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_INVALID_DATA_TYPE as u32,
+    InvalidDataSize = bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_INVALID_DATA_SIZE as u32,
+    DataError = bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_DATA_ERROR as u32,
+    InvalidArray = bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_INVALID_ARRAY as u32,
+    CreateObjectFailed =
+        bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_CREATE_OBJECT_FAILED as u32,
+    LoadFlightplanFailed =
+        bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_LOAD_FLIGHTPLAN_FAILED as u32,
+    OperationInvalidForObjectType =
+        bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_OPERATION_INVALID_FOR_OBJECT_TYPE
+            as u32,
+    IllegalOperation = bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_ILLEGAL_OPERATION as u32,
+    AlreadySubscribed =
+        bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_ALREADY_SUBSCRIBED as u32,
+    InvalidEnum = bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_INVALID_ENUM as u32,
+    DefinitionError = bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_DEFINITION_ERROR as u32,
+    DuplicateId = bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_DUPLICATE_ID as u32,
+    DatumId = bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_DATUM_ID as u32,
+    OutOfBounds = bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_OUT_OF_BOUNDS as u32,
+    AlreadyCreated = bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_ALREADY_CREATED as u32,
+    ObjectOutsideRealityBubble =
+        bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_OBJECT_OUTSIDE_REALITY_BUBBLE as u32,
+    ObjectContainer = bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_OBJECT_CONTAINER as u32,
+    ObjectAI = bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_OBJECT_AI as u32,
+    ObjectATC = bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_OBJECT_ATC as u32,
+    ObjectSchedule = bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_OBJECT_SCHEDULE as u32,
+    JetwayData = bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_JETWAY_DATA as u32,
+    ActionNotFound = bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_ACTION_NOT_FOUND as u32,
+    NotAnAction = bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_NOT_AN_ACTION as u32,
+    IncorrectActionParams =
+        bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_INCORRECT_ACTION_PARAMS as u32,
+    GetInputEventFailed =
+        bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_GET_INPUT_EVENT_FAILED as u32,
+    SetInputEventFailed =
+        bindings::SIMCONNECT_EXCEPTION_SIMCONNECT_EXCEPTION_SET_INPUT_EVENT_FAILED as u32,
+    #[num_enum(catch_all)]
+    Unknown(u32),
+}
+
+#[derive(Debug, Error)]
+pub enum SimConnectError {
+    /// A `SimConnect_*` call returned a failing `HRESULT`
+    #[error("SimConnect call failed with {0}")]
+    HResult(HResult),
+
+    /// The connection to the simulator is not open
+    #[error("SimConnect connection has not been opened")]
+    NotConnected,
+
+    /// The named type has not been registered with `register_struct`
+    #[error("{0} has not yet been registered")]
+    Unregistered(String),
+
+    /// The simulator sent no data for the object with this id
+    #[error("No sim object with id {0} was found")]
+    ObjectNotFound(u32),
+
+    /// The listener thread has exited, so no more messages will be received
+    #[error("The listener thread has stopped")]
+    ListenerStopped,
+
+    /// A thread panicked while holding the connection
+    #[error("SimConnect handle has been poisoned")]
+    Poisoned,
+
+    /// A message received from the simulator could not be parsed
+    #[error("Unable to parse message with SIMCONNECT_RECV_ID {recv_id}: {reason}")]
+    Parse { recv_id: u32, reason: String },
+
+    /// The simulator reported a `SIMCONNECT_RECV_EXCEPTION`. `index` is the parameter of the
+    /// offending call at fault, and `call` describes that call when it could be traced back
+    /// through its send id.
+    #[error(
+        "SimConnect raised {exception:?} for packet {send_id} (parameter {index}){}",
+        call.as_ref().map(|call| format!(" in {call}")).unwrap_or_default()
+    )]
+    Exception {
+        exception: SimConnectException,
+        send_id: u32,
+        index: u32,
+        call: Option<String>,
+    },
+
+    /// A name did not match any variant when parsing an enum from a string
+    #[error("Unable to convert {0} into a SimConnect name")]
+    UnknownName(String),
+
+    /// A keyboard or joystick input definition did not follow SimConnect's grammar
+    #[error("Invalid input definition {definition:?}: {reason}")]
+    InvalidInputDefinition { definition: String, reason: String },
+
+    /// A flight or flight plan file could not be read
+    #[error("Invalid {file_type} file: {reason}")]
+    InvalidFile {
+        file_type: &'static str,
+        reason: String,
+    },
+
+    /// A value could not be written as the data type of its datum
+    #[error("Invalid data: {0}")]
+    InvalidData(String),
+
+    #[error(transparent)]
+    InvalidString(#[from] NulError),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+impl SimConnectError {
+    /// Creates a `Parse` error for the message at `data`
+    pub fn parse(data: NonNull<bindings::SIMCONNECT_RECV>, reason: impl Display) -> Self {
+        Self::Parse {
+            recv_id: unsafe { *data.as_ptr() }.dwID,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl From<HResult> for SimConnectError {
+    fn from(hr: HResult) -> Self {
+        Self::HResult(hr)
+    }
+}
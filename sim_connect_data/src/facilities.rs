@@ -0,0 +1,1329 @@
+use crate::{
+    error::{SimConnectError, SimConnectResult},
+    recv_data::RecvFacilityData,
+};
+use num_enum::{FromPrimitive, IntoPrimitive};
+use sim_connect_sys::bindings;
+use std::collections::HashMap;
+
+/// `SIMCONNECT_FACILITY_DATA_TYPE`, which marks what kind of record each
+/// `SIMCONNECT_RECV_FACILITY_DATA` message holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, FromPrimitive, IntoPrimitive)]
+#[repr(u32)]
+pub enum FacilityDataType {
+    Airport = bindings::SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_AIRPORT as u32,
+    Runway = bindings::SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_RUNWAY as u32,
+    Start = bindings::SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_START as u32,
+    Frequency = bindings::SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_FREQUENCY as u32,
+    Helipad = bindings::SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_HELIPAD as u32,
+    Approach = bindings::SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_APPROACH as u32,
+    ApproachTransition =
+        bindings::SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_APPROACH_TRANSITION as u32,
+    Departure = bindings::SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_DEPARTURE as u32,
+    Arrival = bindings::SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_ARRIVAL as u32,
+    RunwayTransition =
+        bindings::SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_RUNWAY_TRANSITION as u32,
+    EnrouteTransition =
+        bindings::SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_ENROUTE_TRANSITION as u32,
+    Vor = bindings::SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_VOR as u32,
+    Ndb = bindings::SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_NDB as u32,
+    Waypoint = bindings::SIMCONNECT_FACILITY_DATA_TYPE_SIMCONNECT_FACILITY_DATA_WAYPOINT as u32,
+    /// One of the types without a model here, such as taxiways and jetways
+    #[num_enum(catch_all)]
+    Other(u32),
+}
+
+impl FacilityDataType {
+    /// The name the record is opened and closed by in a facility definition, e.g. the
+    /// `RUNWAY` of `OPEN RUNWAY`. `None` for types without a model.
+    pub fn name(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::Airport => "AIRPORT",
+            Self::Runway => "RUNWAY",
+            Self::Start => "START",
+            Self::Frequency => "FREQUENCY",
+            Self::Helipad => "HELIPAD",
+            Self::Approach => "APPROACH",
+            Self::ApproachTransition => "APPROACH_TRANSITION",
+            Self::Departure => "DEPARTURE",
+            Self::Arrival => "ARRIVAL",
+            Self::RunwayTransition => "RUNWAY_TRANSITION",
+            Self::EnrouteTransition => "ENROUTE_TRANSITION",
+            Self::Vor => "VOR",
+            Self::Ndb => "NDB",
+            Self::Waypoint => "WAYPOINT",
+            Self::Other(_) => return None,
+        })
+    }
+
+    /// Parses the name used in `OPEN` and `CLOSE` definition entries
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            Self::Airport,
+            Self::Runway,
+            Self::Start,
+            Self::Frequency,
+            Self::Helipad,
+            Self::Approach,
+            Self::ApproachTransition,
+            Self::Departure,
+            Self::Arrival,
+            Self::RunwayTransition,
+            Self::EnrouteTransition,
+            Self::Vor,
+            Self::Ndb,
+            Self::Waypoint,
+        ]
+        .into_iter()
+        .find(|data_type| data_type.name() == Some(name))
+    }
+}
+
+/// The type the simulator sends a facility field as
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FacilityFieldType {
+    Int32,
+    Float32,
+    Float64,
+    /// A `CHAR[N]`, null-padded to `N` bytes
+    String(usize),
+}
+
+impl FacilityFieldType {
+    pub fn size(&self) -> usize {
+        match self {
+            Self::Int32 | Self::Float32 => 4,
+            Self::Float64 => 8,
+            Self::String(size) => *size,
+        }
+    }
+}
+
+/// A field of a facility record, added to the definition by `name`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FacilityField {
+    pub name: &'static str,
+    pub field_type: FacilityFieldType,
+}
+
+macro_rules! fields {
+    ($($name: literal: $field_type: expr),* $(,)?) => {
+        &[$(FacilityField { name: $name, field_type: $field_type }),*]
+    };
+}
+
+use FacilityFieldType::{Float32 as F32, Float64 as F64, Int32 as I32};
+const ICAO: FacilityFieldType = FacilityFieldType::String(8);
+const NAME: FacilityFieldType = FacilityFieldType::String(32);
+const NAME64: FacilityFieldType = FacilityFieldType::String(64);
+
+/// The value of a facility field
+#[derive(Debug, Clone, PartialEq)]
+pub enum FacilityValue {
+    Int32(i32),
+    Float32(f32),
+    Float64(f64),
+    String(String),
+}
+
+/// The fields of a record, by name
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FacilityValues(HashMap<&'static str, FacilityValue>);
+
+impl FacilityValues {
+    pub fn get(&self, name: &str) -> Option<&FacilityValue> {
+        self.0.get(name)
+    }
+
+    /// The field as an `i32`, or `0` if it is missing or not an `INT32`
+    pub fn i32(&self, name: &str) -> i32 {
+        match self.get(name) {
+            Some(FacilityValue::Int32(value)) => *value,
+            _ => 0,
+        }
+    }
+
+    /// The field as an `f32`, or `0.0` if it is missing or not a `FLOAT32`
+    pub fn f32(&self, name: &str) -> f32 {
+        match self.get(name) {
+            Some(FacilityValue::Float32(value)) => *value,
+            _ => 0.0,
+        }
+    }
+
+    /// The field as an `f64`, or `0.0` if it is missing or not a `FLOAT64`
+    pub fn f64(&self, name: &str) -> f64 {
+        match self.get(name) {
+            Some(FacilityValue::Float64(value)) => *value,
+            _ => 0.0,
+        }
+    }
+
+    /// The field as a string, or empty if it is missing or not a `CHAR[N]`
+    pub fn string(&self, name: &str) -> String {
+        match self.get(name) {
+            Some(FacilityValue::String(value)) => value.clone(),
+            _ => String::new(),
+        }
+    }
+}
+
+/// One record of the answer to `SimConnect_RequestFacilityData`, with the records nested in
+/// it, such as an airport with its runways
+#[derive(Debug, Clone, PartialEq)]
+pub struct FacilityRecord {
+    pub data_type: FacilityDataType,
+    /// The record's fields, packed one after the other in definition order
+    pub data: Vec<u8>,
+    /// The nested records, in the order they were sent
+    pub children: Vec<FacilityRecord>,
+}
+
+impl FacilityRecord {
+    /// Assembles the messages answering one request into the tree of records they describe.
+    /// Each message names its parent by `parent_id`, and is sent after it. Returns `None` if
+    /// nothing was sent, which is how the simulator answers for an unknown facility.
+    pub fn assemble(messages: Vec<RecvFacilityData>) -> Option<Self> {
+        let index: HashMap<u32, usize> = messages
+            .iter()
+            .enumerate()
+            .map(|(i, message)| (message.unique_id, i))
+            .collect();
+        let parents: Vec<Option<usize>> = messages
+            .iter()
+            .enumerate()
+            .map(|(i, message)| index.get(&message.parent_id).copied().filter(|p| *p < i))
+            .collect();
+        let mut records: Vec<Option<Self>> = messages
+            .into_iter()
+            .map(|message| {
+                Some(Self {
+                    data_type: message.data_type.into(),
+                    data: message.data,
+                    children: Vec::new(),
+                })
+            })
+            .collect();
+
+        // Children come after their parent, so working backwards every child is complete by
+        // the time it is moved into its parent
+        for i in (0..records.len()).rev() {
+            if let Some(parent) = parents[i] {
+                let child = records[i].take().expect("each record is moved once");
+                if let Some(parent) = records[parent].as_mut() {
+                    parent.children.insert(0, child);
+                }
+            }
+        }
+        records.into_iter().flatten().next()
+    }
+
+    /// Decodes the record's data, which holds `fields` in order
+    pub fn values(&self, fields: &[FacilityField]) -> SimConnectResult<FacilityValues> {
+        let mut values = HashMap::new();
+        let mut read = 0;
+        for field in fields {
+            let size = field.field_type.size();
+            let bytes = self.data.get(read..read + size).ok_or_else(|| {
+                parse_error(format!(
+                    "{} bytes of {:?} data end before field {}",
+                    self.data.len(),
+                    self.data_type,
+                    field.name
+                ))
+            })?;
+            read += size;
+
+            let value = match field.field_type {
+                FacilityFieldType::Int32 => {
+                    FacilityValue::Int32(i32::from_le_bytes(bytes.try_into().unwrap()))
+                }
+                FacilityFieldType::Float32 => {
+                    FacilityValue::Float32(f32::from_le_bytes(bytes.try_into().unwrap()))
+                }
+                FacilityFieldType::Float64 => {
+                    FacilityValue::Float64(f64::from_le_bytes(bytes.try_into().unwrap()))
+                }
+                FacilityFieldType::String(_) => {
+                    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+                    FacilityValue::String(String::from_utf8_lossy(&bytes[..end]).into_owned())
+                }
+            };
+            values.insert(field.name, value);
+        }
+        Ok(FacilityValues(values))
+    }
+
+    /// Builds a model from each nested record of `T`'s type
+    pub fn children_of<T: FacilityData>(&self) -> SimConnectResult<Vec<T>> {
+        self.children
+            .iter()
+            .filter(|child| child.data_type == T::DATA_TYPE)
+            .map(T::from_record)
+            .collect()
+    }
+}
+
+fn parse_error(reason: String) -> SimConnectError {
+    SimConnectError::Parse {
+        recv_id: bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_FACILITY_DATA as u32,
+        reason,
+    }
+}
+
+/// # Description
+/// A typed model of a facility record, built from the fields it adds to the facility
+/// definition. Records nested in the model, such as the runways of an `Airport`, are added
+/// to the definition between its fields and its `CLOSE` entry.
+pub trait FacilityData: Sized {
+    /// The type marking the record in `SIMCONNECT_RECV_FACILITY_DATA`
+    const DATA_TYPE: FacilityDataType;
+    /// The fields added to the definition, in the order the simulator sends them
+    const FIELDS: &'static [FacilityField];
+
+    /// Adds the definitions of the records nested in this one
+    fn define_children(_definition: &mut Vec<String>) {}
+
+    fn from_values(values: FacilityValues, record: &FacilityRecord) -> SimConnectResult<Self>;
+
+    /// Builds the model from a record of `DATA_TYPE`
+    fn from_record(record: &FacilityRecord) -> SimConnectResult<Self> {
+        if record.data_type != Self::DATA_TYPE {
+            return Err(parse_error(format!(
+                "Expected a {:?} record, got {:?}",
+                Self::DATA_TYPE,
+                record.data_type
+            )));
+        }
+        Self::from_values(record.values(Self::FIELDS)?, record)
+    }
+
+    /// Every entry of the facility definition, each passed to
+    /// `SimConnect_AddToFacilityDefinition` in turn
+    fn definition() -> Vec<String> {
+        let name = Self::DATA_TYPE
+            .name()
+            .expect("every modelled type has a name");
+        let mut definition = vec![format!("OPEN {name}")];
+        definition.extend(Self::FIELDS.iter().map(|field| field.name.to_owned()));
+        Self::define_children(&mut definition);
+        definition.push(format!("CLOSE {name}"));
+        definition
+    }
+}
+
+/// A facility which can be requested by its ICAO code, rather than only being nested in
+/// another record
+pub trait Facility: FacilityData {}
+
+/// The letter following a runway number
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, FromPrimitive, IntoPrimitive)]
+#[repr(i32)]
+pub enum RunwayDesignator {
+    None = 0,
+    Left = 1,
+    Right = 2,
+    Center = 3,
+    Water = 4,
+    A = 5,
+    B = 6,
+    #[num_enum(catch_all)]
+    Unknown(i32),
+}
+
+impl RunwayDesignator {
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            Self::Left => "L",
+            Self::Right => "R",
+            Self::Center => "C",
+            Self::Water => "W",
+            Self::A => "A",
+            Self::B => "B",
+            Self::None | Self::Unknown(_) => "",
+        }
+    }
+}
+
+/// Formats a runway number and designator as painted on the runway, e.g. `"09L"`. Numbers above
+/// 36 stand for compass points, used by water runways and helipads.
+fn runway_ident(number: i32, designator: RunwayDesignator) -> String {
+    const COMPASS: [&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+    let number = match number {
+        1..=36 => format!("{number:02}"),
+        37..=44 => COMPASS[(number - 37) as usize].to_owned(),
+        _ => String::new(),
+    };
+    number + designator.suffix()
+}
+
+/// An ICAO code and region naming another facility, such as an ILS or a fix. Empty if the
+/// field was blank.
+fn facility_ref(icao: String, region: String) -> Option<(String, String)> {
+    (!icao.is_empty()).then_some((icao, region))
+}
+
+/* #region Airport */
+
+/// An airport, with everything nested in it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Airport {
+    pub icao: String,
+    pub region: String,
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Meters above mean sea level
+    pub altitude: f64,
+    /// Magnetic variation in degrees
+    pub magvar: f32,
+    pub runways: Vec<Runway>,
+    pub starts: Vec<Start>,
+    pub frequencies: Vec<Frequency>,
+    pub helipads: Vec<Helipad>,
+    pub approaches: Vec<Approach>,
+    pub departures: Vec<Departure>,
+    pub arrivals: Vec<Arrival>,
+}
+
+impl FacilityData for Airport {
+    const DATA_TYPE: FacilityDataType = FacilityDataType::Airport;
+    const FIELDS: &'static [FacilityField] = fields! {
+        "LATITUDE": F64,
+        "LONGITUDE": F64,
+        "ALTITUDE": F64,
+        "MAGVAR": F32,
+        "NAME": NAME,
+        "ICAO": ICAO,
+        "REGION": ICAO,
+    };
+
+    fn define_children(definition: &mut Vec<String>) {
+        definition.extend(Runway::definition());
+        definition.extend(Start::definition());
+        definition.extend(Frequency::definition());
+        definition.extend(Helipad::definition());
+        definition.extend(Approach::definition());
+        definition.extend(Departure::definition());
+        definition.extend(Arrival::definition());
+    }
+
+    fn from_values(values: FacilityValues, record: &FacilityRecord) -> SimConnectResult<Self> {
+        Ok(Self {
+            icao: values.string("ICAO"),
+            region: values.string("REGION"),
+            name: values.string("NAME"),
+            latitude: values.f64("LATITUDE"),
+            longitude: values.f64("LONGITUDE"),
+            altitude: values.f64("ALTITUDE"),
+            magvar: values.f32("MAGVAR"),
+            runways: record.children_of()?,
+            starts: record.children_of()?,
+            frequencies: record.children_of()?,
+            helipads: record.children_of()?,
+            approaches: record.children_of()?,
+            departures: record.children_of()?,
+            arrivals: record.children_of()?,
+        })
+    }
+}
+
+impl Facility for Airport {}
+
+impl Airport {
+    /// Looks up a runway by either of its ends, e.g. `"16L"`
+    pub fn runway(&self, ident: &str) -> Option<&Runway> {
+        self.runways
+            .iter()
+            .find(|runway| runway.primary.ident() == ident || runway.secondary.ident() == ident)
+    }
+}
+
+/* #endregion */
+
+/* #region Runway */
+
+/// One end of a runway
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunwayEnd {
+    pub number: i32,
+    pub designator: RunwayDesignator,
+    /// The ICAO code and region of the ILS serving this end, if any
+    pub ils: Option<(String, String)>,
+}
+
+impl RunwayEnd {
+    /// The end as painted on the runway, e.g. `"16L"`
+    pub fn ident(&self) -> String {
+        runway_ident(self.number, self.designator)
+    }
+}
+
+/// A runway of an `Airport`, described from its center
+#[derive(Debug, Clone, PartialEq)]
+pub struct Runway {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: f64,
+    /// True heading of the primary end, in degrees
+    pub heading: f32,
+    /// Meters
+    pub length: f32,
+    /// Meters
+    pub width: f32,
+    /// Meters above the runway
+    pub pattern_altitude: f32,
+    /// Slope in degrees
+    pub slope: f32,
+    /// One of the SDK's `SURFACE` values
+    pub surface: i32,
+    pub primary: RunwayEnd,
+    pub secondary: RunwayEnd,
+}
+
+impl FacilityData for Runway {
+    const DATA_TYPE: FacilityDataType = FacilityDataType::Runway;
+    const FIELDS: &'static [FacilityField] = fields! {
+        "LATITUDE": F64,
+        "LONGITUDE": F64,
+        "ALTITUDE": F64,
+        "HEADING": F32,
+        "LENGTH": F32,
+        "WIDTH": F32,
+        "PATTERN_ALTITUDE": F32,
+        "SLOPE": F32,
+        "SURFACE": I32,
+        "PRIMARY_NUMBER": I32,
+        "PRIMARY_DESIGNATOR": I32,
+        "PRIMARY_ILS_ICAO": ICAO,
+        "PRIMARY_ILS_REGION": ICAO,
+        "SECONDARY_NUMBER": I32,
+        "SECONDARY_DESIGNATOR": I32,
+        "SECONDARY_ILS_ICAO": ICAO,
+        "SECONDARY_ILS_REGION": ICAO,
+    };
+
+    fn from_values(values: FacilityValues, _record: &FacilityRecord) -> SimConnectResult<Self> {
+        let end = |prefix: &str| RunwayEnd {
+            number: values.i32(&format!("{prefix}_NUMBER")),
+            designator: values.i32(&format!("{prefix}_DESIGNATOR")).into(),
+            ils: facility_ref(
+                values.string(&format!("{prefix}_ILS_ICAO")),
+                values.string(&format!("{prefix}_ILS_REGION")),
+            ),
+        };
+        Ok(Self {
+            latitude: values.f64("LATITUDE"),
+            longitude: values.f64("LONGITUDE"),
+            altitude: values.f64("ALTITUDE"),
+            heading: values.f32("HEADING"),
+            length: values.f32("LENGTH"),
+            width: values.f32("WIDTH"),
+            pattern_altitude: values.f32("PATTERN_ALTITUDE"),
+            slope: values.f32("SLOPE"),
+            surface: values.i32("SURFACE"),
+            primary: end("PRIMARY"),
+            secondary: end("SECONDARY"),
+        })
+    }
+}
+
+/* #endregion */
+
+/* #region Start */
+
+/// A position aircraft can be placed at to start a flight, such as a runway threshold
+#[derive(Debug, Clone, PartialEq)]
+pub struct Start {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: f64,
+    pub heading: f32,
+    pub number: i32,
+    pub designator: RunwayDesignator,
+    /// `1` for a runway, `2` for water and `3` for a helipad
+    pub start_type: i32,
+}
+
+impl Start {
+    /// The runway or helipad the start is on, e.g. `"16L"`
+    pub fn ident(&self) -> String {
+        runway_ident(self.number, self.designator)
+    }
+}
+
+impl FacilityData for Start {
+    const DATA_TYPE: FacilityDataType = FacilityDataType::Start;
+    const FIELDS: &'static [FacilityField] = fields! {
+        "LATITUDE": F64,
+        "LONGITUDE": F64,
+        "ALTITUDE": F64,
+        "HEADING": F32,
+        "NUMBER": I32,
+        "DESIGNATOR": I32,
+        "TYPE": I32,
+    };
+
+    fn from_values(values: FacilityValues, _record: &FacilityRecord) -> SimConnectResult<Self> {
+        Ok(Self {
+            latitude: values.f64("LATITUDE"),
+            longitude: values.f64("LONGITUDE"),
+            altitude: values.f64("ALTITUDE"),
+            heading: values.f32("HEADING"),
+            number: values.i32("NUMBER"),
+            designator: values.i32("DESIGNATOR").into(),
+            start_type: values.i32("TYPE"),
+        })
+    }
+}
+
+/* #endregion */
+
+/* #region Frequency */
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, FromPrimitive, IntoPrimitive)]
+#[repr(i32)]
+pub enum FrequencyType {
+    None = 0,
+    Atis = 1,
+    Multicom = 2,
+    Unicom = 3,
+    Ctaf = 4,
+    Ground = 5,
+    Tower = 6,
+    Clearance = 7,
+    Approach = 8,
+    Departure = 9,
+    Center = 10,
+    Fss = 11,
+    Awos = 12,
+    Asos = 13,
+    /// Clearance pre-taxi
+    Cpt = 14,
+    /// Remote clearance delivery
+    Gco = 15,
+    #[num_enum(catch_all)]
+    Unknown(i32),
+}
+
+/// A radio frequency of an `Airport`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frequency {
+    pub frequency_type: FrequencyType,
+    /// Hz
+    pub frequency: i32,
+    pub name: String,
+}
+
+impl Frequency {
+    pub fn mhz(&self) -> f64 {
+        self.frequency as f64 / 1_000_000.0
+    }
+}
+
+impl FacilityData for Frequency {
+    const DATA_TYPE: FacilityDataType = FacilityDataType::Frequency;
+    const FIELDS: &'static [FacilityField] = fields! {
+        "TYPE": I32,
+        "FREQUENCY": I32,
+        "NAME": NAME64,
+    };
+
+    fn from_values(values: FacilityValues, _record: &FacilityRecord) -> SimConnectResult<Self> {
+        Ok(Self {
+            frequency_type: values.i32("TYPE").into(),
+            frequency: values.i32("FREQUENCY"),
+            name: values.string("NAME"),
+        })
+    }
+}
+
+/* #endregion */
+
+/* #region Helipad */
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Helipad {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: f64,
+    pub heading: f32,
+    pub length: f32,
+    pub width: f32,
+    /// One of the SDK's `SURFACE` values
+    pub surface: i32,
+    /// One of the SDK's helipad `TYPE` values, such as `1` for a square pad
+    pub helipad_type: i32,
+}
+
+impl FacilityData for Helipad {
+    const DATA_TYPE: FacilityDataType = FacilityDataType::Helipad;
+    const FIELDS: &'static [FacilityField] = fields! {
+        "LATITUDE": F64,
+        "LONGITUDE": F64,
+        "ALTITUDE": F64,
+        "HEADING": F32,
+        "LENGTH": F32,
+        "WIDTH": F32,
+        "SURFACE": I32,
+        "TYPE": I32,
+    };
+
+    fn from_values(values: FacilityValues, _record: &FacilityRecord) -> SimConnectResult<Self> {
+        Ok(Self {
+            latitude: values.f64("LATITUDE"),
+            longitude: values.f64("LONGITUDE"),
+            altitude: values.f64("ALTITUDE"),
+            heading: values.f32("HEADING"),
+            length: values.f32("LENGTH"),
+            width: values.f32("WIDTH"),
+            surface: values.i32("SURFACE"),
+            helipad_type: values.i32("TYPE"),
+        })
+    }
+}
+
+/* #endregion */
+
+/* #region Approach */
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, FromPrimitive, IntoPrimitive)]
+#[repr(i32)]
+pub enum ApproachType {
+    Gps = 1,
+    Vor = 2,
+    Ndb = 3,
+    Ils = 4,
+    Localizer = 5,
+    Sdf = 6,
+    Lda = 7,
+    VorDme = 8,
+    NdbDme = 9,
+    Rnav = 10,
+    LocalizerBackCourse = 11,
+    #[num_enum(catch_all)]
+    Unknown(i32),
+}
+
+/// An instrument approach to a runway of an `Airport`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Approach {
+    pub approach_type: ApproachType,
+    /// The letter telling approaches of the same type to the same runway apart
+    pub suffix: Option<char>,
+    pub runway_number: i32,
+    pub runway_designator: RunwayDesignator,
+    /// The ICAO code and region of the final approach fix
+    pub faf: Option<(String, String)>,
+    /// Meters
+    pub faf_altitude: f32,
+    /// Meters
+    pub missed_altitude: f32,
+    pub transitions: Vec<ApproachTransition>,
+}
+
+impl Approach {
+    /// The runway flown to, e.g. `"16L"`
+    pub fn runway(&self) -> String {
+        runway_ident(self.runway_number, self.runway_designator)
+    }
+}
+
+impl FacilityData for Approach {
+    const DATA_TYPE: FacilityDataType = FacilityDataType::Approach;
+    const FIELDS: &'static [FacilityField] = fields! {
+        "TYPE": I32,
+        "SUFFIX": I32,
+        "RUNWAY_NUMBER": I32,
+        "RUNWAY_DESIGNATOR": I32,
+        "FAF_ICAO": ICAO,
+        "FAF_REGION": ICAO,
+        "FAF_ALTITUDE": F32,
+        "MISSED_ALTITUDE": F32,
+    };
+
+    fn define_children(definition: &mut Vec<String>) {
+        definition.extend(ApproachTransition::definition());
+    }
+
+    fn from_values(values: FacilityValues, record: &FacilityRecord) -> SimConnectResult<Self> {
+        Ok(Self {
+            approach_type: values.i32("TYPE").into(),
+            suffix: u8::try_from(values.i32("SUFFIX"))
+                .ok()
+                .filter(|suffix| *suffix != 0)
+                .map(char::from),
+            runway_number: values.i32("RUNWAY_NUMBER"),
+            runway_designator: values.i32("RUNWAY_DESIGNATOR").into(),
+            faf: facility_ref(values.string("FAF_ICAO"), values.string("FAF_REGION")),
+            faf_altitude: values.f32("FAF_ALTITUDE"),
+            missed_altitude: values.f32("MISSED_ALTITUDE"),
+            transitions: record.children_of()?,
+        })
+    }
+}
+
+/// A transition onto an `Approach` from its initial approach fix
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApproachTransition {
+    /// `1` for a full transition and `2` for a DME arc
+    pub transition_type: i32,
+    /// The ICAO code and region of the initial approach fix
+    pub iaf: Option<(String, String)>,
+}
+
+impl FacilityData for ApproachTransition {
+    const DATA_TYPE: FacilityDataType = FacilityDataType::ApproachTransition;
+    const FIELDS: &'static [FacilityField] = fields! {
+        "TYPE": I32,
+        "IAF_ICAO": ICAO,
+        "IAF_REGION": ICAO,
+    };
+
+    fn from_values(values: FacilityValues, _record: &FacilityRecord) -> SimConnectResult<Self> {
+        Ok(Self {
+            transition_type: values.i32("TYPE"),
+            iaf: facility_ref(values.string("IAF_ICAO"), values.string("IAF_REGION")),
+        })
+    }
+}
+
+/* #endregion */
+
+/* #region Departure and Arrival */
+
+/// A runway a `Departure` or `Arrival` can be flown from or to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunwayTransition {
+    pub runway_number: i32,
+    pub runway_designator: RunwayDesignator,
+}
+
+impl RunwayTransition {
+    pub fn runway(&self) -> String {
+        runway_ident(self.runway_number, self.runway_designator)
+    }
+}
+
+impl FacilityData for RunwayTransition {
+    const DATA_TYPE: FacilityDataType = FacilityDataType::RunwayTransition;
+    const FIELDS: &'static [FacilityField] = fields! {
+        "RUNWAY_NUMBER": I32,
+        "RUNWAY_DESIGNATOR": I32,
+    };
+
+    fn from_values(values: FacilityValues, _record: &FacilityRecord) -> SimConnectResult<Self> {
+        Ok(Self {
+            runway_number: values.i32("RUNWAY_NUMBER"),
+            runway_designator: values.i32("RUNWAY_DESIGNATOR").into(),
+        })
+    }
+}
+
+/// The en-route end of a `Departure` or `Arrival`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnrouteTransition {
+    pub name: String,
+}
+
+impl FacilityData for EnrouteTransition {
+    const DATA_TYPE: FacilityDataType = FacilityDataType::EnrouteTransition;
+    const FIELDS: &'static [FacilityField] = fields! {
+        "NAME": ICAO,
+    };
+
+    fn from_values(values: FacilityValues, _record: &FacilityRecord) -> SimConnectResult<Self> {
+        Ok(Self {
+            name: values.string("NAME"),
+        })
+    }
+}
+
+macro_rules! procedure {
+    ($(#[$meta: meta])* $name: ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        pub struct $name {
+            pub name: String,
+            pub runway_transitions: Vec<RunwayTransition>,
+            pub enroute_transitions: Vec<EnrouteTransition>,
+        }
+
+        impl FacilityData for $name {
+            const DATA_TYPE: FacilityDataType = FacilityDataType::$name;
+            const FIELDS: &'static [FacilityField] = fields! {
+                "NAME": ICAO,
+            };
+
+            fn define_children(definition: &mut Vec<String>) {
+                definition.extend(RunwayTransition::definition());
+                definition.extend(EnrouteTransition::definition());
+            }
+
+            fn from_values(
+                values: FacilityValues,
+                record: &FacilityRecord,
+            ) -> SimConnectResult<Self> {
+                Ok(Self {
+                    name: values.string("NAME"),
+                    runway_transitions: record.children_of()?,
+                    enroute_transitions: record.children_of()?,
+                })
+            }
+        }
+    };
+}
+
+procedure! {
+    /// A standard instrument departure (SID) from an `Airport`
+    Departure
+}
+
+procedure! {
+    /// A standard terminal arrival route (STAR) to an `Airport`
+    Arrival
+}
+
+/* #endregion */
+
+/* #region Navaids */
+
+/// A VOR, which includes ILS localizers and glide slopes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vor {
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: f64,
+    /// Hz
+    pub frequency: i32,
+    /// One of the SDK's VOR `TYPE` values
+    pub vor_type: i32,
+    /// Meters
+    pub range: f32,
+    pub magvar: f32,
+    pub has_dme: bool,
+    /// The localizer's true heading in degrees, if the VOR is an ILS
+    pub localizer: Option<f32>,
+    /// The glide slope angle in degrees, if the VOR has one
+    pub glide_slope: Option<f32>,
+}
+
+impl FacilityData for Vor {
+    const DATA_TYPE: FacilityDataType = FacilityDataType::Vor;
+    const FIELDS: &'static [FacilityField] = fields! {
+        "VOR_LATITUDE": F64,
+        "VOR_LONGITUDE": F64,
+        "VOR_ALTITUDE": F64,
+        "FREQUENCY": I32,
+        "TYPE": I32,
+        "NAV_RANGE": F32,
+        "MAGVAR": F32,
+        "HAS_DME": I32,
+        "HAS_LOCALIZER": I32,
+        "LOCALIZER": F32,
+        "HAS_GLIDE_SLOPE": I32,
+        "GS_ANGLE": F32,
+        "NAME": NAME64,
+    };
+
+    fn from_values(values: FacilityValues, _record: &FacilityRecord) -> SimConnectResult<Self> {
+        Ok(Self {
+            name: values.string("NAME"),
+            latitude: values.f64("VOR_LATITUDE"),
+            longitude: values.f64("VOR_LONGITUDE"),
+            altitude: values.f64("VOR_ALTITUDE"),
+            frequency: values.i32("FREQUENCY"),
+            vor_type: values.i32("TYPE"),
+            range: values.f32("NAV_RANGE"),
+            magvar: values.f32("MAGVAR"),
+            has_dme: values.i32("HAS_DME") != 0,
+            localizer: (values.i32("HAS_LOCALIZER") != 0).then(|| values.f32("LOCALIZER")),
+            glide_slope: (values.i32("HAS_GLIDE_SLOPE") != 0).then(|| values.f32("GS_ANGLE")),
+        })
+    }
+}
+
+impl Facility for Vor {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ndb {
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: f64,
+    /// Hz
+    pub frequency: i32,
+    /// One of the SDK's NDB `TYPE` values
+    pub ndb_type: i32,
+    /// Meters
+    pub range: f32,
+    pub magvar: f32,
+}
+
+impl FacilityData for Ndb {
+    const DATA_TYPE: FacilityDataType = FacilityDataType::Ndb;
+    const FIELDS: &'static [FacilityField] = fields! {
+        "LATITUDE": F64,
+        "LONGITUDE": F64,
+        "ALTITUDE": F64,
+        "FREQUENCY": I32,
+        "TYPE": I32,
+        "RANGE": F32,
+        "MAGVAR": F32,
+        "NAME": NAME64,
+    };
+
+    fn from_values(values: FacilityValues, _record: &FacilityRecord) -> SimConnectResult<Self> {
+        Ok(Self {
+            name: values.string("NAME"),
+            latitude: values.f64("LATITUDE"),
+            longitude: values.f64("LONGITUDE"),
+            altitude: values.f64("ALTITUDE"),
+            frequency: values.i32("FREQUENCY"),
+            ndb_type: values.i32("TYPE"),
+            range: values.f32("RANGE"),
+            magvar: values.f32("MAGVAR"),
+        })
+    }
+}
+
+impl Facility for Ndb {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Waypoint {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: f64,
+    /// One of the SDK's waypoint `TYPE` values
+    pub waypoint_type: i32,
+    pub magvar: f32,
+}
+
+impl FacilityData for Waypoint {
+    const DATA_TYPE: FacilityDataType = FacilityDataType::Waypoint;
+    const FIELDS: &'static [FacilityField] = fields! {
+        "LATITUDE": F64,
+        "LONGITUDE": F64,
+        "ALTITUDE": F64,
+        "TYPE": I32,
+        "MAGVAR": F32,
+    };
+
+    fn from_values(values: FacilityValues, _record: &FacilityRecord) -> SimConnectResult<Self> {
+        Ok(Self {
+            latitude: values.f64("LATITUDE"),
+            longitude: values.f64("LONGITUDE"),
+            altitude: values.f64("ALTITUDE"),
+            waypoint_type: values.i32("TYPE"),
+            magvar: values.f32("MAGVAR"),
+        })
+    }
+}
+
+impl Facility for Waypoint {}
+
+/* #endregion */
+
+/// The type of every field a model here adds to a definition, by the record it belongs to and
+/// its name. Lets something which only sees the definition, such as a mock simulator, lay out
+/// the data.
+pub fn field_type(data_type: FacilityDataType, name: &str) -> Option<FacilityFieldType> {
+    let fields = match data_type {
+        FacilityDataType::Airport => Airport::FIELDS,
+        FacilityDataType::Runway => Runway::FIELDS,
+        FacilityDataType::Start => Start::FIELDS,
+        FacilityDataType::Frequency => Frequency::FIELDS,
+        FacilityDataType::Helipad => Helipad::FIELDS,
+        FacilityDataType::Approach => Approach::FIELDS,
+        FacilityDataType::ApproachTransition => ApproachTransition::FIELDS,
+        FacilityDataType::Departure => Departure::FIELDS,
+        FacilityDataType::Arrival => Arrival::FIELDS,
+        FacilityDataType::RunwayTransition => RunwayTransition::FIELDS,
+        FacilityDataType::EnrouteTransition => EnrouteTransition::FIELDS,
+        FacilityDataType::Vor => Vor::FIELDS,
+        FacilityDataType::Ndb => Ndb::FIELDS,
+        FacilityDataType::Waypoint => Waypoint::FIELDS,
+        FacilityDataType::Other(_) => return None,
+    };
+    fields
+        .iter()
+        .find(|field| field.name == name)
+        .map(|field| field.field_type)
+}
+
+/* #region Facility lists */
+
+/// The facilities `SimConnect_RequestFacilitiesList` and `SimConnect_SubscribeToFacilities` can
+/// list, which are those in the simulator's cache around the user's aircraft
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, FromPrimitive, IntoPrimitive)]
+#[repr(i32)]
+pub enum FacilityListType {
+    Airport = bindings::SIMCONNECT_FACILITY_LIST_TYPE_SIMCONNECT_FACILITY_LIST_TYPE_AIRPORT,
+    Waypoint = bindings::SIMCONNECT_FACILITY_LIST_TYPE_SIMCONNECT_FACILITY_LIST_TYPE_WAYPOINT,
+    Ndb = bindings::SIMCONNECT_FACILITY_LIST_TYPE_SIMCONNECT_FACILITY_LIST_TYPE_NDB,
+    Vor = bindings::SIMCONNECT_FACILITY_LIST_TYPE_SIMCONNECT_FACILITY_LIST_TYPE_VOR,
+    #[num_enum(catch_all)]
+    Unknown(i32),
+}
+
+impl FacilityListType {
+    /// The list type answered with a `SIMCONNECT_RECV_*_LIST` message of `recv_id`
+    pub fn from_recv_id(recv_id: bindings::SIMCONNECT_RECV_ID) -> Option<Self> {
+        match recv_id {
+            bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_AIRPORT_LIST => Some(Self::Airport),
+            bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_WAYPOINT_LIST => Some(Self::Waypoint),
+            bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_NDB_LIST => Some(Self::Ndb),
+            bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_VOR_LIST => Some(Self::Vor),
+            _ => None,
+        }
+    }
+
+    /// The `SIMCONNECT_RECV_ID` of the messages listing facilities of this type
+    pub fn recv_id(&self) -> Option<bindings::SIMCONNECT_RECV_ID> {
+        match self {
+            Self::Airport => Some(bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_AIRPORT_LIST),
+            Self::Waypoint => Some(bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_WAYPOINT_LIST),
+            Self::Ndb => Some(bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_NDB_LIST),
+            Self::Vor => Some(bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_VOR_LIST),
+            Self::Unknown(_) => None,
+        }
+    }
+
+    /// Size of each entry of the list, which is one of the `SIMCONNECT_DATA_FACILITY_*` structs
+    pub fn entry_size(&self) -> Option<usize> {
+        use std::mem::size_of;
+        match self {
+            Self::Airport => Some(size_of::<bindings::SIMCONNECT_DATA_FACILITY_AIRPORT>()),
+            Self::Waypoint => Some(size_of::<bindings::SIMCONNECT_DATA_FACILITY_WAYPOINT>()),
+            Self::Ndb => Some(size_of::<bindings::SIMCONNECT_DATA_FACILITY_NDB>()),
+            Self::Vor => Some(size_of::<bindings::SIMCONNECT_DATA_FACILITY_VOR>()),
+            Self::Unknown(_) => None,
+        }
+    }
+}
+
+/// # Description
+/// An entry of a facility list, decoded from one of the `SIMCONNECT_DATA_FACILITY_*` structs.
+/// Each kind of facility extends the one before it, so a `VorEntry` holds everything a
+/// `NdbEntry` does, which holds everything a `WaypointEntry` does.
+pub trait FacilityListEntry: Clone + Send + 'static {
+    const LIST_TYPE: FacilityListType;
+
+    /// Decodes the entry from exactly `LIST_TYPE.entry_size()` bytes
+    fn from_bytes(bytes: &[u8]) -> Self;
+
+    /// The ICAO code and region identifying the facility
+    fn ident(&self) -> (&str, &str);
+}
+
+/// Decodes the fixed size, null padded string at `offset`
+fn read_ident(bytes: &[u8], offset: usize, size: usize) -> String {
+    let bytes = &bytes[offset..offset + size];
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+fn read_f64(bytes: &[u8], offset: usize) -> f64 {
+    f64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_f32(bytes: &[u8], offset: usize) -> f32 {
+    f32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+/// An airport in the facility cache
+#[derive(Debug, Clone, PartialEq)]
+pub struct AirportEntry {
+    pub icao: String,
+    pub region: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Meters above mean sea level
+    pub altitude: f64,
+}
+
+impl FacilityListEntry for AirportEntry {
+    const LIST_TYPE: FacilityListType = FacilityListType::Airport;
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        use bindings::SIMCONNECT_DATA_FACILITY_AIRPORT as Airport;
+        use std::mem::offset_of;
+
+        Self {
+            icao: read_ident(bytes, offset_of!(Airport, Ident), 6),
+            region: read_ident(bytes, offset_of!(Airport, Region), 3),
+            latitude: read_f64(bytes, offset_of!(Airport, Latitude)),
+            longitude: read_f64(bytes, offset_of!(Airport, Longitude)),
+            altitude: read_f64(bytes, offset_of!(Airport, Altitude)),
+        }
+    }
+
+    fn ident(&self) -> (&str, &str) {
+        (&self.icao, &self.region)
+    }
+}
+
+/// A waypoint in the facility cache
+#[derive(Debug, Clone, PartialEq)]
+pub struct WaypointEntry {
+    pub icao: String,
+    pub region: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: f64,
+    /// Magnetic variation in degrees
+    pub magvar: f32,
+}
+
+impl FacilityListEntry for WaypointEntry {
+    const LIST_TYPE: FacilityListType = FacilityListType::Waypoint;
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        use bindings::SIMCONNECT_DATA_FACILITY_WAYPOINT as Waypoint;
+        use std::mem::offset_of;
+
+        let airport = AirportEntry::from_bytes(bytes);
+        Self {
+            icao: airport.icao,
+            region: airport.region,
+            latitude: airport.latitude,
+            longitude: airport.longitude,
+            altitude: airport.altitude,
+            magvar: read_f32(bytes, offset_of!(Waypoint, fMagVar)),
+        }
+    }
+
+    fn ident(&self) -> (&str, &str) {
+        (&self.icao, &self.region)
+    }
+}
+
+/// An NDB in the facility cache
+#[derive(Debug, Clone, PartialEq)]
+pub struct NdbEntry {
+    pub icao: String,
+    pub region: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: f64,
+    pub magvar: f32,
+    /// Hz
+    pub frequency: u32,
+}
+
+impl FacilityListEntry for NdbEntry {
+    const LIST_TYPE: FacilityListType = FacilityListType::Ndb;
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        use bindings::SIMCONNECT_DATA_FACILITY_NDB as Ndb;
+        use std::mem::offset_of;
+
+        let waypoint = WaypointEntry::from_bytes(bytes);
+        Self {
+            icao: waypoint.icao,
+            region: waypoint.region,
+            latitude: waypoint.latitude,
+            longitude: waypoint.longitude,
+            altitude: waypoint.altitude,
+            magvar: waypoint.magvar,
+            frequency: read_u32(bytes, offset_of!(Ndb, fFrequency)),
+        }
+    }
+
+    fn ident(&self) -> (&str, &str) {
+        (&self.icao, &self.region)
+    }
+}
+
+// The `SIMCONNECT_RECV_ID_VOR_LIST_HAS_*` flags, which are preprocessor defines the bindings
+// don't carry
+pub const VOR_HAS_NAV_SIGNAL: u32 = 0x1;
+pub const VOR_HAS_LOCALIZER: u32 = 0x2;
+pub const VOR_HAS_GLIDE_SLOPE: u32 = 0x4;
+pub const VOR_HAS_DME: u32 = 0x8;
+
+/// A VOR in the facility cache, which includes ILS localizers and glide slopes
+#[derive(Debug, Clone, PartialEq)]
+pub struct VorEntry {
+    pub icao: String,
+    pub region: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub altitude: f64,
+    pub magvar: f32,
+    /// Hz
+    pub frequency: u32,
+    /// `VOR_HAS_*` flags telling which of the fields below are set
+    pub flags: u32,
+    /// The localizer's true heading in degrees
+    pub localizer: f32,
+    pub glide_latitude: f64,
+    pub glide_longitude: f64,
+    pub glide_altitude: f64,
+    /// Degrees
+    pub glide_slope_angle: f32,
+}
+
+impl VorEntry {
+    pub fn has_nav_signal(&self) -> bool {
+        self.flags & VOR_HAS_NAV_SIGNAL != 0
+    }
+
+    pub fn has_localizer(&self) -> bool {
+        self.flags & VOR_HAS_LOCALIZER != 0
+    }
+
+    pub fn has_glide_slope(&self) -> bool {
+        self.flags & VOR_HAS_GLIDE_SLOPE != 0
+    }
+
+    pub fn has_dme(&self) -> bool {
+        self.flags & VOR_HAS_DME != 0
+    }
+}
+
+impl FacilityListEntry for VorEntry {
+    const LIST_TYPE: FacilityListType = FacilityListType::Vor;
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        use bindings::SIMCONNECT_DATA_FACILITY_VOR as Vor;
+        use std::mem::offset_of;
+
+        let ndb = NdbEntry::from_bytes(bytes);
+        Self {
+            icao: ndb.icao,
+            region: ndb.region,
+            latitude: ndb.latitude,
+            longitude: ndb.longitude,
+            altitude: ndb.altitude,
+            magvar: ndb.magvar,
+            frequency: ndb.frequency,
+            flags: read_u32(bytes, offset_of!(Vor, Flags)),
+            localizer: read_f32(bytes, offset_of!(Vor, fLocalizer)),
+            glide_latitude: read_f64(bytes, offset_of!(Vor, GlideLat)),
+            glide_longitude: read_f64(bytes, offset_of!(Vor, GlideLon)),
+            glide_altitude: read_f64(bytes, offset_of!(Vor, GlideAlt)),
+            glide_slope_angle: read_f32(bytes, offset_of!(Vor, fGlideSlopeAngle)),
+        }
+    }
+
+    fn ident(&self) -> (&str, &str) {
+        (&self.icao, &self.region)
+    }
+}
+
+/// A change to the facilities in the cache, as delivered by a facility subscription
+#[derive(Debug, Clone, PartialEq)]
+pub enum FacilityChange<T> {
+    /// The facility came into range
+    Added(T),
+    /// The facility left the cache
+    Removed(T),
+}
+
+/* #endregion */
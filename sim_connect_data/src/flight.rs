@@ -0,0 +1,363 @@
+//! MSFS and FSX `.FLT` flight files, as named by `SystemEventData::FlightLoaded` and
+//! `SystemEventData::FlightSaved`
+
+use crate::{
+    error::{SimConnectError, SimConnectResult},
+    flight_plan::{format_angle, parse_angle},
+    sim_objects::{InitAirspeed, InitPosition},
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, path::Path, str::FromStr};
+
+const FILE_TYPE: &str = ".FLT";
+
+/// A flight file: named sections of `key=value` entries, kept in the order they were read.
+///
+/// Everything in the file is kept, so a flight read and written back only differs where it was
+/// changed. Section names and keys are matched without regard to case, as the simulator does.
+///
+/// # Example
+///
+/// ```
+/// use sim_connect_data::{flight::Flight, sim_objects::InitPosition};
+///
+/// let mut flight = Flight::default();
+/// flight.set_title("Engine failure on the climb out");
+/// flight.set_aircraft_title("Cessna Skyhawk G1000 Asobo");
+/// flight.set_position(&InitPosition::new(47.4638, -122.3079, 433.0).on_ground());
+///
+/// let written = flight.to_string();
+/// assert!(written.contains("[SimVars.0]"));
+/// let read: Flight = written.parse().unwrap();
+/// assert_eq!(read.position().unwrap().unwrap().heading, 0.0);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Flight {
+    sections: Vec<FlightSection>,
+}
+
+impl Flight {
+    /// Reads the `.FLT` file at `path`
+    pub fn from_file(path: impl AsRef<Path>) -> SimConnectResult<Self> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    /// Writes the flight to a `.FLT` file at `path`, which can then be loaded with
+    /// `SimConnect::flight_load`
+    pub fn save(&self, path: impl AsRef<Path>) -> SimConnectResult<()> {
+        std::fs::write(path, self.to_string())?;
+        Ok(())
+    }
+
+    pub fn sections(&self) -> impl Iterator<Item = &FlightSection> {
+        self.sections.iter()
+    }
+
+    pub fn section(&self, name: &str) -> Option<&FlightSection> {
+        self.sections
+            .iter()
+            .find(|section| section.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn section_mut(&mut self, name: &str) -> Option<&mut FlightSection> {
+        self.sections
+            .iter_mut()
+            .find(|section| section.name.eq_ignore_ascii_case(name))
+    }
+
+    /// The section `name`, added to the end of the file if there is none
+    pub fn section_or_insert(&mut self, name: &str) -> &mut FlightSection {
+        let index = match self
+            .sections
+            .iter()
+            .position(|section| section.name.eq_ignore_ascii_case(name))
+        {
+            Some(index) => index,
+            None => {
+                self.sections.push(FlightSection::new(name));
+                self.sections.len() - 1
+            }
+        };
+        &mut self.sections[index]
+    }
+
+    pub fn remove_section(&mut self, name: &str) -> Option<FlightSection> {
+        let index = self
+            .sections
+            .iter()
+            .position(|section| section.name.eq_ignore_ascii_case(name))?;
+        Some(self.sections.remove(index))
+    }
+
+    /// The value of `key` in `section`
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.section(section)?.get(key)
+    }
+
+    /// Sets `key` in `section`, adding either if missing
+    pub fn set(&mut self, section: &str, key: &str, value: impl Display) {
+        self.section_or_insert(section).set(key, value);
+    }
+
+    /// The title the flight is listed with, from `[Main]`
+    pub fn title(&self) -> Option<&str> {
+        self.get("Main", "Title")
+    }
+
+    pub fn set_title(&mut self, title: &str) {
+        self.set("Main", "Title", title);
+    }
+
+    /// From `[Main]`
+    pub fn description(&self) -> Option<&str> {
+        self.get("Main", "Description")
+    }
+
+    pub fn set_description(&mut self, description: &str) {
+        self.set("Main", "Description", description);
+    }
+
+    /// The `title` of the user's aircraft in its `aircraft.cfg`, from `[Sim.0]`
+    pub fn aircraft_title(&self) -> Option<&str> {
+        self.get("Sim.0", "Sim")
+    }
+
+    pub fn set_aircraft_title(&mut self, title: &str) {
+        self.set("Sim.0", "Sim", title);
+    }
+
+    /// Where the user's aircraft starts, from `[SimVars.0]`. The file doesn't store the
+    /// airspeed the way `InitPosition` does, so it is always 0.
+    ///
+    /// `None` if the flight has no latitude or longitude.
+    pub fn position(&self) -> SimConnectResult<Option<InitPosition>> {
+        let Some(vars) = self.section("SimVars.0") else {
+            return Ok(None);
+        };
+        let (Some(latitude), Some(longitude)) = (vars.get("Latitude"), vars.get("Longitude"))
+        else {
+            return Ok(None);
+        };
+
+        let angle = |text: &str, positive, negative| {
+            parse_angle(text, positive, negative)
+                .ok_or_else(|| invalid(format!("malformed angle {text:?}")))
+        };
+        let number = |key| {
+            vars.get(key)
+                .map(|text| parse_number(key, text))
+                .transpose()
+                .map(Option::unwrap_or_default)
+        };
+        Ok(Some(InitPosition {
+            latitude: angle(latitude, 'N', 'S')?,
+            longitude: angle(longitude, 'E', 'W')?,
+            altitude: number("Altitude")?,
+            pitch: number("Pitch")?,
+            bank: number("Bank")?,
+            heading: number("Heading")?,
+            on_ground: vars
+                .get("SimOnGround")
+                .is_some_and(|text| text.eq_ignore_ascii_case("True")),
+            airspeed: InitAirspeed::Knots(0),
+        }))
+    }
+
+    /// Moves the user's aircraft. `position.airspeed` is not stored.
+    pub fn set_position(&mut self, position: &InitPosition) {
+        let vars = self.section_or_insert("SimVars.0");
+        vars.set("Latitude", format_angle(position.latitude, 'N', 'S'));
+        vars.set("Longitude", format_angle(position.longitude, 'E', 'W'));
+        vars.set("Altitude", format!("{:+010.2}", position.altitude));
+        vars.set("Pitch", position.pitch);
+        vars.set("Bank", position.bank);
+        vars.set("Heading", position.heading);
+        vars.set(
+            "SimOnGround",
+            if position.on_ground { "True" } else { "False" },
+        );
+    }
+
+    /// The level of each fuel tank of the user's aircraft in percent, from `[Fuel.0]`,
+    /// e.g. `("LeftMain", 75.0)`
+    pub fn fuel(&self) -> SimConnectResult<Vec<(String, f64)>> {
+        let Some(fuel) = self.section("Fuel.0") else {
+            return Ok(Vec::new());
+        };
+        fuel.entries()
+            .map(|(tank, value)| {
+                // Followed by the capacity of the tank in some versions
+                let level = value.split(',').next().unwrap_or_default();
+                Ok((tank.to_owned(), parse_number(tank, level)?))
+            })
+            .collect()
+    }
+
+    /// Sets the level of a fuel tank in percent, keeping any capacity written after it
+    pub fn set_fuel(&mut self, tank: &str, percent: f64) {
+        let fuel = self.section_or_insert("Fuel.0");
+        let level = match fuel.get(tank).and_then(|value| value.split_once(',')) {
+            Some((_, capacity)) => format!("{percent:.6},{capacity}"),
+            None => format!("{percent:.6}"),
+        };
+        fuel.set(tank, level);
+    }
+
+    /// The date and time the flight starts at, from `[DateTimeSeason]`
+    ///
+    /// `None` if the flight has no year or day.
+    pub fn date_time(&self) -> SimConnectResult<Option<FlightTime>> {
+        let Some(time) = self.section("DateTimeSeason") else {
+            return Ok(None);
+        };
+        let (Some(year), Some(day)) = (time.get("Year"), time.get("Day")) else {
+            return Ok(None);
+        };
+
+        let number = |key| {
+            time.get(key)
+                .map(|text| parse_number(key, text))
+                .transpose()
+                .map(Option::unwrap_or_default)
+        };
+        Ok(Some(FlightTime {
+            year: parse_number("Year", year)?,
+            day: parse_number("Day", day)?,
+            hours: number("Hours")?,
+            minutes: number("Minutes")?,
+            seconds: number("Seconds")?,
+        }))
+    }
+
+    /// Sets when the flight starts. The season is left as it is.
+    pub fn set_date_time(&mut self, time: FlightTime) {
+        let section = self.section_or_insert("DateTimeSeason");
+        section.set("Year", time.year);
+        section.set("Day", time.day);
+        section.set("Hours", time.hours);
+        section.set("Minutes", time.minutes);
+        section.set("Seconds", time.seconds);
+    }
+}
+
+impl FromStr for Flight {
+    type Err = SimConnectError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut flight = Flight::default();
+        for (number, line) in text.trim_start_matches('\u{feff}').lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') {
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                flight.sections.push(FlightSection::new(name.trim()));
+                continue;
+            }
+            let (Some(section), Some((key, value))) =
+                (flight.sections.last_mut(), line.split_once('='))
+            else {
+                return Err(invalid(format!("line {}: {line:?}", number + 1)));
+            };
+            section
+                .entries
+                .push((key.trim().to_owned(), value.trim().to_owned()));
+        }
+        Ok(flight)
+    }
+}
+
+impl Display for Flight {
+    /// Formats the flight as the contents of a `.FLT` file, with the simulator's `\r\n` line
+    /// endings
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for section in &self.sections {
+            write!(f, "[{}]\r\n", section.name)?;
+            for (key, value) in &section.entries {
+                write!(f, "{key}={value}\r\n")?;
+            }
+            f.write_str("\r\n")?;
+        }
+        Ok(())
+    }
+}
+
+/// A `[section]` of a flight file, such as `[Main]` or `[SimVars.0]`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlightSection {
+    name: String,
+    entries: Vec<(String, String)>,
+}
+
+impl FlightSection {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Every `key=value` entry, in order
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(entry, _)| entry.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Replaces the value of `key`, or adds it to the end of the section
+    pub fn set(&mut self, key: &str, value: impl Display) {
+        let value = value.to_string();
+        match self
+            .entries
+            .iter_mut()
+            .find(|(entry, _)| entry.eq_ignore_ascii_case(key))
+        {
+            Some((_, existing)) => *existing = value,
+            None => self.entries.push((key.to_owned(), value)),
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        let index = self
+            .entries
+            .iter()
+            .position(|(entry, _)| entry.eq_ignore_ascii_case(key))?;
+        Some(self.entries.remove(index).1)
+    }
+}
+
+/// When a flight starts, as written in `[DateTimeSeason]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlightTime {
+    pub year: i32,
+    /// Day of the year, starting at 1 for January 1st
+    pub day: u16,
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+}
+
+fn parse_number<T: FromStr>(key: &str, text: &str) -> SimConnectResult<T> {
+    text.trim()
+        .parse()
+        .map_err(|_| invalid(format!("{key} {text:?} is not a number")))
+}
+
+fn invalid(reason: impl Display) -> SimConnectError {
+    SimConnectError::InvalidFile {
+        file_type: FILE_TYPE,
+        reason: reason.to_string(),
+    }
+}
@@ -0,0 +1,582 @@
+//! MSFS and FSX `.PLN` flight plans, as named by `RecvSystemState::flight_plan` and
+//! `SystemEventData::FlightPlanActivated`
+
+use crate::error::{SimConnectError, SimConnectResult};
+use quick_xml::{
+    escape::partial_escape,
+    events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event},
+    Reader, Writer,
+};
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, path::Path, str::FromStr};
+
+const FILE_TYPE: &str = ".PLN";
+
+/// A flight plan read from, or to be written to, a `.PLN` file.
+///
+/// Elements this crate doesn't know about are skipped when reading, so they are not written
+/// back out.
+///
+/// # Example
+///
+/// ```
+/// use sim_connect_data::flight_plan::{FlightPlan, RouteType};
+///
+/// let plan: FlightPlan = r#"
+///     <SimBase.Document Type="AceXML" version="1,0">
+///         <FlightPlan.FlightPlan>
+///             <Title>KSEA to KPDX</Title>
+///             <FPType>IFR</FPType>
+///             <RouteType>HighAlt</RouteType>
+///             <CruisingAlt>11000</CruisingAlt>
+///             <DepartureID>KSEA</DepartureID>
+///             <DestinationID>KPDX</DestinationID>
+///         </FlightPlan.FlightPlan>
+///     </SimBase.Document>"#
+///     .parse()
+///     .unwrap();
+/// assert_eq!(plan.route_type, RouteType::HighAltitude);
+/// assert_eq!(plan.destination.icao, "KPDX");
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlightPlan {
+    pub title: String,
+    pub description: String,
+    pub plan_type: FlightPlanType,
+    pub route_type: RouteType,
+    /// Feet above mean sea level
+    pub cruising_altitude: f64,
+    pub departure: PlanAirport,
+    /// The runway or parking spot the flight departs from, e.g. `16L`
+    pub departure_position: Option<String>,
+    pub destination: PlanAirport,
+    /// The version of the simulator which wrote the plan
+    pub app_version: Option<AppVersion>,
+    /// Every waypoint of the route in order, starting and ending with the airports
+    pub waypoints: Vec<AtcWaypoint>,
+}
+
+impl FlightPlan {
+    /// A direct IFR plan between two airports, with no waypoints
+    pub fn new(departure: &str, destination: &str, cruising_altitude: f64) -> Self {
+        Self {
+            title: format!("{departure} to {destination}"),
+            description: format!("{departure}, {destination}"),
+            plan_type: FlightPlanType::Ifr,
+            route_type: RouteType::Direct,
+            cruising_altitude,
+            departure: PlanAirport::new(departure),
+            departure_position: None,
+            destination: PlanAirport::new(destination),
+            app_version: None,
+            waypoints: Vec::new(),
+        }
+    }
+
+    /// Reads the `.PLN` file at `path`
+    pub fn from_file(path: impl AsRef<Path>) -> SimConnectResult<Self> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    /// Writes the plan to a `.PLN` file at `path`
+    pub fn save(&self, path: impl AsRef<Path>) -> SimConnectResult<()> {
+        std::fs::write(path, self.to_string())?;
+        Ok(())
+    }
+
+    /// The standard instrument departure the route starts with
+    pub fn sid(&self) -> Option<&str> {
+        self.waypoints
+            .iter()
+            .find_map(|waypoint| waypoint.departure_procedure.as_deref())
+    }
+
+    /// The standard terminal arrival the route ends with
+    pub fn star(&self) -> Option<&str> {
+        self.waypoints
+            .iter()
+            .find_map(|waypoint| waypoint.arrival_procedure.as_deref())
+    }
+
+    fn read_field(&mut self, name: &str, text: &str) -> SimConnectResult<()> {
+        match name {
+            "Title" => self.title = text.to_owned(),
+            "Descr" => self.description = text.to_owned(),
+            "FPType" => self.plan_type = text.parse()?,
+            "RouteType" => self.route_type = text.parse()?,
+            "CruisingAlt" => self.cruising_altitude = parse_number(name, text)?,
+            "DepartureID" => self.departure.icao = text.to_owned(),
+            "DepartureLLA" => self.departure.position = Some(text.parse()?),
+            "DepartureName" => self.departure.name = Some(text.to_owned()),
+            "DeparturePosition" => self.departure_position = Some(text.to_owned()),
+            "DestinationID" => self.destination.icao = text.to_owned(),
+            "DestinationLLA" => self.destination.position = Some(text.parse()?),
+            "DestinationName" => self.destination.name = Some(text.to_owned()),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn write(&self, writer: &mut Writer<Vec<u8>>) -> quick_xml::Result<()> {
+        writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+        writer.write_event(Event::Start(
+            BytesStart::new("SimBase.Document")
+                .with_attributes([("Type", "AceXML"), ("version", "1,0")]),
+        ))?;
+        write_text(writer, "Descr", "AceXML Document")?;
+        writer.write_event(Event::Start(BytesStart::new("FlightPlan.FlightPlan")))?;
+
+        write_text(writer, "Title", &self.title)?;
+        write_text(writer, "FPType", &self.plan_type.to_string())?;
+        write_text(writer, "RouteType", &self.route_type.to_string())?;
+        write_text(
+            writer,
+            "CruisingAlt",
+            &format!("{:.3}", self.cruising_altitude),
+        )?;
+        write_text(writer, "DepartureID", &self.departure.icao)?;
+        if let Some(position) = &self.departure.position {
+            write_text(writer, "DepartureLLA", &position.to_string())?;
+        }
+        write_text(writer, "DestinationID", &self.destination.icao)?;
+        if let Some(position) = &self.destination.position {
+            write_text(writer, "DestinationLLA", &position.to_string())?;
+        }
+        write_text(writer, "Descr", &self.description)?;
+        if let Some(position) = &self.departure_position {
+            write_text(writer, "DeparturePosition", position)?;
+        }
+        if let Some(name) = &self.departure.name {
+            write_text(writer, "DepartureName", name)?;
+        }
+        if let Some(name) = &self.destination.name {
+            write_text(writer, "DestinationName", name)?;
+        }
+        if let Some(version) = &self.app_version {
+            writer.write_event(Event::Start(BytesStart::new("AppVersion")))?;
+            write_text(writer, "AppVersionMajor", &version.major.to_string())?;
+            write_text(writer, "AppVersionBuild", &version.build.to_string())?;
+            writer.write_event(Event::End(BytesEnd::new("AppVersion")))?;
+        }
+        for waypoint in &self.waypoints {
+            waypoint.write(writer)?;
+        }
+
+        writer.write_event(Event::End(BytesEnd::new("FlightPlan.FlightPlan")))?;
+        writer.write_event(Event::End(BytesEnd::new("SimBase.Document")))
+    }
+}
+
+impl FromStr for FlightPlan {
+    type Err = SimConnectError;
+
+    fn from_str(xml: &str) -> Result<Self, Self::Err> {
+        let mut reader = Reader::from_str(xml);
+        reader.trim_text(true);
+
+        let mut plan = FlightPlan {
+            title: String::new(),
+            description: String::new(),
+            ..FlightPlan::new("", "", 0.0)
+        };
+        let mut found_plan = false;
+        let mut path: Vec<String> = Vec::new();
+        let mut waypoint: Option<AtcWaypoint> = None;
+
+        loop {
+            match reader.read_event().map_err(invalid)? {
+                Event::Start(element) => {
+                    let name = String::from_utf8_lossy(element.name().as_ref()).into_owned();
+                    match name.as_str() {
+                        "FlightPlan.FlightPlan" => found_plan = true,
+                        "ATCWaypoint" => {
+                            let id = element
+                                .try_get_attribute("id")
+                                .map_err(invalid)?
+                                .map(|id| id.unescape_value().map(|id| id.into_owned()))
+                                .transpose()
+                                .map_err(invalid)?
+                                .unwrap_or_default();
+                            waypoint = Some(AtcWaypoint::new(&id, WaypointType::User));
+                        }
+                        _ => {}
+                    }
+                    path.push(name);
+                }
+                Event::End(element) => {
+                    path.pop();
+                    if element.name().as_ref() == b"ATCWaypoint" {
+                        plan.waypoints.extend(waypoint.take());
+                    }
+                }
+                Event::Text(text) => {
+                    let text = text.unescape().map_err(invalid)?;
+                    let [.., parent, name] = path.as_slice() else {
+                        continue;
+                    };
+                    match waypoint.as_mut() {
+                        Some(waypoint) => waypoint.read_field(parent, name, &text)?,
+                        None if parent == "FlightPlan.FlightPlan" => {
+                            plan.read_field(name, &text)?
+                        }
+                        None if parent == "AppVersion" => plan
+                            .app_version
+                            .get_or_insert(AppVersion { major: 0, build: 0 })
+                            .read_field(name, &text)?,
+                        None => {}
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+
+        if !found_plan {
+            return Err(invalid("no FlightPlan.FlightPlan element"));
+        }
+        Ok(plan)
+    }
+}
+
+impl Display for FlightPlan {
+    /// Formats the plan as the contents of a `.PLN` file
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut writer = Writer::new_with_indent(Vec::new(), b' ', 4);
+        self.write(&mut writer).map_err(|_| std::fmt::Error)?;
+        f.write_str(&String::from_utf8_lossy(&writer.into_inner()))
+    }
+}
+
+/// Whether the flight is planned under instrument or visual flight rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FlightPlanType {
+    Ifr,
+    Vfr,
+}
+
+impl FromStr for FlightPlanType {
+    type Err = SimConnectError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "IFR" => Ok(Self::Ifr),
+            "VFR" => Ok(Self::Vfr),
+            _ => Err(invalid(format!("unknown FPType {text:?}"))),
+        }
+    }
+}
+
+impl Display for FlightPlanType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Ifr => "IFR",
+            Self::Vfr => "VFR",
+        })
+    }
+}
+
+/// How the route between the airports was planned
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RouteType {
+    Direct,
+    /// VOR to VOR
+    Vor,
+    /// Along low altitude (victor) airways
+    LowAltitude,
+    /// Along high altitude (jet) airways
+    HighAltitude,
+}
+
+impl FromStr for RouteType {
+    type Err = SimConnectError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "Direct" => Ok(Self::Direct),
+            "VOR" => Ok(Self::Vor),
+            "LowAlt" => Ok(Self::LowAltitude),
+            "HighAlt" => Ok(Self::HighAltitude),
+            _ => Err(invalid(format!("unknown RouteType {text:?}"))),
+        }
+    }
+}
+
+impl Display for RouteType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Direct => "Direct",
+            Self::Vor => "VOR",
+            Self::LowAltitude => "LowAlt",
+            Self::HighAltitude => "HighAlt",
+        })
+    }
+}
+
+/// The departure or destination airport of a plan
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlanAirport {
+    /// e.g. `KSEA`
+    pub icao: String,
+    pub position: Option<WorldPosition>,
+    pub name: Option<String>,
+}
+
+impl PlanAirport {
+    pub fn new(icao: &str) -> Self {
+        Self {
+            icao: icao.to_owned(),
+            position: None,
+            name: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppVersion {
+    pub major: u32,
+    pub build: u32,
+}
+
+impl AppVersion {
+    fn read_field(&mut self, name: &str, text: &str) -> SimConnectResult<()> {
+        match name {
+            "AppVersionMajor" => self.major = parse_number(name, text)?,
+            "AppVersionBuild" => self.build = parse_number(name, text)?,
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// A point on the route
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AtcWaypoint {
+    /// The name the waypoint is shown with, e.g. `SUMMA`
+    pub id: String,
+    pub waypoint_type: WaypointType,
+    pub position: WorldPosition,
+    /// The airway the route follows to reach this waypoint
+    pub airway: Option<String>,
+    /// The SID this waypoint is part of
+    pub departure_procedure: Option<String>,
+    /// The STAR this waypoint is part of
+    pub arrival_procedure: Option<String>,
+    pub icao: Option<WaypointIcao>,
+}
+
+impl AtcWaypoint {
+    /// A waypoint at 0° N 0° E, to be moved with `position`
+    pub fn new(id: &str, waypoint_type: WaypointType) -> Self {
+        Self {
+            id: id.to_owned(),
+            waypoint_type,
+            position: WorldPosition::default(),
+            airway: None,
+            departure_procedure: None,
+            arrival_procedure: None,
+            icao: None,
+        }
+    }
+
+    fn read_field(&mut self, parent: &str, name: &str, text: &str) -> SimConnectResult<()> {
+        if parent == "ICAO" {
+            let icao = self.icao.get_or_insert_with(WaypointIcao::default);
+            match name {
+                "ICAORegion" => icao.region = Some(text.to_owned()),
+                "ICAOIdent" => icao.ident = text.to_owned(),
+                "ICAOAirport" => icao.airport = Some(text.to_owned()),
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        match name {
+            "ATCWaypointType" => self.waypoint_type = text.parse()?,
+            "WorldPosition" => self.position = text.parse()?,
+            "ATCAirway" => self.airway = Some(text.to_owned()),
+            "DepartureFP" => self.departure_procedure = Some(text.to_owned()),
+            "ArrivalFP" => self.arrival_procedure = Some(text.to_owned()),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn write(&self, writer: &mut Writer<Vec<u8>>) -> quick_xml::Result<()> {
+        writer.write_event(Event::Start(
+            BytesStart::new("ATCWaypoint").with_attributes([("id", self.id.as_str())]),
+        ))?;
+        write_text(writer, "ATCWaypointType", &self.waypoint_type.to_string())?;
+        write_text(writer, "WorldPosition", &self.position.to_string())?;
+        if let Some(airway) = &self.airway {
+            write_text(writer, "ATCAirway", airway)?;
+        }
+        if let Some(procedure) = &self.departure_procedure {
+            write_text(writer, "DepartureFP", procedure)?;
+        }
+        if let Some(procedure) = &self.arrival_procedure {
+            write_text(writer, "ArrivalFP", procedure)?;
+        }
+        if let Some(icao) = &self.icao {
+            writer.write_event(Event::Start(BytesStart::new("ICAO")))?;
+            if let Some(region) = &icao.region {
+                write_text(writer, "ICAORegion", region)?;
+            }
+            write_text(writer, "ICAOIdent", &icao.ident)?;
+            if let Some(airport) = &icao.airport {
+                write_text(writer, "ICAOAirport", airport)?;
+            }
+            writer.write_event(Event::End(BytesEnd::new("ICAO")))?;
+        }
+        writer.write_event(Event::End(BytesEnd::new("ATCWaypoint")))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WaypointType {
+    Airport,
+    Intersection,
+    Vor,
+    Ndb,
+    /// A point placed by the user
+    User,
+    Atc,
+}
+
+impl FromStr for WaypointType {
+    type Err = SimConnectError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text {
+            "Airport" => Ok(Self::Airport),
+            "Intersection" => Ok(Self::Intersection),
+            "VOR" => Ok(Self::Vor),
+            "NDB" => Ok(Self::Ndb),
+            "User" => Ok(Self::User),
+            "ATC" => Ok(Self::Atc),
+            _ => Err(invalid(format!("unknown ATCWaypointType {text:?}"))),
+        }
+    }
+}
+
+impl Display for WaypointType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Airport => "Airport",
+            Self::Intersection => "Intersection",
+            Self::Vor => "VOR",
+            Self::Ndb => "NDB",
+            Self::User => "User",
+            Self::Atc => "ATC",
+        })
+    }
+}
+
+/// Identifies the facility a waypoint stands for in the navigation data
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WaypointIcao {
+    pub region: Option<String>,
+    pub ident: String,
+    /// The airport a terminal waypoint belongs to
+    pub airport: Option<String>,
+}
+
+/// A position as written in `.PLN` files, e.g. `N47° 26' 56.94",W122° 18' 33.51",+000433.00`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct WorldPosition {
+    /// Degrees, north positive
+    pub latitude: f64,
+    /// Degrees, east positive
+    pub longitude: f64,
+    /// Feet above mean sea level
+    pub altitude: f64,
+}
+
+impl FromStr for WorldPosition {
+    type Err = SimConnectError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut parts = text.split(',');
+        let (Some(latitude), Some(longitude), Some(altitude), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid(format!("malformed position {text:?}")));
+        };
+
+        let angle = |text: &str, positive, negative| {
+            parse_angle(text, positive, negative)
+                .ok_or_else(|| invalid(format!("malformed angle {text:?}")))
+        };
+        Ok(Self {
+            latitude: angle(latitude, 'N', 'S')?,
+            longitude: angle(longitude, 'E', 'W')?,
+            altitude: parse_number("altitude", altitude)?,
+        })
+    }
+}
+
+impl Display for WorldPosition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{},{},{:+010.2}",
+            format_angle(self.latitude, 'N', 'S'),
+            format_angle(self.longitude, 'E', 'W'),
+            self.altitude
+        )
+    }
+}
+
+/// Parses an angle such as `N47° 26' 56.94"`, as `.PLN` and `.FLT` files write them
+pub(crate) fn parse_angle(text: &str, positive: char, negative: char) -> Option<f64> {
+    let text = text.trim();
+    let sign = match text.chars().next()? {
+        hemisphere if hemisphere == positive => 1.0,
+        hemisphere if hemisphere == negative => -1.0,
+        _ => return None,
+    };
+
+    let mut angle = 0.0;
+    let mut scale = 1.0;
+    for component in text[1..].split(['°', '\'', '"']).map(str::trim) {
+        if component.is_empty() {
+            continue;
+        }
+        if scale < 1.0 / 3600.0 {
+            return None;
+        }
+        angle += component.parse::<f64>().ok()? * scale;
+        scale /= 60.0;
+    }
+    Some(sign * angle)
+}
+
+pub(crate) fn format_angle(angle: f64, positive: char, negative: char) -> String {
+    let hemisphere = if angle < 0.0 { negative } else { positive };
+    // In hundredths of a second, so rounding never leaves 60 seconds
+    let hundredths = (angle.abs() * 360_000.0).round() as u64;
+    let degrees = hundredths / 360_000;
+    let minutes = hundredths / 6_000 % 60;
+    let seconds = (hundredths % 6_000) as f64 / 100.0;
+    format!("{hemisphere}{degrees}° {minutes}' {seconds:.2}\"")
+}
+
+fn parse_number<T: FromStr>(name: &str, text: &str) -> SimConnectResult<T> {
+    text.trim()
+        .parse()
+        .map_err(|_| invalid(format!("{name} {text:?} is not a number")))
+}
+
+fn write_text(writer: &mut Writer<Vec<u8>>, name: &str, text: &str) -> quick_xml::Result<()> {
+    // Only what must be escaped is, so positions keep their quotes as the simulator writes them
+    writer
+        .create_element(name)
+        .write_text_content(BytesText::from_escaped(partial_escape(text)))?;
+    Ok(())
+}
+
+fn invalid(reason: impl Display) -> SimConnectError {
+    SimConnectError::InvalidFile {
+        file_type: FILE_TYPE,
+        reason: reason.to_string(),
+    }
+}
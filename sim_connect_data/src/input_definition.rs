@@ -0,0 +1,260 @@
+use crate::error::{SimConnectError, SimConnectResult};
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+/// A key held down together with the key of a chord
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Modifier {
+    Shift,
+    Ctrl,
+    Alt,
+    Tab,
+}
+
+impl Modifier {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Shift => "Shift",
+            Self::Ctrl => "Ctrl",
+            Self::Alt => "Alt",
+            Self::Tab => "Tab",
+        }
+    }
+
+    fn parse(token: &str) -> Option<Self> {
+        match token.to_ascii_lowercase().as_str() {
+            "shift" => Some(Self::Shift),
+            "ctrl" | "control" => Some(Self::Ctrl),
+            "alt" => Some(Self::Alt),
+            "tab" => Some(Self::Tab),
+            _ => None,
+        }
+    }
+}
+
+/// A button or axis of a joystick
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JoystickInput {
+    Button(u32),
+    XAxis,
+    YAxis,
+    ZAxis,
+    XRotation,
+    YRotation,
+    ZRotation,
+    Slider,
+    Pov,
+}
+
+/// Every joystick input but buttons, by the name SimConnect knows it by
+const JOYSTICK_AXES: [(JoystickInput, &str); 8] = [
+    (JoystickInput::XAxis, "XAxis"),
+    (JoystickInput::YAxis, "YAxis"),
+    (JoystickInput::ZAxis, "ZAxis"),
+    (JoystickInput::XRotation, "XRotation"),
+    (JoystickInput::YRotation, "YRotation"),
+    (JoystickInput::ZRotation, "ZRotation"),
+    (JoystickInput::Slider, "Slider"),
+    (JoystickInput::Pov, "POV"),
+];
+
+impl JoystickInput {
+    /// Whether the input reports its position, rather than being pressed and released
+    pub fn is_analog(&self) -> bool {
+        !matches!(self, Self::Button(_))
+    }
+}
+
+/// Keys known by name rather than by the character they type
+const NAMED_KEYS: &[&str] = &[
+    "Esc",
+    "Space",
+    "Enter",
+    "Backspace",
+    "Tab",
+    "Insert",
+    "Delete",
+    "Home",
+    "End",
+    "PageUp",
+    "PageDown",
+    "Up",
+    "Down",
+    "Left",
+    "Right",
+    "Pause",
+    "Num_0",
+    "Num_1",
+    "Num_2",
+    "Num_3",
+    "Num_4",
+    "Num_5",
+    "Num_6",
+    "Num_7",
+    "Num_8",
+    "Num_9",
+    "Num_Plus",
+    "Num_Minus",
+    "Num_Star",
+    "Num_Slash",
+    "Num_Del",
+    "Num_Enter",
+];
+
+/// Characters which are keys by themselves, besides letters and digits
+const PUNCTUATION_KEYS: &str = "`-=[]\\;',./";
+
+/// The highest function key, `F24`
+const FUNCTION_KEYS: u32 = 24;
+
+/// A keyboard chord or joystick input, as mapped to client events by
+/// `SimConnect_MapInputEventToClientEvent`.
+///
+/// Definitions are parsed case-insensitively from SimConnect's grammar, e.g. `"shift+ctrl+g"`,
+/// `"joystick:0:button:3"` or `"joystick:1:XAxis"`, and displayed in the form SimConnect
+/// expects, e.g. `Shift+Ctrl+G`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum InputDefinition {
+    /// A key, held down together with `modifiers`. Modifiers are kept in a fixed order, so
+    /// chords compare equal however they were written.
+    Keys {
+        modifiers: Vec<Modifier>,
+        key: String,
+    },
+    /// An input of the joystick numbered `joystick`
+    Joystick { joystick: u32, input: JoystickInput },
+}
+
+impl InputDefinition {
+    /// Whether the input reports its position, rather than being pressed and released
+    pub fn is_analog(&self) -> bool {
+        matches!(self, Self::Joystick { input, .. } if input.is_analog())
+    }
+}
+
+impl FromStr for InputDefinition {
+    type Err = SimConnectError;
+
+    fn from_str(definition: &str) -> SimConnectResult<Self> {
+        let trimmed = definition.trim();
+        let parsed = match trimmed.split_once(':') {
+            _ if trimmed.is_empty() => Err("the definition is empty".to_owned()),
+            Some((device, rest)) if device.eq_ignore_ascii_case("joystick") => parse_joystick(rest),
+            _ => parse_keys(trimmed),
+        };
+        parsed.map_err(|reason| SimConnectError::InvalidInputDefinition {
+            definition: definition.to_owned(),
+            reason,
+        })
+    }
+}
+
+impl Display for InputDefinition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Keys { modifiers, key } => {
+                for modifier in modifiers {
+                    write!(f, "{}+", modifier.name())?;
+                }
+                f.write_str(key)
+            }
+            Self::Joystick {
+                joystick,
+                input: JoystickInput::Button(button),
+            } => write!(f, "joystick:{joystick}:button:{button}"),
+            Self::Joystick { joystick, input } => {
+                let (_, name) = JOYSTICK_AXES
+                    .iter()
+                    .find(|(axis, _)| axis == input)
+                    .expect("every joystick input but buttons is an axis");
+                write!(f, "joystick:{joystick}:{name}")
+            }
+        }
+    }
+}
+
+/// Parses a plain decimal number. `u32::from_str` would also take a leading `+`.
+fn parse_number(token: &str) -> Option<u32> {
+    if token.is_empty() || !token.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+/// Parses what follows `joystick:`, i.e. `<n>:button:<m>` or `<n>:<axis>`
+fn parse_joystick(rest: &str) -> Result<InputDefinition, String> {
+    let parts: Vec<&str> = rest.split(':').collect();
+    let joystick =
+        parse_number(parts[0]).ok_or_else(|| format!("{:?} is not a joystick number", parts[0]))?;
+
+    let input = match parts[1..] {
+        [kind, button] if kind.eq_ignore_ascii_case("button") => JoystickInput::Button(
+            parse_number(button).ok_or_else(|| format!("{button:?} is not a button number"))?,
+        ),
+        [axis] => JOYSTICK_AXES
+            .iter()
+            .find(|(_, name)| name.eq_ignore_ascii_case(axis))
+            .map(|(axis, _)| *axis)
+            .ok_or_else(|| format!("{axis:?} is not a joystick axis"))?,
+        _ => {
+            return Err("expected joystick:<n>:button:<m> or joystick:<n>:<axis>".to_owned());
+        }
+    };
+
+    Ok(InputDefinition::Joystick { joystick, input })
+}
+
+/// Parses a key chord such as `Shift+Ctrl+G`
+fn parse_keys(chord: &str) -> Result<InputDefinition, String> {
+    let tokens: Vec<&str> = chord.split('+').map(str::trim).collect();
+    if tokens.iter().any(|token| token.is_empty()) {
+        return Err("a key in the chord is empty".to_owned());
+    }
+    let (key_token, modifier_tokens) = tokens.split_last().expect("split yields a token");
+
+    let mut modifiers = Vec::with_capacity(modifier_tokens.len());
+    for token in modifier_tokens {
+        let modifier = Modifier::parse(token).ok_or_else(|| {
+            format!("{token:?} is not a modifier, expected Shift, Ctrl, Alt or Tab")
+        })?;
+        if modifiers.contains(&modifier) {
+            return Err(format!("{} is held more than once", modifier.name()));
+        }
+        modifiers.push(modifier);
+    }
+    modifiers.sort();
+
+    let key = match parse_key(key_token) {
+        Some(key) => key,
+        None if Modifier::parse(key_token).is_some() => {
+            return Err("the chord ends in a modifier rather than a key".to_owned());
+        }
+        None => return Err(format!("{key_token:?} is not a key")),
+    };
+
+    Ok(InputDefinition::Keys { modifiers, key })
+}
+
+/// Returns the name SimConnect knows a key by
+fn parse_key(token: &str) -> Option<String> {
+    let mut chars = token.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return (c.is_ascii_alphanumeric() || PUNCTUATION_KEYS.contains(c))
+            .then(|| c.to_ascii_uppercase().to_string());
+    }
+
+    let function_key = token
+        .strip_prefix(['F', 'f'])
+        .and_then(parse_number)
+        .filter(|number| (1..=FUNCTION_KEYS).contains(number));
+    if let Some(number) = function_key {
+        return Some(format!("F{number}"));
+    }
+
+    NAMED_KEYS
+        .iter()
+        .find(|name| name.eq_ignore_ascii_case(token))
+        .map(|name| name.to_string())
+}
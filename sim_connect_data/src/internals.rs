@@ -1,4 +1,4 @@
-use std::ffi::CString;
+use std::ffi::{CString, NulError};
 
 pub trait IterEnum {
     type Item;
@@ -6,5 +6,10 @@ pub trait IterEnum {
 }
 
 pub trait ToSimConnect {
+    /// The name SimConnect knows this by. Panics if the name contains a null character, which
+    /// only names given at runtime, such as `SimVar::Custom`, can.
     fn sc_string(&self) -> CString;
+
+    /// The name SimConnect knows this by, or an error if it contains a null character
+    fn try_sc_string(&self) -> Result<CString, NulError>;
 }
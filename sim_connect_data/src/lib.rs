@@ -1,15 +1,28 @@
+pub mod aircraft_input_events;
+pub mod client_data;
+pub mod data_definition;
+pub mod error;
+pub mod facilities;
+pub mod flight;
+pub mod flight_plan;
+pub mod input_definition;
 mod internals;
 pub mod recv_data;
+pub mod sim_data;
 pub mod sim_event_args;
 pub mod sim_events;
 pub mod sim_input_events;
+pub mod sim_objects;
 pub mod sim_units;
 pub mod sim_var_types;
 pub mod sim_vars;
+pub mod wasm_bridge;
 
 use std::ptr::NonNull;
 
+use error::SimConnectResult;
 pub use internals::ToSimConnect;
+use sim_data::DataWriter;
 use sim_units::SimUnit;
 use sim_var_types::SimVarType;
 use sim_vars::SimVar;
@@ -21,6 +34,9 @@ use sim_vars::SimVar;
 ///
 /// - Required enums must be in scope when specifying them in the `#[datum(..)]` attribute
 /// - Id's cannot be re-used in the same struct. This will create undefined behaviour
+/// - `sim_var` can be any expression, e.g. `sim_var = "SimVar::Custom(\"L:MY_VAR\".into())"`
+/// - `index = N` picks the `N`th instance of a simvar with more than one, e.g. `index = 2` for
+///   the second engine
 /// - Your data type will be automatically converted if the data type is supported. Current supported data types are:
 ///     - i32
 ///     - i64
@@ -30,7 +46,10 @@ use sim_vars::SimVar;
 ///
 /// # Example
 ///
-/// ```
+/// This is re-exported from `sim_connect_rs`, which this crate can't depend on, so the example
+/// is not compiled here.
+///
+/// ```ignore
 ///     use sim_connect_rs::{
 ///         sim_units::{Length, Speed},
 ///         sim_vars::SimVar,
@@ -49,15 +68,30 @@ use sim_vars::SimVar;
 ///              sim_unit = "Length::Foot",
 ///         )]
 ///         altitude: f32,
+///         #[datum(sim_var = "SimVar::GeneralEngRpm", index = 2)]
+///         engine_2_rpm: f64,
 ///}
 /// ```
 pub trait StructToSimConnect: Clone + Sized {
     fn get_fields() -> Vec<SimConnectDatum>;
+
+    /// Writes every field to `writer`, in the order of `get_fields`.
+    ///
+    /// The derive writes each field through `ToDatum`; manual implementations usually call
+    /// `DataWriter::write` once per field. `#[repr(C)]` structs can instead be copied out of
+    /// memory with the unsafe `DataWriter::write_repr_c`.
+    fn write_data(&self, writer: &mut DataWriter) -> SimConnectResult<()>;
 }
 
 pub trait SimConnectToStruct: StructToSimConnect {
     type Error;
     type ReturnType;
+
+    /// # Safety
+    ///
+    /// `pointer` must point at the start of the data of a `SIMCONNECT_RECV_SIMOBJECT_DATA`
+    /// message sent for this struct's data definition, valid for as many bytes as the
+    /// definition describes
     unsafe fn parse_struct(pointer: NonNull<u32>) -> Result<Self::ReturnType, Self::Error>;
 }
 
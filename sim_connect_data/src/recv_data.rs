@@ -1,20 +1,21 @@
-use anyhow::{anyhow, Result as AnyhowResult};
 use num_enum::TryFromPrimitive;
 use semver::Version;
 use serde::{Deserialize, Serialize};
 use std::ffi::{c_char, CStr};
 use std::mem::transmute;
 use std::ptr::NonNull;
-use std::sync::{Arc, Mutex};
 
 use sim_connect_sys::bindings;
 
+use crate::aircraft_input_events::{InputEventDescriptor, InputEventType, InputEventValue};
+use crate::error::{SimConnectError, SimConnectException, SimConnectResult};
+use crate::facilities::{FacilityListEntry, FacilityListType};
 use crate::sim_event_args::SimStateArgs;
-use crate::sim_events::{SystemEventData, SystemEventDataHolder};
-use crate::StructToSimConnect;
+use crate::sim_events::{SystemEvent, SystemEventData, SystemEventDataHolder};
+use crate::sim_objects::SimObjectId;
 
 pub trait FromPtr {
-    fn from_pointer(data: NonNull<bindings::SIMCONNECT_RECV>) -> AnyhowResult<Self>
+    fn from_pointer(data: NonNull<bindings::SIMCONNECT_RECV>) -> SimConnectResult<Self>
     where
         Self: Sized;
 }
@@ -26,12 +27,22 @@ pub enum RecvDataEvent {
     Open(RecVOpen),
     Data(RecvSimData),
     Event(SystemEventDataHolder),
+    ClientEvent(RecvClientEvent),
     SystemState(RecvSystemState),
+    InputEventList(RecvInputEventList),
+    InputEventValue(RecvInputEventValue),
+    InputEventChanged(RecvInputEventChanged),
+    InputEventParams(RecvInputEventParams),
+    FacilityData(RecvFacilityData),
+    FacilityDataEnd(RecvFacilityDataEnd),
+    FacilitiesList(RecvFacilitiesList),
+    AssignedObjectId(RecvAssignedObjectId),
+    Exception(RecvException),
     Quit,
 }
 
 impl RecvDataEvent {
-    pub fn from_pointer(data: NonNull<bindings::SIMCONNECT_RECV>) -> AnyhowResult<Self> {
+    pub fn from_pointer(data: NonNull<bindings::SIMCONNECT_RECV>) -> SimConnectResult<Self> {
         let data_id = unsafe { *data.as_ptr() }.dwID as i32;
         Ok(match data_id {
             bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_OPEN => {
@@ -43,12 +54,51 @@ impl RecvDataEvent {
             bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_SIMOBJECT_DATA_BYTYPE => {
                 Self::Data(RecvSimData::from_pointer(data)?)
             }
+            // Client data messages are laid out exactly like `SIMCONNECT_RECV_SIMOBJECT_DATA`
+            bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_CLIENT_DATA => {
+                Self::Data(RecvSimData::from_pointer(data)?)
+            }
+            bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EXCEPTION => {
+                Self::Exception(RecvException::from_pointer(data)?)
+            }
             bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_NULL => Self::Null,
             bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_QUIT => Self::Quit,
             bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_SYSTEM_STATE => {
                 Self::SystemState(RecvSystemState::from_pointer(data)?)
             }
-            bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT => {
+            bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_ENUMERATE_INPUT_EVENTS => {
+                Self::InputEventList(RecvInputEventList::from_pointer(data)?)
+            }
+            bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_GET_INPUT_EVENT => {
+                Self::InputEventValue(RecvInputEventValue::from_pointer(data)?)
+            }
+            bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_SUBSCRIBE_INPUT_EVENT => {
+                Self::InputEventChanged(RecvInputEventChanged::from_pointer(data)?)
+            }
+            bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_ENUMERATE_INPUT_EVENT_PARAMS => {
+                Self::InputEventParams(RecvInputEventParams::from_pointer(data)?)
+            }
+            bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_FACILITY_DATA => {
+                Self::FacilityData(RecvFacilityData::from_pointer(data)?)
+            }
+            bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_FACILITY_DATA_END => {
+                Self::FacilityDataEnd(RecvFacilityDataEnd::from_pointer(data)?)
+            }
+            bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_AIRPORT_LIST
+            | bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_WAYPOINT_LIST
+            | bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_NDB_LIST
+            | bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_VOR_LIST => {
+                Self::FacilitiesList(RecvFacilitiesList::from_pointer(data)?)
+            }
+            bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_ASSIGNED_OBJECT_ID => {
+                Self::AssignedObjectId(RecvAssignedObjectId::from_pointer(data)?)
+            }
+            bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT if !is_system_event(data) => {
+                Self::ClientEvent(RecvClientEvent::from_pointer(data)?)
+            }
+            bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT
+            | bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_FILENAME
+            | bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_OBJECT_ADDREMOVE => {
                 Self::Event(SystemEventDataHolder::from_pointer(data)?)
             }
             _ => Self::Null,
@@ -57,6 +107,14 @@ impl RecvDataEvent {
 }
 /* #endregion */
 
+/// Client events share `SIMCONNECT_RECV_EVENT` with system events, but are sent to a
+/// notification group and use ids no system event does
+fn is_system_event(data: NonNull<bindings::SIMCONNECT_RECV>) -> bool {
+    let event = unsafe { *(data.as_ptr() as *const bindings::SIMCONNECT_RECV_EVENT) };
+    event.uGroupID == bindings::SIMCONNECT_UNUSED
+        || SystemEvent::try_from_primitive(event.uEventID).is_ok()
+}
+
 /* #region RecvOpen */
 
 #[derive(Debug)]
@@ -69,14 +127,17 @@ pub struct RecVOpen {
 }
 
 impl FromPtr for RecVOpen {
-    fn from_pointer(data: NonNull<bindings::SIMCONNECT_RECV>) -> AnyhowResult<Self> {
+    fn from_pointer(data: NonNull<bindings::SIMCONNECT_RECV>) -> SimConnectResult<Self> {
         let raw_pointer: *mut bindings::SIMCONNECT_RECV_OPEN = unsafe { transmute(data.as_ptr()) };
 
         let open_data = unsafe { *raw_pointer };
 
         let name_ptr = &open_data.szApplicationName as *const c_char;
         let name_cstr = unsafe { CStr::from_ptr(name_ptr) };
-        let name = name_cstr.to_str()?.to_owned();
+        let name = name_cstr
+            .to_str()
+            .map_err(|e| SimConnectError::parse(data, e))?
+            .to_owned();
 
         let sim_connect_version = Version::new(
             open_data.dwSimConnectVersionMajor as u64,
@@ -113,69 +174,200 @@ impl FromPtr for RecVOpen {
 }
 /* #endregion */
 
+/* #region RecvException */
+
+/// An error the simulator raised in response to an earlier call
+#[derive(Debug, Clone, Copy)]
+pub struct RecvException {
+    pub exception: SimConnectException,
+    /// The send id of the packet which caused the exception, as returned by
+    /// `SimConnect_GetLastSentPacketID` after the call was made
+    pub send_id: u32,
+    /// The parameter of the call at fault
+    pub index: u32,
+}
+
+impl FromPtr for RecvException {
+    fn from_pointer(data: NonNull<bindings::SIMCONNECT_RECV>) -> SimConnectResult<Self> {
+        let raw_pointer: *mut bindings::SIMCONNECT_RECV_EXCEPTION =
+            unsafe { transmute(data.as_ptr()) };
+        let exception = unsafe { *raw_pointer };
+
+        Ok(Self {
+            exception: SimConnectException::from(exception.dwException),
+            send_id: exception.dwSendID,
+            index: exception.dwIndex,
+        })
+    }
+}
+
+impl From<RecvException> for SimConnectError {
+    fn from(exception: RecvException) -> Self {
+        Self::Exception {
+            exception: exception.exception,
+            send_id: exception.send_id,
+            index: exception.index,
+            call: None,
+        }
+    }
+}
+/* #endregion */
+
+/* #region RecvClientEvent */
+
+/// A client event sent to a notification group the client added it to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecvClientEvent {
+    pub group_id: u32,
+    pub event_id: u32,
+    /// The parameter of the event, e.g. the value of a `_SET` event
+    pub data: u32,
+}
+
+impl FromPtr for RecvClientEvent {
+    fn from_pointer(data: NonNull<bindings::SIMCONNECT_RECV>) -> SimConnectResult<Self> {
+        let event = unsafe { *(data.as_ptr() as *const bindings::SIMCONNECT_RECV_EVENT) };
+
+        Ok(Self {
+            group_id: event.uGroupID,
+            event_id: event.uEventID,
+            data: event.dwData,
+        })
+    }
+}
+/* #endregion */
+
 /* #region RecvSimData */
-#[derive(Debug)]
+
+/// A `SIMCONNECT_RECV_SIMOBJECT_DATA` or `SIMCONNECT_RECV_CLIENT_DATA` message. The data is
+/// copied out of the buffer SimConnect handed to `GetNextDispatch`, which is reused for the
+/// next message, so it can be kept and sent to other threads.
+#[derive(Debug, Clone)]
 pub struct RecvSimData {
-    data_pointer: Arc<Mutex<NonNull<bindings::SIMCONNECT_RECV_SIMOBJECT_DATA>>>,
+    recv_id: u32,
+    request_id: u32,
+    object_id: u32,
     data_id: u32,
+    flags: u32,
+    entry_number: u32,
+    out_of: u32,
+    define_count: u32,
+    /// `dwData` onwards, in `u64`s so it is as aligned as it was in SimConnect's buffer
+    data: Vec<u64>,
+    data_len: usize,
 }
 
 impl RecvSimData {
-    pub fn to_struct<T: StructToSimConnect>(self) -> AnyhowResult<T> {
-        let locked = self.data_pointer.lock().unwrap();
-        let ptr = unsafe { locked.as_ref() };
+    /// Points at the start of the data, valid for `get_data().len()` bytes for as long as
+    /// `self` is
+    pub fn get_pointer(&self) -> NonNull<u32> {
+        NonNull::new(self.data.as_ptr() as *mut u32).expect("Pointer not expected to be null")
+    }
 
-        let data = NonNull::new(std::ptr::addr_of!(ptr.dwData) as *mut T)
-            .ok_or_else(|| anyhow!("Pointer not expected to be null"))?;
+    pub fn get_id(&self) -> u32 {
+        self.data_id
+    }
 
-        let data = unsafe { data.as_ref().clone() };
+    /// The data following the message header, `dwData` onwards
+    pub fn get_data(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.data.as_ptr() as *const u8, self.data_len) }
+    }
 
-        return Ok(data);
+    /// Number of datums in the data. Only differs from the size of the definition for tagged
+    /// data.
+    pub fn get_define_count(&self) -> u32 {
+        self.define_count
     }
 
-    pub fn get_pointer(&self) -> NonNull<u32> {
-        let locked = self.data_pointer.lock().unwrap();
-        let ptr = unsafe { locked.as_ref() };
+    /// The `SIMCONNECT_DATA_REQUEST_FLAG`s of the request this data answers
+    pub fn get_flags(&self) -> u32 {
+        self.flags
+    }
 
-        NonNull::new(std::ptr::addr_of!(ptr.dwData) as *mut u32)
-            .ok_or_else(|| anyhow!("Pointer not expected to be null"))
-            .unwrap()
+    /// The id of the request this data answers
+    pub fn get_request_id(&self) -> u32 {
+        self.request_id
     }
 
-    pub fn get_id(&self) -> u32 {
-        self.data_id
+    /// The id of the object the data was read from
+    pub fn get_object_id(&self) -> u32 {
+        self.object_id
+    }
+
+    /// The position of this message, starting at 1, among the `get_out_of` messages sent to
+    /// answer a request by type. Every other request is answered in a single message.
+    pub fn get_entry_number(&self) -> u32 {
+        self.entry_number
+    }
+
+    /// The number of messages sent to answer the request. `0` if a request by type found no
+    /// objects, in which case the message carries no data.
+    pub fn get_out_of(&self) -> u32 {
+        self.out_of
+    }
+
+    /// The `SIMCONNECT_RECV_ID` of the message this data was received in
+    pub fn get_recv_id(&self) -> u32 {
+        self.recv_id
     }
 }
 
 impl FromPtr for RecvSimData {
-    fn from_pointer(data: NonNull<bindings::SIMCONNECT_RECV>) -> AnyhowResult<Self>
+    fn from_pointer(data: NonNull<bindings::SIMCONNECT_RECV>) -> SimConnectResult<Self>
     where
         Self: Sized,
     {
-        let raw_ptr: *mut bindings::SIMCONNECT_RECV_SIMOBJECT_DATA =
-            unsafe { transmute(data.as_ptr()) };
+        let offset = std::mem::offset_of!(bindings::SIMCONNECT_RECV_SIMOBJECT_DATA, dwData);
+        // `dwSize` is the `cbData` `GetNextDispatch` reports for the message
+        let size = unsafe { *data.as_ptr() }.dwSize as usize;
+        if size < offset {
+            return Err(SimConnectError::parse(
+                data,
+                format!("Message of {size} bytes is shorter than its header"),
+            ));
+        }
 
-        let data_id = unsafe { *raw_ptr }.dwDefineID;
+        // Only the header is read in place, as a message without data is shorter than
+        // `SIMCONNECT_RECV_SIMOBJECT_DATA`
+        let raw_ptr = data.as_ptr() as *const bindings::SIMCONNECT_RECV_SIMOBJECT_DATA;
+        macro_rules! header {
+            ($field: ident) => {
+                unsafe { std::ptr::addr_of!((*raw_ptr).$field).read_unaligned() }
+            };
+        }
+        let data_len = size - offset;
+        let mut owned = vec![0u64; data_len.div_ceil(8)];
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                (data.as_ptr() as *const u8).add(offset),
+                owned.as_mut_ptr() as *mut u8,
+                data_len,
+            )
+        };
 
-        let ptr =
-            NonNull::new(raw_ptr).ok_or_else(|| anyhow::anyhow!("Unexpected empty pointer"))?;
         Ok(Self {
-            data_pointer: Arc::new(Mutex::new(ptr)),
-            data_id,
+            recv_id: unsafe { *data.as_ptr() }.dwID,
+            request_id: header!(dwRequestID),
+            object_id: header!(dwObjectID),
+            data_id: header!(dwDefineID),
+            flags: header!(dwFlags),
+            entry_number: header!(dwentrynumber),
+            out_of: header!(dwoutof),
+            define_count: header!(dwDefineCount),
+            data: owned,
+            data_len,
         })
     }
 }
 
-unsafe impl Send for RecvSimData {}
-
 /* #endregion */
 
 /* #region RecvSystemEvent */
 #[derive(Debug)]
-pub struct RecvSystemEvent(SystemEventData);
+pub struct RecvSystemEvent(pub SystemEventData);
 
 impl FromPtr for RecvSystemEvent {
-    fn from_pointer(data: NonNull<bindings::SIMCONNECT_RECV>) -> AnyhowResult<Self>
+    fn from_pointer(data: NonNull<bindings::SIMCONNECT_RECV>) -> SimConnectResult<Self>
     where
         Self: Sized,
     {
@@ -200,7 +392,7 @@ unsafe impl Sync for RecvSystemState {}
 unsafe impl Send for RecvSystemState {}
 
 impl FromPtr for RecvSystemState {
-    fn from_pointer(data: NonNull<bindings::SIMCONNECT_RECV>) -> AnyhowResult<Self>
+    fn from_pointer(data: NonNull<bindings::SIMCONNECT_RECV>) -> SimConnectResult<Self>
     where
         Self: Sized,
     {
@@ -208,64 +400,443 @@ impl FromPtr for RecvSystemState {
             unsafe { transmute(data.as_ptr()) };
 
         let system_state = unsafe { *raw_ptr };
-        let arg_type = SimStateArgs::try_from_primitive(system_state.dwRequestID)?;
+        let arg_type = SimStateArgs::try_from_primitive(system_state.dwRequestID)
+            .map_err(|e| SimConnectError::parse(data, e))?;
         match arg_type {
             SimStateArgs::AircraftLoaded => {
-                let name = unsafe { CStr::from_ptr(&system_state.szString as *const i8) };
-                return Ok(Self {
-                    aircraft_loaded: Some(name.to_str()?.to_owned()),
+                let name = unsafe { CStr::from_ptr(&system_state.szString as *const c_char) };
+                Ok(Self {
+                    aircraft_loaded: Some(
+                        name.to_str()
+                            .map_err(|e| SimConnectError::parse(data, e))?
+                            .to_owned(),
+                    ),
                     dialog_mode: None,
                     flight_loaded: None,
                     flight_plan: None,
                     sim: None,
                     state_arg: arg_type,
-                });
+                })
             }
             SimStateArgs::DialogMode => {
                 let dialog_mode_bool: bool = system_state.dwInteger != 0;
-                return Ok(Self {
+                Ok(Self {
                     aircraft_loaded: None,
                     dialog_mode: Some(dialog_mode_bool),
                     flight_loaded: None,
                     flight_plan: None,
                     sim: None,
                     state_arg: arg_type,
-                });
+                })
             }
             SimStateArgs::FlightLoaded => {
-                let flight_name = unsafe { CStr::from_ptr(&system_state.szString as *const i8) };
-                return Ok(Self {
+                let flight_name =
+                    unsafe { CStr::from_ptr(&system_state.szString as *const c_char) };
+                Ok(Self {
                     aircraft_loaded: None,
                     dialog_mode: None,
-                    flight_loaded: Some(flight_name.to_str()?.to_owned()),
+                    flight_loaded: Some(
+                        flight_name
+                            .to_str()
+                            .map_err(|e| SimConnectError::parse(data, e))?
+                            .to_owned(),
+                    ),
                     flight_plan: None,
                     sim: None,
                     state_arg: arg_type,
-                });
+                })
             }
             SimStateArgs::FlightPlan => {
                 let flight_plan_str =
-                    unsafe { CStr::from_ptr(&system_state.szString as *const i8) };
-                return Ok(Self {
+                    unsafe { CStr::from_ptr(&system_state.szString as *const c_char) };
+                Ok(Self {
                     aircraft_loaded: None,
                     dialog_mode: None,
                     flight_loaded: None,
-                    flight_plan: Some(flight_plan_str.to_str()?.to_owned()),
+                    flight_plan: Some(
+                        flight_plan_str
+                            .to_str()
+                            .map_err(|e| SimConnectError::parse(data, e))?
+                            .to_owned(),
+                    ),
                     sim: None,
                     state_arg: arg_type,
-                });
+                })
             }
             SimStateArgs::Sim => {
                 let sim_bool: bool = system_state.dwInteger != 0;
-                return Ok(Self {
+                Ok(Self {
                     aircraft_loaded: None,
                     dialog_mode: None,
                     flight_loaded: None,
                     flight_plan: None,
                     sim: Some(sim_bool),
                     state_arg: arg_type,
-                });
+                })
             }
         }
     }
 }
+
+/* #region RecvInputEvent */
+
+/// The bytes of a message, as long as the `dwSize` it reports
+///
+/// # Safety
+///
+/// `data` must point to a message at least `dwSize` bytes long, which outlives the slice
+unsafe fn message_bytes<'a>(data: NonNull<bindings::SIMCONNECT_RECV>) -> &'a [u8] {
+    let size = (*data.as_ptr()).dwSize as usize;
+    std::slice::from_raw_parts(data.as_ptr() as *const u8, size)
+}
+
+/// Reads the `u32` or `u64` at `offset` of a message
+fn read_field<T: Copy>(
+    data: NonNull<bindings::SIMCONNECT_RECV>,
+    message: &[u8],
+    offset: usize,
+) -> SimConnectResult<T> {
+    if message.len() < offset + std::mem::size_of::<T>() {
+        return Err(SimConnectError::parse(
+            data,
+            format!(
+                "Message of {} bytes is shorter than its header",
+                message.len()
+            ),
+        ));
+    }
+    Ok(unsafe { (message.as_ptr().add(offset) as *const T).read_unaligned() })
+}
+
+/// Reads a null-terminated string, which ends with the message if it isn't terminated
+fn read_string(data: NonNull<bindings::SIMCONNECT_RECV>, bytes: &[u8]) -> SimConnectResult<String> {
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    std::str::from_utf8(&bytes[..end])
+        .map(str::to_owned)
+        .map_err(|e| SimConnectError::parse(data, e))
+}
+
+/// Reads the value of a `GET_INPUT_EVENT` or `SUBSCRIBE_INPUT_EVENT` message, which starts at
+/// `offset` and has the `SIMCONNECT_INPUT_EVENT_TYPE` `event_type`
+fn read_input_event_value(
+    data: NonNull<bindings::SIMCONNECT_RECV>,
+    message: &[u8],
+    event_type: bindings::SIMCONNECT_INPUT_EVENT_TYPE,
+    offset: usize,
+) -> SimConnectResult<InputEventValue> {
+    match event_type {
+        bindings::SIMCONNECT_INPUT_EVENT_TYPE_SIMCONNECT_INPUT_EVENT_TYPE_DOUBLE => {
+            Ok(InputEventValue::Double(read_field(data, message, offset)?))
+        }
+        bindings::SIMCONNECT_INPUT_EVENT_TYPE_SIMCONNECT_INPUT_EVENT_TYPE_STRING => {
+            Ok(InputEventValue::String(read_string(
+                data,
+                message.get(offset..).unwrap_or_default(),
+            )?))
+        }
+        other => Err(SimConnectError::parse(
+            data,
+            format!("Unknown input event type {other}"),
+        )),
+    }
+}
+
+/// One message of the answer to `SimConnect_EnumerateInputEvents`. Large answers are split
+/// over several messages, numbered from `0` up to `out_of - 1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecvInputEventList {
+    pub request_id: u32,
+    pub entry_number: u32,
+    pub out_of: u32,
+    pub descriptors: Vec<InputEventDescriptor>,
+}
+
+impl FromPtr for RecvInputEventList {
+    fn from_pointer(data: NonNull<bindings::SIMCONNECT_RECV>) -> SimConnectResult<Self> {
+        use bindings::{
+            SIMCONNECT_INPUT_EVENT_DESCRIPTOR as Descriptor,
+            SIMCONNECT_RECV_ENUMERATE_INPUT_EVENTS as List,
+        };
+        use std::mem::{offset_of, size_of};
+
+        let message = unsafe { message_bytes(data) };
+        let array_size: u32 = read_field(data, message, offset_of!(List, _base.dwArraySize))?;
+        let start = offset_of!(List, rgData);
+        let end = start + array_size as usize * size_of::<Descriptor>();
+        if message.len() < end {
+            return Err(SimConnectError::parse(
+                data,
+                format!(
+                    "{array_size} input events don't fit in {} bytes",
+                    message.len()
+                ),
+            ));
+        }
+
+        let descriptors = message[start..end]
+            .chunks_exact(size_of::<Descriptor>())
+            .map(|bytes| {
+                let descriptor = unsafe { (bytes.as_ptr() as *const Descriptor).read_unaligned() };
+                let name = descriptor.Name.map(|c| c as u8);
+                Ok(InputEventDescriptor {
+                    name: read_string(data, &name)?,
+                    hash: descriptor.Hash,
+                    event_type: input_event_type(descriptor.eType),
+                })
+            })
+            .collect::<SimConnectResult<_>>()?;
+
+        Ok(Self {
+            request_id: read_field(data, message, offset_of!(List, _base.dwRequestID))?,
+            entry_number: read_field(data, message, offset_of!(List, _base.dwEntryNumber))?,
+            out_of: read_field(data, message, offset_of!(List, _base.dwOutOf))?,
+            descriptors,
+        })
+    }
+}
+
+/// Descriptors report their type as a `SIMCONNECT_DATATYPE`, of which only strings aren't
+/// read as doubles
+fn input_event_type(data_type: bindings::SIMCONNECT_DATATYPE) -> InputEventType {
+    match data_type {
+        bindings::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING8
+        | bindings::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING32
+        | bindings::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING64
+        | bindings::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING128
+        | bindings::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING256
+        | bindings::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING260
+        | bindings::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRINGV => InputEventType::String,
+        _ => InputEventType::Double,
+    }
+}
+
+/// The answer to `SimConnect_GetInputEvent`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecvInputEventValue {
+    pub request_id: u32,
+    pub value: InputEventValue,
+}
+
+impl FromPtr for RecvInputEventValue {
+    fn from_pointer(data: NonNull<bindings::SIMCONNECT_RECV>) -> SimConnectResult<Self> {
+        use bindings::SIMCONNECT_RECV_GET_INPUT_EVENT as Get;
+        use std::mem::offset_of;
+
+        let message = unsafe { message_bytes(data) };
+        let event_type = read_field(data, message, offset_of!(Get, eType))?;
+        Ok(Self {
+            request_id: read_field(data, message, offset_of!(Get, RequestID))?,
+            value: read_input_event_value(data, message, event_type, offset_of!(Get, Value))?,
+        })
+    }
+}
+
+/// Sent whenever an input event subscribed to with `SimConnect_SubscribeInputEvent` changes
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecvInputEventChanged {
+    pub hash: u64,
+    pub value: InputEventValue,
+}
+
+impl FromPtr for RecvInputEventChanged {
+    fn from_pointer(data: NonNull<bindings::SIMCONNECT_RECV>) -> SimConnectResult<Self> {
+        use bindings::SIMCONNECT_RECV_SUBSCRIBE_INPUT_EVENT as Changed;
+        use std::mem::offset_of;
+
+        let message = unsafe { message_bytes(data) };
+        let event_type = read_field(data, message, offset_of!(Changed, eType))?;
+        Ok(Self {
+            hash: read_field(data, message, offset_of!(Changed, Hash))?,
+            value: read_input_event_value(data, message, event_type, offset_of!(Changed, Value))?,
+        })
+    }
+}
+
+/// The answer to `SimConnect_EnumerateInputEventParams`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecvInputEventParams {
+    pub hash: u64,
+    /// The types of the event's parameters, which SimConnect sends separated by `;`
+    pub params: Vec<String>,
+}
+
+impl FromPtr for RecvInputEventParams {
+    fn from_pointer(data: NonNull<bindings::SIMCONNECT_RECV>) -> SimConnectResult<Self> {
+        use bindings::SIMCONNECT_RECV_ENUMERATE_INPUT_EVENT_PARAMS as Params;
+        use std::mem::offset_of;
+
+        let message = unsafe { message_bytes(data) };
+        let params = read_string(
+            data,
+            message.get(offset_of!(Params, Value)..).unwrap_or_default(),
+        )?;
+        Ok(Self {
+            hash: read_field(data, message, offset_of!(Params, Hash))?,
+            params: params
+                .split(';')
+                .filter(|param| !param.is_empty())
+                .map(str::to_owned)
+                .collect(),
+        })
+    }
+}
+/* #endregion */
+
+/* #region RecvFacilityData */
+
+/// One record of the answer to `SimConnect_RequestFacilityData`. A facility is sent as one of
+/// these per record, each naming the record it is nested in, followed by a
+/// `RecvFacilityDataEnd`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecvFacilityData {
+    pub request_id: u32,
+    /// Identifies this record among the answer's records
+    pub unique_id: u32,
+    /// The `unique_id` of the record this one is nested in, or `0` for the facility itself
+    pub parent_id: u32,
+    /// A `SIMCONNECT_FACILITY_DATA_TYPE`
+    pub data_type: u32,
+    pub is_list_item: bool,
+    pub item_index: u32,
+    pub list_size: u32,
+    /// The record's fields, packed one after the other in definition order
+    pub data: Vec<u8>,
+}
+
+impl FromPtr for RecvFacilityData {
+    fn from_pointer(data: NonNull<bindings::SIMCONNECT_RECV>) -> SimConnectResult<Self> {
+        use bindings::SIMCONNECT_RECV_FACILITY_DATA as Facility;
+        use std::mem::offset_of;
+
+        let message = unsafe { message_bytes(data) };
+        Ok(Self {
+            request_id: read_field(data, message, offset_of!(Facility, UserRequestId))?,
+            unique_id: read_field(data, message, offset_of!(Facility, UniqueRequestId))?,
+            parent_id: read_field(data, message, offset_of!(Facility, ParentUniqueRequestId))?,
+            data_type: read_field(data, message, offset_of!(Facility, Type))?,
+            is_list_item: read_field::<u32>(data, message, offset_of!(Facility, IsListItem))? != 0,
+            item_index: read_field(data, message, offset_of!(Facility, ItemIndex))?,
+            list_size: read_field(data, message, offset_of!(Facility, ListSize))?,
+            data: message
+                .get(offset_of!(Facility, Data)..)
+                .unwrap_or_default()
+                .to_vec(),
+        })
+    }
+}
+
+/// Ends the answer to `SimConnect_RequestFacilityData`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecvFacilityDataEnd {
+    pub request_id: u32,
+}
+
+impl FromPtr for RecvFacilityDataEnd {
+    fn from_pointer(data: NonNull<bindings::SIMCONNECT_RECV>) -> SimConnectResult<Self> {
+        use bindings::SIMCONNECT_RECV_FACILITY_DATA_END as End;
+        use std::mem::offset_of;
+
+        let message = unsafe { message_bytes(data) };
+        Ok(Self {
+            request_id: read_field(data, message, offset_of!(End, RequestId))?,
+        })
+    }
+}
+/* #endregion */
+
+/* #region RecvAssignedObjectId */
+
+/// Answers the `SimConnect_AICreate*` calls with the id of the object they created
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecvAssignedObjectId {
+    pub request_id: u32,
+    pub object_id: SimObjectId,
+}
+
+impl FromPtr for RecvAssignedObjectId {
+    fn from_pointer(data: NonNull<bindings::SIMCONNECT_RECV>) -> SimConnectResult<Self> {
+        use bindings::SIMCONNECT_RECV_ASSIGNED_OBJECT_ID as Assigned;
+        use std::mem::offset_of;
+
+        let message = unsafe { message_bytes(data) };
+        Ok(Self {
+            request_id: read_field(data, message, offset_of!(Assigned, dwRequestID))?,
+            object_id: SimObjectId(read_field(data, message, offset_of!(Assigned, dwObjectID))?),
+        })
+    }
+}
+/* #endregion */
+
+/* #region RecvFacilitiesList */
+
+/// One message of the answer to `SimConnect_RequestFacilitiesList` or
+/// `SimConnect_SubscribeToFacilities`. Long lists are split over several messages, numbered
+/// from `0` up to `out_of - 1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecvFacilitiesList {
+    pub request_id: u32,
+    pub list_type: FacilityListType,
+    pub entry_number: u32,
+    pub out_of: u32,
+    /// The message's `SIMCONNECT_DATA_FACILITY_*` entries, one after the other
+    entries: Vec<Vec<u8>>,
+}
+
+impl RecvFacilitiesList {
+    /// Decodes the entries, which must be of `T`'s list type
+    pub fn entries<T: FacilityListEntry>(&self) -> SimConnectResult<Vec<T>> {
+        if self.list_type != T::LIST_TYPE {
+            return Err(SimConnectError::Parse {
+                recv_id: self.list_type.recv_id().unwrap_or_default() as u32,
+                reason: format!(
+                    "Expected a {:?} list, got a {:?} list",
+                    T::LIST_TYPE,
+                    self.list_type
+                ),
+            });
+        }
+        Ok(self
+            .entries
+            .iter()
+            .map(|entry| T::from_bytes(entry))
+            .collect())
+    }
+}
+
+impl FromPtr for RecvFacilitiesList {
+    fn from_pointer(data: NonNull<bindings::SIMCONNECT_RECV>) -> SimConnectResult<Self> {
+        use bindings::SIMCONNECT_RECV_FACILITIES_LIST as List;
+        use std::mem::{offset_of, size_of};
+
+        let message = unsafe { message_bytes(data) };
+        let recv_id = unsafe { *data.as_ptr() }.dwID as bindings::SIMCONNECT_RECV_ID;
+        let list_type = FacilityListType::from_recv_id(recv_id)
+            .ok_or_else(|| SimConnectError::parse(data, "Not a facility list"))?;
+        let entry_size = list_type.entry_size().unwrap_or_default();
+
+        let array_size: u32 = read_field(data, message, offset_of!(List, dwArraySize))?;
+        let entries = message.get(size_of::<List>()..).unwrap_or_default();
+        if entries.len() < array_size as usize * entry_size {
+            return Err(SimConnectError::parse(
+                data,
+                format!(
+                    "{} bytes is too short for {array_size} entries of {entry_size} bytes",
+                    entries.len()
+                ),
+            ));
+        }
+
+        Ok(Self {
+            request_id: read_field(data, message, offset_of!(List, dwRequestID))?,
+            list_type,
+            entry_number: read_field(data, message, offset_of!(List, dwEntryNumber))?,
+            out_of: read_field(data, message, offset_of!(List, dwOutOf))?,
+            entries: entries
+                .chunks_exact(entry_size)
+                .take(array_size as usize)
+                .map(<[u8]>::to_vec)
+                .collect(),
+        })
+    }
+}
+/* #endregion */
@@ -0,0 +1,272 @@
+use std::ops::Range;
+
+use sim_connect_sys::bindings;
+
+use crate::{
+    error::{SimConnectError, SimConnectResult},
+    sim_objects::InitPosition,
+    sim_var_types::SimVarType,
+    StructToSimConnect,
+};
+
+/// A value which can be written to the simulator as a datum of a data definition
+pub trait ToDatum {
+    /// Appends the value to `out`, laid out as `data_type`
+    fn write_datum(&self, data_type: SimVarType, out: &mut Vec<u8>) -> SimConnectResult<()>;
+}
+
+macro_rules! number_datum {
+    ($($number: ty),*) => {
+        $(
+            impl ToDatum for $number {
+                fn write_datum(
+                    &self,
+                    data_type: SimVarType,
+                    out: &mut Vec<u8>,
+                ) -> SimConnectResult<()> {
+                    match data_type {
+                        SimVarType::I32 => out.extend((*self as i32).to_le_bytes()),
+                        SimVarType::I64 => out.extend((*self as i64).to_le_bytes()),
+                        SimVarType::F32 => out.extend((*self as f32).to_le_bytes()),
+                        SimVarType::F64 => out.extend((*self as f64).to_le_bytes()),
+                        data_type => return Err(mismatch(stringify!($number), data_type)),
+                    }
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+number_datum!(i32, i64, u32, f32, f64);
+
+impl ToDatum for bool {
+    fn write_datum(&self, data_type: SimVarType, out: &mut Vec<u8>) -> SimConnectResult<()> {
+        (*self as i32).write_datum(data_type, out)
+    }
+}
+
+impl ToDatum for str {
+    fn write_datum(&self, data_type: SimVarType, out: &mut Vec<u8>) -> SimConnectResult<()> {
+        let size = match data_type {
+            // Variable length strings are null terminated and padded to a whole dword
+            SimVarType::StringV => (self.len() + 1 + 3) & !3,
+            SimVarType::String8
+            | SimVarType::String32
+            | SimVarType::String64
+            | SimVarType::String128
+            | SimVarType::String256
+            | SimVarType::String260 => data_type.size().unwrap_or_default(),
+            data_type => return Err(mismatch("a string", data_type)),
+        };
+        if self.len() >= size {
+            return Err(SimConnectError::InvalidData(format!(
+                "{self:?} does not fit in {data_type:?}"
+            )));
+        }
+
+        out.extend_from_slice(self.as_bytes());
+        out.resize(out.len() + size - self.len(), 0);
+        Ok(())
+    }
+}
+
+impl ToDatum for String {
+    fn write_datum(&self, data_type: SimVarType, out: &mut Vec<u8>) -> SimConnectResult<()> {
+        self.as_str().write_datum(data_type, out)
+    }
+}
+
+impl ToDatum for InitPosition {
+    fn write_datum(&self, data_type: SimVarType, out: &mut Vec<u8>) -> SimConnectResult<()> {
+        if data_type != SimVarType::InitPos {
+            return Err(mismatch("an InitPosition", data_type));
+        }
+        let position = bindings::SIMCONNECT_DATA_INITPOSITION::from(*self);
+        for value in [
+            position.Latitude,
+            position.Longitude,
+            position.Altitude,
+            position.Pitch,
+            position.Bank,
+            position.Heading,
+        ] {
+            out.extend(value.to_le_bytes());
+        }
+        out.extend({ position.OnGround }.to_le_bytes());
+        out.extend({ position.Airspeed }.to_le_bytes());
+        Ok(())
+    }
+}
+
+impl<T: ToDatum + ?Sized> ToDatum for &T {
+    fn write_datum(&self, data_type: SimVarType, out: &mut Vec<u8>) -> SimConnectResult<()> {
+        (**self).write_datum(data_type, out)
+    }
+}
+
+fn mismatch(value: &str, data_type: SimVarType) -> SimConnectError {
+    SimConnectError::InvalidData(format!("Unable to write {value} as {data_type:?}"))
+}
+
+/// Packs the fields of a `StructToSimConnect` struct the way `SimConnect_SetDataOnSimObject`
+/// expects them: every datum of the definition in order, with no padding in between.
+///
+/// Fields are written in the order `get_fields` returns them, each laid out as the data type
+/// of its datum.
+pub struct DataWriter {
+    /// The id and type of every datum of the definition
+    datums: Vec<(u32, SimVarType)>,
+    data: Vec<u8>,
+    /// Where each datum written so far is in `data`
+    written: Vec<Range<usize>>,
+    type_name: &'static str,
+}
+
+impl DataWriter {
+    pub fn new<T: StructToSimConnect>() -> Self {
+        Self {
+            datums: T::get_fields()
+                .into_iter()
+                .map(|datum| (datum.id, datum.data_type))
+                .collect(),
+            data: Vec::new(),
+            written: Vec::new(),
+            type_name: std::any::type_name::<T>(),
+        }
+    }
+
+    /// Writes the next datum
+    pub fn write(&mut self, value: &(impl ToDatum + ?Sized)) -> SimConnectResult<()> {
+        let (datum_id, data_type) = self.next_datum()?;
+        let start = self.data.len();
+        value
+            .write_datum(data_type, &mut self.data)
+            .map_err(|e| self.in_datum(datum_id, e))?;
+        self.written.push(start..self.data.len());
+        Ok(())
+    }
+
+    /// Writes every remaining datum straight from the memory of `value`, which is expected to
+    /// be `#[repr(C)]` with a field for each of them, in order: `i32`, `i64`, `f32` or `f64` for
+    /// numbers, `[u8; N]` for fixed size strings, and the `SIMCONNECT_DATA_*` struct for the
+    /// others. Variable length strings can't be read this way.
+    ///
+    /// Only the bytes of the fields are read, never the padding between them, and never past
+    /// the end of `value`. Each datum is placed at the next offset aligned for its type, so the
+    /// struct needs no other fields, before or between them.
+    ///
+    /// # Safety
+    ///
+    /// `T` must be `#[repr(C)]`, and its fields must match the remaining datums one to one, in
+    /// order, with the types listed above. Otherwise the bytes read may be padding, which is
+    /// uninitialized, or only part of a field.
+    pub unsafe fn write_repr_c<T>(&mut self, value: &T) -> SimConnectResult<()> {
+        let base = value as *const T as *const u8;
+        let mut offset = 0usize;
+        while self.written.len() < self.datums.len() {
+            let (datum_id, data_type) = self.next_datum()?;
+            let (Some(size), Some(align)) = (data_type.size(), repr_c_align(data_type)) else {
+                return Err(self.in_datum(datum_id, mismatch("struct memory", data_type)));
+            };
+            offset = offset.next_multiple_of(align);
+            if offset + size > std::mem::size_of::<T>() {
+                return Err(self.in_datum(
+                    datum_id,
+                    SimConnectError::InvalidData(format!(
+                        "The struct is too small to hold the datum at offset {offset}"
+                    )),
+                ));
+            }
+
+            let start = self.data.len();
+            // Safety: the range is within `value`, and the caller guarantees it is a field
+            // rather than padding
+            self.data
+                .extend_from_slice(unsafe { std::slice::from_raw_parts(base.add(offset), size) });
+            self.written.push(start..self.data.len());
+            offset += size;
+        }
+        Ok(())
+    }
+
+    /// The packed data of every datum
+    pub fn into_data(self) -> SimConnectResult<Vec<u8>> {
+        self.check_complete()?;
+        Ok(self.data)
+    }
+
+    /// The data of the datums with `datum_ids` only, each preceded by its id, for
+    /// `SIMCONNECT_DATA_SET_FLAG_TAGGED`
+    pub fn into_tagged(self, datum_ids: &[u32]) -> SimConnectResult<Vec<u8>> {
+        self.check_complete()?;
+        let mut tagged = Vec::new();
+        for datum_id in datum_ids {
+            let index = self
+                .datums
+                .iter()
+                .position(|(id, _)| id == datum_id)
+                .ok_or_else(|| {
+                    SimConnectError::InvalidData(format!(
+                        "{} has no datum {datum_id}",
+                        self.type_name
+                    ))
+                })?;
+            tagged.extend(datum_id.to_le_bytes());
+            tagged.extend_from_slice(&self.data[self.written[index].clone()]);
+        }
+        Ok(tagged)
+    }
+
+    fn next_datum(&self) -> SimConnectResult<(u32, SimVarType)> {
+        self.datums.get(self.written.len()).copied().ok_or_else(|| {
+            SimConnectError::InvalidData(format!(
+                "{} wrote more than its {} datums",
+                self.type_name,
+                self.datums.len()
+            ))
+        })
+    }
+
+    fn check_complete(&self) -> SimConnectResult<()> {
+        if self.written.len() < self.datums.len() {
+            return Err(SimConnectError::InvalidData(format!(
+                "{} only wrote {} of its {} datums",
+                self.type_name,
+                self.written.len(),
+                self.datums.len()
+            )));
+        }
+        Ok(())
+    }
+
+    fn in_datum(&self, datum_id: u32, error: SimConnectError) -> SimConnectError {
+        match error {
+            SimConnectError::InvalidData(reason) => SimConnectError::InvalidData(format!(
+                "datum {datum_id} of {}: {reason}",
+                self.type_name
+            )),
+            error => error,
+        }
+    }
+}
+
+/// The alignment of the Rust type holding a datum of `data_type` in a `#[repr(C)]` struct
+fn repr_c_align(data_type: SimVarType) -> Option<usize> {
+    Some(match data_type {
+        SimVarType::I32 | SimVarType::F32 => 4,
+        SimVarType::I64 | SimVarType::F64 => 8,
+        SimVarType::String8
+        | SimVarType::String32
+        | SimVarType::String64
+        | SimVarType::String128
+        | SimVarType::String256
+        | SimVarType::String260 => 1,
+        SimVarType::InitPos => std::mem::align_of::<bindings::SIMCONNECT_DATA_INITPOSITION>(),
+        SimVarType::MarkerState => std::mem::align_of::<bindings::SIMCONNECT_DATA_MARKERSTATE>(),
+        SimVarType::Waypoint => std::mem::align_of::<bindings::SIMCONNECT_DATA_WAYPOINT>(),
+        SimVarType::LatLongAlt => std::mem::align_of::<bindings::SIMCONNECT_DATA_LATLONALT>(),
+        SimVarType::XYZ => std::mem::align_of::<bindings::SIMCONNECT_DATA_XYZ>(),
+        SimVarType::StringV | SimVarType::Invalid => return None,
+    })
+}
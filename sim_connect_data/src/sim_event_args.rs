@@ -1,6 +1,9 @@
 use std::mem::transmute;
 
-use crate::internals::{IterEnum, ToSimConnect};
+use crate::{
+    error::{SimConnectError, SimConnectResult},
+    internals::{IterEnum, ToSimConnect},
+};
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use serde::{Deserialize, Serialize};
 use sim_connect_macros::{FromStr, IterEnum, ToSimConnect};
@@ -9,7 +12,7 @@ use std::ffi::CString;
 
 use crate::recv_data::FromPtr;
 
-#[derive(TryFromPrimitive, Debug, Serialize, Deserialize, Clone, Copy)]
+#[derive(TryFromPrimitive, Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 #[repr(i32)]
 #[serde(rename = "camelCase")]
 pub enum SimObjectType {
@@ -22,7 +25,7 @@ pub enum SimObjectType {
 }
 
 impl FromPtr for SimObjectType {
-    fn from_pointer(data: std::ptr::NonNull<bindings::SIMCONNECT_RECV>) -> anyhow::Result<Self>
+    fn from_pointer(data: std::ptr::NonNull<bindings::SIMCONNECT_RECV>) -> SimConnectResult<Self>
     where
         Self: Sized,
     {
@@ -31,10 +34,30 @@ impl FromPtr for SimObjectType {
 
         let obj_type = unsafe { *add_or_remove }.eObjType;
 
-        Ok(Self::try_from_primitive(obj_type)?)
+        Self::try_from_primitive(obj_type).map_err(|e| SimConnectError::parse(data, e))
     }
 }
 
+/// The priority of a notification group. Groups are notified of an event in order of priority,
+/// with the simulator itself at `Standard`.
+#[derive(
+    TryFromPrimitive, IntoPrimitive, Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash,
+)]
+#[repr(u32)]
+#[serde(rename = "camelCase")]
+pub enum GroupPriority {
+    /// Notified before anyone else, but cannot mask events
+    Highest = bindings::SIMCONNECT_GROUP_PRIORITY_HIGHEST,
+    /// The highest priority which can mask events from the simulator
+    HighestMaskable = bindings::SIMCONNECT_GROUP_PRIORITY_HIGHEST_MASKABLE,
+    /// The priority of the simulator
+    Standard = bindings::SIMCONNECT_GROUP_PRIORITY_STANDARD,
+    /// Notified after the simulator
+    Default = bindings::SIMCONNECT_GROUP_PRIORITY_DEFAULT,
+    /// Notified after everyone else
+    Lowest = bindings::SIMCONNECT_GROUP_PRIORITY_LOWEST,
+}
+
 #[derive(TryFromPrimitive, Debug, Serialize, Deserialize, Clone, Copy)]
 #[repr(u32)]
 #[serde(rename = "camelCase")]
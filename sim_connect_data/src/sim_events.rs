@@ -2,11 +2,13 @@ use num_enum::{IntoPrimitive, TryFromPrimitive};
 use sim_connect_macros::{FromStr, IterEnum, ToSimConnect};
 use sim_connect_sys::bindings;
 use std::{
-    ffi::{CStr, CString},
+    ffi::{c_char, CStr, CString},
     mem::transmute,
+    ptr::NonNull,
 };
 
 use crate::{
+    error::{SimConnectError, SimConnectResult},
     internals::IterEnum,
     recv_data::FromPtr,
     sim_event_args::{SimObjectType, SimViewType},
@@ -69,12 +71,13 @@ pub struct SystemEventDataHolder {
 }
 
 impl FromPtr for SystemEventDataHolder {
-    fn from_pointer(data: std::ptr::NonNull<bindings::SIMCONNECT_RECV>) -> anyhow::Result<Self>
+    fn from_pointer(data: NonNull<bindings::SIMCONNECT_RECV>) -> SimConnectResult<Self>
     where
         Self: Sized,
     {
         let raw_ptr: *mut bindings::SIMCONNECT_RECV_EVENT = unsafe { transmute(data.as_ptr()) };
-        let event: SystemEvent = SystemEvent::try_from_primitive(unsafe { *raw_ptr }.uEventID)?;
+        let event: SystemEvent = SystemEvent::try_from_primitive(unsafe { *raw_ptr }.uEventID)
+            .map_err(|e| SimConnectError::parse(data, e))?;
         let event_data = SystemEventData::from_pointer(data)?;
         Ok(Self {
             event_data,
@@ -113,51 +116,38 @@ pub enum SystemEventData {
 }
 
 fn extract_name_from_filename(
-    name_ptr: *mut bindings::SIMCONNECT_RECV_EVENT_FILENAME,
-) -> anyhow::Result<String> {
+    data: NonNull<bindings::SIMCONNECT_RECV>,
+) -> SimConnectResult<String> {
+    let name_ptr: *mut bindings::SIMCONNECT_RECV_EVENT_FILENAME =
+        unsafe { transmute(data.as_ptr()) };
     let evt = unsafe { *name_ptr };
-    let str_ref = &evt.szFileName as *const i8;
-    Ok(unsafe { CStr::from_ptr(str_ref) }.to_str()?.to_owned())
+    let str_ref = &evt.szFileName as *const c_char;
+    Ok(unsafe { CStr::from_ptr(str_ref) }
+        .to_str()
+        .map_err(|e| SimConnectError::parse(data, e))?
+        .to_owned())
 }
 
 impl FromPtr for SystemEventData {
-    fn from_pointer(
-        data: std::ptr::NonNull<sim_connect_sys::bindings::SIMCONNECT_RECV>,
-    ) -> anyhow::Result<Self>
+    fn from_pointer(data: NonNull<bindings::SIMCONNECT_RECV>) -> SimConnectResult<Self>
     where
         Self: Sized,
     {
         let raw_ptr: *mut bindings::SIMCONNECT_RECV_EVENT = unsafe { transmute(data.as_ptr()) };
-        let event: SystemEvent = SystemEvent::try_from_primitive(unsafe { *raw_ptr }.uEventID)?;
+        let event: SystemEvent = SystemEvent::try_from_primitive(unsafe { *raw_ptr }.uEventID)
+            .map_err(|e| SimConnectError::parse(data, e))?;
 
         Ok(match event {
             SystemEvent::OneSec => Self::OneSec,
             SystemEvent::FourSec => Self::FourSec,
             SystemEvent::SixHz => Self::SixHz,
-            SystemEvent::AircraftLoaded => {
-                let evt_filename: *mut bindings::SIMCONNECT_RECV_EVENT_FILENAME =
-                    unsafe { transmute(raw_ptr) };
-                Self::AircraftLoaded(extract_name_from_filename(evt_filename)?)
-            }
+            SystemEvent::AircraftLoaded => Self::AircraftLoaded(extract_name_from_filename(data)?),
             SystemEvent::Crashed => Self::Crashed,
             SystemEvent::CrashReset => Self::CrashReset,
-            SystemEvent::FlightLoaded => {
-                let evt_filename: *mut bindings::SIMCONNECT_RECV_EVENT_FILENAME =
-                    unsafe { transmute(raw_ptr) };
-
-                Self::FlightLoaded(extract_name_from_filename(evt_filename)?)
-            }
-            SystemEvent::FlightSaved => {
-                let evt_filename: *mut bindings::SIMCONNECT_RECV_EVENT_FILENAME =
-                    unsafe { transmute(raw_ptr) };
-
-                Self::FlightSaved(extract_name_from_filename(evt_filename)?)
-            }
+            SystemEvent::FlightLoaded => Self::FlightLoaded(extract_name_from_filename(data)?),
+            SystemEvent::FlightSaved => Self::FlightSaved(extract_name_from_filename(data)?),
             SystemEvent::FlightPlanActivated => {
-                let evt_filename: *mut bindings::SIMCONNECT_RECV_EVENT_FILENAME =
-                    unsafe { transmute(raw_ptr) };
-
-                Self::FlightPlanActivated(extract_name_from_filename(evt_filename)?)
+                Self::FlightPlanActivated(extract_name_from_filename(data)?)
             }
             SystemEvent::FlightPlanDeactivated => Self::FlightPlanDeactivated,
             SystemEvent::Frame => Self::Frame,
@@ -177,7 +167,8 @@ impl FromPtr for SystemEventData {
             SystemEvent::Sound => Self::Sound,
             SystemEvent::Unpaused => Self::Unpaused,
             SystemEvent::View => {
-                let view = SimViewType::try_from_primitive(unsafe { *raw_ptr }.dwData)?;
+                let view = SimViewType::try_from_primitive(unsafe { *raw_ptr }.dwData)
+                    .map_err(|e| SimConnectError::parse(data, e))?;
                 Self::View(view)
             }
         })
@@ -1,4 +1,7 @@
-use crate::internals::{IterEnum, ToSimConnect};
+use crate::{
+    error::SimConnectError,
+    internals::{IterEnum, ToSimConnect},
+};
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use sim_connect_macros::{FromStr, InputEvent, IterEnum, ToSimConnect};
 use std::ffi::CString;
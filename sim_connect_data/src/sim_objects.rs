@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+use sim_connect_sys::bindings;
+use std::fmt::Display;
+
+/// Identifies a simulation object: the user's aircraft, an AI or multiplayer object, or an
+/// object created by the client
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, Default,
+)]
+pub struct SimObjectId(pub u32);
+
+impl SimObjectId {
+    /// The user's aircraft
+    pub const USER: Self = Self(bindings::SIMCONNECT_OBJECT_ID_USER);
+}
+
+impl From<u32> for SimObjectId {
+    fn from(object_id: u32) -> Self {
+        Self(object_id)
+    }
+}
+
+impl From<SimObjectId> for u32 {
+    fn from(object_id: SimObjectId) -> Self {
+        object_id.0
+    }
+}
+
+impl Display for SimObjectId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// `INITPOSITION_AIRSPEED_CRUISE` and `INITPOSITION_AIRSPEED_KEEP` from `SimConnect.h`, which
+/// aren't part of the generated bindings
+const INITPOSITION_AIRSPEED_CRUISE: u32 = u32::MAX;
+const INITPOSITION_AIRSPEED_KEEP: u32 = u32::MAX - 1;
+
+/// The speed an object is created at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename = "camelCase")]
+pub enum InitAirspeed {
+    Knots(u32),
+    /// The cruise speed of the aircraft
+    Cruise,
+    /// The speed the object already has, when repositioning it
+    Keep,
+}
+
+impl From<InitAirspeed> for u32 {
+    fn from(airspeed: InitAirspeed) -> Self {
+        match airspeed {
+            InitAirspeed::Knots(knots) => knots,
+            InitAirspeed::Cruise => INITPOSITION_AIRSPEED_CRUISE,
+            InitAirspeed::Keep => INITPOSITION_AIRSPEED_KEEP,
+        }
+    }
+}
+
+/// Where, and how, an object is created. Mirrors `SIMCONNECT_DATA_INITPOSITION`.
+///
+/// # Example
+///
+/// ```
+/// use sim_connect_data::sim_objects::{InitAirspeed, InitPosition};
+///
+/// // Lined up on runway 16L at KSEA
+/// let position = InitPosition::new(47.4638, -122.3079, 433.0)
+///     .heading(180.0)
+///     .on_ground();
+/// assert_eq!(position.airspeed, InitAirspeed::Knots(0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename = "camelCase")]
+pub struct InitPosition {
+    /// Degrees, north positive
+    pub latitude: f64,
+    /// Degrees, east positive
+    pub longitude: f64,
+    /// Feet above mean sea level
+    pub altitude: f64,
+    /// Degrees, nose down positive
+    pub pitch: f64,
+    /// Degrees, right wing down positive
+    pub bank: f64,
+    /// Degrees true
+    pub heading: f64,
+    pub on_ground: bool,
+    pub airspeed: InitAirspeed,
+}
+
+impl InitPosition {
+    /// Level, in the air, facing north and standing still
+    pub fn new(latitude: f64, longitude: f64, altitude: f64) -> Self {
+        Self {
+            latitude,
+            longitude,
+            altitude,
+            pitch: 0.0,
+            bank: 0.0,
+            heading: 0.0,
+            on_ground: false,
+            airspeed: InitAirspeed::Knots(0),
+        }
+    }
+
+    pub fn heading(mut self, heading: f64) -> Self {
+        self.heading = heading;
+        self
+    }
+
+    pub fn attitude(mut self, pitch: f64, bank: f64) -> Self {
+        self.pitch = pitch;
+        self.bank = bank;
+        self
+    }
+
+    /// Places the object on the ground, ignoring `altitude`
+    pub fn on_ground(mut self) -> Self {
+        self.on_ground = true;
+        self
+    }
+
+    pub fn airspeed(mut self, airspeed: InitAirspeed) -> Self {
+        self.airspeed = airspeed;
+        self
+    }
+}
+
+impl From<InitPosition> for bindings::SIMCONNECT_DATA_INITPOSITION {
+    fn from(position: InitPosition) -> Self {
+        Self {
+            Latitude: position.latitude,
+            Longitude: position.longitude,
+            Altitude: position.altitude,
+            Pitch: position.pitch,
+            Bank: position.bank,
+            Heading: position.heading,
+            OnGround: position.on_ground as u32,
+            Airspeed: position.airspeed.into(),
+        }
+    }
+}
@@ -6,7 +6,7 @@ pub trait IntoSimVarType {
 
 use sim_connect_sys::bindings;
 
-#[derive(Debug, FromPrimitive)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive)]
 #[repr(i32)]
 pub enum SimVarType {
     #[default]
@@ -29,6 +29,28 @@ pub enum SimVarType {
     XYZ = bindings::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_XYZ,
 }
 
+impl SimVarType {
+    /// Size in bytes of a value of this type in a data message, or `None` for `StringV` (whose
+    /// values are sized by their contents) and `Invalid`
+    pub fn size(&self) -> Option<usize> {
+        Some(match self {
+            Self::I32 | Self::F32 => 4,
+            Self::I64 | Self::F64 | Self::String8 => 8,
+            Self::String32 => 32,
+            Self::String64 => 64,
+            Self::String128 => 128,
+            Self::String256 => 256,
+            Self::String260 => 260,
+            Self::InitPos => std::mem::size_of::<bindings::SIMCONNECT_DATA_INITPOSITION>(),
+            Self::MarkerState => std::mem::size_of::<bindings::SIMCONNECT_DATA_MARKERSTATE>(),
+            Self::Waypoint => std::mem::size_of::<bindings::SIMCONNECT_DATA_WAYPOINT>(),
+            Self::LatLongAlt => std::mem::size_of::<bindings::SIMCONNECT_DATA_LATLONALT>(),
+            Self::XYZ => std::mem::size_of::<bindings::SIMCONNECT_DATA_XYZ>(),
+            Self::StringV | Self::Invalid => return None,
+        })
+    }
+}
+
 impl IntoSimVarType for i32 {
     fn into_sim_var() -> SimVarType {
         SimVarType::I32
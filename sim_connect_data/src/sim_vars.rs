@@ -1,9 +1,37 @@
 use sim_connect_macros::ToSimConnect;
-use std::ffi::CString;
+use std::{
+    ffi::CString,
+    hash::{Hash, Hasher},
+};
 
 use super::internals::ToSimConnect;
 
-#[derive(Hash, PartialEq, Eq, Debug, ToSimConnect)]
+/// A simulation variable, by the name SimConnect knows it by.
+///
+/// Variables which aren't listed here can still be used with `SimVar::Custom`, and those with
+/// one instance per engine, tank, radio and so on are picked with `SimVar::index`.
+///
+/// # Example
+///
+/// ```
+/// use sim_connect_data::sim_vars::SimVar;
+///
+/// assert_eq!(SimVar::GeneralEngRpm.index(2).to_string(), "GENERAL ENG RPM:2");
+/// assert_eq!(SimVar::Custom("L:MY_VAR".to_owned()).to_string(), "L:MY_VAR");
+/// ```
+///
+/// Simvars are compared by that name, without regard to case as SimConnect does, so a
+/// `SimVar::Custom` is equal to the listed simvar of the same name:
+///
+/// ```
+/// use sim_connect_data::sim_vars::SimVar;
+///
+/// assert_eq!(
+///     SimVar::Custom("general eng rpm:2".to_owned()),
+///     SimVar::GeneralEngRpm.index(2)
+/// );
+/// ```
+#[derive(Debug, Clone, ToSimConnect)]
 pub enum SimVar {
     #[string(name = "Kohlsman Setting hg")]
     KohlsmanHG,
@@ -66,6 +94,19 @@ pub enum SimVar {
     #[string(name = "Unlimited Fuel")]
     IsUnlimitedFuelSet,
 
+    /* #endregion */
+    /* #region Engines */
+    /// Indexed by engine, starting at 1
+    #[string(name = "GENERAL ENG RPM")]
+    GeneralEngRpm,
+    /// Indexed by engine, starting at 1
+    #[string(name = "GENERAL ENG THROTTLE LEVER POSITION")]
+    GeneralEngThrottleLeverPosition,
+    /// Indexed by engine, starting at 1
+    #[string(name = "GENERAL ENG COMBUSTION")]
+    GeneralEngCombustion,
+    #[string(name = "NUMBER OF ENGINES")]
+    NumberOfEngines,
     /* #endregion */
     #[string(name = "Autobrakes Active")]
     AutobrakesActive,
@@ -92,4 +133,35 @@ pub enum SimVar {
     VerticalSpeed,
     #[string(name = "ZULU TIME")]
     ZuluTime,
+
+    /// Any other simvar, or an L-var such as `L:MY_VAR`, by its full name. Names containing a
+    /// null character are rejected when the simvar is registered.
+    #[string(format = "{0}")]
+    Custom(String),
+    /// An instance of a simvar with more than one, such as the RPM of the second engine,
+    /// written as `NAME:INDEX`. Usually made with `SimVar::index`.
+    #[string(format = "{0}:{1}")]
+    Indexed(Box<SimVar>, u32),
+}
+
+impl PartialEq for SimVar {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_string().eq_ignore_ascii_case(&other.to_string())
+    }
+}
+
+impl Eq for SimVar {}
+
+impl Hash for SimVar {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_string().to_ascii_uppercase().hash(state);
+    }
+}
+
+impl SimVar {
+    /// The `index`th instance of this simvar. Indexes start at 1 for engines, tanks and most
+    /// other simvars.
+    pub fn index(self, index: u32) -> Self {
+        Self::Indexed(Box::new(self), index)
+    }
 }
@@ -0,0 +1,150 @@
+//! The protocol spoken with a WASM module running inside the simulator, which reads local
+//! (`L:`) variables and fires `H:` events on behalf of external clients, neither of which
+//! SimConnect can do itself.
+//!
+//! The protocol follows MobiFlight's WASM module. The module is shared with every other client
+//! using it, such as the MobiFlight Connector, so each client talks to it through a channel of
+//! its own, named after the client:
+//!
+//! - The client registers its channel by writing `BridgeCommand::AddClient` to the default
+//!   channel's `COMMAND_AREA`. The module creates the channel's areas, then answers with
+//!   `client_added` on `RESPONSE_AREA`.
+//! - Commands are written as strings to the channel's `command_area`
+//! - The module writes its responses, such as the answer to a ping, to the channel's
+//!   `response_area`
+//! - Every variable registered with `BridgeCommand::AddVariable` gets a `f32` slot in the
+//!   channel's `value_area`, numbered in the order the variables were registered, which the
+//!   module keeps up to date with the variable's value
+use crate::client_data::{ClientData, ClientDataField};
+use std::fmt::{self, Display};
+
+/// The channel the module sets up for itself, which clients register their own through
+pub const DEFAULT_CLIENT: &str = "MobiFlight";
+/// The command area of the default channel
+pub const COMMAND_AREA: &str = "MobiFlight.Command";
+/// The response area of the default channel
+pub const RESPONSE_AREA: &str = "MobiFlight.Response";
+
+/// Size of the command and response areas, including the null terminator
+pub const MESSAGE_SIZE: usize = 256;
+/// Size of each registered variable's slot in a `value_area`
+pub const VALUE_SIZE: usize = std::mem::size_of::<f32>();
+/// Size of a `value_area`
+pub const VALUE_AREA_SIZE: usize = 4096;
+/// How many variables can be registered at once
+pub const MAX_VARIABLES: usize = VALUE_AREA_SIZE / VALUE_SIZE;
+
+/// The response to `BridgeCommand::Ping`
+pub const PONG: &str = "MF.Pong";
+
+/// The area the commands of `client`'s channel are written to
+pub fn command_area(client: &str) -> String {
+    format!("{client}.Command")
+}
+
+/// The area the module writes the responses on `client`'s channel to
+pub fn response_area(client: &str) -> String {
+    format!("{client}.Response")
+}
+
+/// The area holding the values of the variables registered on `client`'s channel
+pub fn value_area(client: &str) -> String {
+    format!("{client}.LVars")
+}
+
+/// The response on the default channel to `BridgeCommand::AddClient`, once the client's
+/// channel is ready
+pub fn client_added(client: &str) -> String {
+    format!("MF.Clients.Add.{client}.Finished")
+}
+
+/// A command written to a `command_area`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BridgeCommand {
+    /// Asks the module to answer with `PONG`
+    Ping,
+    /// Asks the module for a channel of the client's own. Only written to the default channel.
+    AddClient(String),
+    /// Registers a variable, e.g. `(L:A32NX_AUTOPILOT_1_ACTIVE)`, giving it the next slot in
+    /// the channel's `value_area`
+    AddVariable(String),
+    /// Unregisters every variable, so the next one registered gets the first slot again
+    ClearVariables,
+    /// Executes calculator code, e.g. `(>H:A320_Neo_CDU_1_BTN_INIT)` or `1 (>L:MY_VAR)`
+    Execute(String),
+}
+
+impl BridgeCommand {
+    /// Parses a command as written to a `command_area`, returning `None` if it isn't one
+    pub fn parse(command: &str) -> Option<Self> {
+        let command = command.strip_prefix("MF.")?;
+        match command {
+            "Ping" => Some(Self::Ping),
+            "SimVars.Clear" => Some(Self::ClearVariables),
+            _ => {
+                if let Some(client) = command.strip_prefix("Clients.Add.") {
+                    Some(Self::AddClient(client.to_owned()))
+                } else if let Some(variable) = command.strip_prefix("SimVars.Add.") {
+                    Some(Self::AddVariable(variable.to_owned()))
+                } else {
+                    command
+                        .strip_prefix("SimVars.Set.")
+                        .map(|code| Self::Execute(code.to_owned()))
+                }
+            }
+        }
+    }
+}
+
+impl Display for BridgeCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ping => f.write_str("MF.Ping"),
+            Self::AddClient(client) => write!(f, "MF.Clients.Add.{client}"),
+            Self::AddVariable(variable) => write!(f, "MF.SimVars.Add.{variable}"),
+            Self::ClearVariables => f.write_str("MF.SimVars.Clear"),
+            Self::Execute(code) => write!(f, "MF.SimVars.Set.{code}"),
+        }
+    }
+}
+
+/// The contents of command and response areas, a null-terminated string
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub struct BridgeMessage {
+    text: [u8; MESSAGE_SIZE],
+}
+
+impl BridgeMessage {
+    /// Returns `None` if `text` doesn't fit in the area with its null terminator
+    pub fn new(text: &str) -> Option<Self> {
+        if text.len() >= MESSAGE_SIZE {
+            return None;
+        }
+        let mut message = Self {
+            text: [0; MESSAGE_SIZE],
+        };
+        message.text[..text.len()].copy_from_slice(text.as_bytes());
+        Some(message)
+    }
+
+    /// The text up to the null terminator
+    pub fn text(&self) -> String {
+        let end = self
+            .text
+            .iter()
+            .position(|byte| *byte == 0)
+            .unwrap_or(MESSAGE_SIZE);
+        String::from_utf8_lossy(&self.text[..end]).into_owned()
+    }
+}
+
+unsafe impl ClientData for BridgeMessage {
+    fn fields() -> Vec<ClientDataField> {
+        vec![ClientDataField {
+            offset: 0,
+            size_or_type: MESSAGE_SIZE as u32,
+            epsilon: 0.0,
+        }]
+    }
+}
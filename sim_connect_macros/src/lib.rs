@@ -7,8 +7,10 @@ use syn::{parse_macro_input, Data, DataEnum, DataStruct, DeriveInput, Fields, Id
 #[derive(FromField)]
 #[darling(attributes(datum))]
 struct Opts {
-    sim_var: syn::Path,
+    sim_var: syn::Expr,
     sim_unit: Option<syn::Path>,
+    /// Makes the datum the `index`th instance of `sim_var`, e.g. the RPM of engine 2
+    index: Option<u32>,
 }
 
 #[proc_macro_derive(StructToSimConnect, attributes(datum))]
@@ -26,29 +28,35 @@ pub fn derive(input: TokenStream) -> TokenStream {
     };
 
     let sim_var = fields.iter().map(|field| {
-        Opts::from_field(field)
-            .expect("All fields in a SimConnect struct need to contain a #[datum(..)] attribute")
-            .sim_var
+        let opts = Opts::from_field(field)
+            .expect("All fields in a SimConnect struct need to contain a #[datum(..)] attribute");
+        let sim_var = opts.sim_var;
+
+        match opts.index {
+            Some(index) => quote! {
+                (#sim_var).index(#index)
+            },
+            None => quote! {
+                #sim_var
+            },
+        }
     });
     let sim_unit = fields.iter().map(|field| {
         let unit = Opts::from_field(field)
             .expect("All fields in a SimConnect struct need to contain a #[datum(..)] attribute")
             .sim_unit;
 
-        let to_return;
-        if let None = unit {
-            to_return = quote! {
-                None
-            }
-        } else {
-            let unit = unit.unwrap();
-            to_return = quote! {
+        match unit {
+            Some(unit) => quote! {
                 Some(Box::new(#unit))
-            }
+            },
+            None => quote! {
+                None
+            },
         }
-        to_return
     });
     let data_type = fields.iter().map(|field| field.ty.clone());
+    let field_name = fields.iter().map(|field| field.ident.clone());
 
     let id = (0..sim_var.len()).map(|id| id as u32);
 
@@ -67,6 +75,16 @@ pub fn derive(input: TokenStream) -> TokenStream {
                     )*
                 ]
             }
+
+            fn write_data(
+                &self,
+                writer: &mut sim_connect_rs::sim_data::DataWriter,
+            ) -> sim_connect_rs::error::SimConnectResult<()> {
+                #(
+                    writer.write(&self.#field_name)?;
+                )*
+                Ok(())
+            }
         }
     }
     .into()
@@ -75,7 +93,9 @@ pub fn derive(input: TokenStream) -> TokenStream {
 #[derive(FromVariant)]
 #[darling(attributes(string))]
 struct StringOpts {
-    name: String,
+    name: Option<String>,
+    /// How a variant with fields is written, with `{0}`, `{1}`... standing for its fields
+    format: Option<String>,
 }
 
 #[proc_macro_derive(ToSimConnect, attributes(string))]
@@ -88,33 +108,48 @@ pub fn enum_to_sim_string(input: TokenStream) -> TokenStream {
         _ => panic!("expected an enum"),
     };
 
-    let variant_ident = variants.iter().map(|var| {
-        if !var.fields.is_empty() {
-            panic!("Enum should not have any fields");
-        }
-        var.ident.to_owned()
-    });
+    let variant_arm = variants.iter().map(|var| {
+        let variant_ident = &var.ident;
+        let opts = StringOpts::from_variant(var).expect("Invalid #[string(..)] attribute");
 
-    let variant_string = variants.iter().map(|var| {
-        let default = var.ident.to_string();
-        let opts = StringOpts::from_variant(var).unwrap_or(StringOpts { name: default });
-        opts.name
+        match &var.fields {
+            Fields::Unit => {
+                let name = opts.name.unwrap_or_else(|| variant_ident.to_string());
+                quote! {
+                    Self::#variant_ident => f.write_str(#name),
+                }
+            }
+            Fields::Unnamed(fields) => {
+                let format = opts.format.expect(
+                    "Enum variants with fields need a #[string(format = \"..\")] attribute",
+                );
+                let field: Vec<Ident> = (0..fields.unnamed.len())
+                    .map(|index| Ident::new(&format!("field_{index}"), Span::call_site()))
+                    .collect();
+                quote! {
+                    Self::#variant_ident(#(#field),*) => write!(f, #format, #(#field),*),
+                }
+            }
+            Fields::Named(_) => panic!("Enum variants can only have unnamed fields"),
+        }
     });
 
     let to_return = quote! {
-        impl std::string::ToString for #ident {
-            fn to_string(&self) -> String {
+        impl std::fmt::Display for #ident {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
                 match self {
-                    # (
-                        Self::#variant_ident => #variant_string.to_owned(),
-                    )*
+                    #(#variant_arm)*
                 }
             }
         }
 
         impl ToSimConnect for #ident {
             fn sc_string(&self) -> std::ffi::CString {
-                CString::new(self.to_string()).unwrap()
+                self.try_sc_string().unwrap()
+            }
+
+            fn try_sc_string(&self) -> Result<std::ffi::CString, std::ffi::NulError> {
+                CString::new(self.to_string())
             }
         }
     };
@@ -187,7 +222,7 @@ pub fn enum_from_str(input: TokenStream) -> TokenStream {
 
     let to_return = quote! {
         impl std::str::FromStr for #ident {
-            type Err = anyhow::Error;
+            type Err = SimConnectError;
 
             fn from_str(input_str: &str) -> Result<Self, Self::Err> {
                 let self_iter = Self::iter_enum();
@@ -200,7 +235,7 @@ pub fn enum_from_str(input: TokenStream) -> TokenStream {
                     }
                 }
 
-                Err(anyhow::anyhow!("Unable to convert {0} into a string", input_str))
+                Err(SimConnectError::UnknownName(input_str.to_owned()))
             }
         }
     };
@@ -225,7 +260,7 @@ pub fn c_data_to_struct(input: TokenStream) -> TokenStream {
     let mut converted_field_idents: Vec<Ident> = Vec::new();
     let borrowed_vec = &mut converted_field_idents;
 
-    let base_pointer_name = Ident::new(&format!("pointer"), Span::call_site());
+    let base_pointer_name = Ident::new("pointer", Span::call_site());
     let cloned_base_pointer_name = base_pointer_name.clone();
     let total_fields = &fields.len();
     let mut current_iter = 0usize;
@@ -275,7 +310,7 @@ pub fn c_data_to_struct(input: TokenStream) -> TokenStream {
                 quote! {std::mem::transmute(#previous_field_name)}
             } else {
                 let temp_prev_field_size = previous_field_name.to_token_stream().to_string().replace("_pointer", "_size");
-                let prev_field_size_ident = Ident::new(&format!("{temp_prev_field_size}"), Span::call_site());
+                let prev_field_size_ident = Ident::new(&temp_prev_field_size, Span::call_site());
                 quote! {
                     std::mem::transmute(#previous_field_name.add(#prev_field_size_ident))
                 }
@@ -327,3 +362,65 @@ pub fn c_data_to_struct(input: TokenStream) -> TokenStream {
 
     to_return.into()
 }
+
+#[derive(FromField)]
+#[darling(attributes(client_data))]
+struct ClientDataOpts {
+    epsilon: Option<f32>,
+}
+
+#[proc_macro_derive(ClientData, attributes(client_data))]
+pub fn derive_client_data(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let ident = &input.ident;
+
+    let is_repr_c = input.attrs.iter().any(|attr| {
+        attr.path.is_ident("repr")
+            && attr
+                .parse_args_with(
+                    syn::punctuated::Punctuated::<Ident, syn::Token![,]>::parse_terminated,
+                )
+                .is_ok_and(|reprs| reprs.iter().any(|repr| repr == "C"))
+    });
+    if !is_repr_c {
+        panic!("ClientData structs need to be #[repr(C)], so every client agrees on the layout");
+    }
+    if !input.generics.params.is_empty() {
+        panic!("ClientData structs cannot be generic");
+    }
+
+    let fields = match &input.data {
+        Data::Struct(DataStruct {
+            fields: Fields::Named(fields),
+            ..
+        }) => &fields.named,
+        _ => panic!("expected a struct with named fields"),
+    };
+
+    let field_ident = fields.iter().map(|field| field.ident.clone());
+    let field_type = fields.iter().map(|field| field.ty.clone());
+    let epsilon = fields.iter().map(|field| {
+        ClientDataOpts::from_field(field)
+            .expect("expected #[client_data(epsilon = <f32>)]")
+            .epsilon
+            .unwrap_or_default()
+    });
+
+    quote! {
+        unsafe impl sim_connect_rs::ClientData for #ident {
+            fn fields() -> Vec<sim_connect_rs::client_data::ClientDataField> {
+                use sim_connect_rs::client_data::ClientDataValue;
+                vec![
+                    #(
+                        sim_connect_rs::client_data::ClientDataField {
+                            offset: std::mem::offset_of!(#ident, #field_ident) as u32,
+                            size_or_type: <#field_type as ClientDataValue>::SIZE_OR_TYPE,
+                            epsilon: #epsilon,
+                        },
+                    )*
+                ]
+            }
+        }
+    }
+    .into()
+}
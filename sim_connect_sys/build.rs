@@ -2,24 +2,49 @@ use std::env;
 use std::path::PathBuf;
 
 #[cfg(feature = "static_link")]
-const SDK_PATH: &'static str = r"C:\MSFS SDK\SimConnect SDK\lib\static";
+const SDK_PATH: &str = r"C:\MSFS SDK\SimConnect SDK\lib\static";
 #[cfg(not(feature = "static_link"))]
-const SDK_PATH: &'static str = r"C:\MSFS SDK\SimConnect SDK\lib";
+const SDK_PATH: &str = r"C:\MSFS SDK\SimConnect SDK\lib";
 
 #[cfg(not(feature = "static_link"))]
-const LINK_LIB_ARGS: &'static str = "dylib=SimConnect";
+const LINK_LIB_ARGS: &str = "dylib=SimConnect";
 #[cfg(feature = "static_link")]
-const LINK_LIB_ARGS: &'static str = "static=SimConnect";
+const LINK_LIB_ARGS: &str = "static=SimConnect";
+
+const INCLUDE_PATH: &str = r"C:\MSFS SDK\SimConnect SDK\include";
+
+const PREGENERATED_BINDINGS: &str = "src/pregenerated.rs";
+
+/// The SDK only exists on Windows, so every other target (and docs.rs) falls back to the
+/// checked in bindings and skips linking
+fn use_pregenerated() -> bool {
+    cfg!(feature = "pregenerated")
+        || env::var_os("DOCS_RS").is_some()
+        || env::var("CARGO_CFG_TARGET_OS").is_ok_and(|os| os != "windows")
+}
 
 fn main() {
+    let out_path = PathBuf::from(env::var("OUT_DIR").unwrap());
+    println!("cargo:rerun-if-env-changed=DOCS_RS");
+
+    if use_pregenerated() {
+        println!("cargo:rerun-if-changed={PREGENERATED_BINDINGS}");
+        std::fs::copy(PREGENERATED_BINDINGS, out_path.join("bindings.rs"))
+            .expect("Couldn't copy pregenerated bindings!");
+        return;
+    }
+
     let sdk_path = std::env::var("SIMCONNECT_SDK").unwrap_or(SDK_PATH.to_owned());
+    let include_path = std::env::var("SIMCONNECT_INCLUDE").unwrap_or(INCLUDE_PATH.to_owned());
     println!("cargo:rerun-if-changed=wrapper.hpp");
     println!("cargo:rerun-if-env-changed=SIMCONNECT_SDK");
+    println!("cargo:rerun-if-env-changed=SIMCONNECT_INCLUDE");
     println!("cargo:rustc-link-lib={LINK_LIB_ARGS}");
     println!("cargo:rustc-link-search={sdk_path}");
 
     let bindings = bindgen::Builder::default()
         .header("wrapper.hpp")
+        .clang_arg(format!("-I{include_path}"))
         .parse_callbacks(Box::new(bindgen::CargoCallbacks))
         .allowlist_function("SimConnect.*")
         .allowlist_type("SIMCONNECT.*")
@@ -27,7 +52,6 @@ fn main() {
         .generate()
         .expect("Unable to generate bindings");
 
-    let out_path = PathBuf::from(env::var("OUT_DIR").unwrap());
     bindings
         .write_to_file(out_path.join("bindings.rs"))
         .expect("Couldn't write bindings!");
@@ -1,2 +1,2 @@
 #[allow(dead_code)]
-pub mod bindings;
\ No newline at end of file
+pub mod bindings;
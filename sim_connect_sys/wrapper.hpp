@@ -1,2 +1,2 @@
 #include "Windows.h"
-#include "C:\MSFS SDK\SimConnect SDK\include\SimConnect.h"
\ No newline at end of file
+#include "SimConnect.h"
@@ -1,6 +1,12 @@
 mod sim_connect;
 #[cfg(feature = "derive")]
-pub use sim_connect::sim_connect_macros::{SimConnectToStruct, StructToSimConnect};
-pub use sim_connect::SimConnect;
-pub use sim_connect::{recv_data, sim_event_args, sim_events, sim_units, sim_var_types, sim_vars};
+pub use sim_connect::sim_connect_macros::{ClientData, SimConnectToStruct, StructToSimConnect};
+pub use sim_connect::{
+    aircraft_input_events, client_data, data_definition, error, facilities, flight, flight_plan,
+    input_definition, recv_data, sim_data, sim_event_args, sim_events, sim_input_events,
+    sim_objects, sim_units, sim_var_types, sim_vars, subscription, wasm_bridge,
+};
+pub use sim_connect::{backend, SimConnect};
+pub use sim_connect_data::client_data::ClientData;
+pub use sim_connect_data::error::{SimConnectError, SimConnectResult};
 pub use sim_connect_data::{SimConnectDatum, SimConnectToStruct, StructToSimConnect};
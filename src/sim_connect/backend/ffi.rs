@@ -0,0 +1,710 @@
+use sim_connect_data::error::{SimConnectError, SimConnectResult};
+use sim_connect_sys::bindings;
+use std::{
+    ffi::{c_void, CStr},
+    ptr::NonNull,
+};
+
+use super::SimConnectBackend;
+
+/// Talks to the simulator through the native `SimConnect.dll`
+#[derive(Default)]
+pub struct FfiBackend {
+    handle: Option<NonNull<c_void>>,
+}
+
+// The handle is only ever used behind the `Mutex` owned by `SimConnect`
+unsafe impl Send for FfiBackend {}
+
+impl FfiBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn handle(&self) -> SimConnectResult<bindings::HANDLE> {
+        self.handle
+            .map(|handle| handle.as_ptr())
+            .ok_or(SimConnectError::NotConnected)
+    }
+}
+
+impl SimConnectBackend for FfiBackend {
+    fn open(&mut self, program_name: &CStr) -> SimConnectResult<()> {
+        let mut handle = std::ptr::null_mut() as bindings::HANDLE;
+
+        check_hr!(unsafe {
+            bindings::SimConnect_Open(
+                &mut handle,
+                program_name.as_ptr(),
+                std::ptr::null_mut(),
+                0,
+                std::ptr::null_mut(),
+                0,
+            )
+        });
+
+        self.handle = Some(NonNull::new(handle).ok_or(SimConnectError::NotConnected)?);
+        Ok(())
+    }
+
+    fn close(&mut self) -> SimConnectResult<()> {
+        if let Some(handle) = self.handle.take() {
+            check_hr!(unsafe { bindings::SimConnect_Close(handle.as_ptr()) });
+        }
+        Ok(())
+    }
+
+    fn add_to_data_definition(
+        &mut self,
+        define_id: u32,
+        datum_name: &CStr,
+        units_name: &CStr,
+        datum_type: bindings::SIMCONNECT_DATATYPE,
+        epsilon: f32,
+        datum_id: u32,
+    ) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_AddToDataDefinition(
+                self.handle()?,
+                define_id,
+                datum_name.as_ptr(),
+                units_name.as_ptr(),
+                datum_type,
+                epsilon,
+                datum_id,
+            )
+        });
+        Ok(())
+    }
+
+    fn request_data_on_sim_object(
+        &mut self,
+        request_id: u32,
+        define_id: u32,
+        object_id: u32,
+        period: bindings::SIMCONNECT_PERIOD,
+        flags: bindings::SIMCONNECT_DATA_REQUEST_FLAG,
+        origin: u32,
+        interval: u32,
+        limit: u32,
+    ) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_RequestDataOnSimObject(
+                self.handle()?,
+                request_id,
+                define_id,
+                object_id,
+                period,
+                flags,
+                origin,
+                interval,
+                limit,
+            )
+        });
+        Ok(())
+    }
+
+    fn request_data_on_sim_object_type(
+        &mut self,
+        request_id: u32,
+        define_id: u32,
+        radius_meters: u32,
+        object_type: bindings::SIMCONNECT_SIMOBJECT_TYPE,
+    ) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_RequestDataOnSimObjectType(
+                self.handle()?,
+                request_id,
+                define_id,
+                radius_meters,
+                object_type,
+            )
+        });
+        Ok(())
+    }
+
+    fn set_data_on_sim_object(
+        &mut self,
+        define_id: u32,
+        object_id: u32,
+        flags: u32,
+        array_count: u32,
+        unit_size: u32,
+        data: &[u8],
+    ) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_SetDataOnSimObject(
+                self.handle()?,
+                define_id,
+                object_id,
+                flags,
+                array_count,
+                unit_size,
+                data.as_ptr() as *mut c_void,
+            )
+        });
+        Ok(())
+    }
+
+    fn map_client_event_to_sim_event(
+        &mut self,
+        event_id: u32,
+        event_name: &CStr,
+    ) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_MapClientEventToSimEvent(
+                self.handle()?,
+                event_id,
+                event_name.as_ptr(),
+            )
+        });
+        Ok(())
+    }
+
+    fn transmit_client_event(
+        &mut self,
+        object_id: u32,
+        event_id: u32,
+        data: u32,
+        group_id: u32,
+        flags: bindings::SIMCONNECT_EVENT_FLAG,
+    ) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_TransmitClientEvent(
+                self.handle()?,
+                object_id,
+                event_id,
+                data,
+                group_id,
+                flags,
+            )
+        });
+        Ok(())
+    }
+
+    fn transmit_client_event_ex1(
+        &mut self,
+        object_id: u32,
+        event_id: u32,
+        group_id: u32,
+        flags: bindings::SIMCONNECT_EVENT_FLAG,
+        data: [u32; 5],
+    ) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_TransmitClientEvent_EX1(
+                self.handle()?,
+                object_id,
+                event_id,
+                group_id,
+                flags,
+                data[0],
+                data[1],
+                data[2],
+                data[3],
+                data[4],
+            )
+        });
+        Ok(())
+    }
+
+    fn add_client_event_to_notification_group(
+        &mut self,
+        group_id: u32,
+        event_id: u32,
+        maskable: bool,
+    ) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_AddClientEventToNotificationGroup(
+                self.handle()?,
+                group_id,
+                event_id,
+                maskable as bindings::BOOL,
+            )
+        });
+        Ok(())
+    }
+
+    fn remove_client_event(&mut self, group_id: u32, event_id: u32) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_RemoveClientEvent(self.handle()?, group_id, event_id)
+        });
+        Ok(())
+    }
+
+    fn set_notification_group_priority(
+        &mut self,
+        group_id: u32,
+        priority: u32,
+    ) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_SetNotificationGroupPriority(self.handle()?, group_id, priority)
+        });
+        Ok(())
+    }
+
+    fn map_input_event_to_client_event(
+        &mut self,
+        group_id: u32,
+        input_definition: &CStr,
+        down_event_id: u32,
+        down_value: u32,
+        up_event_id: u32,
+        up_value: u32,
+        maskable: bool,
+    ) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_MapInputEventToClientEvent(
+                self.handle()?,
+                group_id,
+                input_definition.as_ptr(),
+                down_event_id,
+                down_value,
+                up_event_id,
+                up_value,
+                maskable as bindings::BOOL,
+            )
+        });
+        Ok(())
+    }
+
+    fn set_input_group_priority(&mut self, group_id: u32, priority: u32) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_SetInputGroupPriority(self.handle()?, group_id, priority)
+        });
+        Ok(())
+    }
+
+    fn remove_input_event(
+        &mut self,
+        group_id: u32,
+        input_definition: &CStr,
+    ) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_RemoveInputEvent(
+                self.handle()?,
+                group_id,
+                input_definition.as_ptr(),
+            )
+        });
+        Ok(())
+    }
+
+    fn clear_input_group(&mut self, group_id: u32) -> SimConnectResult<()> {
+        check_hr!(unsafe { bindings::SimConnect_ClearInputGroup(self.handle()?, group_id) });
+        Ok(())
+    }
+
+    fn set_input_group_state(
+        &mut self,
+        group_id: u32,
+        state: bindings::SIMCONNECT_STATE,
+    ) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_SetInputGroupState(self.handle()?, group_id, state as u32)
+        });
+        Ok(())
+    }
+
+    fn enumerate_input_events(&mut self, request_id: u32) -> SimConnectResult<()> {
+        check_hr!(unsafe { bindings::SimConnect_EnumerateInputEvents(self.handle()?, request_id) });
+        Ok(())
+    }
+
+    fn get_input_event(&mut self, request_id: u32, hash: u64) -> SimConnectResult<()> {
+        check_hr!(unsafe { bindings::SimConnect_GetInputEvent(self.handle()?, request_id, hash) });
+        Ok(())
+    }
+
+    fn set_input_event(&mut self, hash: u64, value: &[u8]) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_SetInputEvent(
+                self.handle()?,
+                hash,
+                value.len() as u32,
+                value.as_ptr() as *mut _,
+            )
+        });
+        Ok(())
+    }
+
+    fn subscribe_input_event(&mut self, hash: u64) -> SimConnectResult<()> {
+        check_hr!(unsafe { bindings::SimConnect_SubscribeInputEvent(self.handle()?, hash) });
+        Ok(())
+    }
+
+    fn unsubscribe_input_event(&mut self, hash: u64) -> SimConnectResult<()> {
+        check_hr!(unsafe { bindings::SimConnect_UnsubscribeInputEvent(self.handle()?, hash) });
+        Ok(())
+    }
+
+    fn enumerate_input_event_params(&mut self, hash: u64) -> SimConnectResult<()> {
+        check_hr!(unsafe { bindings::SimConnect_EnumerateInputEventParams(self.handle()?, hash) });
+        Ok(())
+    }
+
+    fn map_client_data_name_to_id(
+        &mut self,
+        client_data_name: &CStr,
+        client_data_id: u32,
+    ) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_MapClientDataNameToID(
+                self.handle()?,
+                client_data_name.as_ptr(),
+                client_data_id,
+            )
+        });
+        Ok(())
+    }
+
+    fn create_client_data(
+        &mut self,
+        client_data_id: u32,
+        size: u32,
+        flags: bindings::SIMCONNECT_CREATE_CLIENT_DATA_FLAG,
+    ) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_CreateClientData(self.handle()?, client_data_id, size, flags)
+        });
+        Ok(())
+    }
+
+    fn add_to_client_data_definition(
+        &mut self,
+        define_id: u32,
+        offset: u32,
+        size_or_type: u32,
+        epsilon: f32,
+        datum_id: u32,
+    ) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_AddToClientDataDefinition(
+                self.handle()?,
+                define_id,
+                offset,
+                size_or_type,
+                epsilon,
+                datum_id,
+            )
+        });
+        Ok(())
+    }
+
+    fn clear_client_data_definition(&mut self, define_id: u32) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_ClearClientDataDefinition(self.handle()?, define_id)
+        });
+        Ok(())
+    }
+
+    fn request_client_data(
+        &mut self,
+        client_data_id: u32,
+        request_id: u32,
+        define_id: u32,
+        period: bindings::SIMCONNECT_CLIENT_DATA_PERIOD,
+        flags: bindings::SIMCONNECT_CLIENT_DATA_REQUEST_FLAG,
+        origin: u32,
+        interval: u32,
+        limit: u32,
+    ) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_RequestClientData(
+                self.handle()?,
+                client_data_id,
+                request_id,
+                define_id,
+                period,
+                flags,
+                origin,
+                interval,
+                limit,
+            )
+        });
+        Ok(())
+    }
+
+    fn set_client_data(
+        &mut self,
+        client_data_id: u32,
+        define_id: u32,
+        flags: bindings::SIMCONNECT_CLIENT_DATA_SET_FLAG,
+        data: &[u8],
+    ) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_SetClientData(
+                self.handle()?,
+                client_data_id,
+                define_id,
+                flags,
+                0,
+                data.len() as u32,
+                data.as_ptr() as *mut c_void,
+            )
+        });
+        Ok(())
+    }
+
+    fn add_to_facility_definition(
+        &mut self,
+        define_id: u32,
+        field_name: &CStr,
+    ) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_AddToFacilityDefinition(
+                self.handle()?,
+                define_id,
+                field_name.as_ptr(),
+            )
+        });
+        Ok(())
+    }
+
+    fn request_facility_data(
+        &mut self,
+        define_id: u32,
+        request_id: u32,
+        icao: &CStr,
+        region: &CStr,
+    ) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_RequestFacilityData(
+                self.handle()?,
+                define_id,
+                request_id,
+                icao.as_ptr(),
+                region.as_ptr(),
+            )
+        });
+        Ok(())
+    }
+
+    fn subscribe_to_facilities(
+        &mut self,
+        list_type: bindings::SIMCONNECT_FACILITY_LIST_TYPE,
+        request_id: u32,
+    ) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_SubscribeToFacilities(self.handle()?, list_type, request_id)
+        });
+        Ok(())
+    }
+
+    fn unsubscribe_to_facilities(
+        &mut self,
+        list_type: bindings::SIMCONNECT_FACILITY_LIST_TYPE,
+    ) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_UnsubscribeToFacilities(self.handle()?, list_type)
+        });
+        Ok(())
+    }
+
+    fn request_facilities_list(
+        &mut self,
+        list_type: bindings::SIMCONNECT_FACILITY_LIST_TYPE,
+        request_id: u32,
+    ) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_RequestFacilitiesList(self.handle()?, list_type, request_id)
+        });
+        Ok(())
+    }
+
+    fn ai_create_parked_atc_aircraft(
+        &mut self,
+        container_title: &CStr,
+        tail_number: &CStr,
+        airport_id: &CStr,
+        request_id: u32,
+    ) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_AICreateParkedATCAircraft(
+                self.handle()?,
+                container_title.as_ptr(),
+                tail_number.as_ptr(),
+                airport_id.as_ptr(),
+                request_id,
+            )
+        });
+        Ok(())
+    }
+
+    fn ai_create_enroute_atc_aircraft(
+        &mut self,
+        container_title: &CStr,
+        tail_number: &CStr,
+        flight_number: i32,
+        flight_plan_path: &CStr,
+        flight_plan_position: f64,
+        touch_and_go: bool,
+        request_id: u32,
+    ) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_AICreateEnrouteATCAircraft(
+                self.handle()?,
+                container_title.as_ptr(),
+                tail_number.as_ptr(),
+                flight_number,
+                flight_plan_path.as_ptr(),
+                flight_plan_position,
+                touch_and_go as bindings::BOOL,
+                request_id,
+            )
+        });
+        Ok(())
+    }
+
+    fn ai_create_non_atc_aircraft(
+        &mut self,
+        container_title: &CStr,
+        tail_number: &CStr,
+        init_position: bindings::SIMCONNECT_DATA_INITPOSITION,
+        request_id: u32,
+    ) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_AICreateNonATCAircraft(
+                self.handle()?,
+                container_title.as_ptr(),
+                tail_number.as_ptr(),
+                init_position,
+                request_id,
+            )
+        });
+        Ok(())
+    }
+
+    fn ai_create_simulated_object(
+        &mut self,
+        container_title: &CStr,
+        init_position: bindings::SIMCONNECT_DATA_INITPOSITION,
+        request_id: u32,
+    ) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_AICreateSimulatedObject(
+                self.handle()?,
+                container_title.as_ptr(),
+                init_position,
+                request_id,
+            )
+        });
+        Ok(())
+    }
+
+    fn ai_release_control(&mut self, object_id: u32, request_id: u32) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_AIReleaseControl(self.handle()?, object_id, request_id)
+        });
+        Ok(())
+    }
+
+    fn ai_remove_object(&mut self, object_id: u32, request_id: u32) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_AIRemoveObject(self.handle()?, object_id, request_id)
+        });
+        Ok(())
+    }
+
+    fn ai_set_aircraft_flight_plan(
+        &mut self,
+        object_id: u32,
+        flight_plan_path: &CStr,
+        request_id: u32,
+    ) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_AISetAircraftFlightPlan(
+                self.handle()?,
+                object_id,
+                flight_plan_path.as_ptr(),
+                request_id,
+            )
+        });
+        Ok(())
+    }
+
+    fn flight_load(&mut self, file_name: &CStr) -> SimConnectResult<()> {
+        check_hr!(unsafe { bindings::SimConnect_FlightLoad(self.handle()?, file_name.as_ptr()) });
+        Ok(())
+    }
+
+    fn flight_save(
+        &mut self,
+        file_name: &CStr,
+        title: &CStr,
+        description: &CStr,
+        flags: u32,
+    ) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_FlightSave(
+                self.handle()?,
+                file_name.as_ptr(),
+                title.as_ptr(),
+                description.as_ptr(),
+                flags,
+            )
+        });
+        Ok(())
+    }
+
+    fn flight_plan_load(&mut self, file_name: &CStr) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_FlightPlanLoad(self.handle()?, file_name.as_ptr())
+        });
+        Ok(())
+    }
+
+    fn subscribe_to_system_event(
+        &mut self,
+        event_id: u32,
+        event_name: &CStr,
+    ) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_SubscribeToSystemEvent(
+                self.handle()?,
+                event_id,
+                event_name.as_ptr(),
+            )
+        });
+        Ok(())
+    }
+
+    fn unsubscribe_from_system_event(&mut self, event_id: u32) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_UnsubscribeFromSystemEvent(self.handle()?, event_id)
+        });
+        Ok(())
+    }
+
+    fn request_system_state(&mut self, request_id: u32, state: &CStr) -> SimConnectResult<()> {
+        check_hr!(unsafe {
+            bindings::SimConnect_RequestSystemState(self.handle()?, request_id, state.as_ptr())
+        });
+        Ok(())
+    }
+
+    fn last_sent_packet_id(&mut self) -> SimConnectResult<u32> {
+        let mut send_id: bindings::DWORD = 0;
+        check_hr!(unsafe {
+            bindings::SimConnect_GetLastSentPacketID(self.handle()?, &mut send_id)
+        });
+        Ok(send_id)
+    }
+
+    fn get_next_dispatch(
+        &mut self,
+    ) -> SimConnectResult<Option<NonNull<bindings::SIMCONNECT_RECV>>> {
+        let mut data = std::ptr::null_mut();
+        let mut cb_data_size: bindings::DWORD = 0;
+
+        let hr = unsafe {
+            bindings::SimConnect_GetNextDispatch(self.handle()?, &mut data, &mut cb_data_size)
+        };
+
+        // A failed HRESULT here only means there is nothing waiting in the queue
+        if hr != 0 || cb_data_size == 0 {
+            return Ok(None);
+        }
+
+        Ok(NonNull::new(data))
+    }
+}
@@ -0,0 +1,2839 @@
+use sim_connect_data::{
+    client_data::{ClientData, ClientDataField},
+    error::{SimConnectError, SimConnectException, SimConnectResult},
+    facilities::{self, FacilityDataType, FacilityFieldType, FacilityListType},
+    input_definition::InputDefinition,
+    sim_event_args::{SimObjectType, SimStateArgs},
+    sim_events::SystemEvent,
+    sim_var_types::SimVarType,
+    wasm_bridge::{self, BridgeCommand, BridgeMessage},
+};
+use sim_connect_sys::bindings;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    ffi::CStr,
+    ptr::NonNull,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use super::{aligned_copy, to_str, SimConnectBackend};
+
+const RECV_VERSION: u32 = 4;
+
+/// A value stored in the mock simulator, either for a simvar or a system state
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockValue {
+    Number(f64),
+    Text(String),
+}
+
+impl From<f64> for MockValue {
+    fn from(value: f64) -> Self {
+        Self::Number(value)
+    }
+}
+
+impl From<f32> for MockValue {
+    fn from(value: f32) -> Self {
+        Self::Number(value as f64)
+    }
+}
+
+impl From<i32> for MockValue {
+    fn from(value: i32) -> Self {
+        Self::Number(value as f64)
+    }
+}
+
+impl From<i64> for MockValue {
+    fn from(value: i64) -> Self {
+        Self::Number(value as f64)
+    }
+}
+
+impl From<bool> for MockValue {
+    fn from(value: bool) -> Self {
+        Self::Number(if value { 1.0 } else { 0.0 })
+    }
+}
+
+impl From<&str> for MockValue {
+    fn from(value: &str) -> Self {
+        Self::Text(value.to_owned())
+    }
+}
+
+impl From<String> for MockValue {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl MockValue {
+    fn as_number(&self) -> f64 {
+        match self {
+            Self::Number(number) => *number,
+            Self::Text(text) => text.parse().unwrap_or_default(),
+        }
+    }
+
+    fn as_text(&self) -> String {
+        match self {
+            Self::Number(number) => number.to_string(),
+            Self::Text(text) => text.clone(),
+        }
+    }
+}
+
+/// A client event transmitted to the simulator
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransmittedEvent {
+    /// The sim event the client event is mapped to, e.g. `"GEAR_DOWN"`
+    pub name: String,
+    pub object_id: u32,
+    /// `dwData`, or all five parameters if sent with `TransmitClientEvent_EX1`
+    pub data: Vec<u32>,
+}
+
+/// A `SetDataOnSimObject` call the mock simulator applied
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockDataSet {
+    pub object_id: u32,
+    /// The simvars set by each element of the array, or by the only element when the client
+    /// didn't send an array. Tagged writes only contain the datums which were sent.
+    pub elements: Vec<Vec<(String, MockValue)>>,
+    pub tagged: bool,
+}
+
+/// A `.FLT` flight the client can load, added with `MockSim::add_flight` or saved by the client
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockFlight {
+    pub title: String,
+    pub description: String,
+}
+
+/// An object the client created with one of the `AICreate*` calls
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockAiObject {
+    pub container_title: String,
+    pub tail_number: Option<String>,
+    /// The airport a parked ATC aircraft was created at
+    pub airport: Option<String>,
+    /// The flight plan the aircraft was created with or last given, as sent by the client
+    pub flight_plan: Option<String>,
+    /// Whether the client released the object from the simulator's AI
+    pub released: bool,
+}
+
+/// A facility in the mock simulator's navigation data, or a record nested in one
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockFacility {
+    data_type: FacilityDataType,
+    fields: HashMap<String, MockValue>,
+    children: Vec<MockFacility>,
+}
+
+impl MockFacility {
+    /// A record of `data_type` whose fields are all zero or empty
+    pub fn new(data_type: FacilityDataType) -> Self {
+        Self {
+            data_type,
+            fields: HashMap::new(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Sets a field by the name it is added to a facility definition with, e.g. `"LATITUDE"`
+    pub fn with(mut self, field: &str, value: impl Into<MockValue>) -> Self {
+        self.fields.insert(field.to_owned(), value.into());
+        self
+    }
+
+    /// Nests a record in this one, such as a runway in an airport
+    pub fn child(mut self, child: MockFacility) -> Self {
+        self.children.push(child);
+        self
+    }
+}
+
+impl MockFacility {
+    /// The facility list the facility is listed in, if it is one that can be listed
+    fn list_type(&self) -> Option<FacilityListType> {
+        match self.data_type {
+            FacilityDataType::Airport => Some(FacilityListType::Airport),
+            FacilityDataType::Waypoint => Some(FacilityListType::Waypoint),
+            FacilityDataType::Ndb => Some(FacilityListType::Ndb),
+            FacilityDataType::Vor => Some(FacilityListType::Vor),
+            _ => None,
+        }
+    }
+
+    fn number(&self, field: &str) -> f64 {
+        self.fields
+            .get(field)
+            .map(MockValue::as_number)
+            .unwrap_or_default()
+    }
+
+    /// Encodes the facility as the `SIMCONNECT_DATA_FACILITY_*` entry of its list. VORs keep
+    /// their position in `VOR_*` fields, as they do in facility definitions.
+    fn list_entry(&self, icao: &str, region: &str) -> Vec<u8> {
+        let position = |field: &str| match self.data_type {
+            FacilityDataType::Vor => self.number(&format!("VOR_{field}")),
+            _ => self.number(field),
+        };
+        let mut entry = RecvMessage(Vec::new());
+        entry.fixed_string(icao, 6);
+        entry.fixed_string(region, 3);
+        entry.f64(position("LATITUDE"));
+        entry.f64(position("LONGITUDE"));
+        entry.f64(position("ALTITUDE"));
+        if self.data_type == FacilityDataType::Airport {
+            return entry.0;
+        }
+        entry.f32(self.number("MAGVAR") as f32);
+        if self.data_type == FacilityDataType::Waypoint {
+            return entry.0;
+        }
+        entry.dword(self.number("FREQUENCY") as u32);
+        if self.data_type == FacilityDataType::Ndb {
+            return entry.0;
+        }
+        let mut flags = facilities::VOR_HAS_NAV_SIGNAL;
+        for (field, flag) in [
+            ("HAS_LOCALIZER", facilities::VOR_HAS_LOCALIZER),
+            ("HAS_GLIDE_SLOPE", facilities::VOR_HAS_GLIDE_SLOPE),
+            ("HAS_DME", facilities::VOR_HAS_DME),
+        ] {
+            if self.number(field) != 0.0 {
+                flags |= flag;
+            }
+        }
+        entry.dword(flags);
+        entry.f32(self.number("LOCALIZER") as f32);
+        entry.f64(self.number("GS_LATITUDE"));
+        entry.f64(self.number("GS_LONGITUDE"));
+        entry.f64(self.number("GS_ALTITUDE"));
+        entry.f32(self.number("GS_ANGLE") as f32);
+        entry.0
+    }
+}
+
+/// Facility lists are sent a few entries per message, so long lists span several messages like
+/// the simulator's do
+const FACILITIES_PER_MESSAGE: usize = 4;
+
+/// An `OPEN`ed record of a facility definition, with the fields and records added to it
+struct MockFacilityDefinition {
+    data_type: FacilityDataType,
+    fields: Vec<(String, FacilityFieldType)>,
+    children: Vec<MockFacilityDefinition>,
+    closed: bool,
+}
+
+impl MockFacilityDefinition {
+    /// The record the next entry of the definition is added to, which is the innermost one
+    /// not closed yet
+    fn open_record(&mut self) -> &mut Self {
+        if self.children.last().is_some_and(|child| !child.closed) {
+            self.children.last_mut().unwrap().open_record()
+        } else {
+            self
+        }
+    }
+}
+
+struct MockDatum {
+    name: String,
+    data_type: bindings::SIMCONNECT_DATATYPE,
+    datum_id: u32,
+}
+
+/// An AI or multiplayer object, which the client can request data on by id or by type
+struct MockObject {
+    object_type: SimObjectType,
+    distance_meters: u32,
+    sim_vars: HashMap<String, MockValue>,
+    /// Set for objects the client created
+    ai: Option<MockAiObject>,
+}
+
+/// A notification group, created by adding the first client event to it
+#[derive(Default)]
+struct MockGroup {
+    priority: Option<u32>,
+    /// Client event ids, and whether the group masks them
+    events: Vec<(u32, bool)>,
+}
+
+/// An input group, created by mapping the first input to it
+#[derive(Default)]
+struct MockInputGroup {
+    priority: Option<u32>,
+    enabled: bool,
+    mappings: Vec<MockInputMapping>,
+}
+
+struct MockInputMapping {
+    definition: InputDefinition,
+    /// `(event_id, value)` sent when the input is pressed
+    down: (u32, u32),
+    /// `(event_id, value)` sent when the input is released
+    up: (u32, u32),
+    maskable: bool,
+}
+
+/// An input event of the loaded aircraft
+struct MockInputEvent {
+    name: String,
+    hash: u64,
+    value: MockValue,
+}
+
+/// Input events are enumerated a few per message, so enumerations span several messages like
+/// the simulator's do
+const INPUT_EVENTS_PER_MESSAGE: usize = 4;
+
+/// A client data area. Areas belong to the simulator rather than to a connection, so they are
+/// shared with whatever else created or mapped them.
+struct MockClientData {
+    data: Vec<u8>,
+    read_only: bool,
+    /// Whether the client created the area, and so can write to it even if it is read-only
+    created_by_client: bool,
+}
+
+/// A `RequestClientData` call which is answered every `period`
+struct MockClientDataRequest {
+    client_data_id: u32,
+    define_id: u32,
+    period: bindings::SIMCONNECT_CLIENT_DATA_PERIOD,
+    flags: bindings::SIMCONNECT_CLIENT_DATA_REQUEST_FLAG,
+    origin: u32,
+    interval: u32,
+    limit: u32,
+    /// Periods elapsed since the request was made
+    elapsed: u32,
+    sent: u32,
+    /// Each datum of the last answer, as sent, to tell what changed since
+    last_sent: Option<Vec<Vec<u8>>>,
+}
+
+/// A `RequestDataOnSimObject` call which is answered every `period`
+struct MockDataRequest {
+    define_id: u32,
+    object_id: u32,
+    period: bindings::SIMCONNECT_PERIOD,
+    flags: bindings::SIMCONNECT_DATA_REQUEST_FLAG,
+    origin: u32,
+    interval: u32,
+    limit: u32,
+    /// Periods elapsed since the request was made
+    elapsed: u32,
+    sent: u32,
+    /// Each datum of the last answer, as sent, to tell what changed since
+    last_sent: Option<Vec<Vec<u8>>>,
+}
+
+#[derive(Default)]
+struct MockState {
+    program_name: Option<String>,
+    definitions: HashMap<u32, Vec<MockDatum>>,
+    sim_vars: HashMap<String, MockValue>,
+    objects: HashMap<u32, MockObject>,
+    last_object_id: u32,
+    /// The type of object each container title creates
+    containers: HashMap<String, SimObjectType>,
+    system_states: HashMap<String, MockValue>,
+    subscribed_events: HashMap<String, u32>,
+    client_events: HashMap<u32, String>,
+    notification_groups: HashMap<u32, MockGroup>,
+    input_groups: HashMap<u32, MockInputGroup>,
+    transmitted_events: Vec<TransmittedEvent>,
+    data_sets: Vec<MockDataSet>,
+    data_requests: HashMap<u32, MockDataRequest>,
+    client_data: HashMap<String, MockClientData>,
+    client_data_names: HashMap<u32, String>,
+    client_data_definitions: HashMap<u32, Vec<ClientDataField>>,
+    client_data_requests: HashMap<u32, MockClientDataRequest>,
+    lvars: HashMap<String, f64>,
+    /// The variables registered on each channel of the WASM bridge, by slot, keyed by client
+    /// name. `None` until the bridge is installed with `MockSim::install_wasm_bridge`.
+    bridge_clients: Option<HashMap<String, Vec<String>>>,
+    fired_hevents: Vec<String>,
+    /// Facilities by ICAO code, with their region
+    facilities: HashMap<String, (String, MockFacility)>,
+    facility_definitions: HashMap<u32, MockFacilityDefinition>,
+    last_facility_record_id: u32,
+    /// The ICAO codes of the facilities in the cache around the user's aircraft
+    facilities_in_range: Vec<String>,
+    /// The request id of the subscription to each list type
+    facility_subscriptions: HashMap<FacilityListType, u32>,
+    /// Flights and flight plans by `file_key`
+    flights: HashMap<String, MockFlight>,
+    flight_plans: HashSet<String>,
+    /// The last flight and flight plan the client loaded, as it named them
+    loaded_flight: Option<String>,
+    loaded_flight_plan: Option<String>,
+    rejected_names: HashSet<String>,
+    input_events: Vec<MockInputEvent>,
+    subscribed_input_events: HashSet<u64>,
+    pending: VecDeque<Vec<u8>>,
+    paused: bool,
+    last_send_id: u32,
+}
+
+impl MockState {
+    /// Queues a `SIMCONNECT_RECV_EXCEPTION` for the last packet the client sent
+    fn raise(&mut self, exception: SimConnectException, index: u32) {
+        let mut message =
+            RecvMessage::new(bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EXCEPTION);
+        message.dword(exception.into());
+        message.dword(self.last_send_id);
+        message.dword(index);
+        self.pending.push_back(message.finish());
+    }
+
+    /// The simvars of `object_id`, which for the user's aircraft are the ones set through
+    /// `MockSim::set_sim_var`
+    fn sim_vars_of(&self, object_id: u32) -> Option<&HashMap<String, MockValue>> {
+        match object_id {
+            bindings::SIMCONNECT_OBJECT_ID_USER => Some(&self.sim_vars),
+            object_id => self.objects.get(&object_id).map(|object| &object.sim_vars),
+        }
+    }
+
+    fn sim_vars_of_mut(&mut self, object_id: u32) -> Option<&mut HashMap<String, MockValue>> {
+        match object_id {
+            bindings::SIMCONNECT_OBJECT_ID_USER => Some(&mut self.sim_vars),
+            object_id => self
+                .objects
+                .get_mut(&object_id)
+                .map(|object| &mut object.sim_vars),
+        }
+    }
+
+    /// Creates an object from a container added with `MockSim::add_container`, and answers
+    /// `request_id` with its id. Objects created at a position start out with it in their
+    /// simvars.
+    fn create_object(
+        &mut self,
+        request_id: u32,
+        ai: MockAiObject,
+        position: Option<bindings::SIMCONNECT_DATA_INITPOSITION>,
+    ) {
+        let Some(object_type) = self.containers.get(&ai.container_title).copied() else {
+            self.raise(SimConnectException::CreateObjectFailed, 1);
+            return;
+        };
+
+        let mut sim_vars = HashMap::new();
+        if let Some(position) = position {
+            sim_vars.extend([
+                ("PLANE LATITUDE".to_owned(), position.Latitude.into()),
+                ("PLANE LONGITUDE".to_owned(), position.Longitude.into()),
+                ("PLANE ALTITUDE".to_owned(), position.Altitude.into()),
+                ("PLANE PITCH DEGREES".to_owned(), position.Pitch.into()),
+                ("PLANE BANK DEGREES".to_owned(), position.Bank.into()),
+                (
+                    "PLANE HEADING DEGREES TRUE".to_owned(),
+                    position.Heading.into(),
+                ),
+                ("SIM ON GROUND".to_owned(), (position.OnGround != 0).into()),
+            ]);
+        }
+
+        self.last_object_id += 1;
+        let object_id = self.last_object_id;
+        self.objects.insert(
+            object_id,
+            MockObject {
+                object_type,
+                distance_meters: 0,
+                sim_vars,
+                ai: Some(ai),
+            },
+        );
+
+        let mut message =
+            RecvMessage::new(bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_ASSIGNED_OBJECT_ID);
+        message.dword(request_id);
+        message.dword(object_id);
+        self.pending.push_back(message.finish());
+    }
+
+    /// The object `object_id` the client created, or raises an exception if there is none
+    fn ai_object_mut(&mut self, object_id: u32) -> Option<&mut MockAiObject> {
+        if self
+            .objects
+            .get(&object_id)
+            .is_none_or(|object| object.ai.is_none())
+        {
+            self.raise(SimConnectException::UnrecognizedId, 1);
+            return None;
+        }
+        self.objects.get_mut(&object_id)?.ai.as_mut()
+    }
+
+    /// Encodes every datum of `define_id` as read from `object_id`, or `None` if either does
+    /// not exist
+    fn read_values(&self, define_id: u32, object_id: u32) -> Option<Vec<Vec<u8>>> {
+        let definition = self.definitions.get(&define_id)?;
+        let sim_vars = self.sim_vars_of(object_id)?;
+        Some(
+            definition
+                .iter()
+                .map(|datum| {
+                    let mut value = RecvMessage(Vec::new());
+                    value.datum(datum.data_type, sim_vars.get(&datum.name));
+                    value.0
+                })
+                .collect(),
+        )
+    }
+
+    /// The objects a `RequestDataOnSimObjectType` call answers with, in id order. The user's
+    /// aircraft counts as an aircraft right at the center of the radius.
+    fn objects_in_range(&self, object_type: SimObjectType, radius_meters: u32) -> Vec<u32> {
+        if object_type == SimObjectType::User || radius_meters == 0 {
+            return vec![bindings::SIMCONNECT_OBJECT_ID_USER];
+        }
+
+        let mut object_ids: Vec<u32> = self
+            .objects
+            .iter()
+            .filter(|(_, object)| {
+                (object_type == SimObjectType::All || object.object_type == object_type)
+                    && object.distance_meters <= radius_meters
+            })
+            .map(|(object_id, _)| *object_id)
+            .collect();
+        if matches!(object_type, SimObjectType::All | SimObjectType::Aircraft) {
+            object_ids.push(bindings::SIMCONNECT_OBJECT_ID_USER);
+        }
+        object_ids.sort_unstable();
+        object_ids
+    }
+
+    /// Records a transmitted client event, or raises an exception if it was never mapped
+    fn transmit(&mut self, object_id: u32, event_id: u32, data: Vec<u32>) {
+        if self.sim_vars_of(object_id).is_none() {
+            self.raise(SimConnectException::UnrecognizedId, 1);
+            return;
+        }
+        let Some(name) = self.client_events.get(&event_id) else {
+            self.raise(SimConnectException::UnrecognizedId, 2);
+            return;
+        };
+        self.transmitted_events.push(TransmittedEvent {
+            name: name.clone(),
+            object_id,
+            data,
+        });
+    }
+
+    /// Sends client events to every notification group they were added to, in order of
+    /// priority. Returns `false` if a group above the simulator's priority masked them.
+    fn notify(&mut self, event_ids: &[u32], data: u32) -> bool {
+        let mut groups: Vec<(u32, u32, u32, bool)> = Vec::new();
+        for (group_id, group) in &self.notification_groups {
+            for (event_id, maskable) in &group.events {
+                if event_ids.contains(event_id) {
+                    let priority = group
+                        .priority
+                        .unwrap_or(bindings::SIMCONNECT_GROUP_PRIORITY_DEFAULT);
+                    groups.push((priority, *group_id, *event_id, *maskable));
+                }
+            }
+        }
+        groups.sort_unstable();
+
+        for (priority, group_id, event_id, maskable) in groups {
+            let mut message =
+                RecvMessage::new(bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT);
+            message.dword(group_id);
+            message.dword(event_id);
+            message.dword(data);
+            self.pending.push_back(message.finish());
+
+            // Masked events go no further down the groups, nor to the simulator if it comes
+            // later
+            if maskable && priority >= bindings::SIMCONNECT_GROUP_PRIORITY_HIGHEST_MASKABLE {
+                return priority >= bindings::SIMCONNECT_GROUP_PRIORITY_STANDARD;
+            }
+        }
+        true
+    }
+
+    /// Sends the facilities in `icaos` which are of `list_type` as a list answering
+    /// `request_id`. An empty list is still sent, as a single message without entries.
+    fn send_facilities_list(
+        &mut self,
+        list_type: FacilityListType,
+        request_id: u32,
+        icaos: &[String],
+    ) {
+        let entries: Vec<Vec<u8>> = icaos
+            .iter()
+            .filter_map(|icao| {
+                let (region, facility) = self.facilities.get(icao)?;
+                (facility.list_type() == Some(list_type)).then(|| facility.list_entry(icao, region))
+            })
+            .collect();
+        let recv_id = list_type
+            .recv_id()
+            .expect("only listable types are subscribed to");
+
+        let chunks: Vec<&[Vec<u8>]> = match entries.is_empty() {
+            true => vec![&[]],
+            false => entries.chunks(FACILITIES_PER_MESSAGE).collect(),
+        };
+        for (entry_number, chunk) in chunks.iter().enumerate() {
+            let mut message = RecvMessage::new(recv_id);
+            message.dword(request_id);
+            message.dword(chunk.len() as u32);
+            message.dword(entry_number as u32);
+            message.dword(chunks.len() as u32);
+            for entry in *chunk {
+                message.0.extend_from_slice(entry);
+            }
+            self.pending.push_back(message.finish());
+        }
+    }
+
+    /// Sends `facility` as laid out by `definition`, followed by the records nested in it.
+    /// `list` is the record's `(index, size)` among its siblings of the same type.
+    fn send_facility_record(
+        &mut self,
+        request_id: u32,
+        parent_id: u32,
+        list: Option<(usize, usize)>,
+        definition: &MockFacilityDefinition,
+        facility: &MockFacility,
+    ) {
+        self.last_facility_record_id += 1;
+        let unique_id = self.last_facility_record_id;
+
+        let mut message =
+            RecvMessage::new(bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_FACILITY_DATA);
+        message.dword(request_id);
+        message.dword(unique_id);
+        message.dword(parent_id);
+        message.dword(definition.data_type.into());
+        let (index, size) = list.unwrap_or_default();
+        message.dword(list.is_some().into());
+        message.dword(index as u32);
+        message.dword(size as u32);
+        for (name, field_type) in &definition.fields {
+            message.facility_field(*field_type, facility.fields.get(name));
+        }
+        self.pending.push_back(message.finish());
+
+        for child_definition in &definition.children {
+            let children: Vec<&MockFacility> = facility
+                .children
+                .iter()
+                .filter(|child| child.data_type == child_definition.data_type)
+                .collect();
+            for (index, child) in children.iter().enumerate() {
+                self.send_facility_record(
+                    request_id,
+                    unique_id,
+                    Some((index, children.len())),
+                    child_definition,
+                    child,
+                );
+            }
+        }
+    }
+
+    /// Sends the `(event_id, value)` `event` picks from each mapping of `definition` in the
+    /// enabled input groups, in order of group priority
+    fn input(&mut self, definition: &str, event: impl Fn(&MockInputMapping) -> (u32, u32)) -> bool {
+        let definition: InputDefinition = definition.parse().expect("invalid input definition");
+
+        let mut mapped: Vec<(u32, u32, u32, bool)> = Vec::new();
+        for group in self.input_groups.values().filter(|group| group.enabled) {
+            let priority = group
+                .priority
+                .unwrap_or(bindings::SIMCONNECT_GROUP_PRIORITY_DEFAULT);
+            for mapping in &group.mappings {
+                let (event_id, value) = event(mapping);
+                if mapping.definition == definition && event_id != bindings::SIMCONNECT_UNUSED {
+                    mapped.push((priority, event_id, value, mapping.maskable));
+                }
+            }
+        }
+        mapped.sort_unstable();
+
+        for (priority, event_id, value, maskable) in mapped {
+            self.notify(&[event_id], value);
+            // A masked input goes no further down the input groups
+            if maskable {
+                return priority >= bindings::SIMCONNECT_GROUP_PRIORITY_STANDARD;
+            }
+        }
+        true
+    }
+
+    fn input_event_mut(&mut self, hash: u64) -> Option<&mut MockInputEvent> {
+        self.input_events
+            .iter_mut()
+            .find(|input_event| input_event.hash == hash)
+    }
+
+    /// Sends a `SIMCONNECT_RECV_SUBSCRIBE_INPUT_EVENT` if the client subscribed to the event.
+    /// Returns whether it did.
+    fn input_event_changed(&mut self, hash: u64) -> bool {
+        if !self.subscribed_input_events.contains(&hash) {
+            return false;
+        }
+        let Some(input_event) = self.input_events.iter().find(|event| event.hash == hash) else {
+            return false;
+        };
+
+        let mut message =
+            RecvMessage::new(bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_SUBSCRIBE_INPUT_EVENT);
+        message.qword(hash);
+        message.input_event_value(&input_event.value);
+        self.pending.push_back(message.finish());
+        true
+    }
+
+    /// Counts one `period` for every data request made with it, answering those which are due
+    fn elapse(&mut self, periods: &[bindings::SIMCONNECT_PERIOD]) {
+        let mut request_ids: Vec<u32> = self.data_requests.keys().copied().collect();
+        request_ids.sort_unstable();
+
+        for request_id in request_ids {
+            let request = self.data_requests.get_mut(&request_id).unwrap();
+            if !periods.contains(&request.period) {
+                continue;
+            }
+            request.elapsed += 1;
+            if is_due(request.elapsed, request.origin, request.interval) {
+                self.answer_data_request(request_id);
+            }
+        }
+    }
+
+    /// The area mapped to `client_data_id`, if it was created
+    fn client_data_area(&self, client_data_id: u32) -> Option<&MockClientData> {
+        self.client_data
+            .get(self.client_data_names.get(&client_data_id)?)
+    }
+
+    /// Counts one `period` for every client data request made with it, answering those which
+    /// are due. `OnSet` requests only count sets of `client_data_id`.
+    fn elapse_client_data(
+        &mut self,
+        period: bindings::SIMCONNECT_CLIENT_DATA_PERIOD,
+        client_data_id: Option<u32>,
+    ) {
+        let mut request_ids: Vec<u32> = self.client_data_requests.keys().copied().collect();
+        request_ids.sort_unstable();
+
+        for request_id in request_ids {
+            let request = self.client_data_requests.get_mut(&request_id).unwrap();
+            if request.period != period
+                || client_data_id.is_some_and(|id| id != request.client_data_id)
+            {
+                continue;
+            }
+            request.elapsed += 1;
+            if is_due(request.elapsed, request.origin, request.interval) {
+                self.answer_client_data_request(request_id);
+            }
+        }
+    }
+
+    /// Counts a set of the area `name` for every `OnSet` client data request on it
+    fn client_data_set(&mut self, name: &str) {
+        let client_data_ids: Vec<u32> = self
+            .client_data_names
+            .iter()
+            .filter(|(_, mapped)| *mapped == name)
+            .map(|(client_data_id, _)| *client_data_id)
+            .collect();
+        for client_data_id in client_data_ids {
+            self.elapse_client_data(
+                bindings::SIMCONNECT_CLIENT_DATA_PERIOD_SIMCONNECT_CLIENT_DATA_PERIOD_ON_SET,
+                Some(client_data_id),
+            );
+        }
+        let client = name.strip_suffix(".Command").filter(|client| {
+            self.bridge_clients
+                .as_ref()
+                .is_some_and(|clients| clients.contains_key(*client))
+        });
+        if let Some(client) = client {
+            self.run_bridge_command(client);
+        }
+    }
+
+    /// Runs the command just written to the command area of `client`'s channel of the WASM
+    /// bridge, the way the module would
+    fn run_bridge_command(&mut self, client: &str) {
+        let Some(command) = self
+            .client_data
+            .get(&wasm_bridge::command_area(client))
+            .and_then(|area| BridgeMessage::from_bytes(&area.data))
+            .and_then(|message| BridgeCommand::parse(&message.text()))
+        else {
+            return;
+        };
+        let clients = self.bridge_clients.get_or_insert_with(HashMap::new);
+        let variables = clients.entry(client.to_owned()).or_default();
+        match command {
+            BridgeCommand::Ping => {
+                let pong = BridgeMessage::new(wasm_bridge::PONG).expect("pong fits");
+                self.write_bridge_area(&wasm_bridge::response_area(client), &pong.to_bytes());
+            }
+            BridgeCommand::AddClient(added) => {
+                if client != wasm_bridge::DEFAULT_CLIENT {
+                    return;
+                }
+                clients.entry(added.clone()).or_default();
+                self.create_bridge_areas(&added);
+                if let Some(finished) = BridgeMessage::new(&wasm_bridge::client_added(&added)) {
+                    self.write_bridge_area(wasm_bridge::RESPONSE_AREA, &finished.to_bytes());
+                }
+            }
+            BridgeCommand::AddVariable(variable) => {
+                if variables.len() < wasm_bridge::MAX_VARIABLES {
+                    variables.push(variable);
+                }
+                self.write_bridge_values();
+            }
+            BridgeCommand::ClearVariables => variables.clear(),
+            BridgeCommand::Execute(code) => {
+                self.execute_calculator_code(&code);
+                self.write_bridge_values();
+            }
+        }
+    }
+
+    /// Runs the little calculator code the bridge is used for: numbers, reading and writing
+    /// local variables, and firing `H:` events
+    fn execute_calculator_code(&mut self, code: &str) {
+        let mut stack = Vec::new();
+        for token in code.split_whitespace() {
+            let Some(variable) = token.strip_prefix('(').and_then(|t| t.strip_suffix(')')) else {
+                if let Ok(value) = token.parse::<f64>() {
+                    stack.push(value);
+                }
+                continue;
+            };
+            if let Some(event) = variable.strip_prefix(">H:") {
+                self.fired_hevents.push(event.to_owned());
+            } else if let Some(name) = variable.strip_prefix(">L:") {
+                let value = stack.pop().unwrap_or_default();
+                self.lvars.insert(name.to_owned(), value);
+            } else if let Some(name) = variable.strip_prefix("L:") {
+                stack.push(self.lvars.get(name).copied().unwrap_or_default());
+            }
+        }
+    }
+
+    /// Writes the value of every registered variable to its slot on each channel, as the
+    /// module does every frame
+    fn write_bridge_values(&mut self) {
+        let Some(clients) = &self.bridge_clients else {
+            return;
+        };
+        let areas: Vec<(String, Vec<u8>)> = clients
+            .iter()
+            .map(|(client, variables)| {
+                let values = variables
+                    .iter()
+                    .flat_map(|variable| {
+                        let value = variable
+                            .strip_prefix("(L:")
+                            .and_then(|name| name.strip_suffix(')'))
+                            .and_then(|name| self.lvars.get(name))
+                            .copied()
+                            .unwrap_or_default();
+                        (value as f32).to_le_bytes()
+                    })
+                    .collect();
+                (wasm_bridge::value_area(client), values)
+            })
+            .collect();
+        for (area, values) in areas {
+            self.write_bridge_area(&area, &values);
+        }
+    }
+
+    /// Creates the client data areas of `client`'s channel of the WASM bridge
+    fn create_bridge_areas(&mut self, client: &str) {
+        for (name, size) in [
+            (wasm_bridge::command_area(client), wasm_bridge::MESSAGE_SIZE),
+            (
+                wasm_bridge::response_area(client),
+                wasm_bridge::MESSAGE_SIZE,
+            ),
+            (
+                wasm_bridge::value_area(client),
+                wasm_bridge::VALUE_AREA_SIZE,
+            ),
+        ] {
+            self.client_data
+                .entry(name)
+                .or_insert_with(|| MockClientData {
+                    data: vec![0; size],
+                    read_only: false,
+                    created_by_client: false,
+                });
+        }
+    }
+
+    /// Writes `data` to the start of one of the WASM bridge's areas, as the module would
+    fn write_bridge_area(&mut self, name: &str, data: &[u8]) {
+        let Some(area) = self.client_data.get_mut(name) else {
+            return;
+        };
+        area.data[..data.len()].copy_from_slice(data);
+        self.client_data_set(name);
+    }
+
+    /// Queues the answer to a client data request, unless only changes were asked for and no
+    /// datum changed by more than its epsilon. Requests which reached their limit are removed.
+    fn answer_client_data_request(&mut self, request_id: u32) {
+        let Some(request) = self.client_data_requests.get(&request_id) else {
+            return;
+        };
+        let (Some(area), Some(definition)) = (
+            self.client_data_area(request.client_data_id),
+            self.client_data_definitions.get(&request.define_id),
+        ) else {
+            self.client_data_requests.remove(&request_id);
+            return;
+        };
+        let Some(values) = read_client_data(&area.data, definition) else {
+            self.raise(SimConnectException::OutOfBounds, 3);
+            self.client_data_requests.remove(&request_id);
+            return;
+        };
+        let changed_only =
+            request.flags & bindings::SIMCONNECT_CLIENT_DATA_REQUEST_FLAG_CHANGED != 0;
+        let changed = match &request.last_sent {
+            Some(last_sent) => definition
+                .iter()
+                .zip(values.iter().zip(last_sent))
+                .any(|(field, (value, last))| client_datum_changed(field, last, value)),
+            None => true,
+        };
+        if changed_only && !changed {
+            return;
+        }
+
+        let mut message =
+            RecvMessage::new(bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_CLIENT_DATA);
+        message.dword(request_id);
+        message.dword(request.client_data_id);
+        message.dword(request.define_id);
+        message.dword(request.flags);
+        message.dword(1);
+        message.dword(1);
+        message.dword(values.len() as u32);
+        message.0.extend(values.concat());
+        self.pending.push_back(message.finish());
+
+        let request = self.client_data_requests.get_mut(&request_id).unwrap();
+        request.last_sent = Some(values);
+        request.sent += 1;
+        if request.limit != 0 && request.sent >= request.limit {
+            self.client_data_requests.remove(&request_id);
+        }
+    }
+
+    /// Queues the answer to a data request, unless only changes were asked for and nothing
+    /// changed. Requests which reached their limit are removed.
+    fn answer_data_request(&mut self, request_id: u32) {
+        let Some(request) = self.data_requests.get(&request_id) else {
+            return;
+        };
+        let (Some(definition), Some(values)) = (
+            self.definitions.get(&request.define_id),
+            self.read_values(request.define_id, request.object_id),
+        ) else {
+            // The object was removed since the request was made
+            self.data_requests.remove(&request_id);
+            return;
+        };
+        let changed: Vec<bool> = match &request.last_sent {
+            Some(last_sent) => values.iter().zip(last_sent).map(|(a, b)| a != b).collect(),
+            None => vec![true; values.len()],
+        };
+        let changed_only = request.flags & bindings::SIMCONNECT_DATA_REQUEST_FLAG_CHANGED != 0;
+        let tagged = request.flags & bindings::SIMCONNECT_DATA_REQUEST_FLAG_TAGGED != 0;
+        if changed_only && !changed.contains(&true) {
+            return;
+        }
+
+        let mut data = RecvMessage(Vec::new());
+        let mut count = 0;
+        for ((datum, value), changed) in definition.iter().zip(&values).zip(&changed) {
+            if tagged && changed_only && !changed {
+                continue;
+            }
+            if tagged {
+                data.dword(datum.datum_id);
+            }
+            data.0.extend_from_slice(value);
+            count += 1;
+        }
+
+        let mut message =
+            RecvMessage::new(bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_SIMOBJECT_DATA);
+        message.dword(request_id);
+        message.dword(request.object_id);
+        message.dword(request.define_id);
+        message.dword(request.flags);
+        message.dword(1);
+        message.dword(1);
+        message.dword(count);
+        message.0.extend(data.0);
+        self.pending.push_back(message.finish());
+
+        let request = self.data_requests.get_mut(&request_id).unwrap();
+        request.last_sent = Some(values);
+        request.sent += 1;
+        if request.limit != 0 && request.sent >= request.limit {
+            self.data_requests.remove(&request_id);
+        }
+    }
+
+    /// Queues a system event for the client, if it subscribed to it
+    fn push_event(
+        &mut self,
+        event: SystemEvent,
+        write: impl FnOnce(&mut RecvMessage, u32),
+    ) -> bool {
+        let event_id = match self
+            .subscribed_events
+            .get(&event.to_string().to_lowercase())
+        {
+            Some(event_id) => *event_id,
+            None => return false,
+        };
+
+        let recv_id = match event {
+            SystemEvent::AircraftLoaded
+            | SystemEvent::FlightLoaded
+            | SystemEvent::FlightSaved
+            | SystemEvent::FlightPlanActivated => {
+                bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_FILENAME
+            }
+            SystemEvent::ObjectAdded | SystemEvent::ObjectRemoved => {
+                bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT_OBJECT_ADDREMOVE
+            }
+            _ => bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT,
+        };
+
+        let mut message = RecvMessage::new(recv_id);
+        write(&mut message, event_id);
+        self.pending.push_back(message.finish());
+        true
+    }
+
+    fn push_filename_event(&mut self, event: SystemEvent, file_name: &str) -> bool {
+        self.push_event(event, |message, event_id| {
+            message.event(event_id, 0);
+            message.fixed_string(file_name, 260);
+            message.dword(0);
+        })
+    }
+}
+
+/// Scriptable, in-process stand in for the simulator.
+///
+/// Hand `MockSim::backend` to `SimConnect::open_with_backend`, then use the `MockSim` to
+/// seed simvar values and system states, or to fire system events at the client.
+///
+/// # Example
+///
+/// ```
+/// use sim_connect_rs::{backend::MockSim, sim_events::SystemEvent, SimConnect};
+/// use std::time::Duration;
+///
+/// let sim = MockSim::new();
+/// sim.set_sim_var("Plane Latitude", 47.45);
+///
+/// let mut sc = SimConnect::open_with_backend(sim.backend(), "Test", Some(Duration::from_millis(5)))
+///     .unwrap();
+/// sc.subscribe_to_system_event(SystemEvent::Crashed, |_| println!("crashed!"))
+///     .unwrap();
+/// ```
+#[derive(Clone, Default)]
+pub struct MockSim {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockSim {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a backend connected to this mock simulator
+    pub fn backend(&self) -> MockBackend {
+        MockBackend {
+            sim: self.clone(),
+            current: None,
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, MockState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poison| poison.into_inner())
+    }
+
+    /// The name the client registered with, or `None` if the connection is not open
+    pub fn program_name(&self) -> Option<String> {
+        self.lock().program_name.clone()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.lock().program_name.is_some()
+    }
+
+    /// Sets the value returned for the simvar `name` (e.g. `"Plane Latitude"`)
+    pub fn set_sim_var(&self, name: &str, value: impl Into<MockValue>) {
+        self.lock()
+            .sim_vars
+            .insert(name.to_uppercase(), value.into());
+    }
+
+    /// Gets the current value of the simvar `name`, including values written by the client
+    pub fn sim_var(&self, name: &str) -> Option<MockValue> {
+        self.lock().sim_vars.get(&name.to_uppercase()).cloned()
+    }
+
+    /// Sets the value answered to `SimConnect::request_system_state`. Numbers are sent as the
+    /// integer value of the state, text as its string value.
+    pub fn set_system_state(&self, state: SimStateArgs, value: impl Into<MockValue>) {
+        self.lock()
+            .system_states
+            .insert(state.to_string(), value.into());
+    }
+
+    /// Makes the simulator raise `SimConnectException::NameUnrecognized` whenever `name` is
+    /// added to a data definition, subscribed to as a system event or mapped to a client event,
+    /// the way it does for a misspelled simvar or event
+    pub fn reject_name(&self, name: &str) {
+        self.lock().rejected_names.insert(name.to_uppercase());
+    }
+
+    /// Returns `true` if the client is currently subscribed to `event`
+    pub fn is_subscribed(&self, event: SystemEvent) -> bool {
+        self.lock()
+            .subscribed_events
+            .contains_key(&event.to_string().to_lowercase())
+    }
+
+    /// Fires a system event which carries no data.
+    ///
+    /// Returns `false` without sending anything if the client is not subscribed to `event`
+    pub fn fire_system_event(&self, event: SystemEvent) -> bool {
+        self.fire_system_event_with_data(event, 0)
+    }
+
+    /// Fires a system event carrying a single `dwData` value, such as `SystemEvent::Sim`
+    /// or `SystemEvent::View`
+    pub fn fire_system_event_with_data(&self, event: SystemEvent, data: u32) -> bool {
+        self.push_event(event, |message, event_id| {
+            message.event(event_id, data);
+        })
+    }
+
+    /// Fires a system event carrying a file name, such as `SystemEvent::FlightLoaded`
+    pub fn fire_filename_event(&self, event: SystemEvent, file_name: &str) -> bool {
+        self.lock().push_filename_event(event, file_name)
+    }
+
+    /// Fires `SystemEvent::ObjectAdded` or `SystemEvent::ObjectRemoved`
+    pub fn fire_object_event(&self, event: SystemEvent, object_type: SimObjectType) -> bool {
+        self.push_event(event, |message, event_id| {
+            message.event(event_id, 0);
+            message.dword(object_type as u32);
+        })
+    }
+
+    /// Adds an input event to the loaded aircraft, returning its hash. Events holding a
+    /// `MockValue::Text` are string events, any other is a double.
+    pub fn add_input_event(&self, name: &str, value: impl Into<MockValue>) -> u64 {
+        let mut state = self.lock();
+        // FNV-1a, standing in for whatever the simulator hashes names with
+        let hash = name.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        });
+        state
+            .input_events
+            .retain(|input_event| input_event.name != name);
+        state.input_events.push(MockInputEvent {
+            name: name.to_owned(),
+            hash,
+            value: value.into(),
+        });
+        hash
+    }
+
+    pub fn input_event(&self, name: &str) -> Option<MockValue> {
+        self.lock()
+            .input_events
+            .iter()
+            .find(|input_event| input_event.name == name)
+            .map(|input_event| input_event.value.clone())
+    }
+
+    /// Changes an input event as if the user had moved it in the cockpit. Returns `false` if
+    /// the client isn't subscribed to the event, so wasn't told.
+    pub fn set_input_event(&self, name: &str, value: impl Into<MockValue>) -> bool {
+        let mut state = self.lock();
+        let Some(input_event) = state
+            .input_events
+            .iter_mut()
+            .find(|input_event| input_event.name == name)
+        else {
+            return false;
+        };
+        input_event.value = value.into();
+        let hash = input_event.hash;
+        state.input_event_changed(hash)
+    }
+
+    /// Adds an AI or multiplayer object `distance_meters` away from the user's aircraft,
+    /// returning its object id
+    pub fn add_object(&self, object_type: SimObjectType, distance_meters: u32) -> u32 {
+        let mut state = self.lock();
+        state.last_object_id += 1;
+        let object_id = state.last_object_id;
+        state.objects.insert(
+            object_id,
+            MockObject {
+                object_type,
+                distance_meters,
+                sim_vars: HashMap::new(),
+                ai: None,
+            },
+        );
+        object_id
+    }
+
+    /// Adds a container, the title of an aircraft or other object installed in the simulator,
+    /// which the client can create objects of `object_type` from. Creating an object from any
+    /// other container fails with `SimConnectException::CreateObjectFailed`.
+    pub fn add_container(&self, title: &str, object_type: SimObjectType) {
+        self.lock().containers.insert(title.to_owned(), object_type);
+    }
+
+    /// Adds a `.FLT` flight the client can load from `file_name`. Loading any other flight
+    /// fails with `SimConnectException::Error`.
+    pub fn add_flight(&self, file_name: &str, title: &str) {
+        self.lock().flights.insert(
+            file_key(file_name, "flt"),
+            MockFlight {
+                title: title.to_owned(),
+                description: String::new(),
+            },
+        );
+    }
+
+    /// Adds a `.PLN` flight plan the client can load from `file_name`. Loading any other flight
+    /// plan fails with `SimConnectException::Error`.
+    pub fn add_flight_plan(&self, file_name: &str) {
+        self.lock().flight_plans.insert(file_key(file_name, "pln"));
+    }
+
+    /// The flight at `file_name`, if it was added or the client saved it
+    pub fn flight(&self, file_name: &str) -> Option<MockFlight> {
+        self.lock()
+            .flights
+            .get(&file_key(file_name, "flt"))
+            .cloned()
+    }
+
+    /// The last flight the client loaded, as it named it
+    pub fn loaded_flight(&self) -> Option<String> {
+        self.lock().loaded_flight.clone()
+    }
+
+    /// The last flight plan the client loaded, as it named it
+    pub fn loaded_flight_plan(&self) -> Option<String> {
+        self.lock().loaded_flight_plan.clone()
+    }
+
+    /// The object `object_id`, if the client created it and has not removed it since
+    pub fn ai_object(&self, object_id: u32) -> Option<MockAiObject> {
+        self.lock().objects.get(&object_id)?.ai.clone()
+    }
+
+    /// Removes an object added with `add_object`. Returns `false` if there was no such object.
+    pub fn remove_object(&self, object_id: u32) -> bool {
+        self.lock().objects.remove(&object_id).is_some()
+    }
+
+    /// Sets the value of the simvar `name` on `object_id`, which can also be
+    /// `SIMCONNECT_OBJECT_ID_USER`. Does nothing if there is no such object.
+    pub fn set_object_var(&self, object_id: u32, name: &str, value: impl Into<MockValue>) {
+        if let Some(sim_vars) = self.lock().sim_vars_of_mut(object_id) {
+            sim_vars.insert(name.to_uppercase(), value.into());
+        }
+    }
+
+    /// Gets the current value of the simvar `name` on `object_id`, including values written by
+    /// the client
+    pub fn object_var(&self, object_id: u32, name: &str) -> Option<MockValue> {
+        self.lock()
+            .sim_vars_of(object_id)?
+            .get(&name.to_uppercase())
+            .cloned()
+    }
+
+    /// Triggers the sim event `name` (e.g. `"BRAKES"`) as if the user pressed its key, sending
+    /// it to every notification group of the client it was added to.
+    ///
+    /// Returns `false` if the client masked the event from a group with a priority above
+    /// the simulator's, in which case the simulator itself would never act on it.
+    pub fn trigger_event(&self, name: &str, data: u32) -> bool {
+        let mut state = self.lock();
+        let name = name.to_uppercase();
+        let event_ids: Vec<u32> = state
+            .client_events
+            .iter()
+            .filter(|(_, event_name)| **event_name == name)
+            .map(|(event_id, _)| *event_id)
+            .collect();
+        state.notify(&event_ids, data)
+    }
+
+    /// Presses a key chord or joystick button, e.g. `"shift+ctrl+g"`, sending the down event
+    /// of every mapping of it in an enabled input group.
+    ///
+    /// Returns `false` if the client masked the input from a group with a priority above the
+    /// simulator's, in which case the simulator's own binding would never see it.
+    ///
+    /// # Panics
+    ///
+    /// If `definition` is not a valid input definition
+    pub fn press_input(&self, definition: &str) -> bool {
+        self.lock().input(definition, |mapping| mapping.down)
+    }
+
+    /// Releases an input pressed with `press_input`, sending the up events mapped to it
+    ///
+    /// # Panics
+    ///
+    /// If `definition` is not a valid input definition
+    pub fn release_input(&self, definition: &str) -> bool {
+        self.lock().input(definition, |mapping| mapping.up)
+    }
+
+    /// Moves a joystick axis, slider or POV to `position`, which is sent as the data of the
+    /// down events mapped to it
+    ///
+    /// # Panics
+    ///
+    /// If `definition` is not a valid input definition
+    pub fn move_input(&self, definition: &str, position: i32) -> bool {
+        self.lock()
+            .input(definition, |mapping| (mapping.down.0, position as u32))
+    }
+
+    /// Whether the client has turned the input group on with `SetInputGroupState`
+    pub fn is_input_group_enabled(&self, group_id: u32) -> bool {
+        self.lock()
+            .input_groups
+            .get(&group_id)
+            .is_some_and(|group| group.enabled)
+    }
+
+    /// Takes every client event transmitted since the last call, oldest first
+    pub fn take_transmitted_events(&self) -> Vec<TransmittedEvent> {
+        std::mem::take(&mut self.lock().transmitted_events)
+    }
+
+    /// Takes every `SetDataOnSimObject` call applied since the last call, oldest first
+    pub fn take_data_sets(&self) -> Vec<MockDataSet> {
+        std::mem::take(&mut self.lock().data_sets)
+    }
+
+    /// Number of data requests currently being answered every period
+    pub fn data_request_count(&self) -> usize {
+        self.lock().data_requests.len()
+    }
+
+    /// Number of client data requests currently being answered every period
+    pub fn client_data_request_count(&self) -> usize {
+        self.lock().client_data_requests.len()
+    }
+
+    /// Creates a client data area of `size` bytes, as another client such as a WASM gauge
+    /// would. A `read_only` area can't be written to by the client. Returns `false` if an area
+    /// called `name` already exists.
+    pub fn create_client_data(&self, name: &str, size: usize, read_only: bool) -> bool {
+        let mut state = self.lock();
+        if state.client_data.contains_key(name) {
+            return false;
+        }
+        state.client_data.insert(
+            name.to_owned(),
+            MockClientData {
+                data: vec![0; size],
+                read_only,
+                created_by_client: false,
+            },
+        );
+        true
+    }
+
+    /// The contents of the client data area `name`, including what the client wrote to it
+    pub fn client_data(&self, name: &str) -> Option<Vec<u8>> {
+        self.lock()
+            .client_data
+            .get(name)
+            .map(|area| area.data.clone())
+    }
+
+    /// Writes `data` into the client data area `name` at `offset`, as another client would,
+    /// answering the client's requests for whenever the area is set. Returns `false` if there
+    /// is no such area or the data doesn't fit in it.
+    pub fn set_client_data(&self, name: &str, offset: usize, data: &[u8]) -> bool {
+        let mut state = self.lock();
+        let Some(area) = state.client_data.get_mut(name) else {
+            return false;
+        };
+        let Some(target) = area.data.get_mut(offset..offset + data.len()) else {
+            return false;
+        };
+        target.copy_from_slice(data);
+        state.client_data_set(name);
+        true
+    }
+
+    /// Installs the WASM bridge module, creating the client data areas of its default channel.
+    /// The module answers whatever is written to the command area of a channel right away.
+    pub fn install_wasm_bridge(&self) {
+        let mut state = self.lock();
+        state.create_bridge_areas(wasm_bridge::DEFAULT_CLIENT);
+        state
+            .bridge_clients
+            .get_or_insert_with(HashMap::new)
+            .entry(wasm_bridge::DEFAULT_CLIENT.to_owned())
+            .or_default();
+    }
+
+    /// The variables registered on `client`'s channel of the WASM bridge, in slot order, or
+    /// `None` if the client has no channel
+    pub fn bridge_variables(&self, client: &str) -> Option<Vec<String>> {
+        self.lock().bridge_clients.as_ref()?.get(client).cloned()
+    }
+
+    /// Sets a local variable, which the WASM bridge passes on to the client if it registered
+    /// the variable
+    pub fn set_lvar(&self, name: &str, value: f64) {
+        let mut state = self.lock();
+        state.lvars.insert(name.to_owned(), value);
+        state.write_bridge_values();
+    }
+
+    pub fn lvar(&self, name: &str) -> Option<f64> {
+        self.lock().lvars.get(name).copied()
+    }
+
+    /// Takes every `H:` event fired through the WASM bridge since the last call, oldest first
+    pub fn take_fired_hevents(&self) -> Vec<String> {
+        std::mem::take(&mut self.lock().fired_hevents)
+    }
+
+    /// Adds a facility to the navigation data, which `MockFacility::new` builds
+    pub fn add_facility(&self, icao: &str, region: &str, facility: MockFacility) {
+        self.lock()
+            .facilities
+            .insert(icao.to_owned(), (region.to_owned(), facility));
+    }
+
+    /// Moves the user's aircraft so exactly the facilities in `icaos` are in range, and so in
+    /// the facility cache. Subscribers are sent the facilities which came into range, but like
+    /// the simulator, nothing is sent for those which left it.
+    pub fn set_facilities_in_range(&self, icaos: &[&str]) {
+        let mut state = self.lock();
+        let added: Vec<String> = icaos
+            .iter()
+            .filter(|icao| !state.facilities_in_range.iter().any(|known| known == *icao))
+            .map(|icao| icao.to_string())
+            .collect();
+        state.facilities_in_range = icaos.iter().map(|icao| icao.to_string()).collect();
+
+        let subscriptions: Vec<(FacilityListType, u32)> = state
+            .facility_subscriptions
+            .iter()
+            .map(|(list_type, request_id)| (*list_type, *request_id))
+            .collect();
+        for (list_type, request_id) in subscriptions {
+            let has_added = added.iter().any(|icao| {
+                state
+                    .facilities
+                    .get(icao)
+                    .is_some_and(|(_, facility)| facility.list_type() == Some(list_type))
+            });
+            if has_added {
+                state.send_facilities_list(list_type, request_id, &added);
+            }
+        }
+    }
+
+    /// Advances the simulation by one frame, answering data requested every visual or sim frame
+    pub fn next_frame(&self) {
+        let mut state = self.lock();
+        state.elapse(&[
+            bindings::SIMCONNECT_PERIOD_SIMCONNECT_PERIOD_VISUAL_FRAME,
+            bindings::SIMCONNECT_PERIOD_SIMCONNECT_PERIOD_SIM_FRAME,
+        ]);
+        state.elapse_client_data(
+            bindings::SIMCONNECT_CLIENT_DATA_PERIOD_SIMCONNECT_CLIENT_DATA_PERIOD_VISUAL_FRAME,
+            None,
+        );
+    }
+
+    /// Advances the simulation by one second, answering data requested every second
+    pub fn next_second(&self) {
+        let mut state = self.lock();
+        state.elapse(&[bindings::SIMCONNECT_PERIOD_SIMCONNECT_PERIOD_SECOND]);
+        state.elapse_client_data(
+            bindings::SIMCONNECT_CLIENT_DATA_PERIOD_SIMCONNECT_CLIENT_DATA_PERIOD_SECOND,
+            None,
+        );
+    }
+
+    /// Holds back every message for the client until `resume` is called, the way a busy
+    /// simulator takes a while to answer
+    pub fn pause(&self) {
+        self.lock().paused = true;
+    }
+
+    /// Delivers the messages held back since `pause`
+    pub fn resume(&self) {
+        self.lock().paused = false;
+    }
+
+    /// Queues a raw, fully laid out `SIMCONNECT_RECV_*` message for the client
+    pub fn push_message(&self, message: Vec<u8>) {
+        self.lock().pending.push_back(message);
+    }
+
+    fn push_event(&self, event: SystemEvent, write: impl FnOnce(&mut RecvMessage, u32)) -> bool {
+        self.lock().push_event(event, write)
+    }
+}
+
+/// `SimConnectBackend` which answers requests from a `MockSim`
+pub struct MockBackend {
+    sim: MockSim,
+    // Kept alive so the pointer handed out by `get_next_dispatch` stays valid
+    current: Option<Vec<u64>>,
+}
+
+impl MockBackend {
+    /// Locks the simulator for a call which sends a packet
+    fn lock_open(&self) -> SimConnectResult<MutexGuard<'_, MockState>> {
+        let mut state = self.sim.lock();
+        if state.program_name.is_none() {
+            return Err(SimConnectError::NotConnected);
+        }
+        state.last_send_id += 1;
+        Ok(state)
+    }
+}
+
+impl SimConnectBackend for MockBackend {
+    fn open(&mut self, program_name: &CStr) -> SimConnectResult<()> {
+        let name = to_str(program_name)?.to_owned();
+
+        let mut message = RecvMessage::new(bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_OPEN);
+        message.fixed_string("MockSim", 256);
+        for _ in 0..10 {
+            message.dword(0);
+        }
+
+        let mut state = self.sim.lock();
+        state.program_name = Some(name);
+        state.last_send_id += 1;
+        state.pending.push_back(message.finish());
+        Ok(())
+    }
+
+    fn close(&mut self) -> SimConnectResult<()> {
+        let mut state = self.sim.lock();
+        state.program_name = None;
+        state.subscribed_events.clear();
+        state.client_events.clear();
+        state.notification_groups.clear();
+        state.input_groups.clear();
+        state.subscribed_input_events.clear();
+        state.data_requests.clear();
+        state.definitions.clear();
+        state.client_data_names.clear();
+        state.client_data_definitions.clear();
+        state.client_data_requests.clear();
+        // Objects created by a client go away with it
+        state.objects.retain(|_, object| object.ai.is_none());
+        state.pending.clear();
+        Ok(())
+    }
+
+    fn add_to_data_definition(
+        &mut self,
+        define_id: u32,
+        datum_name: &CStr,
+        _units_name: &CStr,
+        datum_type: bindings::SIMCONNECT_DATATYPE,
+        _epsilon: f32,
+        datum_id: u32,
+    ) -> SimConnectResult<()> {
+        let datum = MockDatum {
+            name: to_str(datum_name)?.to_uppercase(),
+            data_type: datum_type,
+            datum_id,
+        };
+
+        let mut state = self.lock_open()?;
+        if state.rejected_names.contains(&datum.name) {
+            state.raise(SimConnectException::NameUnrecognized, 2);
+            return Ok(());
+        }
+
+        state.definitions.entry(define_id).or_default().push(datum);
+        Ok(())
+    }
+
+    fn request_data_on_sim_object(
+        &mut self,
+        request_id: u32,
+        define_id: u32,
+        object_id: u32,
+        period: bindings::SIMCONNECT_PERIOD,
+        flags: bindings::SIMCONNECT_DATA_REQUEST_FLAG,
+        origin: u32,
+        interval: u32,
+        limit: u32,
+    ) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        if !state.definitions.contains_key(&define_id) {
+            state.raise(SimConnectException::UnrecognizedId, 2);
+            return Ok(());
+        }
+        if state.sim_vars_of(object_id).is_none() {
+            state.raise(SimConnectException::UnrecognizedId, 3);
+            return Ok(());
+        }
+
+        if period == bindings::SIMCONNECT_PERIOD_SIMCONNECT_PERIOD_NEVER {
+            state.data_requests.remove(&request_id);
+            return Ok(());
+        }
+
+        state.data_requests.insert(
+            request_id,
+            MockDataRequest {
+                define_id,
+                object_id,
+                period,
+                flags,
+                origin,
+                interval,
+                limit,
+                elapsed: 0,
+                sent: 0,
+                last_sent: None,
+            },
+        );
+        if period == bindings::SIMCONNECT_PERIOD_SIMCONNECT_PERIOD_ONCE {
+            state.answer_data_request(request_id);
+            state.data_requests.remove(&request_id);
+        }
+        Ok(())
+    }
+
+    fn request_data_on_sim_object_type(
+        &mut self,
+        request_id: u32,
+        define_id: u32,
+        radius_meters: u32,
+        object_type: bindings::SIMCONNECT_SIMOBJECT_TYPE,
+    ) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        if !state.definitions.contains_key(&define_id) {
+            state.raise(SimConnectException::UnrecognizedId, 2);
+            return Ok(());
+        }
+        let Ok(object_type) = SimObjectType::try_from(object_type) else {
+            state.raise(SimConnectException::InvalidEnum, 4);
+            return Ok(());
+        };
+
+        let object_ids = state.objects_in_range(object_type, radius_meters);
+        let out_of = object_ids.len() as u32;
+        let mut messages = Vec::with_capacity(object_ids.len().max(1));
+        for (entry, object_id) in object_ids.into_iter().enumerate() {
+            let values = state.read_values(define_id, object_id).unwrap_or_default();
+            let mut message = RecvMessage::new(
+                bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_SIMOBJECT_DATA_BYTYPE,
+            );
+            message.dword(request_id);
+            message.dword(object_id);
+            message.dword(define_id);
+            message.dword(0);
+            message.dword(entry as u32 + 1);
+            message.dword(out_of);
+            message.dword(values.len() as u32);
+            message.0.extend(values.concat());
+            messages.push(message.finish());
+        }
+        // Finding nothing is answered with a single, empty message
+        if messages.is_empty() {
+            let mut message = RecvMessage::new(
+                bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_SIMOBJECT_DATA_BYTYPE,
+            );
+            message.dword(request_id);
+            message.dword(bindings::SIMCONNECT_OBJECT_ID_USER);
+            message.dword(define_id);
+            for _ in 0..4 {
+                message.dword(0);
+            }
+            messages.push(message.finish());
+        }
+
+        state.pending.extend(messages);
+        Ok(())
+    }
+
+    fn set_data_on_sim_object(
+        &mut self,
+        define_id: u32,
+        object_id: u32,
+        flags: u32,
+        array_count: u32,
+        unit_size: u32,
+        data: &[u8],
+    ) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        let Some(definition) = state.definitions.get(&define_id) else {
+            state.raise(SimConnectException::UnrecognizedId, 1);
+            return Ok(());
+        };
+
+        let tagged = flags & bindings::SIMCONNECT_DATA_SET_FLAG_TAGGED != 0;
+        let unit_size = unit_size as usize;
+        if unit_size == 0 || array_count.max(1) as usize * unit_size != data.len() {
+            state.raise(SimConnectException::InvalidDataSize, 5);
+            return Ok(());
+        }
+        let elements = data
+            .chunks(unit_size)
+            .map(|element| match tagged {
+                true => read_tagged_data(definition, element),
+                false => read_data(definition, element),
+            })
+            .collect::<Result<Vec<_>, _>>();
+        let elements = match elements {
+            Ok(elements) => elements,
+            Err(exception) => {
+                state.raise(exception, 6);
+                return Ok(());
+            }
+        };
+
+        let Some(sim_vars) = state.sim_vars_of_mut(object_id) else {
+            state.raise(SimConnectException::UnrecognizedId, 2);
+            return Ok(());
+        };
+        for element in &elements {
+            sim_vars.extend(element.iter().cloned());
+        }
+        state.data_sets.push(MockDataSet {
+            object_id,
+            elements,
+            tagged,
+        });
+        Ok(())
+    }
+
+    fn map_client_event_to_sim_event(
+        &mut self,
+        event_id: u32,
+        event_name: &CStr,
+    ) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        let event_name = to_str(event_name)?.to_uppercase();
+        if state.rejected_names.contains(&event_name) {
+            state.raise(SimConnectException::NameUnrecognized, 2);
+            return Ok(());
+        }
+        if state.client_events.contains_key(&event_id) {
+            state.raise(SimConnectException::EventIdDuplicate, 1);
+            return Ok(());
+        }
+        state.client_events.insert(event_id, event_name);
+        Ok(())
+    }
+
+    fn transmit_client_event(
+        &mut self,
+        object_id: u32,
+        event_id: u32,
+        data: u32,
+        _group_id: u32,
+        _flags: bindings::SIMCONNECT_EVENT_FLAG,
+    ) -> SimConnectResult<()> {
+        self.lock_open()?.transmit(object_id, event_id, vec![data]);
+        Ok(())
+    }
+
+    fn transmit_client_event_ex1(
+        &mut self,
+        object_id: u32,
+        event_id: u32,
+        _group_id: u32,
+        _flags: bindings::SIMCONNECT_EVENT_FLAG,
+        data: [u32; 5],
+    ) -> SimConnectResult<()> {
+        self.lock_open()?
+            .transmit(object_id, event_id, data.to_vec());
+        Ok(())
+    }
+
+    fn add_client_event_to_notification_group(
+        &mut self,
+        group_id: u32,
+        event_id: u32,
+        maskable: bool,
+    ) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        if !state.client_events.contains_key(&event_id) {
+            state.raise(SimConnectException::UnrecognizedId, 2);
+            return Ok(());
+        }
+        let group = state.notification_groups.entry(group_id).or_default();
+        group.events.retain(|(id, _)| *id != event_id);
+        group.events.push((event_id, maskable));
+        Ok(())
+    }
+
+    fn remove_client_event(&mut self, group_id: u32, event_id: u32) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        match state.notification_groups.get_mut(&group_id) {
+            Some(group) => group.events.retain(|(id, _)| *id != event_id),
+            None => state.raise(SimConnectException::UnrecognizedId, 1),
+        }
+        Ok(())
+    }
+
+    fn set_notification_group_priority(
+        &mut self,
+        group_id: u32,
+        priority: u32,
+    ) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        match state.notification_groups.get_mut(&group_id) {
+            Some(group) => group.priority = Some(priority),
+            None => state.raise(SimConnectException::UnrecognizedId, 1),
+        }
+        Ok(())
+    }
+
+    fn map_input_event_to_client_event(
+        &mut self,
+        group_id: u32,
+        input_definition: &CStr,
+        down_event_id: u32,
+        down_value: u32,
+        up_event_id: u32,
+        up_value: u32,
+        maskable: bool,
+    ) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        let Ok(definition) = to_str(input_definition)?.parse::<InputDefinition>() else {
+            state.raise(SimConnectException::NameUnrecognized, 2);
+            return Ok(());
+        };
+        for (event_id, index) in [(down_event_id, 3), (up_event_id, 5)] {
+            if event_id != bindings::SIMCONNECT_UNUSED
+                && !state.client_events.contains_key(&event_id)
+            {
+                state.raise(SimConnectException::UnrecognizedId, index);
+                return Ok(());
+            }
+        }
+
+        let group = state.input_groups.entry(group_id).or_default();
+        group
+            .mappings
+            .retain(|mapping| mapping.definition != definition);
+        group.mappings.push(MockInputMapping {
+            definition,
+            down: (down_event_id, down_value),
+            up: (up_event_id, up_value),
+            maskable,
+        });
+        Ok(())
+    }
+
+    fn set_input_group_priority(&mut self, group_id: u32, priority: u32) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        match state.input_groups.get_mut(&group_id) {
+            Some(group) => group.priority = Some(priority),
+            None => state.raise(SimConnectException::UnrecognizedId, 1),
+        }
+        Ok(())
+    }
+
+    fn remove_input_event(
+        &mut self,
+        group_id: u32,
+        input_definition: &CStr,
+    ) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        let definition = to_str(input_definition)?.parse::<InputDefinition>().ok();
+        let Some(group) = state.input_groups.get_mut(&group_id) else {
+            state.raise(SimConnectException::UnrecognizedId, 1);
+            return Ok(());
+        };
+        let mappings = group.mappings.len();
+        group
+            .mappings
+            .retain(|mapping| Some(&mapping.definition) != definition.as_ref());
+        if group.mappings.len() == mappings {
+            state.raise(SimConnectException::NameUnrecognized, 2);
+        }
+        Ok(())
+    }
+
+    fn clear_input_group(&mut self, group_id: u32) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        if state.input_groups.remove(&group_id).is_none() {
+            state.raise(SimConnectException::UnrecognizedId, 1);
+        }
+        Ok(())
+    }
+
+    fn set_input_group_state(
+        &mut self,
+        group_id: u32,
+        group_state: bindings::SIMCONNECT_STATE,
+    ) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        match state.input_groups.get_mut(&group_id) {
+            Some(group) => {
+                group.enabled = group_state == bindings::SIMCONNECT_STATE_SIMCONNECT_STATE_ON
+            }
+            None => state.raise(SimConnectException::UnrecognizedId, 1),
+        }
+        Ok(())
+    }
+
+    fn enumerate_input_events(&mut self, request_id: u32) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        let mut messages = Vec::new();
+        let pages: Vec<&[MockInputEvent]> = match state.input_events.len() {
+            0 => vec![&[]],
+            _ => state
+                .input_events
+                .chunks(INPUT_EVENTS_PER_MESSAGE)
+                .collect(),
+        };
+        for (entry_number, page) in pages.iter().enumerate() {
+            let mut message = RecvMessage::new(
+                bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_ENUMERATE_INPUT_EVENTS,
+            );
+            message.dword(request_id);
+            message.dword(page.len() as u32);
+            message.dword(entry_number as u32);
+            message.dword(pages.len() as u32);
+            for input_event in *page {
+                message.fixed_string(&input_event.name, 64);
+                message.qword(input_event.hash);
+                message.dword(match input_event.value {
+                    MockValue::Number(_) => {
+                        bindings::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT64 as u32
+                    }
+                    MockValue::Text(_) => {
+                        bindings::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING256 as u32
+                    }
+                });
+            }
+            messages.push(message.finish());
+        }
+        state.pending.extend(messages);
+        Ok(())
+    }
+
+    fn get_input_event(&mut self, request_id: u32, hash: u64) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        let Some(input_event) = state.input_event_mut(hash) else {
+            state.raise(SimConnectException::GetInputEventFailed, 2);
+            return Ok(());
+        };
+
+        let mut message =
+            RecvMessage::new(bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_GET_INPUT_EVENT);
+        message.dword(request_id);
+        message.input_event_value(&input_event.value);
+        state.pending.push_back(message.finish());
+        Ok(())
+    }
+
+    fn set_input_event(&mut self, hash: u64, value: &[u8]) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        let Some(input_event) = state.input_event_mut(hash) else {
+            state.raise(SimConnectException::SetInputEventFailed, 1);
+            return Ok(());
+        };
+
+        let value = match (&input_event.value, value.try_into()) {
+            (MockValue::Number(_), Ok(bytes)) => MockValue::Number(f64::from_le_bytes(bytes)),
+            (MockValue::Text(_), _) if value.last() == Some(&0) => {
+                MockValue::Text(String::from_utf8_lossy(&value[..value.len() - 1]).into_owned())
+            }
+            _ => {
+                state.raise(SimConnectException::SetInputEventFailed, 3);
+                return Ok(());
+            }
+        };
+        input_event.value = value;
+        state.input_event_changed(hash);
+        Ok(())
+    }
+
+    fn subscribe_input_event(&mut self, hash: u64) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        if state.input_event_mut(hash).is_none() {
+            state.raise(SimConnectException::UnrecognizedId, 1);
+            return Ok(());
+        }
+        state.subscribed_input_events.insert(hash);
+        Ok(())
+    }
+
+    fn unsubscribe_input_event(&mut self, hash: u64) -> SimConnectResult<()> {
+        self.lock_open()?.subscribed_input_events.remove(&hash);
+        Ok(())
+    }
+
+    fn enumerate_input_event_params(&mut self, hash: u64) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        let Some(input_event) = state.input_event_mut(hash) else {
+            state.raise(SimConnectException::UnrecognizedId, 1);
+            return Ok(());
+        };
+        let params = match input_event.value {
+            MockValue::Number(_) => "FLOAT64;",
+            MockValue::Text(_) => "STRING;",
+        };
+
+        let mut message = RecvMessage::new(
+            bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_ENUMERATE_INPUT_EVENT_PARAMS,
+        );
+        message.qword(hash);
+        message.fixed_string(params, 260);
+        state.pending.push_back(message.finish());
+        Ok(())
+    }
+
+    fn map_client_data_name_to_id(
+        &mut self,
+        client_data_name: &CStr,
+        client_data_id: u32,
+    ) -> SimConnectResult<()> {
+        let name = to_str(client_data_name)?.to_owned();
+        let mut state = self.lock_open()?;
+        if state.client_data_names.contains_key(&client_data_id) {
+            state.raise(SimConnectException::DuplicateId, 2);
+            return Ok(());
+        }
+        state.client_data_names.insert(client_data_id, name);
+        Ok(())
+    }
+
+    fn create_client_data(
+        &mut self,
+        client_data_id: u32,
+        size: u32,
+        flags: bindings::SIMCONNECT_CREATE_CLIENT_DATA_FLAG,
+    ) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        let Some(name) = state.client_data_names.get(&client_data_id).cloned() else {
+            state.raise(SimConnectException::UnrecognizedId, 1);
+            return Ok(());
+        };
+        if size == 0 || size > bindings::SIMCONNECT_CLIENTDATA_MAX_SIZE {
+            state.raise(SimConnectException::OutOfBounds, 2);
+            return Ok(());
+        }
+        if state.client_data.contains_key(&name) {
+            state.raise(SimConnectException::AlreadyCreated, 1);
+            return Ok(());
+        }
+        state.client_data.insert(
+            name,
+            MockClientData {
+                data: vec![0; size as usize],
+                read_only: flags & bindings::SIMCONNECT_CREATE_CLIENT_DATA_FLAG_READ_ONLY != 0,
+                created_by_client: true,
+            },
+        );
+        Ok(())
+    }
+
+    fn add_to_client_data_definition(
+        &mut self,
+        define_id: u32,
+        offset: u32,
+        size_or_type: u32,
+        epsilon: f32,
+        _datum_id: u32,
+    ) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        let definition = state.client_data_definitions.entry(define_id).or_default();
+        // An automatic offset places the datum right after the previous one
+        let offset = match offset {
+            bindings::SIMCONNECT_CLIENTDATAOFFSET_AUTO => definition
+                .last()
+                .map(|field| field.offset + field.size() as u32)
+                .unwrap_or_default(),
+            offset => offset,
+        };
+        definition.push(ClientDataField {
+            offset,
+            size_or_type,
+            epsilon,
+        });
+        Ok(())
+    }
+
+    fn clear_client_data_definition(&mut self, define_id: u32) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        if state.client_data_definitions.remove(&define_id).is_none() {
+            state.raise(SimConnectException::UnrecognizedId, 1);
+        }
+        Ok(())
+    }
+
+    fn request_client_data(
+        &mut self,
+        client_data_id: u32,
+        request_id: u32,
+        define_id: u32,
+        period: bindings::SIMCONNECT_CLIENT_DATA_PERIOD,
+        flags: bindings::SIMCONNECT_CLIENT_DATA_REQUEST_FLAG,
+        origin: u32,
+        interval: u32,
+        limit: u32,
+    ) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        if state.client_data_area(client_data_id).is_none() {
+            state.raise(SimConnectException::UnrecognizedId, 1);
+            return Ok(());
+        }
+        if !state.client_data_definitions.contains_key(&define_id) {
+            state.raise(SimConnectException::UnrecognizedId, 3);
+            return Ok(());
+        }
+
+        if period == bindings::SIMCONNECT_CLIENT_DATA_PERIOD_SIMCONNECT_CLIENT_DATA_PERIOD_NEVER {
+            state.client_data_requests.remove(&request_id);
+            return Ok(());
+        }
+
+        state.client_data_requests.insert(
+            request_id,
+            MockClientDataRequest {
+                client_data_id,
+                define_id,
+                period,
+                flags,
+                origin,
+                interval,
+                limit,
+                elapsed: 0,
+                sent: 0,
+                last_sent: None,
+            },
+        );
+        if period == bindings::SIMCONNECT_CLIENT_DATA_PERIOD_SIMCONNECT_CLIENT_DATA_PERIOD_ONCE {
+            state.answer_client_data_request(request_id);
+            state.client_data_requests.remove(&request_id);
+        }
+        Ok(())
+    }
+
+    fn set_client_data(
+        &mut self,
+        client_data_id: u32,
+        define_id: u32,
+        _flags: bindings::SIMCONNECT_CLIENT_DATA_SET_FLAG,
+        data: &[u8],
+    ) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        let Some(name) = state.client_data_names.get(&client_data_id).cloned() else {
+            state.raise(SimConnectException::UnrecognizedId, 1);
+            return Ok(());
+        };
+        let Some(definition) = state.client_data_definitions.get(&define_id).cloned() else {
+            state.raise(SimConnectException::UnrecognizedId, 2);
+            return Ok(());
+        };
+        let Some(area) = state.client_data.get_mut(&name) else {
+            state.raise(SimConnectException::UnrecognizedId, 1);
+            return Ok(());
+        };
+        if area.read_only && !area.created_by_client {
+            state.raise(SimConnectException::IllegalOperation, 1);
+            return Ok(());
+        }
+        if definition.iter().map(ClientDataField::size).sum::<usize>() != data.len() {
+            state.raise(SimConnectException::SizeMismatch, 5);
+            return Ok(());
+        }
+        if !write_client_data(&mut area.data, &definition, data) {
+            state.raise(SimConnectException::OutOfBounds, 2);
+            return Ok(());
+        }
+
+        state.client_data_set(&name);
+        Ok(())
+    }
+
+    fn add_to_facility_definition(
+        &mut self,
+        define_id: u32,
+        field_name: &CStr,
+    ) -> SimConnectResult<()> {
+        let entry = to_str(field_name)?;
+        let mut state = self.lock_open()?;
+
+        if let Some(name) = entry.strip_prefix("OPEN ") {
+            let Some(data_type) = FacilityDataType::from_name(name) else {
+                state.raise(SimConnectException::InvalidDataType, 2);
+                return Ok(());
+            };
+            let record = MockFacilityDefinition {
+                data_type,
+                fields: Vec::new(),
+                children: Vec::new(),
+                closed: false,
+            };
+            match state.facility_definitions.get_mut(&define_id) {
+                Some(definition) if !definition.closed => {
+                    definition.open_record().children.push(record)
+                }
+                Some(_) => state.raise(SimConnectException::DuplicateId, 1),
+                None => {
+                    state.facility_definitions.insert(define_id, record);
+                }
+            }
+            return Ok(());
+        }
+
+        let Some(record) = state
+            .facility_definitions
+            .get_mut(&define_id)
+            .filter(|definition| !definition.closed)
+            .map(MockFacilityDefinition::open_record)
+        else {
+            state.raise(SimConnectException::UnrecognizedId, 1);
+            return Ok(());
+        };
+        if let Some(name) = entry.strip_prefix("CLOSE ") {
+            if record.data_type.name() != Some(name) {
+                state.raise(SimConnectException::InvalidDataType, 2);
+                return Ok(());
+            }
+            record.closed = true;
+            return Ok(());
+        }
+        match facilities::field_type(record.data_type, entry) {
+            Some(field_type) => record.fields.push((entry.to_owned(), field_type)),
+            None => state.raise(SimConnectException::NameUnrecognized, 2),
+        }
+        Ok(())
+    }
+
+    fn request_facility_data(
+        &mut self,
+        define_id: u32,
+        request_id: u32,
+        icao: &CStr,
+        region: &CStr,
+    ) -> SimConnectResult<()> {
+        let icao = to_str(icao)?;
+        let region = to_str(region)?;
+        let mut state = self.lock_open()?;
+        let Some(definition) = state.facility_definitions.remove(&define_id) else {
+            state.raise(SimConnectException::UnrecognizedId, 1);
+            return Ok(());
+        };
+
+        // Only a facility of the definition's type is sent, anything else is as good as missing
+        let facility = state
+            .facilities
+            .get(icao)
+            .filter(|(facility_region, facility)| {
+                (region.is_empty() || facility_region == region)
+                    && facility.data_type == definition.data_type
+            })
+            .map(|(_, facility)| facility.clone());
+        if let Some(facility) = facility {
+            state.send_facility_record(request_id, 0, None, &definition, &facility);
+        }
+        state.facility_definitions.insert(define_id, definition);
+
+        let mut end =
+            RecvMessage::new(bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_FACILITY_DATA_END);
+        end.dword(request_id);
+        state.pending.push_back(end.finish());
+        Ok(())
+    }
+
+    fn subscribe_to_facilities(
+        &mut self,
+        list_type: bindings::SIMCONNECT_FACILITY_LIST_TYPE,
+        request_id: u32,
+    ) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        let list_type = FacilityListType::from(list_type);
+        if list_type.recv_id().is_none() {
+            state.raise(SimConnectException::InvalidEnum, 1);
+            return Ok(());
+        }
+        state.facility_subscriptions.insert(list_type, request_id);
+        let in_range = state.facilities_in_range.clone();
+        state.send_facilities_list(list_type, request_id, &in_range);
+        Ok(())
+    }
+
+    fn unsubscribe_to_facilities(
+        &mut self,
+        list_type: bindings::SIMCONNECT_FACILITY_LIST_TYPE,
+    ) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        state
+            .facility_subscriptions
+            .remove(&FacilityListType::from(list_type));
+        Ok(())
+    }
+
+    fn request_facilities_list(
+        &mut self,
+        list_type: bindings::SIMCONNECT_FACILITY_LIST_TYPE,
+        request_id: u32,
+    ) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        let list_type = FacilityListType::from(list_type);
+        if list_type.recv_id().is_none() {
+            state.raise(SimConnectException::InvalidEnum, 1);
+            return Ok(());
+        }
+        let in_range = state.facilities_in_range.clone();
+        state.send_facilities_list(list_type, request_id, &in_range);
+        Ok(())
+    }
+
+    fn ai_create_parked_atc_aircraft(
+        &mut self,
+        container_title: &CStr,
+        tail_number: &CStr,
+        airport_id: &CStr,
+        request_id: u32,
+    ) -> SimConnectResult<()> {
+        let ai = MockAiObject {
+            container_title: to_str(container_title)?.to_owned(),
+            tail_number: Some(to_str(tail_number)?.to_owned()),
+            airport: Some(to_str(airport_id)?.to_owned()),
+            flight_plan: None,
+            released: false,
+        };
+        self.lock_open()?.create_object(request_id, ai, None);
+        Ok(())
+    }
+
+    fn ai_create_enroute_atc_aircraft(
+        &mut self,
+        container_title: &CStr,
+        tail_number: &CStr,
+        _flight_number: i32,
+        flight_plan_path: &CStr,
+        _flight_plan_position: f64,
+        _touch_and_go: bool,
+        request_id: u32,
+    ) -> SimConnectResult<()> {
+        let ai = MockAiObject {
+            container_title: to_str(container_title)?.to_owned(),
+            tail_number: Some(to_str(tail_number)?.to_owned()),
+            airport: None,
+            flight_plan: Some(to_str(flight_plan_path)?.to_owned()),
+            released: false,
+        };
+        self.lock_open()?.create_object(request_id, ai, None);
+        Ok(())
+    }
+
+    fn ai_create_non_atc_aircraft(
+        &mut self,
+        container_title: &CStr,
+        tail_number: &CStr,
+        init_position: bindings::SIMCONNECT_DATA_INITPOSITION,
+        request_id: u32,
+    ) -> SimConnectResult<()> {
+        let ai = MockAiObject {
+            container_title: to_str(container_title)?.to_owned(),
+            tail_number: Some(to_str(tail_number)?.to_owned()),
+            airport: None,
+            flight_plan: None,
+            released: false,
+        };
+        self.lock_open()?
+            .create_object(request_id, ai, Some(init_position));
+        Ok(())
+    }
+
+    fn ai_create_simulated_object(
+        &mut self,
+        container_title: &CStr,
+        init_position: bindings::SIMCONNECT_DATA_INITPOSITION,
+        request_id: u32,
+    ) -> SimConnectResult<()> {
+        let ai = MockAiObject {
+            container_title: to_str(container_title)?.to_owned(),
+            tail_number: None,
+            airport: None,
+            flight_plan: None,
+            released: false,
+        };
+        self.lock_open()?
+            .create_object(request_id, ai, Some(init_position));
+        Ok(())
+    }
+
+    fn ai_release_control(&mut self, object_id: u32, _request_id: u32) -> SimConnectResult<()> {
+        if let Some(ai) = self.lock_open()?.ai_object_mut(object_id) {
+            ai.released = true;
+        }
+        Ok(())
+    }
+
+    fn ai_remove_object(&mut self, object_id: u32, _request_id: u32) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        if state.ai_object_mut(object_id).is_some() {
+            state.objects.remove(&object_id);
+        }
+        Ok(())
+    }
+
+    fn ai_set_aircraft_flight_plan(
+        &mut self,
+        object_id: u32,
+        flight_plan_path: &CStr,
+        _request_id: u32,
+    ) -> SimConnectResult<()> {
+        let flight_plan = to_str(flight_plan_path)?.to_owned();
+        if let Some(ai) = self.lock_open()?.ai_object_mut(object_id) {
+            ai.flight_plan = Some(flight_plan);
+        }
+        Ok(())
+    }
+
+    fn flight_load(&mut self, file_name: &CStr) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        let file_name = to_str(file_name)?;
+        if !state.flights.contains_key(&file_key(file_name, "flt")) {
+            state.raise(SimConnectException::Error, 1);
+            return Ok(());
+        }
+        state.loaded_flight = Some(file_name.to_owned());
+        state.push_filename_event(SystemEvent::FlightLoaded, file_name);
+        Ok(())
+    }
+
+    fn flight_save(
+        &mut self,
+        file_name: &CStr,
+        title: &CStr,
+        description: &CStr,
+        _flags: u32,
+    ) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        let file_name = to_str(file_name)?;
+        let flight = MockFlight {
+            title: to_str(title)?.to_owned(),
+            description: to_str(description)?.to_owned(),
+        };
+        state.flights.insert(file_key(file_name, "flt"), flight);
+        state.push_filename_event(SystemEvent::FlightSaved, file_name);
+        Ok(())
+    }
+
+    fn flight_plan_load(&mut self, file_name: &CStr) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        let file_name = to_str(file_name)?;
+        if !state.flight_plans.contains(&file_key(file_name, "pln")) {
+            state.raise(SimConnectException::Error, 1);
+            return Ok(());
+        }
+        state.loaded_flight_plan = Some(file_name.to_owned());
+        state.push_filename_event(SystemEvent::FlightPlanActivated, file_name);
+        Ok(())
+    }
+
+    fn subscribe_to_system_event(
+        &mut self,
+        event_id: u32,
+        event_name: &CStr,
+    ) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        let event_name = to_str(event_name)?;
+        if state.rejected_names.contains(&event_name.to_uppercase()) {
+            state.raise(SimConnectException::NameUnrecognized, 2);
+            return Ok(());
+        }
+        state
+            .subscribed_events
+            .insert(event_name.to_lowercase(), event_id);
+        Ok(())
+    }
+
+    fn unsubscribe_from_system_event(&mut self, event_id: u32) -> SimConnectResult<()> {
+        self.lock_open()?
+            .subscribed_events
+            .retain(|_, subscribed_id| *subscribed_id != event_id);
+        Ok(())
+    }
+
+    fn request_system_state(&mut self, request_id: u32, state_name: &CStr) -> SimConnectResult<()> {
+        let mut state = self.lock_open()?;
+        let value = state.system_states.get(to_str(state_name)?);
+
+        let mut message =
+            RecvMessage::new(bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_SYSTEM_STATE);
+        message.dword(request_id);
+        match value {
+            Some(MockValue::Number(number)) => {
+                message.dword(*number as u32);
+                message.f32(*number as f32);
+                message.fixed_string("", 260);
+            }
+            Some(MockValue::Text(text)) => {
+                message.dword(0);
+                message.f32(0.0);
+                message.fixed_string(text, 260);
+            }
+            None => {
+                message.dword(0);
+                message.f32(0.0);
+                message.fixed_string("", 260);
+            }
+        }
+
+        state.pending.push_back(message.finish());
+        Ok(())
+    }
+
+    fn last_sent_packet_id(&mut self) -> SimConnectResult<u32> {
+        Ok(self.sim.lock().last_send_id)
+    }
+
+    fn get_next_dispatch(
+        &mut self,
+    ) -> SimConnectResult<Option<NonNull<bindings::SIMCONNECT_RECV>>> {
+        let mut state = self.sim.lock();
+        self.current = match state.paused {
+            true => None,
+            false => state
+                .pending
+                .pop_front()
+                .map(|message| aligned_copy(&message)),
+        };
+
+        Ok(self.current.as_mut().and_then(|message| {
+            NonNull::new(message.as_mut_ptr() as *mut bindings::SIMCONNECT_RECV)
+        }))
+    }
+}
+
+/// Whether a data request counting `elapsed` periods is due an answer
+fn is_due(elapsed: u32, origin: u32, interval: u32) -> bool {
+    elapsed > origin && (elapsed - origin - 1).is_multiple_of(interval + 1)
+}
+
+/// Reads every datum of a client data definition out of an area, or `None` if one lies
+/// outside it
+fn read_client_data(area: &[u8], definition: &[ClientDataField]) -> Option<Vec<Vec<u8>>> {
+    definition
+        .iter()
+        .map(|field| {
+            let offset = field.offset as usize;
+            area.get(offset..offset + field.size()).map(<[u8]>::to_vec)
+        })
+        .collect()
+}
+
+/// Writes the datums of a client data definition, packed one after the other in `data`, into
+/// an area. Returns `false`, writing nothing, if a datum lies outside the area.
+fn write_client_data(area: &mut [u8], definition: &[ClientDataField], data: &[u8]) -> bool {
+    if definition
+        .iter()
+        .any(|field| field.offset as usize + field.size() > area.len())
+    {
+        return false;
+    }
+    let mut read = 0;
+    for field in definition {
+        let (offset, size) = (field.offset as usize, field.size());
+        area[offset..offset + size].copy_from_slice(&data[read..read + size]);
+        read += size;
+    }
+    true
+}
+
+/// Whether a client data datum changed enough to be sent for
+/// `SIMCONNECT_CLIENT_DATA_REQUEST_FLAG_CHANGED`. Typed datums have to change by more than
+/// their epsilon, raw bytes by anything at all.
+fn client_datum_changed(field: &ClientDataField, old: &[u8], new: &[u8]) -> bool {
+    let number = |bytes: &[u8]| -> Option<f64> {
+        Some(match field.size_or_type {
+            bindings::SIMCONNECT_CLIENTDATATYPE_INT8 => {
+                i8::from_le_bytes(bytes.try_into().ok()?) as f64
+            }
+            bindings::SIMCONNECT_CLIENTDATATYPE_INT16 => {
+                i16::from_le_bytes(bytes.try_into().ok()?) as f64
+            }
+            bindings::SIMCONNECT_CLIENTDATATYPE_INT32 => {
+                i32::from_le_bytes(bytes.try_into().ok()?) as f64
+            }
+            bindings::SIMCONNECT_CLIENTDATATYPE_INT64 => {
+                i64::from_le_bytes(bytes.try_into().ok()?) as f64
+            }
+            bindings::SIMCONNECT_CLIENTDATATYPE_FLOAT32 => {
+                f32::from_le_bytes(bytes.try_into().ok()?) as f64
+            }
+            bindings::SIMCONNECT_CLIENTDATATYPE_FLOAT64 => {
+                f64::from_le_bytes(bytes.try_into().ok()?)
+            }
+            _ => return None,
+        })
+    };
+    match (number(old), number(new)) {
+        (Some(old), Some(new)) => (new - old).abs() > field.epsilon as f64,
+        _ => old != new,
+    }
+}
+
+/// Size in bytes of a fixed size `SIMCONNECT_DATATYPE`, or `None` for `STRINGV`
+fn datum_size(data_type: bindings::SIMCONNECT_DATATYPE) -> Option<usize> {
+    SimVarType::from(data_type).size()
+}
+
+/// Identifies a flight or flight plan file however the client names it: in any case, with
+/// either kind of slash, and with or without its extension
+fn file_key(file_name: &str, extension: &str) -> String {
+    let key = file_name.to_lowercase().replace('\\', "/");
+    match key.strip_suffix(&format!(".{extension}")) {
+        Some(stem) => stem.to_owned(),
+        None => key,
+    }
+}
+
+/// Reads a value for every datum of `definition`, in order, from untagged data
+fn read_data(
+    definition: &[MockDatum],
+    mut data: &[u8],
+) -> Result<Vec<(String, MockValue)>, SimConnectException> {
+    let mut values = Vec::with_capacity(definition.len());
+    for datum in definition {
+        let (value, size) =
+            read_datum(datum.data_type, data).ok_or(SimConnectException::InvalidDataSize)?;
+        data = &data[size..];
+        if let Some(value) = value {
+            values.push((datum.name.clone(), value));
+        }
+    }
+    Ok(values)
+}
+
+/// Reads `(datum id, value)` pairs of `SIMCONNECT_DATA_SET_FLAG_TAGGED` data
+fn read_tagged_data(
+    definition: &[MockDatum],
+    mut data: &[u8],
+) -> Result<Vec<(String, MockValue)>, SimConnectException> {
+    let mut values = Vec::new();
+    while !data.is_empty() {
+        let datum_id = data.get(..4).ok_or(SimConnectException::InvalidDataSize)?;
+        let datum_id = u32::from_le_bytes(datum_id.try_into().unwrap());
+        let datum = definition
+            .iter()
+            .find(|datum| datum.datum_id == datum_id)
+            .ok_or(SimConnectException::DatumId)?;
+        let (value, size) =
+            read_datum(datum.data_type, &data[4..]).ok_or(SimConnectException::InvalidDataSize)?;
+        data = &data[4 + size..];
+        if let Some(value) = value {
+            values.push((datum.name.clone(), value));
+        }
+    }
+    Ok(values)
+}
+
+/// Decodes a single datum from the front of `data`, returning the value (if the data type
+/// maps onto a `MockValue`) and the number of bytes consumed
+fn read_datum(
+    data_type: bindings::SIMCONNECT_DATATYPE,
+    data: &[u8],
+) -> Option<(Option<MockValue>, usize)> {
+    if data_type == bindings::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRINGV {
+        let text = CStr::from_bytes_until_nul(data).ok()?;
+        let size = (text.to_bytes_with_nul().len() + 3) & !3;
+        let value = MockValue::Text(text.to_string_lossy().into_owned());
+        return Some((Some(value), size.min(data.len())));
+    }
+
+    let size = datum_size(data_type)?;
+    let bytes = data.get(..size)?;
+    let value = match data_type {
+        bindings::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_INT32 => Some(MockValue::Number(
+            i32::from_le_bytes(bytes.try_into().ok()?) as f64,
+        )),
+        bindings::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_INT64 => Some(MockValue::Number(
+            i64::from_le_bytes(bytes.try_into().ok()?) as f64,
+        )),
+        bindings::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT32 => Some(MockValue::Number(
+            f32::from_le_bytes(bytes.try_into().ok()?) as f64,
+        )),
+        bindings::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT64 => Some(MockValue::Number(
+            f64::from_le_bytes(bytes.try_into().ok()?),
+        )),
+        bindings::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING8
+        | bindings::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING32
+        | bindings::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING64
+        | bindings::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING128
+        | bindings::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING256
+        | bindings::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING260 => {
+            let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+            Some(MockValue::Text(
+                String::from_utf8_lossy(&bytes[..end]).into_owned(),
+            ))
+        }
+        _ => None,
+    };
+
+    Some((value, size))
+}
+
+/// Builds a `SIMCONNECT_RECV_*` message laid out exactly as SimConnect would deliver it
+struct RecvMessage(Vec<u8>);
+
+impl RecvMessage {
+    fn new(recv_id: bindings::SIMCONNECT_RECV_ID) -> Self {
+        let mut message = Self(Vec::with_capacity(64));
+        message.dword(0);
+        message.dword(RECV_VERSION);
+        message.dword(recv_id as u32);
+        message
+    }
+
+    fn dword(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn qword(&mut self, value: u64) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn f32(&mut self, value: f32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn f64(&mut self, value: f64) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    /// Writes the `eType` and `Value` of an input event
+    fn input_event_value(&mut self, value: &MockValue) {
+        match value {
+            MockValue::Number(number) => {
+                self.dword(
+                    bindings::SIMCONNECT_INPUT_EVENT_TYPE_SIMCONNECT_INPUT_EVENT_TYPE_DOUBLE as u32,
+                );
+                self.0.extend_from_slice(&number.to_le_bytes());
+            }
+            MockValue::Text(text) => {
+                self.dword(
+                    bindings::SIMCONNECT_INPUT_EVENT_TYPE_SIMCONNECT_INPUT_EVENT_TYPE_STRING as u32,
+                );
+                self.fixed_string(text, (text.len() + 1 + 3) & !3);
+            }
+        }
+    }
+
+    fn fixed_string(&mut self, value: &str, size: usize) {
+        let mut bytes = value.as_bytes()[..value.len().min(size - 1)].to_vec();
+        bytes.resize(size, 0);
+        self.0.extend_from_slice(&bytes);
+    }
+
+    /// Writes the body of a system event, which isn't sent to any notification group
+    fn event(&mut self, event_id: u32, data: u32) {
+        self.dword(bindings::SIMCONNECT_UNUSED);
+        self.dword(event_id);
+        self.dword(data);
+    }
+
+    fn datum(&mut self, data_type: bindings::SIMCONNECT_DATATYPE, value: Option<&MockValue>) {
+        let number = value.map(MockValue::as_number).unwrap_or_default();
+        let text = value.map(MockValue::as_text).unwrap_or_default();
+
+        match data_type {
+            bindings::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_INT32 => {
+                self.0.extend_from_slice(&(number as i32).to_le_bytes())
+            }
+            bindings::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_INT64 => {
+                self.0.extend_from_slice(&(number as i64).to_le_bytes())
+            }
+            bindings::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT32 => self.f32(number as f32),
+            bindings::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_FLOAT64 => {
+                self.0.extend_from_slice(&number.to_le_bytes())
+            }
+            bindings::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRINGV => {
+                let size = (text.len() + 1 + 3) & !3;
+                self.fixed_string(&text, size);
+            }
+            data_type => match datum_size(data_type) {
+                Some(size)
+                    if data_type <= bindings::SIMCONNECT_DATATYPE_SIMCONNECT_DATATYPE_STRING260 =>
+                {
+                    self.fixed_string(&text, size)
+                }
+                Some(size) => self.0.resize(self.0.len() + size, 0),
+                None => {}
+            },
+        }
+    }
+
+    fn facility_field(&mut self, field_type: FacilityFieldType, value: Option<&MockValue>) {
+        let number = value.map(MockValue::as_number).unwrap_or_default();
+        match field_type {
+            FacilityFieldType::Int32 => self.0.extend_from_slice(&(number as i32).to_le_bytes()),
+            FacilityFieldType::Float32 => self.f32(number as f32),
+            FacilityFieldType::Float64 => self.0.extend_from_slice(&number.to_le_bytes()),
+            FacilityFieldType::String(size) => {
+                self.fixed_string(&value.map(MockValue::as_text).unwrap_or_default(), size)
+            }
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let size = self.0.len() as u32;
+        self.0[..4].copy_from_slice(&size.to_le_bytes());
+        self.0
+    }
+}
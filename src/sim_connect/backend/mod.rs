@@ -0,0 +1,375 @@
+use sim_connect_data::error::{HResult, SimConnectResult};
+use sim_connect_sys::bindings;
+use std::{ffi::CStr, ptr::NonNull};
+
+macro_rules! check_hr {
+    ($hr: expr) => {
+        let hr = $hr;
+        if hr < 0 {
+            return Err(sim_connect_data::error::HResult::from(hr).into());
+        }
+    };
+}
+
+mod ffi;
+mod mock;
+mod net;
+
+pub use ffi::FfiBackend;
+pub use mock::{
+    MockAiObject, MockBackend, MockDataSet, MockFacility, MockFlight, MockSim, MockValue,
+    TransmittedEvent,
+};
+pub use net::NetworkBackend;
+
+/// The transport `SimConnect` uses to talk to the simulator.
+///
+/// Each method mirrors the `SimConnect_*` function of the same name. The default
+/// implementation, `FfiBackend`, calls straight into `SimConnect.dll`; other implementations
+/// can be handed to `SimConnect::open_with_backend` to run without the native library.
+pub trait SimConnectBackend: Send + 'static {
+    /// Opens the connection to the simulator, registering the client as `program_name`
+    fn open(&mut self, program_name: &CStr) -> SimConnectResult<()>;
+
+    /// Closes the connection. Called once when `SimConnect` is dropped.
+    fn close(&mut self) -> SimConnectResult<()>;
+
+    fn add_to_data_definition(
+        &mut self,
+        define_id: u32,
+        datum_name: &CStr,
+        units_name: &CStr,
+        datum_type: bindings::SIMCONNECT_DATATYPE,
+        epsilon: f32,
+        datum_id: u32,
+    ) -> SimConnectResult<()>;
+
+    /// Requests data on `object_id` every `period`. Sending `SIMCONNECT_PERIOD_NEVER` for an
+    /// existing `request_id` stops the request.
+    #[allow(clippy::too_many_arguments)]
+    fn request_data_on_sim_object(
+        &mut self,
+        request_id: u32,
+        define_id: u32,
+        object_id: u32,
+        period: bindings::SIMCONNECT_PERIOD,
+        flags: bindings::SIMCONNECT_DATA_REQUEST_FLAG,
+        origin: u32,
+        interval: u32,
+        limit: u32,
+    ) -> SimConnectResult<()>;
+
+    fn request_data_on_sim_object_type(
+        &mut self,
+        request_id: u32,
+        define_id: u32,
+        radius_meters: u32,
+        object_type: bindings::SIMCONNECT_SIMOBJECT_TYPE,
+    ) -> SimConnectResult<()>;
+
+    /// `data` holds `array_count` elements of `unit_size` bytes each
+    fn set_data_on_sim_object(
+        &mut self,
+        define_id: u32,
+        object_id: u32,
+        flags: u32,
+        array_count: u32,
+        unit_size: u32,
+        data: &[u8],
+    ) -> SimConnectResult<()>;
+
+    fn map_client_event_to_sim_event(
+        &mut self,
+        event_id: u32,
+        event_name: &CStr,
+    ) -> SimConnectResult<()>;
+
+    fn transmit_client_event(
+        &mut self,
+        object_id: u32,
+        event_id: u32,
+        data: u32,
+        group_id: u32,
+        flags: bindings::SIMCONNECT_EVENT_FLAG,
+    ) -> SimConnectResult<()>;
+
+    /// Like `transmit_client_event`, but with up to five parameters for the event
+    fn transmit_client_event_ex1(
+        &mut self,
+        object_id: u32,
+        event_id: u32,
+        group_id: u32,
+        flags: bindings::SIMCONNECT_EVENT_FLAG,
+        data: [u32; 5],
+    ) -> SimConnectResult<()>;
+
+    /// Adds a client event to a notification group, creating the group if needed. A
+    /// `maskable` event is not passed on to anyone with a lower priority than the group.
+    fn add_client_event_to_notification_group(
+        &mut self,
+        group_id: u32,
+        event_id: u32,
+        maskable: bool,
+    ) -> SimConnectResult<()>;
+
+    fn remove_client_event(&mut self, group_id: u32, event_id: u32) -> SimConnectResult<()>;
+
+    /// `priority` is one of the `SIMCONNECT_GROUP_PRIORITY_*` values
+    fn set_notification_group_priority(
+        &mut self,
+        group_id: u32,
+        priority: u32,
+    ) -> SimConnectResult<()>;
+
+    /// Maps a keyboard or joystick input to client events, creating the input group if needed.
+    /// `down_event_id` is sent with `down_value` when the input is pressed, and `up_event_id`
+    /// with `up_value` when it is released. Either can be `SIMCONNECT_UNUSED`.
+    #[allow(clippy::too_many_arguments)]
+    fn map_input_event_to_client_event(
+        &mut self,
+        group_id: u32,
+        input_definition: &CStr,
+        down_event_id: u32,
+        down_value: u32,
+        up_event_id: u32,
+        up_value: u32,
+        maskable: bool,
+    ) -> SimConnectResult<()>;
+
+    /// `priority` is one of the `SIMCONNECT_GROUP_PRIORITY_*` values
+    fn set_input_group_priority(&mut self, group_id: u32, priority: u32) -> SimConnectResult<()>;
+
+    fn remove_input_event(
+        &mut self,
+        group_id: u32,
+        input_definition: &CStr,
+    ) -> SimConnectResult<()>;
+
+    fn clear_input_group(&mut self, group_id: u32) -> SimConnectResult<()>;
+
+    /// `state` is `SIMCONNECT_STATE_ON` or `SIMCONNECT_STATE_OFF`. Input groups start off.
+    fn set_input_group_state(
+        &mut self,
+        group_id: u32,
+        state: bindings::SIMCONNECT_STATE,
+    ) -> SimConnectResult<()>;
+
+    /// Enumerates the input events of the loaded aircraft. MSFS only, like the rest of the
+    /// input event calls.
+    fn enumerate_input_events(&mut self, request_id: u32) -> SimConnectResult<()>;
+
+    fn get_input_event(&mut self, request_id: u32, hash: u64) -> SimConnectResult<()>;
+
+    /// `value` is an `f64` or a null-terminated string, depending on the type of the event
+    fn set_input_event(&mut self, hash: u64, value: &[u8]) -> SimConnectResult<()>;
+
+    fn subscribe_input_event(&mut self, hash: u64) -> SimConnectResult<()>;
+
+    fn unsubscribe_input_event(&mut self, hash: u64) -> SimConnectResult<()>;
+
+    fn enumerate_input_event_params(&mut self, hash: u64) -> SimConnectResult<()>;
+
+    /// Maps the name of a client data area to `client_data_id`. Every client sharing the area
+    /// maps its name, whichever of them creates it.
+    fn map_client_data_name_to_id(
+        &mut self,
+        client_data_name: &CStr,
+        client_data_id: u32,
+    ) -> SimConnectResult<()>;
+
+    /// Creates the client data area mapped to `client_data_id`, `size` bytes long. `flags` is
+    /// `SIMCONNECT_CREATE_CLIENT_DATA_FLAG_READ_ONLY` to keep other clients from writing to it.
+    fn create_client_data(
+        &mut self,
+        client_data_id: u32,
+        size: u32,
+        flags: bindings::SIMCONNECT_CREATE_CLIENT_DATA_FLAG,
+    ) -> SimConnectResult<()>;
+
+    /// `size_or_type` is one of the `SIMCONNECT_CLIENTDATATYPE_*` values, or a size in bytes
+    fn add_to_client_data_definition(
+        &mut self,
+        define_id: u32,
+        offset: u32,
+        size_or_type: u32,
+        epsilon: f32,
+        datum_id: u32,
+    ) -> SimConnectResult<()>;
+
+    fn clear_client_data_definition(&mut self, define_id: u32) -> SimConnectResult<()>;
+
+    /// Requests the data of a client data area every `period`. Sending
+    /// `SIMCONNECT_CLIENT_DATA_PERIOD_NEVER` for an existing `request_id` stops the request.
+    #[allow(clippy::too_many_arguments)]
+    fn request_client_data(
+        &mut self,
+        client_data_id: u32,
+        request_id: u32,
+        define_id: u32,
+        period: bindings::SIMCONNECT_CLIENT_DATA_PERIOD,
+        flags: bindings::SIMCONNECT_CLIENT_DATA_REQUEST_FLAG,
+        origin: u32,
+        interval: u32,
+        limit: u32,
+    ) -> SimConnectResult<()>;
+
+    /// `data` holds the datums of `define_id`, packed one after the other
+    fn set_client_data(
+        &mut self,
+        client_data_id: u32,
+        define_id: u32,
+        flags: bindings::SIMCONNECT_CLIENT_DATA_SET_FLAG,
+        data: &[u8],
+    ) -> SimConnectResult<()>;
+
+    /// Adds a field, or an `OPEN`/`CLOSE` entry nesting other records, to a facility
+    /// definition. MSFS only, like the rest of the facility calls.
+    fn add_to_facility_definition(
+        &mut self,
+        define_id: u32,
+        field_name: &CStr,
+    ) -> SimConnectResult<()>;
+
+    /// Requests the facility with `icao` using `define_id`. An empty `region` matches any.
+    fn request_facility_data(
+        &mut self,
+        define_id: u32,
+        request_id: u32,
+        icao: &CStr,
+        region: &CStr,
+    ) -> SimConnectResult<()>;
+
+    /// Lists the facilities of `list_type` in the simulator's cache, then every facility added
+    /// to it until unsubscribed. Only one subscription per list type is kept.
+    fn subscribe_to_facilities(
+        &mut self,
+        list_type: bindings::SIMCONNECT_FACILITY_LIST_TYPE,
+        request_id: u32,
+    ) -> SimConnectResult<()>;
+
+    fn unsubscribe_to_facilities(
+        &mut self,
+        list_type: bindings::SIMCONNECT_FACILITY_LIST_TYPE,
+    ) -> SimConnectResult<()>;
+
+    /// Lists the facilities of `list_type` in the simulator's cache
+    fn request_facilities_list(
+        &mut self,
+        list_type: bindings::SIMCONNECT_FACILITY_LIST_TYPE,
+        request_id: u32,
+    ) -> SimConnectResult<()>;
+
+    /// Creates an ATC controlled aircraft parked at `airport_id`. Answered with
+    /// `SIMCONNECT_RECV_ASSIGNED_OBJECT_ID`, like every `ai_create_*` call.
+    fn ai_create_parked_atc_aircraft(
+        &mut self,
+        container_title: &CStr,
+        tail_number: &CStr,
+        airport_id: &CStr,
+        request_id: u32,
+    ) -> SimConnectResult<()>;
+
+    /// Creates an ATC controlled aircraft flying the flight plan at `flight_plan_path`, a
+    /// `.PLN` file given without its extension. `flight_plan_position` is the leg it starts on,
+    /// with the fraction of the leg already flown.
+    #[allow(clippy::too_many_arguments)]
+    fn ai_create_enroute_atc_aircraft(
+        &mut self,
+        container_title: &CStr,
+        tail_number: &CStr,
+        flight_number: i32,
+        flight_plan_path: &CStr,
+        flight_plan_position: f64,
+        touch_and_go: bool,
+        request_id: u32,
+    ) -> SimConnectResult<()>;
+
+    fn ai_create_non_atc_aircraft(
+        &mut self,
+        container_title: &CStr,
+        tail_number: &CStr,
+        init_position: bindings::SIMCONNECT_DATA_INITPOSITION,
+        request_id: u32,
+    ) -> SimConnectResult<()>;
+
+    /// Creates an object which is not an aircraft, such as a boat, vehicle or animal
+    fn ai_create_simulated_object(
+        &mut self,
+        container_title: &CStr,
+        init_position: bindings::SIMCONNECT_DATA_INITPOSITION,
+        request_id: u32,
+    ) -> SimConnectResult<()>;
+
+    /// Stops the simulator's AI from controlling an object the client created, leaving it to
+    /// the client
+    fn ai_release_control(&mut self, object_id: u32, request_id: u32) -> SimConnectResult<()>;
+
+    /// Removes an object the client created
+    fn ai_remove_object(&mut self, object_id: u32, request_id: u32) -> SimConnectResult<()>;
+
+    fn ai_set_aircraft_flight_plan(
+        &mut self,
+        object_id: u32,
+        flight_plan_path: &CStr,
+        request_id: u32,
+    ) -> SimConnectResult<()>;
+
+    /// Loads the `.FLT` flight at `file_name`, given with or without its extension. The
+    /// simulator fires `FlightLoaded` once it is loaded.
+    fn flight_load(&mut self, file_name: &CStr) -> SimConnectResult<()>;
+
+    /// Saves the current flight to `file_name`. `flags` is reserved and should be 0. The
+    /// simulator fires `FlightSaved` once it is saved.
+    fn flight_save(
+        &mut self,
+        file_name: &CStr,
+        title: &CStr,
+        description: &CStr,
+        flags: u32,
+    ) -> SimConnectResult<()>;
+
+    /// Loads and activates the `.PLN` flight plan at `file_name`, given without its extension.
+    /// The simulator fires `FlightPlanActivated` once it is active.
+    fn flight_plan_load(&mut self, file_name: &CStr) -> SimConnectResult<()>;
+
+    fn subscribe_to_system_event(
+        &mut self,
+        event_id: u32,
+        event_name: &CStr,
+    ) -> SimConnectResult<()>;
+
+    fn unsubscribe_from_system_event(&mut self, event_id: u32) -> SimConnectResult<()>;
+
+    fn request_system_state(&mut self, request_id: u32, state: &CStr) -> SimConnectResult<()>;
+
+    /// The send id of the last packet sent to the simulator. Exceptions raised by the
+    /// simulator carry this id to identify the call which caused them.
+    fn last_sent_packet_id(&mut self) -> SimConnectResult<u32>;
+
+    /// Returns the next pending message, or `None` if there is nothing to dispatch.
+    ///
+    /// The returned pointer must stay valid until the next call to `get_next_dispatch`
+    /// or until the backend is closed.
+    fn get_next_dispatch(&mut self)
+        -> SimConnectResult<Option<NonNull<bindings::SIMCONNECT_RECV>>>;
+}
+
+/// Copies a received message into a `u64` buffer, so the data inside is as aligned as it is
+/// in the buffers handed out by `SimConnect.dll`
+pub(crate) fn aligned_copy(message: &[u8]) -> Vec<u64> {
+    let mut aligned = vec![0u64; message.len().div_ceil(8)];
+    unsafe {
+        std::ptr::copy_nonoverlapping(
+            message.as_ptr(),
+            aligned.as_mut_ptr() as *mut u8,
+            message.len(),
+        )
+    };
+    aligned
+}
+
+/// Names handed to SimConnect are ASCII, anything else is rejected the same way SimConnect
+/// rejects a bad argument
+fn to_str(value: &CStr) -> SimConnectResult<&str> {
+    value.to_str().map_err(|_| HResult::InvalidArg.into())
+}
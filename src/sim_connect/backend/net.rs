@@ -0,0 +1,1023 @@
+use sim_connect_data::{
+    error::{HResult, SimConnectError, SimConnectResult},
+    recv_data::{FromPtr, RecVOpen},
+};
+use sim_connect_sys::bindings;
+use std::{
+    collections::VecDeque,
+    ffi::CStr,
+    io::{Error as IoError, ErrorKind, Read, Write},
+    net::{SocketAddr, TcpStream, ToSocketAddrs},
+    path::Path,
+    ptr::NonNull,
+    time::{Duration, Instant},
+};
+
+use super::{aligned_copy, SimConnectBackend};
+
+/// Protocol spoken by this client. Version 4 (FSX SP2 / Acceleration) is understood by every
+/// SimConnect server from FSX SP2 up to and including MSFS.
+const PROTOCOL_VERSION: u32 = 4;
+const CLIENT_VERSION: [u32; 4] = [10, 0, 61259, 0];
+
+const SEND_HEADER_SIZE: usize = 16;
+const RECV_HEADER_SIZE: usize = 12;
+
+/// Sizes of the fixed length strings some packets carry, null terminator included
+const TAIL_NUMBER_SIZE: usize = 12;
+const AIRPORT_ID_SIZE: usize = 5;
+const MAX_PATH: usize = 260;
+const DESCRIPTION_SIZE: usize = 2048;
+
+/// Identifies a client to server packet. The value is or'ed into the `dwID` of the header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+enum SendId {
+    Open = 0x01,
+    MapClientEventToSimEvent = 0x04,
+    TransmitClientEvent = 0x05,
+    AddClientEventToNotificationGroup = 0x07,
+    RemoveClientEvent = 0x08,
+    SetNotificationGroupPriority = 0x09,
+    AddToDataDefinition = 0x0C,
+    RequestDataOnSimObject = 0x0E,
+    RequestDataOnSimObjectType = 0x0F,
+    SetDataOnSimObject = 0x10,
+    MapInputEventToClientEvent = 0x11,
+    SetInputGroupPriority = 0x12,
+    RemoveInputEvent = 0x13,
+    ClearInputGroup = 0x14,
+    SetInputGroupState = 0x15,
+    SubscribeToSystemEvent = 0x17,
+    UnsubscribeFromSystemEvent = 0x18,
+    AICreateParkedATCAircraft = 0x27,
+    AICreateEnrouteATCAircraft = 0x28,
+    AICreateNonATCAircraft = 0x29,
+    AICreateSimulatedObject = 0x2A,
+    AIReleaseControl = 0x2B,
+    AIRemoveObject = 0x2C,
+    AISetAircraftFlightPlan = 0x2D,
+    RequestSystemState = 0x35,
+    MapClientDataNameToID = 0x37,
+    CreateClientData = 0x38,
+    AddToClientDataDefinition = 0x39,
+    ClearClientDataDefinition = 0x3A,
+    RequestClientData = 0x3B,
+    SetClientData = 0x3C,
+    FlightLoad = 0x3D,
+    FlightSave = 0x3E,
+    FlightPlanLoad = 0x3F,
+    SubscribeToFacilities = 0x41,
+    UnsubscribeToFacilities = 0x42,
+    RequestFacilitiesList = 0x43,
+}
+
+/// Body of a client to server packet
+#[derive(Default)]
+struct SendPacket(Vec<u8>);
+
+impl SendPacket {
+    fn dword(mut self, value: u32) -> Self {
+        self.0.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    fn int(self, value: i32) -> Self {
+        self.dword(value as u32)
+    }
+
+    fn float(mut self, value: f32) -> Self {
+        self.0.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    fn bytes(mut self, value: &[u8]) -> Self {
+        self.0.extend_from_slice(value);
+        self
+    }
+
+    fn double(mut self, value: f64) -> Self {
+        self.0.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    /// Writes `value` as a fixed size, null padded, 256 byte string
+    fn string256(self, value: &CStr) -> Self {
+        self.string(value, 256)
+    }
+
+    /// Writes `value` as a fixed size, null padded string of `size` bytes
+    fn string(mut self, value: &CStr, size: usize) -> Self {
+        let bytes = value.to_bytes();
+        let len = bytes.len().min(size - 1);
+        self.0.extend_from_slice(&bytes[..len]);
+        self.0.resize(self.0.len() + size - len, 0);
+        self
+    }
+
+    fn init_position(self, position: bindings::SIMCONNECT_DATA_INITPOSITION) -> Self {
+        self.double(position.Latitude)
+            .double(position.Longitude)
+            .double(position.Altitude)
+            .double(position.Pitch)
+            .double(position.Bank)
+            .double(position.Heading)
+            .dword(position.OnGround)
+            .dword(position.Airspeed)
+    }
+}
+
+/// Talks to a simulator over TCP using the SimConnect network protocol, without needing
+/// `SimConnect.dll`. The simulator must be configured to accept remote clients through its
+/// `SimConnect.xml`.
+///
+/// # Example
+///
+/// ```no_run
+/// use sim_connect_rs::{backend::NetworkBackend, SimConnect};
+///
+/// let backend = NetworkBackend::new("192.168.1.20:500").unwrap();
+/// let sc = SimConnect::open_with_backend(backend, "Telemetry", None).unwrap();
+/// ```
+pub struct NetworkBackend {
+    addresses: Vec<SocketAddr>,
+    timeout: Duration,
+    stream: Option<TcpStream>,
+    send_id: u32,
+    inbox: Vec<u8>,
+    pending: VecDeque<Vec<u8>>,
+    current: Option<Vec<u64>>,
+    server_info: Option<RecVOpen>,
+}
+
+impl NetworkBackend {
+    /// Creates a backend which will connect to the simulator at `address` once opened
+    pub fn new(address: impl ToSocketAddrs) -> SimConnectResult<Self> {
+        let addresses: Vec<SocketAddr> = address.to_socket_addrs()?.collect();
+        if addresses.is_empty() {
+            return Err(IoError::new(ErrorKind::InvalidInput, "No address to connect to").into());
+        }
+
+        Ok(Self {
+            addresses,
+            timeout: Duration::from_secs(5),
+            stream: None,
+            send_id: 0,
+            inbox: Vec::new(),
+            pending: VecDeque::new(),
+            current: None,
+            server_info: None,
+        })
+    }
+
+    /// Creates a backend from the `[SimConnect]` (index 0) or `[SimConnect.<index>]` section of
+    /// a `SimConnect.cfg` file. Only the `IPv4` and `IPv6` protocols are supported.
+    pub fn from_config(path: impl AsRef<Path>, index: u32) -> SimConnectResult<Self> {
+        let config = std::fs::read_to_string(path)?;
+        Self::new(parse_config(&config, index)?)
+    }
+
+    /// How long to wait when connecting and for the simulator to answer the handshake.
+    /// Defaults to 5 seconds.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Information about the simulator, available once the connection has been opened
+    pub fn server_info(&self) -> Option<&RecVOpen> {
+        self.server_info.as_ref()
+    }
+
+    fn send(&mut self, send_id: SendId, packet: SendPacket) -> SimConnectResult<()> {
+        let stream = self.stream.as_mut().ok_or(SimConnectError::NotConnected)?;
+
+        self.send_id += 1;
+        let size = (SEND_HEADER_SIZE + packet.0.len()) as u32;
+
+        let mut buffer = Vec::with_capacity(size as usize);
+        buffer.extend_from_slice(&size.to_le_bytes());
+        buffer.extend_from_slice(&PROTOCOL_VERSION.to_le_bytes());
+        buffer.extend_from_slice(&(0xF000_0000 | send_id as u32).to_le_bytes());
+        buffer.extend_from_slice(&self.send_id.to_le_bytes());
+        buffer.extend_from_slice(&packet.0);
+
+        stream.write_all(&buffer)?;
+        Ok(())
+    }
+
+    /// Splits the next complete packet off the front of the inbox
+    fn take_packet(&mut self) -> SimConnectResult<Option<Vec<u8>>> {
+        if self.inbox.len() < 4 {
+            return Ok(None);
+        }
+
+        let size = read_dword(&self.inbox, 0) as usize;
+        if size < RECV_HEADER_SIZE {
+            return Err(IoError::new(
+                ErrorKind::InvalidData,
+                format!("Received a malformed packet of {size} bytes"),
+            )
+            .into());
+        }
+        if self.inbox.len() < size {
+            return Ok(None);
+        }
+
+        let rest = self.inbox.split_off(size);
+        Ok(Some(std::mem::replace(&mut self.inbox, rest)))
+    }
+
+    /// Reads whatever is available on the socket without blocking. Returns `false` if the
+    /// simulator has closed the connection.
+    fn fill_inbox(&mut self) -> SimConnectResult<bool> {
+        let stream = self.stream.as_mut().ok_or(SimConnectError::NotConnected)?;
+
+        stream.set_nonblocking(true)?;
+        let mut chunk = [0u8; 4096];
+        let result = loop {
+            match stream.read(&mut chunk) {
+                Ok(0) => break Ok(false),
+                Ok(read) => self.inbox.extend_from_slice(&chunk[..read]),
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break Ok(true),
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => break Err(err.into()),
+            }
+        };
+        stream.set_nonblocking(false)?;
+        result
+    }
+
+    /// Blocks until a full packet has been read or `deadline` has passed
+    fn read_packet(&mut self, deadline: Instant) -> SimConnectResult<Vec<u8>> {
+        loop {
+            if let Some(packet) = self.take_packet()? {
+                return Ok(packet);
+            }
+
+            let remaining = deadline
+                .checked_duration_since(Instant::now())
+                .filter(|remaining| !remaining.is_zero())
+                .ok_or_else(|| {
+                    IoError::new(
+                        ErrorKind::TimedOut,
+                        "Timed out waiting for the simulator to respond",
+                    )
+                })?;
+
+            let stream = self.stream.as_mut().ok_or(SimConnectError::NotConnected)?;
+            stream.set_read_timeout(Some(remaining))?;
+
+            let mut chunk = [0u8; 4096];
+            match stream.read(&mut chunk) {
+                Ok(0) => return Err(IoError::from(ErrorKind::ConnectionAborted).into()),
+                Ok(read) => self.inbox.extend_from_slice(&chunk[..read]),
+                Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    fn connect(&self) -> SimConnectResult<TcpStream> {
+        let mut last_error = None;
+        for address in &self.addresses {
+            match TcpStream::connect_timeout(address, self.timeout) {
+                Ok(stream) => return Ok(stream),
+                Err(err) => last_error = Some(err),
+            }
+        }
+        Err(last_error
+            .unwrap_or_else(|| IoError::new(ErrorKind::InvalidInput, "No address to connect to"))
+            .into())
+    }
+}
+
+impl SimConnectBackend for NetworkBackend {
+    fn open(&mut self, program_name: &CStr) -> SimConnectResult<()> {
+        let stream = self.connect()?;
+        stream.set_nodelay(true)?;
+        self.stream = Some(stream);
+
+        let [major, minor, build_major, build_minor] = CLIENT_VERSION;
+        self.send(
+            SendId::Open,
+            SendPacket::default()
+                .string256(program_name)
+                .dword(0)
+                .bytes(&[0, b'X', b'S', b'E'])
+                .dword(major)
+                .dword(minor)
+                .dword(build_major)
+                .dword(build_minor),
+        )?;
+
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let packet = self.read_packet(deadline)?;
+            let recv_id = read_dword(&packet, 8) as i32;
+
+            match recv_id {
+                bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_OPEN => {
+                    let mut aligned = aligned_copy(&packet);
+                    let pointer = NonNull::from(&mut aligned[0]).cast();
+                    self.server_info = Some(RecVOpen::from_pointer(pointer)?);
+                    self.pending.push_back(packet);
+                    return Ok(());
+                }
+                bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EXCEPTION => {
+                    self.stream = None;
+                    return Err(SimConnectError::Exception {
+                        exception: read_dword(&packet, 12).into(),
+                        send_id: read_dword(&packet, 16),
+                        index: read_dword(&packet, 20),
+                        call: Some("Open".to_owned()),
+                    });
+                }
+                _ => self.pending.push_back(packet),
+            }
+        }
+    }
+
+    fn close(&mut self) -> SimConnectResult<()> {
+        if let Some(stream) = self.stream.take() {
+            let _ = stream.shutdown(std::net::Shutdown::Both);
+        }
+        self.inbox.clear();
+        self.pending.clear();
+        Ok(())
+    }
+
+    fn add_to_data_definition(
+        &mut self,
+        define_id: u32,
+        datum_name: &CStr,
+        units_name: &CStr,
+        datum_type: bindings::SIMCONNECT_DATATYPE,
+        epsilon: f32,
+        datum_id: u32,
+    ) -> SimConnectResult<()> {
+        self.send(
+            SendId::AddToDataDefinition,
+            SendPacket::default()
+                .dword(define_id)
+                .string256(datum_name)
+                .string256(units_name)
+                .int(datum_type)
+                .float(epsilon)
+                .dword(datum_id),
+        )
+    }
+
+    fn request_data_on_sim_object(
+        &mut self,
+        request_id: u32,
+        define_id: u32,
+        object_id: u32,
+        period: bindings::SIMCONNECT_PERIOD,
+        flags: bindings::SIMCONNECT_DATA_REQUEST_FLAG,
+        origin: u32,
+        interval: u32,
+        limit: u32,
+    ) -> SimConnectResult<()> {
+        self.send(
+            SendId::RequestDataOnSimObject,
+            SendPacket::default()
+                .dword(request_id)
+                .dword(define_id)
+                .dword(object_id)
+                .int(period)
+                .dword(flags)
+                .dword(origin)
+                .dword(interval)
+                .dword(limit),
+        )
+    }
+
+    fn request_data_on_sim_object_type(
+        &mut self,
+        request_id: u32,
+        define_id: u32,
+        radius_meters: u32,
+        object_type: bindings::SIMCONNECT_SIMOBJECT_TYPE,
+    ) -> SimConnectResult<()> {
+        self.send(
+            SendId::RequestDataOnSimObjectType,
+            SendPacket::default()
+                .dword(request_id)
+                .dword(define_id)
+                .dword(radius_meters)
+                .int(object_type),
+        )
+    }
+
+    fn set_data_on_sim_object(
+        &mut self,
+        define_id: u32,
+        object_id: u32,
+        flags: u32,
+        array_count: u32,
+        unit_size: u32,
+        data: &[u8],
+    ) -> SimConnectResult<()> {
+        self.send(
+            SendId::SetDataOnSimObject,
+            SendPacket::default()
+                .dword(define_id)
+                .dword(object_id)
+                .dword(flags)
+                .dword(array_count.max(1))
+                .dword(unit_size)
+                .bytes(data),
+        )
+    }
+
+    fn map_client_event_to_sim_event(
+        &mut self,
+        event_id: u32,
+        event_name: &CStr,
+    ) -> SimConnectResult<()> {
+        self.send(
+            SendId::MapClientEventToSimEvent,
+            SendPacket::default().dword(event_id).string256(event_name),
+        )
+    }
+
+    fn transmit_client_event(
+        &mut self,
+        object_id: u32,
+        event_id: u32,
+        data: u32,
+        group_id: u32,
+        flags: bindings::SIMCONNECT_EVENT_FLAG,
+    ) -> SimConnectResult<()> {
+        self.send(
+            SendId::TransmitClientEvent,
+            SendPacket::default()
+                .dword(object_id)
+                .dword(event_id)
+                .dword(data)
+                .dword(group_id)
+                .dword(flags),
+        )
+    }
+
+    /// `TransmitClientEvent_EX1` was added in MSFS and has no packet in the protocol version
+    /// this backend speaks
+    fn transmit_client_event_ex1(
+        &mut self,
+        _object_id: u32,
+        _event_id: u32,
+        _group_id: u32,
+        _flags: bindings::SIMCONNECT_EVENT_FLAG,
+        _data: [u32; 5],
+    ) -> SimConnectResult<()> {
+        Err(HResult::NotImpl.into())
+    }
+
+    fn add_client_event_to_notification_group(
+        &mut self,
+        group_id: u32,
+        event_id: u32,
+        maskable: bool,
+    ) -> SimConnectResult<()> {
+        self.send(
+            SendId::AddClientEventToNotificationGroup,
+            SendPacket::default()
+                .dword(group_id)
+                .dword(event_id)
+                .dword(maskable as u32),
+        )
+    }
+
+    fn remove_client_event(&mut self, group_id: u32, event_id: u32) -> SimConnectResult<()> {
+        self.send(
+            SendId::RemoveClientEvent,
+            SendPacket::default().dword(group_id).dword(event_id),
+        )
+    }
+
+    fn set_notification_group_priority(
+        &mut self,
+        group_id: u32,
+        priority: u32,
+    ) -> SimConnectResult<()> {
+        self.send(
+            SendId::SetNotificationGroupPriority,
+            SendPacket::default().dword(group_id).dword(priority),
+        )
+    }
+
+    fn map_input_event_to_client_event(
+        &mut self,
+        group_id: u32,
+        input_definition: &CStr,
+        down_event_id: u32,
+        down_value: u32,
+        up_event_id: u32,
+        up_value: u32,
+        maskable: bool,
+    ) -> SimConnectResult<()> {
+        self.send(
+            SendId::MapInputEventToClientEvent,
+            SendPacket::default()
+                .dword(group_id)
+                .string256(input_definition)
+                .dword(down_event_id)
+                .dword(down_value)
+                .dword(up_event_id)
+                .dword(up_value)
+                .dword(maskable as u32),
+        )
+    }
+
+    fn set_input_group_priority(&mut self, group_id: u32, priority: u32) -> SimConnectResult<()> {
+        self.send(
+            SendId::SetInputGroupPriority,
+            SendPacket::default().dword(group_id).dword(priority),
+        )
+    }
+
+    fn remove_input_event(
+        &mut self,
+        group_id: u32,
+        input_definition: &CStr,
+    ) -> SimConnectResult<()> {
+        self.send(
+            SendId::RemoveInputEvent,
+            SendPacket::default()
+                .dword(group_id)
+                .string256(input_definition),
+        )
+    }
+
+    fn clear_input_group(&mut self, group_id: u32) -> SimConnectResult<()> {
+        self.send(
+            SendId::ClearInputGroup,
+            SendPacket::default().dword(group_id),
+        )
+    }
+
+    fn set_input_group_state(
+        &mut self,
+        group_id: u32,
+        state: bindings::SIMCONNECT_STATE,
+    ) -> SimConnectResult<()> {
+        self.send(
+            SendId::SetInputGroupState,
+            SendPacket::default().dword(group_id).dword(state as u32),
+        )
+    }
+
+    /// Input events were added in MSFS and have no packets in the protocol version this
+    /// backend speaks
+    fn enumerate_input_events(&mut self, _request_id: u32) -> SimConnectResult<()> {
+        Err(HResult::NotImpl.into())
+    }
+
+    fn get_input_event(&mut self, _request_id: u32, _hash: u64) -> SimConnectResult<()> {
+        Err(HResult::NotImpl.into())
+    }
+
+    fn set_input_event(&mut self, _hash: u64, _value: &[u8]) -> SimConnectResult<()> {
+        Err(HResult::NotImpl.into())
+    }
+
+    fn subscribe_input_event(&mut self, _hash: u64) -> SimConnectResult<()> {
+        Err(HResult::NotImpl.into())
+    }
+
+    fn unsubscribe_input_event(&mut self, _hash: u64) -> SimConnectResult<()> {
+        Err(HResult::NotImpl.into())
+    }
+
+    fn enumerate_input_event_params(&mut self, _hash: u64) -> SimConnectResult<()> {
+        Err(HResult::NotImpl.into())
+    }
+
+    fn map_client_data_name_to_id(
+        &mut self,
+        client_data_name: &CStr,
+        client_data_id: u32,
+    ) -> SimConnectResult<()> {
+        self.send(
+            SendId::MapClientDataNameToID,
+            SendPacket::default()
+                .string256(client_data_name)
+                .dword(client_data_id),
+        )
+    }
+
+    fn create_client_data(
+        &mut self,
+        client_data_id: u32,
+        size: u32,
+        flags: bindings::SIMCONNECT_CREATE_CLIENT_DATA_FLAG,
+    ) -> SimConnectResult<()> {
+        self.send(
+            SendId::CreateClientData,
+            SendPacket::default()
+                .dword(client_data_id)
+                .dword(size)
+                .dword(flags),
+        )
+    }
+
+    fn add_to_client_data_definition(
+        &mut self,
+        define_id: u32,
+        offset: u32,
+        size_or_type: u32,
+        epsilon: f32,
+        datum_id: u32,
+    ) -> SimConnectResult<()> {
+        self.send(
+            SendId::AddToClientDataDefinition,
+            SendPacket::default()
+                .dword(define_id)
+                .dword(offset)
+                .dword(size_or_type)
+                .float(epsilon)
+                .dword(datum_id),
+        )
+    }
+
+    fn clear_client_data_definition(&mut self, define_id: u32) -> SimConnectResult<()> {
+        self.send(
+            SendId::ClearClientDataDefinition,
+            SendPacket::default().dword(define_id),
+        )
+    }
+
+    fn request_client_data(
+        &mut self,
+        client_data_id: u32,
+        request_id: u32,
+        define_id: u32,
+        period: bindings::SIMCONNECT_CLIENT_DATA_PERIOD,
+        flags: bindings::SIMCONNECT_CLIENT_DATA_REQUEST_FLAG,
+        origin: u32,
+        interval: u32,
+        limit: u32,
+    ) -> SimConnectResult<()> {
+        self.send(
+            SendId::RequestClientData,
+            SendPacket::default()
+                .dword(client_data_id)
+                .dword(request_id)
+                .dword(define_id)
+                .int(period)
+                .dword(flags)
+                .dword(origin)
+                .dword(interval)
+                .dword(limit),
+        )
+    }
+
+    fn set_client_data(
+        &mut self,
+        client_data_id: u32,
+        define_id: u32,
+        flags: bindings::SIMCONNECT_CLIENT_DATA_SET_FLAG,
+        data: &[u8],
+    ) -> SimConnectResult<()> {
+        self.send(
+            SendId::SetClientData,
+            SendPacket::default()
+                .dword(client_data_id)
+                .dword(define_id)
+                .dword(flags)
+                .dword(0)
+                .dword(data.len() as u32)
+                .bytes(data),
+        )
+    }
+
+    /// Facility data was added in MSFS as well
+    fn add_to_facility_definition(
+        &mut self,
+        _define_id: u32,
+        _field_name: &CStr,
+    ) -> SimConnectResult<()> {
+        Err(HResult::NotImpl.into())
+    }
+
+    fn request_facility_data(
+        &mut self,
+        _define_id: u32,
+        _request_id: u32,
+        _icao: &CStr,
+        _region: &CStr,
+    ) -> SimConnectResult<()> {
+        Err(HResult::NotImpl.into())
+    }
+
+    fn subscribe_to_facilities(
+        &mut self,
+        list_type: bindings::SIMCONNECT_FACILITY_LIST_TYPE,
+        request_id: u32,
+    ) -> SimConnectResult<()> {
+        self.send(
+            SendId::SubscribeToFacilities,
+            SendPacket::default().int(list_type).dword(request_id),
+        )
+    }
+
+    fn unsubscribe_to_facilities(
+        &mut self,
+        list_type: bindings::SIMCONNECT_FACILITY_LIST_TYPE,
+    ) -> SimConnectResult<()> {
+        self.send(
+            SendId::UnsubscribeToFacilities,
+            SendPacket::default().int(list_type),
+        )
+    }
+
+    fn request_facilities_list(
+        &mut self,
+        list_type: bindings::SIMCONNECT_FACILITY_LIST_TYPE,
+        request_id: u32,
+    ) -> SimConnectResult<()> {
+        self.send(
+            SendId::RequestFacilitiesList,
+            SendPacket::default().int(list_type).dword(request_id),
+        )
+    }
+
+    fn ai_create_parked_atc_aircraft(
+        &mut self,
+        container_title: &CStr,
+        tail_number: &CStr,
+        airport_id: &CStr,
+        request_id: u32,
+    ) -> SimConnectResult<()> {
+        self.send(
+            SendId::AICreateParkedATCAircraft,
+            SendPacket::default()
+                .string256(container_title)
+                .string(tail_number, TAIL_NUMBER_SIZE)
+                .string(airport_id, AIRPORT_ID_SIZE)
+                .dword(request_id),
+        )
+    }
+
+    fn ai_create_enroute_atc_aircraft(
+        &mut self,
+        container_title: &CStr,
+        tail_number: &CStr,
+        flight_number: i32,
+        flight_plan_path: &CStr,
+        flight_plan_position: f64,
+        touch_and_go: bool,
+        request_id: u32,
+    ) -> SimConnectResult<()> {
+        self.send(
+            SendId::AICreateEnrouteATCAircraft,
+            SendPacket::default()
+                .string256(container_title)
+                .string(tail_number, TAIL_NUMBER_SIZE)
+                .int(flight_number)
+                .string(flight_plan_path, MAX_PATH)
+                .double(flight_plan_position)
+                .dword(touch_and_go as u32)
+                .dword(request_id),
+        )
+    }
+
+    fn ai_create_non_atc_aircraft(
+        &mut self,
+        container_title: &CStr,
+        tail_number: &CStr,
+        init_position: bindings::SIMCONNECT_DATA_INITPOSITION,
+        request_id: u32,
+    ) -> SimConnectResult<()> {
+        self.send(
+            SendId::AICreateNonATCAircraft,
+            SendPacket::default()
+                .string256(container_title)
+                .string(tail_number, TAIL_NUMBER_SIZE)
+                .init_position(init_position)
+                .dword(request_id),
+        )
+    }
+
+    fn ai_create_simulated_object(
+        &mut self,
+        container_title: &CStr,
+        init_position: bindings::SIMCONNECT_DATA_INITPOSITION,
+        request_id: u32,
+    ) -> SimConnectResult<()> {
+        self.send(
+            SendId::AICreateSimulatedObject,
+            SendPacket::default()
+                .string256(container_title)
+                .init_position(init_position)
+                .dword(request_id),
+        )
+    }
+
+    fn ai_release_control(&mut self, object_id: u32, request_id: u32) -> SimConnectResult<()> {
+        self.send(
+            SendId::AIReleaseControl,
+            SendPacket::default().dword(object_id).dword(request_id),
+        )
+    }
+
+    fn ai_remove_object(&mut self, object_id: u32, request_id: u32) -> SimConnectResult<()> {
+        self.send(
+            SendId::AIRemoveObject,
+            SendPacket::default().dword(object_id).dword(request_id),
+        )
+    }
+
+    fn ai_set_aircraft_flight_plan(
+        &mut self,
+        object_id: u32,
+        flight_plan_path: &CStr,
+        request_id: u32,
+    ) -> SimConnectResult<()> {
+        self.send(
+            SendId::AISetAircraftFlightPlan,
+            SendPacket::default()
+                .dword(object_id)
+                .string(flight_plan_path, MAX_PATH)
+                .dword(request_id),
+        )
+    }
+
+    fn flight_load(&mut self, file_name: &CStr) -> SimConnectResult<()> {
+        self.send(
+            SendId::FlightLoad,
+            SendPacket::default().string(file_name, MAX_PATH),
+        )
+    }
+
+    fn flight_save(
+        &mut self,
+        file_name: &CStr,
+        title: &CStr,
+        description: &CStr,
+        flags: u32,
+    ) -> SimConnectResult<()> {
+        self.send(
+            SendId::FlightSave,
+            SendPacket::default()
+                .string(file_name, MAX_PATH)
+                .string(title, MAX_PATH)
+                .string(description, DESCRIPTION_SIZE)
+                .dword(flags),
+        )
+    }
+
+    fn flight_plan_load(&mut self, file_name: &CStr) -> SimConnectResult<()> {
+        self.send(
+            SendId::FlightPlanLoad,
+            SendPacket::default().string(file_name, MAX_PATH),
+        )
+    }
+
+    fn subscribe_to_system_event(
+        &mut self,
+        event_id: u32,
+        event_name: &CStr,
+    ) -> SimConnectResult<()> {
+        self.send(
+            SendId::SubscribeToSystemEvent,
+            SendPacket::default().dword(event_id).string256(event_name),
+        )
+    }
+
+    fn unsubscribe_from_system_event(&mut self, event_id: u32) -> SimConnectResult<()> {
+        self.send(
+            SendId::UnsubscribeFromSystemEvent,
+            SendPacket::default().dword(event_id),
+        )
+    }
+
+    fn request_system_state(&mut self, request_id: u32, state: &CStr) -> SimConnectResult<()> {
+        self.send(
+            SendId::RequestSystemState,
+            SendPacket::default().dword(request_id).string256(state),
+        )
+    }
+
+    fn last_sent_packet_id(&mut self) -> SimConnectResult<u32> {
+        Ok(self.send_id)
+    }
+
+    fn get_next_dispatch(
+        &mut self,
+    ) -> SimConnectResult<Option<NonNull<bindings::SIMCONNECT_RECV>>> {
+        let packet = match self.pending.pop_front() {
+            Some(packet) => Some(packet),
+            None if self.stream.is_none() => None,
+            None => {
+                let connected = self.fill_inbox()?;
+                match self.take_packet()? {
+                    Some(packet) => Some(packet),
+                    None if connected => None,
+                    None => {
+                        // Let the client know the simulator is gone, the same way it would
+                        // be told if the simulator exited normally
+                        self.stream = None;
+                        let mut quit = Vec::with_capacity(RECV_HEADER_SIZE);
+                        quit.extend_from_slice(&(RECV_HEADER_SIZE as u32).to_le_bytes());
+                        quit.extend_from_slice(&PROTOCOL_VERSION.to_le_bytes());
+                        quit.extend_from_slice(
+                            &(bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_QUIT as u32)
+                                .to_le_bytes(),
+                        );
+                        Some(quit)
+                    }
+                }
+            }
+        };
+
+        self.current = packet.map(|packet| aligned_copy(&packet));
+        Ok(self.current.as_mut().and_then(|message| {
+            NonNull::new(message.as_mut_ptr() as *mut bindings::SIMCONNECT_RECV)
+        }))
+    }
+}
+
+/// Finds the address of the simulator in the contents of a `SimConnect.cfg` file
+fn parse_config(config: &str, index: u32) -> SimConnectResult<String> {
+    let mut in_section = false;
+    let mut protocol = None;
+    let mut address = None;
+    let mut port = None;
+
+    for line in config.lines() {
+        let line = line.split(';').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            let section = section.trim().to_lowercase();
+            in_section = match section.strip_prefix("simconnect") {
+                Some("") => index == 0,
+                Some(suffix) => {
+                    suffix
+                        .strip_prefix('.')
+                        .and_then(|n| n.trim().parse::<u32>().ok())
+                        == Some(index)
+                }
+                None => false,
+            };
+            continue;
+        }
+
+        if !in_section {
+            continue;
+        }
+
+        if let Some((key, value)) = line.split_once('=') {
+            let value = value.trim().to_owned();
+            match key.trim().to_lowercase().as_str() {
+                "protocol" => protocol = Some(value),
+                "address" => address = Some(value),
+                "port" => port = Some(value),
+                _ => {}
+            }
+        }
+    }
+
+    let protocol = protocol.unwrap_or_else(|| "IPv4".to_owned());
+    if !protocol.eq_ignore_ascii_case("ipv4") && !protocol.eq_ignore_ascii_case("ipv6") {
+        return Err(invalid_config(format!(
+            "Unsupported SimConnect protocol {protocol}, only IPv4 and IPv6 are supported"
+        )));
+    }
+
+    let address = address.ok_or_else(|| {
+        invalid_config(format!(
+            "No Address found for SimConnect config index {index}"
+        ))
+    })?;
+    let port: u16 = port
+        .ok_or_else(|| {
+            invalid_config(format!("No Port found for SimConnect config index {index}"))
+        })?
+        .parse()
+        .map_err(|_| invalid_config(format!("Invalid Port for SimConnect config index {index}")))?;
+
+    Ok(if address.contains(':') && !address.starts_with('[') {
+        format!("[{address}]:{port}")
+    } else {
+        format!("{address}:{port}")
+    })
+}
+
+/// Reads a little endian `DWORD` at `offset`, treating missing bytes as zero
+fn read_dword(packet: &[u8], offset: usize) -> u32 {
+    let mut bytes = [0u8; 4];
+    if let Some(slice) = packet.get(offset..offset + 4) {
+        bytes.copy_from_slice(slice);
+    }
+    u32::from_le_bytes(bytes)
+}
+
+fn invalid_config(message: String) -> SimConnectError {
+    IoError::new(ErrorKind::InvalidData, message).into()
+}
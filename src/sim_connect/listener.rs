@@ -0,0 +1,577 @@
+use super::{
+    backend::SimConnectBackend,
+    oneshot::Responder,
+    packet_log::{PacketLog, Waiter},
+    ClientEventCallback, InputEventValueCallback, SystemEventCallback,
+};
+use sim_connect_data::{
+    aircraft_input_events::InputEventValue,
+    error::{SimConnectError, SimConnectResult},
+    facilities::FacilityRecord,
+    recv_data::{
+        RecvDataEvent, RecvException, RecvFacilitiesList, RecvFacilityData, RecvInputEventList,
+        RecvSimData, RecvSystemState,
+    },
+    sim_events::{SystemEvent, SystemEventData},
+    sim_objects::SimObjectId,
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        mpsc::{Receiver, Sender},
+        Arc, Mutex, RwLock,
+    },
+    time::Duration,
+};
+
+/// Tells the listener where to deliver what the simulator sends. Registrations must be sent
+/// before the call they belong to, so that whatever the simulator answers with can be routed.
+pub(crate) enum Registration {
+    /// Registers (`Some`) or removes (`None`) the callback for a system event
+    Callback(SystemEvent, Option<SystemEventCallback>),
+    /// Registers (`Some`) or removes (`None`) the callback for a client event id
+    ClientCallback(u32, Option<ClientEventCallback>),
+    /// A `RequestDataOnSimObject*` call, answered by request id
+    Data(u32, Responder<RecvSimData>),
+    /// A data request answered in more than one message, such as a periodic
+    /// `RequestDataOnSimObject` call or a `RequestDataOnSimObjectType` call for many objects.
+    /// Answered by request id until the sink returns `false`.
+    Subscription(u32, DataSink),
+    /// Stops delivering to a data request, subscription, input event enumeration, facility
+    /// request or list, object creation or flight command
+    Unsubscribe(u32),
+    /// A `RequestSystemState` call. The request id is the requested state, so requests for the
+    /// same state are answered in the order they were made.
+    SystemState(u32, Responder<RecvSystemState>),
+    /// Withdraws the latest `SystemState` registration for the state, whose call failed
+    CancelSystemState(u32),
+    /// An `EnumerateInputEvents` call, answered by request id until the sink returns `false`
+    InputEventList(u32, InputEventListSink),
+    /// A `GetInputEvent` call, answered by request id
+    InputEventValue(u32, Responder<InputEventValue>),
+    /// An `EnumerateInputEventParams` call. Answers only carry the hash of the input event, so
+    /// requests for the same event are answered in the order they were made.
+    InputEventParams(u64, Responder<Vec<String>>),
+    /// Registers (`Some`) or removes (`None`) the callback for changes to an input event
+    InputEventCallback(u64, Option<InputEventValueCallback>),
+    /// A `RequestFacilityData` call with the request id, using the facility definition id.
+    /// Answered with every record sent for the request once `FACILITY_DATA_END` arrives.
+    FacilityData(u32, u32, Responder<Option<FacilityRecord>>),
+    /// A `RequestFacilitiesList` or `SubscribeToFacilities` call, answered by request id until
+    /// the sink returns `false`
+    FacilitiesList(u32, FacilitiesListSink),
+    /// An `AICreate*` call, answered by request id with the id of the created object
+    CreateObject(u32, Responder<SimObjectId>),
+    /// A `FlightLoad`, `FlightSave` or `FlightPlanLoad` call for a file, answered with the file
+    /// name of the `FlightLoaded`, `FlightSaved` or `FlightPlanActivated` event naming the same
+    /// file, and by request id with any exception
+    FlightFile(SystemEvent, FlightCommand),
+    /// Fails every flight command waiting on a system event the client unsubscribed from, as
+    /// the event naming its file will never arrive
+    CancelFlightFiles(SystemEvent),
+}
+
+/// Receives every answer to a subscription. Returns `false` once the subscription is over.
+pub(crate) type DataSink = Box<dyn FnMut(SimConnectResult<RecvSimData>) -> bool + Send>;
+
+/// Receives every message enumerating input events. Returns `false` once the last has arrived.
+pub(crate) type InputEventListSink =
+    Box<dyn FnMut(SimConnectResult<RecvInputEventList>) -> bool + Send>;
+
+/// Receives every message listing facilities. Returns `false` once no more are wanted.
+pub(crate) type FacilitiesListSink =
+    Box<dyn FnMut(SimConnectResult<RecvFacilitiesList>) -> bool + Send>;
+
+/// An exception raised by a data definition, kept so every request using the definition fails
+struct FailedDefinition {
+    exception: RecvException,
+    call: Option<String>,
+}
+
+impl FailedDefinition {
+    fn to_error(&self) -> SimConnectError {
+        SimConnectError::Exception {
+            exception: self.exception.exception,
+            send_id: self.exception.send_id,
+            index: self.exception.index,
+            call: self.call.clone(),
+        }
+    }
+}
+
+/// A facility request whose answer is still arriving
+struct FacilityRequest {
+    define_id: u32,
+    responder: Responder<Option<FacilityRecord>>,
+    records: Vec<RecvFacilityData>,
+}
+
+/// A flight command waiting on the system event naming its file
+pub(crate) struct FlightCommand {
+    pub request_id: u32,
+    pub file_name: String,
+    pub responder: Responder<String>,
+}
+
+/// Reads everything the simulator sends on a background thread, and hands it to whoever is
+/// waiting on it
+pub(crate) struct Listener<B: SimConnectBackend> {
+    backend: Arc<Mutex<B>>,
+    should_quit: Arc<RwLock<bool>>,
+    poll_interval: Duration,
+    registrations: Receiver<Registration>,
+    exception_sender: Sender<SimConnectError>,
+    packet_log: Arc<Mutex<PacketLog>>,
+    callbacks: HashMap<SystemEvent, SystemEventCallback>,
+    client_callbacks: HashMap<u32, ClientEventCallback>,
+    data_requests: HashMap<u32, Responder<RecvSimData>>,
+    subscriptions: HashMap<u32, DataSink>,
+    state_requests: HashMap<u32, VecDeque<Responder<RecvSystemState>>>,
+    failed_definitions: HashMap<u32, FailedDefinition>,
+    input_event_lists: HashMap<u32, InputEventListSink>,
+    input_event_values: HashMap<u32, Responder<InputEventValue>>,
+    input_event_params: HashMap<u64, VecDeque<Responder<Vec<String>>>>,
+    input_event_callbacks: HashMap<u64, InputEventValueCallback>,
+    facility_requests: HashMap<u32, FacilityRequest>,
+    facility_lists: HashMap<u32, FacilitiesListSink>,
+    object_creations: HashMap<u32, Responder<SimObjectId>>,
+    flight_files: HashMap<SystemEvent, VecDeque<FlightCommand>>,
+}
+
+impl<B: SimConnectBackend> Listener<B> {
+    pub fn new(
+        backend: Arc<Mutex<B>>,
+        should_quit: Arc<RwLock<bool>>,
+        poll_interval: Duration,
+        registrations: Receiver<Registration>,
+        exception_sender: Sender<SimConnectError>,
+        packet_log: Arc<Mutex<PacketLog>>,
+    ) -> Self {
+        Self {
+            backend,
+            should_quit,
+            poll_interval,
+            registrations,
+            exception_sender,
+            packet_log,
+            callbacks: HashMap::new(),
+            client_callbacks: HashMap::new(),
+            data_requests: HashMap::new(),
+            subscriptions: HashMap::new(),
+            state_requests: HashMap::new(),
+            failed_definitions: HashMap::new(),
+            input_event_lists: HashMap::new(),
+            input_event_values: HashMap::new(),
+            input_event_params: HashMap::new(),
+            input_event_callbacks: HashMap::new(),
+            facility_requests: HashMap::new(),
+            facility_lists: HashMap::new(),
+            object_creations: HashMap::new(),
+            flight_files: HashMap::new(),
+        }
+    }
+
+    pub fn run(mut self) -> SimConnectResult<()> {
+        loop {
+            {
+                let should_quit = self.should_quit.as_ref().read().unwrap();
+                if *should_quit {
+                    break;
+                }
+            }
+
+            // Get data and unlock ASAP
+            let next_dispatch = self
+                .backend
+                .lock()
+                .map_err(|_| SimConnectError::Poisoned)?
+                .get_next_dispatch()?;
+
+            // Registrations are sent before the call they belong to, so anything the simulator
+            // has answered with is guaranteed to be registered by now
+            while let Ok(registration) = self.registrations.try_recv() {
+                self.register(registration);
+            }
+
+            match next_dispatch.map(RecvDataEvent::from_pointer) {
+                Some(Ok(RecvDataEvent::Quit)) => break,
+                Some(Ok(data)) => self.dispatch(data)?,
+                // A message which can't be read is reported, but doesn't stop the answers to
+                // everything else
+                Some(Err(e)) => {
+                    let _ = self.exception_sender.send(e);
+                }
+                None => std::thread::sleep(self.poll_interval),
+            }
+        }
+        Ok(())
+    }
+
+    fn register(&mut self, registration: Registration) {
+        match registration {
+            Registration::Callback(event, Some(callback)) => {
+                self.callbacks.insert(event, callback);
+            }
+            Registration::Callback(event, None) => {
+                self.callbacks.remove(&event);
+            }
+            Registration::ClientCallback(event_id, Some(callback)) => {
+                self.client_callbacks.insert(event_id, callback);
+            }
+            Registration::ClientCallback(event_id, None) => {
+                self.client_callbacks.remove(&event_id);
+            }
+            Registration::Data(request_id, responder) => {
+                // Requests whose caller stopped waiting are only removed once answered, so
+                // clear out any the simulator never answered
+                self.data_requests
+                    .retain(|_, responder| !responder.is_closed());
+                self.data_requests.insert(request_id, responder);
+            }
+            Registration::Subscription(request_id, sink) => {
+                self.subscriptions.insert(request_id, sink);
+            }
+            Registration::Unsubscribe(request_id) => {
+                self.data_requests.remove(&request_id);
+                self.subscriptions.remove(&request_id);
+                self.input_event_lists.remove(&request_id);
+                self.facility_lists.remove(&request_id);
+                self.facility_requests.remove(&request_id);
+                self.object_creations.remove(&request_id);
+                for waiting in self.flight_files.values_mut() {
+                    waiting.retain(|command| command.request_id != request_id);
+                }
+            }
+            Registration::SystemState(request_id, responder) => {
+                self.state_requests
+                    .entry(request_id)
+                    .or_default()
+                    .push_back(responder);
+            }
+            Registration::CancelSystemState(request_id) => {
+                // Calls are made one at a time, so the failed call's registration is the
+                // latest for its state
+                if let Some(requests) = self.state_requests.get_mut(&request_id) {
+                    requests.pop_back();
+                }
+            }
+            Registration::InputEventList(request_id, sink) => {
+                self.input_event_lists.insert(request_id, sink);
+            }
+            Registration::InputEventValue(request_id, responder) => {
+                self.input_event_values
+                    .retain(|_, responder| !responder.is_closed());
+                self.input_event_values.insert(request_id, responder);
+            }
+            Registration::InputEventParams(hash, responder) => {
+                self.input_event_params
+                    .entry(hash)
+                    .or_default()
+                    .push_back(responder);
+            }
+            Registration::InputEventCallback(hash, Some(callback)) => {
+                self.input_event_callbacks.insert(hash, callback);
+            }
+            Registration::InputEventCallback(hash, None) => {
+                self.input_event_callbacks.remove(&hash);
+            }
+            Registration::FacilitiesList(request_id, sink) => {
+                self.facility_lists.insert(request_id, sink);
+            }
+            Registration::CreateObject(request_id, responder) => {
+                self.object_creations
+                    .retain(|_, responder| !responder.is_closed());
+                self.object_creations.insert(request_id, responder);
+            }
+            Registration::FlightFile(event, command) => {
+                // Commands are only answered by the event naming their file, so clear out any
+                // whose caller stopped waiting on an event that never came
+                let waiting = self.flight_files.entry(event).or_default();
+                waiting.retain(|command| !command.responder.is_closed());
+                waiting.push_back(command);
+            }
+            Registration::CancelFlightFiles(event) => {
+                for command in self.flight_files.remove(&event).unwrap_or_default() {
+                    let _ = command
+                        .responder
+                        .send(Err(SimConnectError::InvalidData(format!(
+                            "unsubscribed from {event} before the flight command completed"
+                        ))));
+                }
+            }
+            Registration::FacilityData(request_id, define_id, responder) => {
+                self.facility_requests.insert(
+                    request_id,
+                    FacilityRequest {
+                        define_id,
+                        responder,
+                        records: Vec::new(),
+                    },
+                );
+            }
+        }
+    }
+
+    fn dispatch(&mut self, data: RecvDataEvent) -> SimConnectResult<()> {
+        match data {
+            RecvDataEvent::SystemState(state) => {
+                let request_id: u32 = state.state_arg.into();
+                if let Some(responder) = self.next_state_request(request_id) {
+                    let _ = responder.send(Ok(state));
+                }
+            }
+            RecvDataEvent::Null => {}
+            RecvDataEvent::Open(_) => {}
+            RecvDataEvent::Data(data) => {
+                let request_id = data.get_request_id();
+                let answer = match self.failed_definitions.get(&data.get_id()) {
+                    Some(failed) => Err(failed.to_error()),
+                    None => Ok(data),
+                };
+                if let Some(responder) = self.data_requests.remove(&request_id) {
+                    let _ = responder.send(answer);
+                } else {
+                    self.feed_subscription(request_id, answer);
+                }
+            }
+            RecvDataEvent::Event(evt_type) => {
+                if let SystemEventData::FlightLoaded(file_name)
+                | SystemEventData::FlightSaved(file_name)
+                | SystemEventData::FlightPlanActivated(file_name) = &evt_type.event_data
+                {
+                    if let Some(command) = self.next_flight_file(evt_type.system_event, file_name) {
+                        let _ = command.responder.send(Ok(file_name.clone()));
+                    }
+                }
+                if let Some(callback) = self.callbacks.get(&evt_type.system_event) {
+                    callback.as_ref()(evt_type);
+                };
+            }
+            RecvDataEvent::ClientEvent(event) => {
+                if let Some(callback) = self.client_callbacks.get(&event.event_id) {
+                    callback.as_ref()(event);
+                }
+            }
+            RecvDataEvent::InputEventList(list) => {
+                self.feed_input_event_list(list.request_id, Ok(list));
+            }
+            RecvDataEvent::InputEventValue(answer) => {
+                if let Some(responder) = self.input_event_values.remove(&answer.request_id) {
+                    let _ = responder.send(Ok(answer.value));
+                }
+            }
+            RecvDataEvent::InputEventParams(answer) => {
+                if let Some(responder) = self.next_input_event_params(answer.hash) {
+                    let _ = responder.send(Ok(answer.params));
+                }
+            }
+            RecvDataEvent::InputEventChanged(changed) => {
+                if let Some(callback) = self.input_event_callbacks.get(&changed.hash) {
+                    callback.as_ref()(changed.value);
+                }
+            }
+            RecvDataEvent::FacilityData(record) => {
+                if let Some(request) = self.facility_requests.get_mut(&record.request_id) {
+                    request.records.push(record);
+                }
+            }
+            RecvDataEvent::FacilityDataEnd(end) => {
+                if let Some(request) = self.facility_requests.remove(&end.request_id) {
+                    let answer = match self.failed_definitions.get(&request.define_id) {
+                        Some(failed) => Err(failed.to_error()),
+                        None => Ok(FacilityRecord::assemble(request.records)),
+                    };
+                    let _ = request.responder.send(answer);
+                }
+            }
+            RecvDataEvent::FacilitiesList(list) => {
+                self.feed_facilities_list(list.request_id, Ok(list));
+            }
+            RecvDataEvent::AssignedObjectId(assigned) => {
+                if let Some(responder) = self.object_creations.remove(&assigned.request_id) {
+                    let _ = responder.send(Ok(assigned.object_id));
+                }
+            }
+            RecvDataEvent::Exception(exception) => self.raise(exception)?,
+            // Handled by `run`, as nothing more arrives after it
+            RecvDataEvent::Quit => {}
+        }
+        Ok(())
+    }
+
+    /// Hands an exception to whoever is waiting on the call which caused it. Anything nobody is
+    /// waiting on is kept for `SimConnect::take_exceptions`.
+    fn raise(&mut self, exception: RecvException) -> SimConnectResult<()> {
+        let (call, waiter) = match self
+            .packet_log
+            .lock()
+            .map_err(|_| SimConnectError::Poisoned)?
+            .find(exception.send_id)
+        {
+            Some(packet) => (Some(packet.call.clone()), packet.waiter),
+            None => (None, Waiter::None),
+        };
+        let error = SimConnectError::Exception {
+            exception: exception.exception,
+            send_id: exception.send_id,
+            index: exception.index,
+            call: call.clone(),
+        };
+
+        let undelivered = match waiter {
+            Waiter::Definition(define_id) => {
+                // The simulator still answers requests on a broken definition, so the error is
+                // handed out in place of those answers instead
+                self.failed_definitions
+                    .entry(define_id)
+                    .or_insert(FailedDefinition { exception, call });
+                None
+            }
+            Waiter::Data(request_id) => match self.data_requests.remove(&request_id) {
+                Some(responder) => responder.send(Err(error)).err().and_then(|e| e.err()),
+                None if self.subscriptions.contains_key(&request_id) => {
+                    self.feed_subscription(request_id, Err(error));
+                    None
+                }
+                None => Some(error),
+            },
+            Waiter::SystemState(request_id) => match self.next_state_request(request_id) {
+                Some(responder) => responder.send(Err(error)).err().and_then(|e| e.err()),
+                None => Some(error),
+            },
+            Waiter::InputEvent(request_id) => match self.input_event_values.remove(&request_id) {
+                Some(responder) => responder.send(Err(error)).err().and_then(|e| e.err()),
+                None if self.input_event_lists.contains_key(&request_id) => {
+                    self.feed_input_event_list(request_id, Err(error));
+                    None
+                }
+                None => Some(error),
+            },
+            Waiter::InputEventParams(hash) => match self.next_input_event_params(hash) {
+                Some(responder) => responder.send(Err(error)).err().and_then(|e| e.err()),
+                None => Some(error),
+            },
+            Waiter::FacilityData(request_id) => match self.facility_requests.remove(&request_id) {
+                Some(request) => request
+                    .responder
+                    .send(Err(error))
+                    .err()
+                    .and_then(|e| e.err()),
+                None => Some(error),
+            },
+            Waiter::FacilitiesList(request_id) if self.facility_lists.contains_key(&request_id) => {
+                self.feed_facilities_list(request_id, Err(error));
+                None
+            }
+            Waiter::FacilitiesList(_) => Some(error),
+            Waiter::CreateObject(request_id) => match self.object_creations.remove(&request_id) {
+                Some(responder) => responder.send(Err(error)).err().and_then(|e| e.err()),
+                None => Some(error),
+            },
+            Waiter::FlightFile(event, request_id) => {
+                match self.take_flight_command(event, request_id) {
+                    Some(command) => command
+                        .responder
+                        .send(Err(error))
+                        .err()
+                        .and_then(|e| e.err()),
+                    None => Some(error),
+                }
+            }
+            Waiter::None => Some(error),
+        };
+        if let Some(error) = undelivered {
+            let _ = self.exception_sender.send(error);
+        }
+        Ok(())
+    }
+
+    fn feed_subscription(&mut self, request_id: u32, answer: SimConnectResult<RecvSimData>) {
+        if let Some(sink) = self.subscriptions.get_mut(&request_id) {
+            if !sink(answer) {
+                self.subscriptions.remove(&request_id);
+            }
+        }
+    }
+
+    fn feed_input_event_list(
+        &mut self,
+        request_id: u32,
+        answer: SimConnectResult<RecvInputEventList>,
+    ) {
+        if let Some(sink) = self.input_event_lists.get_mut(&request_id) {
+            if !sink(answer) {
+                self.input_event_lists.remove(&request_id);
+            }
+        }
+    }
+
+    fn feed_facilities_list(
+        &mut self,
+        request_id: u32,
+        answer: SimConnectResult<RecvFacilitiesList>,
+    ) {
+        if let Some(sink) = self.facility_lists.get_mut(&request_id) {
+            if !sink(answer) {
+                self.facility_lists.remove(&request_id);
+            }
+        }
+    }
+
+    /// Every request for the same event is answered alike, so requests whose caller stopped
+    /// waiting are skipped rather than answered
+    fn next_input_event_params(&mut self, hash: u64) -> Option<Responder<Vec<String>>> {
+        let requests = self.input_event_params.get_mut(&hash)?;
+        std::iter::from_fn(|| requests.pop_front()).find(|responder| !responder.is_closed())
+    }
+
+    fn next_state_request(&mut self, request_id: u32) -> Option<Responder<RecvSystemState>> {
+        self.state_requests.get_mut(&request_id)?.pop_front()
+    }
+
+    /// The oldest flight command waiting on `event` for `file_name`. The simulator fires the
+    /// event for files loaded or saved through its menus as well, so commands are only ever
+    /// answered by an event naming their file. When the simulator reports the file by another
+    /// path than the command named it, the command is matched by the file's stem instead.
+    fn next_flight_file(&mut self, event: SystemEvent, file_name: &str) -> Option<FlightCommand> {
+        let waiting = self.flight_files.get_mut(&event)?;
+        let reported = flight_file_key(file_name);
+        let stem = flight_file_stem(&reported);
+        let index = waiting
+            .iter()
+            .position(|command| flight_file_key(&command.file_name) == reported)
+            .or_else(|| {
+                waiting.iter().position(|command| {
+                    flight_file_stem(&flight_file_key(&command.file_name)) == stem
+                })
+            })?;
+        waiting.remove(index)
+    }
+
+    fn take_flight_command(
+        &mut self,
+        event: SystemEvent,
+        request_id: u32,
+    ) -> Option<FlightCommand> {
+        let waiting = self.flight_files.get_mut(&event)?;
+        let index = waiting
+            .iter()
+            .position(|command| command.request_id == request_id)?;
+        waiting.remove(index)
+    }
+}
+
+/// Compares file names the way Windows does: without regard to case, separator or extension
+fn flight_file_key(file_name: &str) -> String {
+    let key = file_name.trim().to_lowercase().replace('\\', "/");
+    let stem_start = key.rfind('/').map_or(0, |slash| slash + 1);
+    match key[stem_start..].rfind('.') {
+        Some(dot) => key[..stem_start + dot].to_owned(),
+        None => key,
+    }
+}
+
+/// The file named by a key from `flight_file_key`, without the directories leading to it
+fn flight_file_stem(key: &str) -> &str {
+    key.rsplit('/').next().unwrap_or(key)
+}
@@ -1,17 +1,29 @@
 use self::sim_events::SystemEvent;
 pub use sim_connect_data::ToSimConnect;
 
-use anyhow::{anyhow, Result as AnyhowResult};
+use backend::{FfiBackend, SimConnectBackend};
 use sim_connect_data::{
-    recv_data::RecvSystemState, sim_event_args::SimStateArgs, sim_events::SystemEventDataHolder,
-    sim_input_events::InputEvent, SimConnectToStruct, StructToSimConnect,
+    aircraft_input_events::{InputEventCatalog, InputEventValue},
+    client_data::{ClientData, ClientDataArea},
+    data_definition::{DataDefinition, SimValue},
+    error::{HResult, SimConnectError, SimConnectResult},
+    facilities::{Facility, FacilityListEntry},
+    input_definition::InputDefinition,
+    recv_data::{RecvClientEvent, RecvSystemState},
+    sim_data::DataWriter,
+    sim_event_args::{GroupPriority, SimObjectType, SimStateArgs},
+    sim_events::SystemEventDataHolder,
+    sim_input_events::InputEvent,
+    sim_objects::{InitPosition, SimObjectId},
+    sim_var_types::SimVarType,
+    wasm_bridge::{BridgeCommand, BridgeMessage},
+    SimConnectToStruct, StructToSimConnect,
 };
 #[cfg(feature = "derive")]
 pub use sim_connect_macros;
 use std::{
-    collections::HashMap,
-    ffi::{c_void, CString},
-    ptr::NonNull,
+    collections::{HashMap, HashSet, VecDeque},
+    ffi::CString,
     sync::{
         mpsc::{channel, Receiver, Sender},
         Arc, Mutex, MutexGuard, RwLock,
@@ -22,199 +34,153 @@ use std::{
 
 use sim_connect_sys::bindings;
 
+pub mod backend;
+mod listener;
+mod oneshot;
+mod packet_log;
+mod queue;
+pub mod subscription;
+
+pub use sim_connect_data::aircraft_input_events;
+pub use sim_connect_data::client_data;
+pub use sim_connect_data::data_definition;
+pub use sim_connect_data::error;
+pub use sim_connect_data::facilities;
+pub use sim_connect_data::flight;
+pub use sim_connect_data::flight_plan;
+pub use sim_connect_data::input_definition;
 pub use sim_connect_data::recv_data;
+pub use sim_connect_data::sim_data;
 pub use sim_connect_data::sim_event_args;
 pub use sim_connect_data::sim_events;
+pub use sim_connect_data::sim_input_events;
+pub use sim_connect_data::sim_objects;
 pub use sim_connect_data::sim_units;
 pub use sim_connect_data::sim_var_types;
 pub use sim_connect_data::sim_vars;
+pub use sim_connect_data::wasm_bridge;
 
+use facilities::FacilityRecord;
+use listener::{DataSink, FlightCommand, Listener, Registration};
+use oneshot::{Answer, Responder};
+use packet_log::{PacketLog, Waiter};
+use queue::Feed;
 use recv_data::RecvSimData;
+use subscription::{
+    ClientDataPeriod, ClientDataRequest, ClientDataSubscription, DataParser, DataRequest,
+    DataSubscription, FacilityListMerger, FacilitySubscription, FacilityTracker, Unsubscribe,
+    ValuesSubscription,
+};
 
-use recv_data::RecvDataEvent;
+type SystemEventCallback = Box<dyn Fn(SystemEventDataHolder) + Send + Sync>;
+type ClientEventCallback = Box<dyn Fn(RecvClientEvent) + Send + Sync>;
+type InputEventValueCallback = Box<dyn Fn(InputEventValue) + Send + Sync>;
+/// Client event ids are allocated from here up, clear of the ids used for `SystemEvent`s
+const FIRST_CLIENT_EVENT_ID: u32 = 0x0001_0000;
+/// Client data definition ids are allocated from here up, clear of the ids `register_struct`
+/// uses for data definitions
+const FIRST_CLIENT_DATA_DEFINITION_ID: u32 = 0x0001_0000;
+/// Facility definition ids are allocated from here up, clear of both kinds of definition above
+const FIRST_FACILITY_DEFINITION_ID: u32 = 0x0002_0000;
+
+/// `(object_id, data)` for every object answering a data request
+type Snapshot<T> = Vec<(u32, <T as SimConnectToStruct>::ReturnType)>;
+/// `(object_id, values)` for every object answering a request for a `DataDefinition`
+type ValuesSnapshot = Vec<(u32, Vec<SimValue>)>;
+
+/// The private client event an input is mapped to for `SimConnect::map_input`, delivered to
+/// its callback through a notification group
+#[derive(Clone, Copy)]
+struct InputCallback {
+    notification_group: u32,
+    event_id: u32,
+}
 
-macro_rules! check_hr {
-    ($hr: expr) => {
-        let hr = $hr;
-        if hr != 0 {
-            return Err(anyhow!(format!(
-                "HRESULT indicates error: 0x{:x}",
-                hr as u32
-            )));
-        }
-    };
+/// The client side of the WASM bridge, set up by the first call which uses it
+struct WasmBridge {
+    /// The request id of the subscription to the default channel's responses, which is only
+    /// needed until the module has set up the client's channel
+    registration_request_id: u32,
+    registration_area: ClientDataArea<BridgeMessage>,
+    command: ClientDataArea<BridgeMessage>,
+    response: ClientDataArea<BridgeMessage>,
+    value_area_id: u32,
+    /// Set once the client's channel is set up and its responses are subscribed to
+    connected: bool,
+    /// The client data definition of each registered variable's slot, by the variable as
+    /// registered, e.g. `(L:MY_VAR)`
+    variables: HashMap<String, u32>,
+    responses: Arc<Mutex<BridgeResponses>>,
 }
 
-struct ThreadSafeHandle(Arc<Mutex<std::ptr::NonNull<c_void>>>);
+/// Shared with the listener, which answers pings as the module's responses arrive
+#[derive(Default)]
+struct BridgeResponses {
+    /// Calls waiting for the module to set up the client's channel
+    registrations: Vec<Responder<()>>,
+    /// Set once the module has set up the client's channel
+    registered: bool,
+    /// Pings waiting for their pong, oldest first
+    pings: VecDeque<Responder<()>>,
+    /// Set once the subscription to the responses failed, after which the bridge has to be set
+    /// up again
+    stopped: bool,
+    /// Why the subscription failed, if no call was waiting to be told
+    error: Option<SimConnectError>,
+}
 
-impl Clone for ThreadSafeHandle {
-    fn clone(&self) -> Self {
-        let cloned_arc = self.0.clone();
-        Self(cloned_arc)
+impl BridgeResponses {
+    /// Hands `error` to the oldest call waiting on the module, or keeps it for the next one.
+    /// Nothing will answer the other calls anymore.
+    fn fail(&mut self, error: SimConnectError) {
+        self.stopped = true;
+        let waiting = self
+            .registrations
+            .drain(..)
+            .chain(self.pings.drain(..))
+            .find(|responder| !responder.is_closed());
+        match waiting {
+            Some(responder) => {
+                let _ = responder.send(Err(error));
+            }
+            None => self.error = Some(error),
+        }
     }
 }
 
-unsafe impl Send for ThreadSafeHandle {}
-
-pub struct SimConnect {
-    handle: ThreadSafeHandle,
+pub struct SimConnect<B: SimConnectBackend = FfiBackend> {
+    backend: Arc<Mutex<B>>,
     type_map: HashMap<String, u32>,
+    /// The client data definition of each `ClientData` type, by type name, and of each slot of
+    /// the WASM bridge's value area
+    client_data_definitions: HashMap<String, u32>,
+    /// The id each client data area name is mapped to
+    client_data_ids: HashMap<String, u32>,
+    wasm_bridge: Option<WasmBridge>,
+    /// The facility definition of each `Facility` type, by type name
+    facility_definitions: HashMap<String, u32>,
+    client_events: HashMap<String, u32>,
+    next_client_event_id: u32,
+    /// The notification group of each priority, allocated the first time it is used
+    notification_groups: HashMap<GroupPriority, u32>,
+    next_notification_group_id: u32,
+    /// The notification group each input event subscribed to was added to
+    input_event_groups: HashMap<u32, u32>,
+    /// The inputs mapped to each input group, with the private client event of those mapped
+    /// to a callback
+    input_groups: HashMap<u32, Vec<(InputDefinition, Option<InputCallback>)>>,
+    /// The system events subscribed to with the simulator
+    system_events: HashSet<SystemEvent>,
     program_name: String,
-    data_event_map: HashMap<u32, Receiver<RecvSimData>>,
-    system_event_callback_sender: Sender<(
-        SystemEvent,
-        Option<Box<dyn Fn(SystemEventDataHolder) + Send + Sync>>,
-        bool,
-    )>,
-    state_request_reciever: Receiver<RecvSystemState>,
+    registration_sender: Sender<Registration>,
+    exception_receiver: Receiver<SimConnectError>,
+    packet_log: Arc<Mutex<PacketLog>>,
+    next_request_id: u32,
     should_quit: Arc<RwLock<bool>>,
-    listen_handle: Option<JoinHandle<AnyhowResult<()>>>,
-    sender_sender: Sender<(u32, Sender<RecvSimData>)>,
+    listen_handle: Option<JoinHandle<SimConnectResult<()>>>,
 }
 
-impl SimConnect {
-    fn get_handle_lock(&self) -> AnyhowResult<MutexGuard<NonNull<c_void>>> {
-        self.handle
-            .0
-            .lock()
-            .map_err(|_| anyhow!("SimConnect handle has been poisoned"))
-    }
-
-    fn get_client_data_name(&self, name: &str) -> String {
-        format!("{0}{name}", self.program_name)
-    }
-
-    fn begin_listen_for_events(
-        data_sender: Receiver<(u32, Sender<RecvSimData>)>,
-        callback_sender: Receiver<(
-            SystemEvent,
-            Option<Box<dyn Fn(SystemEventDataHolder) + Send + Sync>>,
-            bool,
-        )>,
-        state_sender: Sender<RecvSystemState>,
-        should_quit: Arc<RwLock<bool>>,
-        handle: ThreadSafeHandle,
-        poll_interval: Duration,
-    ) -> AnyhowResult<()> {
-        let mut should_wait: bool;
-        let mut data_map: HashMap<u32, Sender<RecvSimData>> = HashMap::new();
-        let mut callback_map: HashMap<SystemEvent, Box<dyn Fn(SystemEventDataHolder)>> =
-            HashMap::new();
-        loop {
-            for (data_id, sender) in data_sender.try_iter() {
-                data_map.insert(data_id, sender);
-            }
-            for (event_type, event_callback, insert) in callback_sender.try_iter() {
-                if insert {
-                    if let Some(callback) = event_callback {
-                        callback_map.insert(event_type, callback);
-                    }
-                } else {
-                    callback_map.remove(&event_type);
-                }
-            }
-            should_wait = true;
-            {
-                let should_quit = should_quit.as_ref().read().unwrap();
-                if *should_quit {
-                    break;
-                }
-            }
-
-            let mut data = std::ptr::null_mut();
-
-            let mut cb_data_size: bindings::DWORD = 0;
-            let hr: i32;
-            // Get data and unlock ASAP
-            {
-                let handle = handle
-                    .0
-                    .lock()
-                    .map_err(|_| anyhow!("SimConnect handle has been poisoned"))?;
-
-                hr = unsafe {
-                    bindings::SimConnect_GetNextDispatch(
-                        handle.as_ptr(),
-                        &mut data,
-                        &mut cb_data_size,
-                    )
-                };
-            }
-
-            if hr == 0 && cb_data_size > 0 {
-                let ptr = std::ptr::NonNull::new(data)
-                    .ok_or_else(|| anyhow!("Pointer not expected to be null"))?;
-                let data = recv_data::RecvDataEvent::from_pointer(ptr)?;
-
-                match data {
-                    RecvDataEvent::SystemState(state) => {
-                        state_sender.send(state)?;
-                    }
-                    RecvDataEvent::Null => {}
-                    RecvDataEvent::Open(_) => {}
-                    RecvDataEvent::Data(data) => {
-                        let data_id = data.get_id();
-                        let sender = data_map.get(&data_id);
-                        if let Some(sender) = sender {
-                            sender
-                                .send(data)
-                                .expect("Unable to send data across threads.");
-                        }
-                    }
-                    RecvDataEvent::Event(evt_type) => {
-                        if let Some(callback) = callback_map.get(&evt_type.system_event) {
-                            callback.as_ref()(evt_type);
-                        };
-                    }
-                    RecvDataEvent::Quit => {}
-                }
-
-                should_wait = false;
-            }
-
-            if should_wait {
-                std::thread::sleep(poll_interval);
-            }
-        }
-        Ok(())
-    }
-
-    fn get_struct_name<T: StructToSimConnect>(&self) -> String {
-        let struct_name = std::any::type_name::<T>();
-        self.get_client_data_name(struct_name)
-    }
-
-    /// Can ONLY be called after a call to `register_struct` has been called.
-    /// Data will be found in the `check_events` function call.
-    ///
-    /// This function will return an error if the struct has not yet been registered
-    /// with SimConnect
-    fn request_data_on_self_object<T: StructToSimConnect>(&self) -> AnyhowResult<()> {
-        let type_name = std::any::type_name::<T>();
-        let data_name = self.get_client_data_name(type_name);
-        let object_id = self
-            .type_map
-            .get(&data_name)
-            .ok_or_else(|| anyhow!("{type_name} has not yet been registered"))?;
-
-        {
-            let handle_lock = self.get_handle_lock()?;
-            let handle = *handle_lock;
-
-            check_hr!(unsafe {
-                bindings::SimConnect_RequestDataOnSimObjectType(
-                    handle.as_ptr(),
-                    0,
-                    *object_id,
-                    0,
-                    bindings::SIMCONNECT_SIMOBJECT_TYPE_SIMCONNECT_SIMOBJECT_TYPE_USER,
-                )
-            });
-        }
-
-        Ok(())
-    }
-
+impl SimConnect<FfiBackend> {
     /// Opens a new connection to SimConnect using the program name defined
     /// Program will then automatically start listening for events, caching the latest
     /// of all the unique events. Events can be retrieved by requesting the latest.
@@ -230,347 +196,3311 @@ impl SimConnect {
     ///
     /// # Example
     ///
-    /// ```
+    /// ```no_run
+    /// use sim_connect_rs::SimConnect;
+    /// use std::time::Duration;
+    ///
     /// // This will poll MSFS SimConnect every .5 seconds for data
     /// let sc = SimConnect::open("My Awesome Application", Some(Duration::from_millis(500)));
     /// ```
-    pub fn open(program_name: &str, mut poll_interval: Option<Duration>) -> AnyhowResult<Self> {
-        if poll_interval.is_none() {
-            poll_interval = Some(Duration::from_secs(1));
-        }
-        let mut handle = std::ptr::null_mut() as bindings::HANDLE;
-        let program_name = CString::new(program_name)?;
-
-        check_hr!(unsafe {
-            bindings::SimConnect_Open(
-                &mut handle,
-                program_name.as_ptr(),
-                std::ptr::null_mut(),
-                0,
-                std::ptr::null_mut(),
-                0,
-            )
-        });
+    pub fn open(program_name: &str, poll_interval: Option<Duration>) -> SimConnectResult<Self> {
+        Self::open_with_backend(FfiBackend::new(), program_name, poll_interval)
+    }
+}
 
-        let handle = ThreadSafeHandle(Arc::new(Mutex::new(
-            std::ptr::NonNull::new(handle)
-                .ok_or_else(|| anyhow!("pointer expected to not be null"))?,
-        )));
+impl<B: SimConnectBackend> SimConnect<B> {
+    fn get_backend_lock(&self) -> SimConnectResult<MutexGuard<'_, B>> {
+        self.backend.lock().map_err(|_| SimConnectError::Poisoned)
+    }
 
-        let should_quit = Arc::new(RwLock::new(false));
+    /// Records the packet `backend` just sent, so an exception raised for it can be traced back
+    /// to `call` and handed to `waiter`. Must be called before the backend lock is released,
+    /// otherwise the listener could see the exception first.
+    fn record_sent(&self, backend: &mut B, call: String, waiter: Waiter) -> SimConnectResult<()> {
+        let send_id = backend.last_sent_packet_id()?;
+        self.packet_log
+            .lock()
+            .map_err(|_| SimConnectError::Poisoned)?
+            .record(send_id, call, waiter);
+        Ok(())
+    }
 
-        let cloned_should_quit = should_quit.clone();
-        let cloned_handle = handle.clone();
-
-        let (sx, rc) = channel();
-        let (evt_sx, evt_rcv) = channel();
-        let (state_sx, state_rcv) = channel();
-
-        let listen_handle: JoinHandle<AnyhowResult<()>> = thread::spawn(move || {
-            let should_quit = cloned_should_quit;
-            let handle = cloned_handle;
-
-            Self::begin_listen_for_events(
-                rc,
-                evt_rcv,
-                state_sx,
-                should_quit,
-                handle,
-                poll_interval.unwrap(),
-            )?;
-            Ok(())
-        });
+    fn register(&self, registration: Registration) -> SimConnectResult<()> {
+        self.registration_sender
+            .send(registration)
+            .map_err(|_| SimConnectError::ListenerStopped)
+    }
 
-        Ok(Self {
-            handle,
-            type_map: HashMap::new(),
-            program_name: program_name.to_str().unwrap().to_owned(),
-            data_event_map: HashMap::new(),
-            system_event_callback_sender: evt_sx,
-            state_request_reciever: state_rcv,
-            should_quit,
-            listen_handle: Some(listen_handle),
-            sender_sender: sx,
-        })
+    fn get_client_data_name(&self, name: &str) -> String {
+        format!("{0}{name}", self.program_name)
     }
 
-    /// Registers the struct's field definitions with SimConnect
-    pub fn register_struct<T: StructToSimConnect>(&mut self) -> AnyhowResult<()> {
-        let data_name = self.get_struct_name::<T>();
+    fn get_struct_name<T: StructToSimConnect>(&self) -> String {
+        let struct_name = std::any::type_name::<T>();
+        self.get_client_data_name(struct_name)
+    }
 
-        let new_data_id = self.type_map.len() as u32;
+    /// Definitions with the same datums share a name, and so are only registered once
+    fn get_definition_name(&self, definition: &DataDefinition) -> String {
+        self.get_client_data_name(&definition.to_string())
+    }
 
-        if self.type_map.contains_key(&data_name) {
-            return Ok(());
-        }
+    fn new_request_id(&mut self) -> u32 {
+        let request_id = self.next_request_id;
+        self.next_request_id = self.next_request_id.wrapping_add(1);
+        request_id
+    }
 
-        let fields = T::get_fields();
+    fn new_client_event_id(&mut self) -> u32 {
+        let event_id = self.next_client_event_id;
+        self.next_client_event_id += 1;
+        event_id
+    }
 
-        {
-            let handle_lock = self.get_handle_lock()?;
-            let handle = *handle_lock;
-
-            for field in fields {
-                // let sim_unit = field
-                //     .sim_unit
-                //     .map(|unit| unsafe { *unit.sc_string().as_ptr() as *const i8 })
-                //     .unwrap_or(std::ptr::null());
-
-                check_hr!(unsafe {
-                    bindings::SimConnect_AddToDataDefinition(
-                        handle.as_ptr(),
-                        new_data_id,
-                        field.sim_var.sc_string().as_ptr(),
-                        field
-                            .sim_unit
-                            .map(|unit| unit.sc_string())
-                            .or(Some(CString::new("").unwrap()))
-                            .unwrap()
-                            .as_ptr(),
-                        field.data_type as i32,
-                        0.0,
-                        field.id,
-                    )
-                });
-            }
+    /// The notification group events are added to at `priority`, allocated the first time the
+    /// priority is used
+    fn notification_group(&mut self, priority: GroupPriority) -> u32 {
+        if let Some(group_id) = self.notification_groups.get(&priority) {
+            return *group_id;
         }
+        let group_id = self.next_notification_group_id;
+        self.next_notification_group_id += 1;
+        self.notification_groups.insert(priority, group_id);
+        group_id
+    }
 
-        self.type_map.insert(data_name, new_data_id);
+    /// Can ONLY be called after a call to `register_struct` has been called.
+    /// The data is delivered through the returned `Answer`.
+    ///
+    /// This function will return an error if the struct has not yet been registered
+    /// with SimConnect
+    fn request_data_on_self_object<T: StructToSimConnect>(
+        &mut self,
+    ) -> SimConnectResult<Answer<RecvSimData>> {
+        let type_name = std::any::type_name::<T>();
+        let data_name = self.get_client_data_name(type_name);
+        let object_id = *self
+            .type_map
+            .get(&data_name)
+            .ok_or_else(|| SimConnectError::Unregistered(type_name.to_owned()))?;
 
-        let (sx, rc) = channel();
+        let request_id = self.new_request_id();
+        let (responder, answer) = oneshot::channel();
+        self.register(Registration::Data(request_id, responder))?;
 
-        self.sender_sender
-            .send((new_data_id, sx))
-            .expect("Program encountered a fatal error sending data across threads");
-        self.data_event_map.insert(new_data_id, rc);
-        Ok(())
+        let requested = self.get_backend_lock().and_then(|mut backend| {
+            backend.request_data_on_sim_object_type(
+                request_id,
+                object_id,
+                0,
+                bindings::SIMCONNECT_SIMOBJECT_TYPE_SIMCONNECT_SIMOBJECT_TYPE_USER,
+            )?;
+            self.record_sent(
+                &mut backend,
+                format!("RequestDataOnSimObjectType for {type_name}"),
+                Waiter::Data(request_id),
+            )
+        });
+        if let Err(e) = requested {
+            let _ = self.register(Registration::Unsubscribe(request_id));
+            return Err(e);
+        }
+        Ok(answer)
     }
 
-    /* #region request_system_state */
-    #[cfg(feature = "async")]
-    /// Requests current information about the system state.
-    pub async fn request_system_state(
+    /// Sends a data request with `send`, which is handed the request and definition ids, and
+    /// collects every object the simulator answers with into one snapshot of
+    /// `(object_id, data)` pairs, delivered through the returned `Answer`
+    fn send_snapshot_request<T>(
         &mut self,
-        state_request: SimStateArgs,
-    ) -> AnyhowResult<RecvSystemState> {
-        {
-            let handle = self.get_handle_lock()?;
-            let request_id: u32 = state_request.into();
+        call: String,
+        send: impl FnOnce(&mut B, u32, u32) -> SimConnectResult<()>,
+    ) -> SimConnectResult<Answer<Snapshot<T>>>
+    where
+        T: SimConnectToStruct + 'static,
+        T::ReturnType: Send + 'static,
+    {
+        self.register_struct::<T>()?;
+        let define_id = self.define_id::<T>()?;
+        let mut parser = DataParser::<T>::new(false);
+        self.send_parsed_snapshot_request(call, define_id, move |data| parser.parse(data), send)
+    }
 
-            check_hr!(unsafe {
-                bindings::SimConnect_RequestSystemState(
-                    handle.as_ptr(),
-                    request_id,
-                    state_request.sc_string().as_ptr(),
-                )
-            });
-        }
+    /// Like `send_snapshot_request`, for the definition `define_id` whose data is read with
+    /// `parse`
+    fn send_parsed_snapshot_request<V: Send + 'static>(
+        &mut self,
+        call: String,
+        define_id: u32,
+        mut parse: impl FnMut(&RecvSimData) -> SimConnectResult<V> + Send + 'static,
+        send: impl FnOnce(&mut B, u32, u32) -> SimConnectResult<()>,
+    ) -> SimConnectResult<Answer<Vec<(u32, V)>>> {
+        let request_id = self.new_request_id();
+
+        let (responder, answer) = oneshot::channel();
+        let mut responder = Some(responder);
+        let mut snapshot = Vec::new();
+        self.register(Registration::Subscription(
+            request_id,
+            Box::new(move |answer| {
+                // Objects are sent one message each, numbered from 1 up to `out_of`
+                let complete = answer.and_then(|data| {
+                    if data.get_out_of() > 0 {
+                        snapshot.push((data.get_object_id(), parse(&data)?));
+                    }
+                    Ok(data.get_entry_number() >= data.get_out_of())
+                });
+                let answer = match complete {
+                    Ok(false) => return true,
+                    Ok(true) => Ok(std::mem::take(&mut snapshot)),
+                    Err(e) => Err(e),
+                };
+                if let Some(responder) = responder.take() {
+                    let _ = responder.send(answer);
+                }
+                false
+            }),
+        ))?;
 
-        let possible_found = self.state_request_reciever.try_iter().last();
-        if let None = possible_found {
-            let data = self.state_request_reciever.recv().or_else(|_| {
-                Err(anyhow!(
-                    "Thread has been closed and channel no longer available"
-                ))
-            })?;
-            return Ok(data);
+        let requested = self.get_backend_lock().and_then(|mut backend| {
+            send(&mut backend, request_id, define_id)?;
+            self.record_sent(&mut backend, call, Waiter::Data(request_id))
+        });
+        if let Err(e) = requested {
+            let _ = self.register(Registration::Unsubscribe(request_id));
+            return Err(e);
         }
-
-        Ok(possible_found.unwrap())
+        Ok(answer)
     }
 
-    #[cfg(not(feature = "async"))]
-    /// Requests current information about the system state.
-    pub fn request_system_state(
+    fn send_data_by_type_request<T>(
         &mut self,
-        state_request: SimStateArgs,
-    ) -> AnyhowResult<RecvSystemState> {
-        {
-            let handle = self.get_handle_lock()?;
-            let request_id: u32 = state_request.into();
-
-            check_hr!(unsafe {
-                bindings::SimConnect_RequestSystemState(
-                    handle.as_ptr(),
+        object_type: SimObjectType,
+        radius_meters: u32,
+    ) -> SimConnectResult<Answer<Snapshot<T>>>
+    where
+        T: SimConnectToStruct + 'static,
+        T::ReturnType: Send + 'static,
+    {
+        self.send_snapshot_request::<T>(
+            format!(
+                "RequestDataOnSimObjectType({object_type:?}, {radius_meters}m) for {}",
+                std::any::type_name::<T>()
+            ),
+            |backend, request_id, define_id| {
+                backend.request_data_on_sim_object_type(
                     request_id,
-                    state_request.sc_string().as_ptr(),
+                    define_id,
+                    radius_meters,
+                    object_type as bindings::SIMCONNECT_SIMOBJECT_TYPE,
                 )
-            });
-        }
+            },
+        )
+    }
 
-        let possible_found = self.state_request_reciever.try_iter().last();
-        if possible_found.is_none() {
-            let data = self
-                .state_request_reciever
-                .recv()
-                .map_err(|_| anyhow!("Thread has been closed and channel no longer available"))?;
-            return Ok(data);
-        }
+    fn send_data_on_object_request<T>(
+        &mut self,
+        object_id: u32,
+    ) -> SimConnectResult<Answer<Snapshot<T>>>
+    where
+        T: SimConnectToStruct + 'static,
+        T::ReturnType: Send + 'static,
+    {
+        self.send_snapshot_request::<T>(
+            format!(
+                "RequestDataOnSimObject({object_id}) for {}",
+                std::any::type_name::<T>()
+            ),
+            |backend, request_id, define_id| {
+                backend.request_data_on_sim_object(
+                    request_id,
+                    define_id,
+                    object_id,
+                    bindings::SIMCONNECT_PERIOD_SIMCONNECT_PERIOD_ONCE,
+                    bindings::SIMCONNECT_DATA_REQUEST_FLAG_DEFAULT,
+                    0,
+                    0,
+                    0,
+                )
+            },
+        )
+    }
 
-        Ok(possible_found.unwrap())
+    /// Like `send_snapshot_request`, for a definition put together at runtime
+    fn send_values_snapshot_request(
+        &mut self,
+        definition: &DataDefinition,
+        call: String,
+        send: impl FnOnce(&mut B, u32, u32) -> SimConnectResult<()>,
+    ) -> SimConnectResult<Answer<ValuesSnapshot>> {
+        let define_id = self.definition_id(definition)?;
+        let parser = definition.clone();
+        self.send_parsed_snapshot_request(
+            format!("{call} for {definition}"),
+            define_id,
+            move |data| parser.read(data),
+            send,
+        )
     }
 
-    /* #endregion */
+    fn send_values_by_type_request(
+        &mut self,
+        definition: &DataDefinition,
+        object_type: SimObjectType,
+        radius_meters: u32,
+    ) -> SimConnectResult<Answer<ValuesSnapshot>> {
+        self.send_values_snapshot_request(
+            definition,
+            format!("RequestDataOnSimObjectType({object_type:?}, {radius_meters}m)"),
+            |backend, request_id, define_id| {
+                backend.request_data_on_sim_object_type(
+                    request_id,
+                    define_id,
+                    radius_meters,
+                    object_type as bindings::SIMCONNECT_SIMOBJECT_TYPE,
+                )
+            },
+        )
+    }
 
-    /* #region system_event */
-    /// Requests a subscription to a system event. Events can be checked by using the
-    /// `check_events` function
-    pub fn subscribe_to_system_event(
+    fn send_values_on_object_request(
         &mut self,
-        event: SystemEvent,
-        callback: impl Fn(SystemEventDataHolder) + Send + Sync + 'static,
-    ) -> AnyhowResult<()> {
-        let event_id: u32 = event.into();
+        definition: &DataDefinition,
+        object_id: u32,
+    ) -> SimConnectResult<Answer<ValuesSnapshot>> {
+        self.send_values_snapshot_request(
+            definition,
+            format!("RequestDataOnSimObject({object_id})"),
+            |backend, request_id, define_id| {
+                backend.request_data_on_sim_object(
+                    request_id,
+                    define_id,
+                    object_id,
+                    bindings::SIMCONNECT_PERIOD_SIMCONNECT_PERIOD_ONCE,
+                    bindings::SIMCONNECT_DATA_REQUEST_FLAG_DEFAULT,
+                    0,
+                    0,
+                    0,
+                )
+            },
+        )
+    }
 
-        {
-            let handle = self.get_handle_lock()?;
+    /// Registers `definition` if it hasn't been already, returning its definition id
+    fn definition_id(&mut self, definition: &DataDefinition) -> SimConnectResult<u32> {
+        self.register_definition(definition)?;
+        self.type_map
+            .get(&self.get_definition_name(definition))
+            .copied()
+            .ok_or_else(|| SimConnectError::Unregistered(definition.to_string()))
+    }
 
-            check_hr!(unsafe {
-                bindings::SimConnect_SubscribeToSystemEvent(
-                    handle.as_ptr(),
-                    event_id,
-                    event.sc_string().as_ptr(),
-                )
-            });
+    /// Adds the fields of `T` to a client data definition the first time it is used, returning
+    /// the definition id
+    fn client_data_definition<T: ClientData>(&mut self) -> SimConnectResult<u32> {
+        let type_name = std::any::type_name::<T>();
+        if let Some(define_id) = self.client_data_definitions.get(type_name) {
+            return Ok(*define_id);
         }
+        let define_id = FIRST_CLIENT_DATA_DEFINITION_ID + self.client_data_definitions.len() as u32;
 
-        self.system_event_callback_sender
-            .send((event, Some(Box::new(callback)), true))
-            .map_err(|_| anyhow!("Unable to send function to the listener"))?;
+        {
+            let mut backend = self.get_backend_lock()?;
+            for (datum_id, field) in T::fields().into_iter().enumerate() {
+                backend.add_to_client_data_definition(
+                    define_id,
+                    field.offset,
+                    field.size_or_type,
+                    field.epsilon,
+                    datum_id as u32,
+                )?;
+                self.record_sent(
+                    &mut backend,
+                    format!(
+                        "AddToClientDataDefinition({}, {}) for field {datum_id} of {type_name}",
+                        field.offset, field.size_or_type
+                    ),
+                    Waiter::Definition(define_id),
+                )?;
+            }
+        }
 
-        Ok(())
+        self.client_data_definitions
+            .insert(type_name.to_owned(), define_id);
+        Ok(define_id)
     }
 
-    pub fn unsubscribe_from_system_event(&mut self, event: SystemEvent) -> AnyhowResult<()> {
-        let evt_id: u32 = event.into();
-        let handle = self.get_handle_lock()?;
+    /// Maps the client data area `name` to an id the first time it is used, returning the id
+    fn client_data_id(&mut self, name: &str) -> SimConnectResult<u32> {
+        if let Some(client_data_id) = self.client_data_ids.get(name) {
+            return Ok(*client_data_id);
+        }
+        let client_data_id = self.client_data_ids.len() as u32;
+
+        let client_data_name = CString::new(name)?;
+        let mut backend = self.get_backend_lock()?;
+        backend.map_client_data_name_to_id(&client_data_name, client_data_id)?;
+        self.record_sent(
+            &mut backend,
+            format!("MapClientDataNameToID({name})"),
+            Waiter::None,
+        )?;
+        drop(backend);
+
+        self.client_data_ids.insert(name.to_owned(), client_data_id);
+        Ok(client_data_id)
+    }
 
-        check_hr!(unsafe {
-            bindings::SimConnect_UnsubscribeFromSystemEvent(handle.as_ptr(), evt_id)
+    /// Sends a `RequestClientData` for a single answer, which is delivered through the returned
+    /// `Answer`
+    fn send_client_data_request(
+        &mut self,
+        client_data_id: u32,
+        define_id: u32,
+        call: String,
+    ) -> SimConnectResult<Answer<RecvSimData>> {
+        let request_id = self.new_request_id();
+        let (responder, answer) = oneshot::channel();
+        self.register(Registration::Data(request_id, responder))?;
+
+        let requested = self.get_backend_lock().and_then(|mut backend| {
+            backend.request_client_data(
+                client_data_id,
+                request_id,
+                define_id,
+                bindings::SIMCONNECT_CLIENT_DATA_PERIOD_SIMCONNECT_CLIENT_DATA_PERIOD_ONCE,
+                bindings::SIMCONNECT_CLIENT_DATA_REQUEST_FLAG_DEFAULT,
+                0,
+                0,
+                0,
+            )?;
+            self.record_sent(&mut backend, call, Waiter::Data(request_id))
         });
+        if let Err(e) = requested {
+            let _ = self.register(Registration::Unsubscribe(request_id));
+            return Err(e);
+        }
+        Ok(answer)
+    }
 
-        self.system_event_callback_sender
-            .send((event, None, false))
-            .map_err(|_| anyhow!("Unable to unsubscribe from system event"))?;
+    /// Sends a `RequestClientData` answered every `request` period, each answer being handed to
+    /// `sink`. Returns the request id.
+    fn send_client_data_subscription(
+        &mut self,
+        client_data_id: u32,
+        define_id: u32,
+        request: &ClientDataRequest,
+        call: String,
+        sink: DataSink,
+    ) -> SimConnectResult<u32> {
+        let request_id = self.new_request_id();
+        self.register(Registration::Subscription(request_id, sink))?;
+
+        let requested = self.get_backend_lock().and_then(|mut backend| {
+            backend.request_client_data(
+                client_data_id,
+                request_id,
+                define_id,
+                request.period.into(),
+                request.flags(),
+                request.origin,
+                request.interval,
+                request.limit,
+            )?;
+            self.record_sent(&mut backend, call, Waiter::Data(request_id))
+        });
+        if let Err(e) = requested {
+            let _ = self.register(Registration::Unsubscribe(request_id));
+            return Err(e);
+        }
+        Ok(request_id)
+    }
 
-        Ok(())
+    /// Asks the WASM module for a channel of this client's own the first time the bridge is
+    /// used, as the default channel is shared with every other client of the module. Returns an
+    /// `Answer` to wait on until the module has set the channel up, or `None` if it already has.
+    fn send_bridge_registration(&mut self) -> SimConnectResult<Option<Answer<()>>> {
+        if let Some(bridge) = &self.wasm_bridge {
+            let mut responses = bridge
+                .responses
+                .lock()
+                .map_err(|_| SimConnectError::Poisoned)?;
+            if !responses.stopped {
+                if responses.registered {
+                    return Ok(None);
+                }
+                let (responder, answer) = oneshot::channel();
+                responses.registrations.push(responder);
+                return Ok(Some(answer));
+            }
+            // Report why the bridge failed if that wasn't already, and set it up again next time
+            let error = responses.error.take();
+            drop(responses);
+            self.wasm_bridge = None;
+            if let Some(e) = error {
+                return Err(e);
+            }
+        }
+        let client = self.program_name.clone();
+        let registration_area = self.map_client_data::<BridgeMessage>(wasm_bridge::COMMAND_AREA)?;
+        let default_response = self.map_client_data::<BridgeMessage>(wasm_bridge::RESPONSE_AREA)?;
+        let command = self.map_client_data::<BridgeMessage>(&wasm_bridge::command_area(&client))?;
+        let response =
+            self.map_client_data::<BridgeMessage>(&wasm_bridge::response_area(&client))?;
+        let value_area_id = self.client_data_id(&wasm_bridge::value_area(&client))?;
+
+        let (responder, answer) = oneshot::channel();
+        let responses = Arc::new(Mutex::new(BridgeResponses {
+            registrations: vec![responder],
+            ..Default::default()
+        }));
+        let shared = responses.clone();
+        let added = wasm_bridge::client_added(&client);
+        let registration_request_id = self.send_client_data_subscription(
+            default_response.client_data_id(),
+            default_response.define_id(),
+            &ClientDataPeriod::OnSet.into(),
+            format!("RequestClientData({}, OnSet)", default_response.name()),
+            Box::new(move |answer| {
+                let mut responses = shared.lock().unwrap_or_else(|e| e.into_inner());
+                match answer.and_then(|data| client_data_from::<BridgeMessage>(&data)) {
+                    // Other clients registering are answered on the same area
+                    Ok(response) if response.text() != added => true,
+                    Ok(_) => {
+                        responses.registered = true;
+                        for registration in responses.registrations.drain(..) {
+                            let _ = registration.send(Ok(()));
+                        }
+                        false
+                    }
+                    Err(e) => {
+                        responses.fail(e);
+                        false
+                    }
+                }
+            }),
+        )?;
+
+        self.write_bridge_command(&registration_area, &BridgeCommand::AddClient(client))?;
+        self.wasm_bridge = Some(WasmBridge {
+            registration_request_id,
+            registration_area: default_response,
+            command,
+            response,
+            value_area_id,
+            connected: false,
+            variables: HashMap::new(),
+            responses,
+        });
+        Ok(Some(answer))
     }
 
-    /* #endregion */
+    /// Starts listening for the module's responses on the client's channel once the module has
+    /// set it up, and stops listening on the default channel. Variables registered by an
+    /// earlier connection are cleared, so the slots of this connection's variables are
+    /// numbered from the first.
+    fn connect_bridge_channel(&mut self) -> SimConnectResult<()> {
+        let bridge = self.wasm_bridge.as_ref().expect("bridge is registered");
+        if bridge.connected {
+            return Ok(());
+        }
+        let registration = &bridge.registration_area;
+        let (registration_id, registration_define_id) =
+            (registration.client_data_id(), registration.define_id());
+        let stop_registration = format!("RequestClientData({}, Never)", registration.name());
+        let registration_request_id = bridge.registration_request_id;
+        let response = &bridge.response;
+        let (response_id, response_define_id) = (response.client_data_id(), response.define_id());
+        let subscribe_response = format!("RequestClientData({}, OnSet)", response.name());
+        let shared = bridge.responses.clone();
 
-    /* #region input_event */
-    /// Request subscription to an input event. Input events are located in the `sim_connect_rs::sim_connect_data::sim_input_events` package
-    pub fn subscribe_to_input_event(&mut self, input_event: impl InputEvent) -> AnyhowResult<()> {
-        let _event = Box::new(input_event);
+        {
+            let mut backend = self.get_backend_lock()?;
+            backend.request_client_data(
+                registration_id,
+                registration_request_id,
+                registration_define_id,
+                bindings::SIMCONNECT_CLIENT_DATA_PERIOD_SIMCONNECT_CLIENT_DATA_PERIOD_NEVER,
+                bindings::SIMCONNECT_CLIENT_DATA_REQUEST_FLAG_DEFAULT,
+                0,
+                0,
+                0,
+            )?;
+            self.record_sent(&mut backend, stop_registration, Waiter::None)?;
+        }
+
+        self.send_client_data_subscription(
+            response_id,
+            response_define_id,
+            &ClientDataPeriod::OnSet.into(),
+            subscribe_response,
+            Box::new(move |answer| {
+                let mut responses = shared.lock().unwrap_or_else(|e| e.into_inner());
+                match answer.and_then(|data| client_data_from::<BridgeMessage>(&data)) {
+                    Ok(response) => {
+                        if response.text() == wasm_bridge::PONG {
+                            if let Some(ping) = responses.pings.pop_front() {
+                                let _ = ping.send(Ok(()));
+                            }
+                        }
+                        true
+                    }
+                    Err(e) => {
+                        responses.fail(e);
+                        false
+                    }
+                }
+            }),
+        )?;
+
+        self.send_bridge_command(BridgeCommand::ClearVariables)?;
+        self.wasm_bridge
+            .as_mut()
+            .expect("bridge is registered")
+            .connected = true;
         Ok(())
     }
-    /* #endregion */
 
-    /* #region get_latest_data */
     #[cfg(feature = "async")]
-    /// Gets data on a sim object. Calls `register_struct` if it hasn't already been called.
-    /// If it hasn't been called, chances are this function will return None as SimConnect needs
-    /// time to process the data.
-    pub async fn get_latest_data<T: SimConnectToStruct>(&mut self) -> AnyhowResult<T::ReturnType> {
-        self.register_struct::<T>()?;
-        let data_name = self.get_struct_name::<T>();
-        let data_id = self.type_map.get(&data_name);
-        let data_id = data_id.unwrap();
-
-        self.request_data_on_self_object::<T>()?;
-
-        let recv = self
-            .data_event_map
-            .get(data_id)
-            .ok_or_else(|| anyhow!("data_event_map not expected to be empty"))?;
-
-        let mut data = recv.try_iter().last();
-        if let None = data {
-            data = Some(recv.recv().or_else(|_| {
-                Err(anyhow!(
-                    "Thread has been closed and channel no longer available"
-                ))
-            })?)
+    /// Sets up the WASM bridge the first time it is used, waiting for the module to set up the
+    /// client's channel
+    async fn connect_wasm_bridge(&mut self) -> SimConnectResult<()> {
+        if let Some(registered) = self.send_bridge_registration()? {
+            registered.await?;
         }
-        let data = data.unwrap();
-        let s = unsafe { T::parse_struct(data.get_pointer()) }
-            .map_err(|_| anyhow!("Unable to parse struct"))?;
-        Ok(s)
+        self.connect_bridge_channel()
     }
 
     #[cfg(not(feature = "async"))]
-    /// Gets data on a sim object. Calls `register_struct` if it hasn't already been called.
-    /// If it hasn't been called, chances are this function will return None as SimConnect needs
-    /// time to process the data.
-    pub fn get_latest_data<T: SimConnectToStruct>(&mut self) -> AnyhowResult<T> {
-        self.register_struct::<T>()?;
-        let data_name = self.get_struct_name::<T>();
-        let data_id = self.type_map.get(&data_name);
-        let data_id = data_id.unwrap();
-
-        self.request_data_on_self_object::<T>()?;
-
-        let recv = self
-            .data_event_map
-            .get(data_id)
-            .ok_or_else(|| anyhow!("data_event_map not expected to be empty"))?;
-
-        let mut data = recv.try_iter().map(|d| d.to_struct()).last();
-        if data.is_none() {
-            data = Some(
-                recv.recv()
-                    .map_err(|_| anyhow!("Thread has been closed and channel no longer available"))?
-                    .to_struct(),
-            )
+    /// Sets up the WASM bridge the first time it is used, blocking until the module has set up
+    /// the client's channel
+    fn connect_wasm_bridge(&mut self) -> SimConnectResult<()> {
+        if let Some(registered) = self.send_bridge_registration()? {
+            registered.recv()?;
         }
-        let data = data.unwrap()?;
-        Ok(data)
+        self.connect_bridge_channel()
     }
 
-    /* #endregion */
+    fn write_bridge_command(
+        &self,
+        area: &ClientDataArea<BridgeMessage>,
+        command: &BridgeCommand,
+    ) -> SimConnectResult<()> {
+        let message = BridgeMessage::new(&command.to_string()).ok_or(HResult::InvalidArg)?;
+        let mut backend = self.get_backend_lock()?;
+        backend.set_client_data(
+            area.client_data_id(),
+            area.define_id(),
+            bindings::SIMCONNECT_CLIENT_DATA_SET_FLAG_DEFAULT,
+            &message.to_bytes(),
+        )?;
+        self.record_sent(
+            &mut backend,
+            format!("SetClientData({}) for {command}", area.name()),
+            Waiter::None,
+        )
+    }
 
-    /* #region set_data_on_object */
+    /// Writes `command` to the client's channel, which has to be connected
+    fn send_bridge_command(&self, command: BridgeCommand) -> SimConnectResult<()> {
+        let area = &self
+            .wasm_bridge
+            .as_ref()
+            .expect("bridge is connected")
+            .command;
+        self.write_bridge_command(area, &command)
+    }
 
-    pub fn set_data_on_self<T: StructToSimConnect>(&mut self, data: T) -> AnyhowResult<()> {
-        self.register_struct::<T>()?;
-        let struct_name = self.get_struct_name::<T>();
-        let data_id = self.type_map.get(&struct_name);
-        let data_id = data_id.unwrap();
-        let data_ptr = Box::new(data);
+    /// Sends a ping, answered through the returned `Answer` once the module has worked through
+    /// every command sent before it
+    fn send_bridge_ping(&self) -> SimConnectResult<Answer<()>> {
+        let bridge = self.wasm_bridge.as_ref().expect("bridge is connected");
+        let mut responses = bridge
+            .responses
+            .lock()
+            .map_err(|_| SimConnectError::Poisoned)?;
+        if responses.stopped {
+            return Err(responses
+                .error
+                .take()
+                .unwrap_or(SimConnectError::ListenerStopped));
+        }
 
+        // The responses are locked until the ping is queued, so its pong can't be missed
+        self.write_bridge_command(&bridge.command, &BridgeCommand::Ping)?;
+        let (responder, answer) = oneshot::channel();
+        responses.pings.push_back(responder);
+        Ok(answer)
+    }
 
-        {
-            let handle_lock = self.get_handle_lock()?;
-            let handle = *handle_lock;
-            check_hr!(unsafe {
-                sim_connect_sys::bindings::SimConnect_SetDataOnSimObject(
-                    handle.as_ptr(),
-                    *data_id,
-                    bindings::SIMCONNECT_OBJECT_ID_USER,
-                    bindings::SIMCONNECT_DATA_SET_FLAG_DEFAULT,
-                    0,
-                    std::mem::size_of::<T>() as u32,
-                    Box::into_raw(data_ptr) as *mut c_void,
+    /// Registers `variable` with the bridge the first time it is used, returning the client
+    /// data definition of its slot. A newly registered variable also comes with a ping, which
+    /// is answered once the module has written the variable's value to its slot.
+    fn bridge_variable(&mut self, variable: &str) -> SimConnectResult<(u32, Option<Answer<()>>)> {
+        let bridge = self.wasm_bridge.as_ref().expect("bridge is connected");
+        if let Some(define_id) = bridge.variables.get(variable) {
+            return Ok((*define_id, None));
+        }
+        let slot = bridge.variables.len();
+        if slot >= wasm_bridge::MAX_VARIABLES {
+            return Err(HResult::OutOfMemory.into());
+        }
+
+        let define_id = self.bridge_slot_definition(slot)?;
+        self.send_bridge_command(BridgeCommand::AddVariable(variable.to_owned()))?;
+        let registered = self.send_bridge_ping()?;
+        self.wasm_bridge
+            .as_mut()
+            .expect("bridge is connected")
+            .variables
+            .insert(variable.to_owned(), define_id);
+        Ok((define_id, Some(registered)))
+    }
+
+    /// Adds the slot numbered `slot` of the bridge's value area to a client data definition the
+    /// first time it is used, returning the definition id
+    fn bridge_slot_definition(&mut self, slot: usize) -> SimConnectResult<u32> {
+        let key = format!("{}[{slot}]", wasm_bridge::value_area(&self.program_name));
+        if let Some(define_id) = self.client_data_definitions.get(&key) {
+            return Ok(*define_id);
+        }
+        let define_id = FIRST_CLIENT_DATA_DEFINITION_ID + self.client_data_definitions.len() as u32;
+
+        {
+            let mut backend = self.get_backend_lock()?;
+            backend.add_to_client_data_definition(
+                define_id,
+                (slot * wasm_bridge::VALUE_SIZE) as u32,
+                bindings::SIMCONNECT_CLIENTDATATYPE_FLOAT32,
+                0.0,
+                0,
+            )?;
+            self.record_sent(
+                &mut backend,
+                format!("AddToClientDataDefinition for {key}"),
+                Waiter::Definition(define_id),
+            )?;
+        }
+
+        self.client_data_definitions.insert(key, define_id);
+        Ok(define_id)
+    }
+
+    /// Requests the value in a slot of the bridge's value area, delivered through the returned
+    /// `Answer`
+    fn send_bridge_value_request(
+        &mut self,
+        variable: &str,
+        define_id: u32,
+    ) -> SimConnectResult<Answer<RecvSimData>> {
+        let value_area_id = self
+            .wasm_bridge
+            .as_ref()
+            .expect("bridge is connected")
+            .value_area_id;
+        self.send_client_data_request(
+            value_area_id,
+            define_id,
+            format!(
+                "RequestClientData({}) for {variable}",
+                wasm_bridge::value_area(&self.program_name)
+            ),
+        )
+    }
+
+    /// Adds every entry of `F`'s definition to a facility definition the first time it is used,
+    /// returning the definition id
+    fn facility_definition<F: Facility>(&mut self) -> SimConnectResult<u32> {
+        let type_name = std::any::type_name::<F>();
+        if let Some(define_id) = self.facility_definitions.get(type_name) {
+            return Ok(*define_id);
+        }
+        let define_id = FIRST_FACILITY_DEFINITION_ID + self.facility_definitions.len() as u32;
+
+        {
+            let mut backend = self.get_backend_lock()?;
+            for entry in F::definition() {
+                backend.add_to_facility_definition(define_id, &CString::new(entry.as_str())?)?;
+                self.record_sent(
+                    &mut backend,
+                    format!("AddToFacilityDefinition({entry}) for {type_name}"),
+                    Waiter::Definition(define_id),
+                )?;
+            }
+        }
+
+        self.facility_definitions
+            .insert(type_name.to_owned(), define_id);
+        Ok(define_id)
+    }
+
+    /// Sends a `RequestFacilityData`, the records answering which are delivered through the
+    /// returned `Answer` once they have all arrived
+    fn send_facility_request<F: Facility>(
+        &mut self,
+        icao: &str,
+        region: Option<&str>,
+    ) -> SimConnectResult<Answer<Option<FacilityRecord>>> {
+        let define_id = self.facility_definition::<F>()?;
+        let icao_name = CString::new(icao)?;
+        let region_name = CString::new(region.unwrap_or_default())?;
+
+        let request_id = self.new_request_id();
+        let (responder, answer) = oneshot::channel();
+        self.register(Registration::FacilityData(request_id, define_id, responder))?;
+
+        let requested = self.get_backend_lock().and_then(|mut backend| {
+            backend.request_facility_data(define_id, request_id, &icao_name, &region_name)?;
+            self.record_sent(
+                &mut backend,
+                format!(
+                    "RequestFacilityData({icao}) for {}",
+                    std::any::type_name::<F>()
+                ),
+                Waiter::FacilityData(request_id),
+            )
+        });
+        if let Err(e) = requested {
+            let _ = self.register(Registration::Unsubscribe(request_id));
+            return Err(e);
+        }
+        Ok(answer)
+    }
+
+    /// Sends a `RequestFacilitiesList`, merging every message of the answer into the list
+    /// delivered through the returned `Answer`
+    fn send_facilities_list_request<T: FacilityListEntry>(
+        &mut self,
+    ) -> SimConnectResult<Answer<Vec<T>>> {
+        let request_id = self.new_request_id();
+        let (responder, answer) = oneshot::channel();
+        let mut responder = Some(responder);
+        let mut merger = FacilityListMerger::<T>::new();
+        self.register(Registration::FacilitiesList(
+            request_id,
+            Box::new(move |answer| {
+                let answer = match answer.and_then(|list| merger.merge(list)) {
+                    Ok(None) => return true,
+                    Ok(Some(entries)) => Ok(entries),
+                    Err(e) => Err(e),
+                };
+                if let Some(responder) = responder.take() {
+                    let _ = responder.send(answer);
+                }
+                false
+            }),
+        ))?;
+
+        let requested = self.get_backend_lock().and_then(|mut backend| {
+            backend.request_facilities_list(T::LIST_TYPE.into(), request_id)?;
+            self.record_sent(
+                &mut backend,
+                format!("RequestFacilitiesList({:?})", T::LIST_TYPE),
+                Waiter::FacilitiesList(request_id),
+            )
+        });
+        if let Err(e) = requested {
+            let _ = self.register(Registration::Unsubscribe(request_id));
+            return Err(e);
+        }
+        Ok(answer)
+    }
+
+    /// Sends an `AICreate*` call with `send`, which is handed the request id. The id of the
+    /// created object is delivered through the returned `Answer`.
+    fn send_object_creation(
+        &mut self,
+        call: String,
+        send: impl FnOnce(&mut B, u32) -> SimConnectResult<()>,
+    ) -> SimConnectResult<Answer<SimObjectId>> {
+        let request_id = self.new_request_id();
+        let (responder, answer) = oneshot::channel();
+        self.register(Registration::CreateObject(request_id, responder))?;
+
+        let requested = self.get_backend_lock().and_then(|mut backend| {
+            send(&mut backend, request_id)?;
+            self.record_sent(&mut backend, call, Waiter::CreateObject(request_id))
+        });
+        if let Err(e) = requested {
+            let _ = self.register(Registration::Unsubscribe(request_id));
+            return Err(e);
+        }
+        Ok(answer)
+    }
+
+    /// Sends an AI call on an object the client created, which the simulator does not answer.
+    /// Exceptions are kept for `take_exceptions`.
+    fn send_ai_call(
+        &mut self,
+        call: String,
+        send: impl FnOnce(&mut B, u32) -> SimConnectResult<()>,
+    ) -> SimConnectResult<()> {
+        let request_id = self.new_request_id();
+        let mut backend = self.get_backend_lock()?;
+        send(&mut backend, request_id)?;
+        self.record_sent(&mut backend, call, Waiter::None)
+    }
+
+    fn send_parked_atc_aircraft(
+        &mut self,
+        container_title: &str,
+        tail_number: &str,
+        airport: &str,
+    ) -> SimConnectResult<Answer<SimObjectId>> {
+        let title = CString::new(container_title)?;
+        let tail = CString::new(tail_number)?;
+        let airport_id = CString::new(airport)?;
+        self.send_object_creation(
+            format!("AICreateParkedATCAircraft({container_title}, {airport})"),
+            |backend, request_id| {
+                backend.ai_create_parked_atc_aircraft(&title, &tail, &airport_id, request_id)
+            },
+        )
+    }
+
+    fn send_enroute_atc_aircraft(
+        &mut self,
+        container_title: &str,
+        tail_number: &str,
+        flight_number: i32,
+        flight_plan: &str,
+        flight_plan_position: f64,
+        touch_and_go: bool,
+    ) -> SimConnectResult<Answer<SimObjectId>> {
+        let title = CString::new(container_title)?;
+        let tail = CString::new(tail_number)?;
+        let path = CString::new(flight_plan)?;
+        self.send_object_creation(
+            format!("AICreateEnrouteATCAircraft({container_title}, {flight_plan})"),
+            |backend, request_id| {
+                backend.ai_create_enroute_atc_aircraft(
+                    &title,
+                    &tail,
+                    flight_number,
+                    &path,
+                    flight_plan_position,
+                    touch_and_go,
+                    request_id,
+                )
+            },
+        )
+    }
+
+    fn send_non_atc_aircraft(
+        &mut self,
+        container_title: &str,
+        tail_number: &str,
+        position: InitPosition,
+    ) -> SimConnectResult<Answer<SimObjectId>> {
+        let title = CString::new(container_title)?;
+        let tail = CString::new(tail_number)?;
+        self.send_object_creation(
+            format!("AICreateNonATCAircraft({container_title})"),
+            |backend, request_id| {
+                backend.ai_create_non_atc_aircraft(&title, &tail, position.into(), request_id)
+            },
+        )
+    }
+
+    fn send_simulated_object(
+        &mut self,
+        container_title: &str,
+        position: InitPosition,
+    ) -> SimConnectResult<Answer<SimObjectId>> {
+        let title = CString::new(container_title)?;
+        self.send_object_creation(
+            format!("AICreateSimulatedObject({container_title})"),
+            |backend, request_id| {
+                backend.ai_create_simulated_object(&title, position.into(), request_id)
+            },
+        )
+    }
+
+    /// Sends a flight command with `send`, whose completion the simulator reports by firing
+    /// `event`. The file name the event carries is delivered through the returned `Answer`.
+    fn send_flight_command(
+        &mut self,
+        event: SystemEvent,
+        file_name: &str,
+        call: String,
+        send: impl FnOnce(&mut B) -> SimConnectResult<()>,
+    ) -> SimConnectResult<Answer<String>> {
+        let (responder, answer) = oneshot::channel();
+        let request_id = self.new_request_id();
+        self.register(Registration::FlightFile(
+            event,
+            FlightCommand {
+                request_id,
+                file_name: file_name.to_owned(),
+                responder,
+            },
+        ))?;
+
+        let subscribed = match self.system_events.contains(&event) {
+            true => Ok(()),
+            false => self.get_backend_lock().and_then(|mut backend| {
+                backend.subscribe_to_system_event(event.into(), &event.sc_string())?;
+                self.record_sent(
+                    &mut backend,
+                    format!("SubscribeToSystemEvent({event})"),
+                    Waiter::None,
                 )
+            }),
+        };
+        let sent = subscribed.and_then(|()| {
+            self.system_events.insert(event);
+            let mut backend = self.get_backend_lock()?;
+            send(&mut backend)?;
+            self.record_sent(&mut backend, call, Waiter::FlightFile(event, request_id))
+        });
+        if let Err(e) = sent {
+            let _ = self.register(Registration::Unsubscribe(request_id));
+            return Err(e);
+        }
+        Ok(answer)
+    }
+
+    fn send_flight_load(&mut self, file_name: &str) -> SimConnectResult<Answer<String>> {
+        let path = CString::new(file_name)?;
+        self.send_flight_command(
+            SystemEvent::FlightLoaded,
+            file_name,
+            format!("FlightLoad({file_name})"),
+            |backend| backend.flight_load(&path),
+        )
+    }
+
+    fn send_flight_save(
+        &mut self,
+        file_name: &str,
+        title: &str,
+        description: &str,
+    ) -> SimConnectResult<Answer<String>> {
+        let path = CString::new(file_name)?;
+        let title = CString::new(title)?;
+        let description = CString::new(description)?;
+        self.send_flight_command(
+            SystemEvent::FlightSaved,
+            file_name,
+            format!("FlightSave({file_name})"),
+            |backend| backend.flight_save(&path, &title, &description, 0),
+        )
+    }
+
+    fn send_flight_plan_load(&mut self, file_name: &str) -> SimConnectResult<Answer<String>> {
+        let path = CString::new(file_name)?;
+        self.send_flight_command(
+            SystemEvent::FlightPlanActivated,
+            file_name,
+            format!("FlightPlanLoad({file_name})"),
+            |backend| backend.flight_plan_load(&path),
+        )
+    }
+
+    /// Sends a `RequestSystemState`, the answer to which is delivered through the returned
+    /// `Answer`
+    fn send_system_state_request(
+        &self,
+        state_request: SimStateArgs,
+    ) -> SimConnectResult<Answer<RecvSystemState>> {
+        let request_id: u32 = state_request.into();
+        let (responder, answer) = oneshot::channel();
+        self.register(Registration::SystemState(request_id, responder))?;
+
+        let requested = self.get_backend_lock().and_then(|mut backend| {
+            backend.request_system_state(request_id, &state_request.sc_string())?;
+            self.record_sent(
+                &mut backend,
+                format!("RequestSystemState({state_request})"),
+                Waiter::SystemState(request_id),
+            )
+        });
+        if let Err(e) = requested {
+            let _ = self.register(Registration::CancelSystemState(request_id));
+            return Err(e);
+        }
+        Ok(answer)
+    }
+
+    /// Sends an `EnumerateInputEvents`, collecting every message of the answer into the
+    /// catalog delivered through the returned `Answer`
+    fn send_input_event_enumeration(&mut self) -> SimConnectResult<Answer<InputEventCatalog>> {
+        let request_id = self.new_request_id();
+        let (responder, answer) = oneshot::channel();
+        let mut responder = Some(responder);
+        let mut catalog = InputEventCatalog::default();
+        self.register(Registration::InputEventList(
+            request_id,
+            Box::new(move |answer| {
+                // Messages are numbered from 0 up to `out_of - 1`
+                let complete = answer.map(|list| {
+                    catalog.extend(list.descriptors);
+                    list.entry_number + 1 >= list.out_of
+                });
+                let answer = match complete {
+                    Ok(false) => return true,
+                    Ok(true) => Ok(std::mem::take(&mut catalog)),
+                    Err(e) => Err(e),
+                };
+                if let Some(responder) = responder.take() {
+                    let _ = responder.send(answer);
+                }
+                false
+            }),
+        ))?;
+
+        let requested = self.get_backend_lock().and_then(|mut backend| {
+            backend.enumerate_input_events(request_id)?;
+            self.record_sent(
+                &mut backend,
+                "EnumerateInputEvents".to_owned(),
+                Waiter::InputEvent(request_id),
+            )
+        });
+        if let Err(e) = requested {
+            let _ = self.register(Registration::Unsubscribe(request_id));
+            return Err(e);
+        }
+        Ok(answer)
+    }
+
+    fn send_input_event_request(&mut self, hash: u64) -> SimConnectResult<Answer<InputEventValue>> {
+        let request_id = self.new_request_id();
+        let (responder, answer) = oneshot::channel();
+        self.register(Registration::InputEventValue(request_id, responder))?;
+
+        let mut backend = self.get_backend_lock()?;
+        backend.get_input_event(request_id, hash)?;
+        self.record_sent(
+            &mut backend,
+            format!("GetInputEvent({hash:#x})"),
+            Waiter::InputEvent(request_id),
+        )?;
+        Ok(answer)
+    }
+
+    fn send_input_event_params_request(
+        &mut self,
+        hash: u64,
+    ) -> SimConnectResult<Answer<Vec<String>>> {
+        let (responder, answer) = oneshot::channel();
+        self.register(Registration::InputEventParams(hash, responder))?;
+
+        let mut backend = self.get_backend_lock()?;
+        backend.enumerate_input_event_params(hash)?;
+        self.record_sent(
+            &mut backend,
+            format!("EnumerateInputEventParams({hash:#x})"),
+            Waiter::InputEventParams(hash),
+        )?;
+        Ok(answer)
+    }
+
+    /// Maps `definition` in `group_id` to the client event `event_id`, replacing any earlier
+    /// mapping of it. Events delivered to a `callback` are sent with `1` when the input is
+    /// pressed and `0` when it is released, others only when it is pressed, as a binding in the
+    /// simulator would be. Axes are always sent with their position.
+    fn send_input_mapping(
+        &mut self,
+        group_id: u32,
+        definition: InputDefinition,
+        event_id: u32,
+        callback: Option<InputCallback>,
+        mask: bool,
+    ) -> SimConnectResult<()> {
+        self.unmap_input(group_id, &definition)?;
+
+        let (down_value, up_event_id) = if callback.is_some() && !definition.is_analog() {
+            (1, event_id)
+        } else {
+            (0, bindings::SIMCONNECT_UNUSED)
+        };
+        let input_definition = CString::new(definition.to_string())?;
+        {
+            let mut backend = self.get_backend_lock()?;
+            backend.map_input_event_to_client_event(
+                group_id,
+                &input_definition,
+                event_id,
+                down_value,
+                up_event_id,
+                0,
+                mask,
+            )?;
+            self.record_sent(
+                &mut backend,
+                format!("MapInputEventToClientEvent({group_id}, {definition})"),
+                Waiter::None,
+            )?;
+        }
+
+        self.input_groups
+            .entry(group_id)
+            .or_default()
+            .push((definition, callback));
+        Ok(())
+    }
+
+    /// Removes the mapping of `definition` from `group_id`, if there is one
+    fn unmap_input(&mut self, group_id: u32, definition: &InputDefinition) -> SimConnectResult<()> {
+        let Some(mappings) = self.input_groups.get_mut(&group_id) else {
+            return Ok(());
+        };
+        let Some(index) = mappings.iter().position(|(mapped, _)| mapped == definition) else {
+            return Ok(());
+        };
+        let (_, callback) = mappings.remove(index);
+
+        let input_definition = CString::new(definition.to_string())?;
+        let mut backend = self.get_backend_lock()?;
+        backend.remove_input_event(group_id, &input_definition)?;
+        self.record_sent(
+            &mut backend,
+            format!("RemoveInputEvent({group_id}, {definition})"),
+            Waiter::None,
+        )?;
+        if let Some(callback) = callback {
+            self.release_input_callback(&mut backend, callback)?;
+        }
+        Ok(())
+    }
+
+    /// Stops delivering the private client event an input was mapped to for a callback
+    fn release_input_callback(
+        &self,
+        backend: &mut B,
+        callback: InputCallback,
+    ) -> SimConnectResult<()> {
+        let InputCallback {
+            notification_group,
+            event_id,
+        } = callback;
+        backend.remove_client_event(notification_group, event_id)?;
+        self.record_sent(
+            backend,
+            format!("RemoveClientEvent({notification_group}, {event_id})"),
+            Waiter::None,
+        )?;
+        self.register(Registration::ClientCallback(event_id, None))
+    }
+
+    /// Opens a new connection to SimConnect through the provided backend. Behaves exactly like
+    /// `SimConnect::open`, but allows the transport to be swapped out, e.g. for a test double
+    /// or a network client.
+    pub fn open_with_backend(
+        mut backend: B,
+        program_name: &str,
+        mut poll_interval: Option<Duration>,
+    ) -> SimConnectResult<Self> {
+        if poll_interval.is_none() {
+            poll_interval = Some(Duration::from_secs(1));
+        }
+        let program_name = CString::new(program_name)?;
+
+        backend.open(&program_name)?;
+
+        let backend = Arc::new(Mutex::new(backend));
+
+        let should_quit = Arc::new(RwLock::new(false));
+
+        let cloned_should_quit = should_quit.clone();
+        let cloned_backend = backend.clone();
+
+        let (registration_sx, registration_rcv) = channel();
+        let (exception_sx, exception_rcv) = channel();
+        let packet_log = Arc::new(Mutex::new(PacketLog::default()));
+
+        let listener = Listener::new(
+            cloned_backend,
+            cloned_should_quit,
+            poll_interval.unwrap(),
+            registration_rcv,
+            exception_sx,
+            packet_log.clone(),
+        );
+        let listen_handle: JoinHandle<SimConnectResult<()>> = thread::spawn(move || listener.run());
+
+        Ok(Self {
+            backend,
+            type_map: HashMap::new(),
+            client_data_definitions: HashMap::new(),
+            client_data_ids: HashMap::new(),
+            wasm_bridge: None,
+            facility_definitions: HashMap::new(),
+            client_events: HashMap::new(),
+            next_client_event_id: FIRST_CLIENT_EVENT_ID,
+            notification_groups: HashMap::new(),
+            next_notification_group_id: 0,
+            input_event_groups: HashMap::new(),
+            input_groups: HashMap::new(),
+            system_events: HashSet::new(),
+            program_name: program_name.to_str().unwrap().to_owned(),
+            registration_sender: registration_sx,
+            exception_receiver: exception_rcv,
+            packet_log,
+            next_request_id: 0,
+            should_quit,
+            listen_handle: Some(listen_handle),
+        })
+    }
+
+    /// Registers the struct's field definitions with SimConnect
+    pub fn register_struct<T: StructToSimConnect>(&mut self) -> SimConnectResult<()> {
+        let fields = T::get_fields()
+            .into_iter()
+            .map(|field| {
+                let sim_unit = field
+                    .sim_unit
+                    .map(|unit| unit.sc_string())
+                    .unwrap_or_default();
+                Ok((
+                    field.id,
+                    field.sim_var.try_sc_string()?,
+                    sim_unit,
+                    field.data_type,
+                ))
+            })
+            .collect::<SimConnectResult<Vec<_>>>()?;
+        self.add_data_definition(
+            self.get_struct_name::<T>(),
+            std::any::type_name::<T>(),
+            fields,
+        )
+    }
+
+    /// Registers the datums of a definition put together at runtime with SimConnect. Like
+    /// `register_struct`, this only happens once for each distinct definition, and is done by
+    /// the calls which use one if it hasn't been already.
+    pub fn register_definition(&mut self, definition: &DataDefinition) -> SimConnectResult<()> {
+        definition.check()?;
+        let datums = definition
+            .datums()
+            .iter()
+            .enumerate()
+            .map(|(datum_id, datum)| {
+                Ok((
+                    datum_id as u32,
+                    CString::new(datum.name.as_str())?,
+                    CString::new(datum.unit.as_str())?,
+                    datum.data_type,
+                ))
+            })
+            .collect::<SimConnectResult<Vec<_>>>()?;
+        self.add_data_definition(
+            self.get_definition_name(definition),
+            &definition.to_string(),
+            datums,
+        )
+    }
+
+    /// Adds `(datum_id, sim_var, sim_unit, data_type)` datums to a new data definition, unless
+    /// one was already added under `data_name`
+    fn add_data_definition(
+        &mut self,
+        data_name: String,
+        type_name: &str,
+        datums: impl IntoIterator<Item = (u32, CString, CString, SimVarType)>,
+    ) -> SimConnectResult<()> {
+        let new_data_id = self.type_map.len() as u32;
+
+        if self.type_map.contains_key(&data_name) {
+            return Ok(());
+        }
+
+        {
+            let mut backend = self.get_backend_lock()?;
+
+            for (datum_id, sim_var, sim_unit, data_type) in datums {
+                backend.add_to_data_definition(
+                    new_data_id,
+                    &sim_var,
+                    &sim_unit,
+                    data_type as i32,
+                    0.0,
+                    datum_id,
+                )?;
+                self.record_sent(
+                    &mut backend,
+                    format!(
+                        "AddToDataDefinition({}, {}) for field {datum_id} of {type_name}",
+                        sim_var.to_string_lossy(),
+                        sim_unit.to_string_lossy(),
+                    ),
+                    Waiter::Definition(new_data_id),
+                )?;
+            }
+        }
+
+        self.type_map.insert(data_name, new_data_id);
+        Ok(())
+    }
+
+    /* #region request_system_state */
+    #[cfg(feature = "async")]
+    /// Requests current information about the system state. The future is driven by the
+    /// listener thread, so it works on any executor without blocking it.
+    ///
+    /// Cancellation safe: if the future is dropped before the simulator answers, the answer is
+    /// discarded when it arrives.
+    pub async fn request_system_state(
+        &mut self,
+        state_request: SimStateArgs,
+    ) -> SimConnectResult<RecvSystemState> {
+        self.send_system_state_request(state_request)?.await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Requests current information about the system state.
+    pub fn request_system_state(
+        &mut self,
+        state_request: SimStateArgs,
+    ) -> SimConnectResult<RecvSystemState> {
+        self.send_system_state_request(state_request)?.recv()
+    }
+
+    /* #endregion */
+
+    /* #region system_event */
+    /// Requests a subscription to a system event. Events can be checked by using the
+    /// `check_events` function
+    pub fn subscribe_to_system_event(
+        &mut self,
+        event: SystemEvent,
+        callback: impl Fn(SystemEventDataHolder) + Send + Sync + 'static,
+    ) -> SimConnectResult<()> {
+        let event_id: u32 = event.into();
+
+        // The listener has to know about the callback before the simulator can fire the event
+        self.register(Registration::Callback(event, Some(Box::new(callback))))?;
+
+        let subscribed = self.get_backend_lock().and_then(|mut backend| {
+            backend.subscribe_to_system_event(event_id, &event.sc_string())?;
+            self.record_sent(
+                &mut backend,
+                format!("SubscribeToSystemEvent({event})"),
+                Waiter::None,
+            )
+        });
+
+        match subscribed {
+            Ok(()) => {
+                self.system_events.insert(event);
+            }
+            Err(_) => {
+                let _ = self.register(Registration::Callback(event, None));
+            }
+        }
+
+        subscribed
+    }
+
+    pub fn unsubscribe_from_system_event(&mut self, event: SystemEvent) -> SimConnectResult<()> {
+        let evt_id: u32 = event.into();
+
+        {
+            let mut backend = self.get_backend_lock()?;
+            backend.unsubscribe_from_system_event(evt_id)?;
+            self.record_sent(
+                &mut backend,
+                format!("UnsubscribeFromSystemEvent({event})"),
+                Waiter::None,
+            )?;
+        }
+        self.system_events.remove(&event);
+
+        self.register(Registration::Callback(event, None))?;
+        self.register(Registration::CancelFlightFiles(event))
+    }
+
+    /* #endregion */
+
+    /* #region client_event */
+    /// Maps a client event to the sim event it stands for, such as `GearAndBrakes::GearDown`
+    /// to `GEAR_DOWN`, and returns its client event id. Events are only mapped once, so
+    /// calling this again returns the same id.
+    ///
+    /// `transmit_client_event` and `send` map the event if it isn't mapped yet, so this only
+    /// needs calling to map events ahead of time.
+    pub fn map_client_event(&mut self, event: impl InputEvent) -> SimConnectResult<u32> {
+        let event_name = event.sc_string();
+        let key = event_name.to_string_lossy().into_owned();
+        if let Some(event_id) = self.client_events.get(&key) {
+            return Ok(*event_id);
+        }
+
+        let event_id = self.new_client_event_id();
+        {
+            let mut backend = self.get_backend_lock()?;
+            backend.map_client_event_to_sim_event(event_id, &event_name)?;
+            self.record_sent(
+                &mut backend,
+                format!("MapClientEventToSimEvent({key})"),
+                Waiter::None,
+            )?;
+        }
+
+        self.client_events.insert(key, event_id);
+        Ok(event_id)
+    }
+
+    /// Transmits a client event to the user's aircraft, as if the user had triggered it.
+    /// `data` is the event's parameter, e.g. the value for a `_SET` event.
+    pub fn transmit_client_event(
+        &mut self,
+        event: impl InputEvent,
+        data: u32,
+    ) -> SimConnectResult<()> {
+        let event_name = event.sc_string().to_string_lossy().into_owned();
+        let event_id = self.map_client_event(event)?;
+
+        let mut backend = self.get_backend_lock()?;
+        backend.transmit_client_event(
+            bindings::SIMCONNECT_OBJECT_ID_USER,
+            event_id,
+            data,
+            bindings::SIMCONNECT_GROUP_PRIORITY_HIGHEST,
+            bindings::SIMCONNECT_EVENT_FLAG_GROUPID_IS_PRIORITY,
+        )?;
+        self.record_sent(
+            &mut backend,
+            format!("TransmitClientEvent({event_name}, {data})"),
+            Waiter::None,
+        )
+    }
+
+    /// Like `transmit_client_event`, for events taking up to five parameters. Parameters which
+    /// aren't given are sent as `0`.
+    ///
+    /// Returns `HResult::InvalidArg` if more than five parameters are given.
+    pub fn transmit_client_event_ex1(
+        &mut self,
+        event: impl InputEvent,
+        data: &[u32],
+    ) -> SimConnectResult<()> {
+        if data.len() > 5 {
+            return Err(HResult::InvalidArg.into());
+        }
+        let mut parameters = [0; 5];
+        parameters[..data.len()].copy_from_slice(data);
+
+        let event_name = event.sc_string().to_string_lossy().into_owned();
+        let event_id = self.map_client_event(event)?;
+
+        let mut backend = self.get_backend_lock()?;
+        backend.transmit_client_event_ex1(
+            bindings::SIMCONNECT_OBJECT_ID_USER,
+            event_id,
+            bindings::SIMCONNECT_GROUP_PRIORITY_HIGHEST,
+            bindings::SIMCONNECT_EVENT_FLAG_GROUPID_IS_PRIORITY,
+            parameters,
+        )?;
+        self.record_sent(
+            &mut backend,
+            format!("TransmitClientEvent_EX1({event_name}, {data:?})"),
+            Waiter::None,
+        )
+    }
+
+    /// Triggers an event which takes no parameter on the user's aircraft
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sim_connect_rs::{sim_input_events::GearAndBrakes, SimConnect};
+    ///
+    /// let mut sc = SimConnect::open("My Awesome Application", None).unwrap();
+    /// sc.send(GearAndBrakes::GearDown).unwrap();
+    /// ```
+    pub fn send(&mut self, event: impl InputEvent) -> SimConnectResult<()> {
+        self.transmit_client_event(event, 0)
+    }
+
+    /* #endregion */
+
+    /* #region input_event */
+    /// Request subscription to an input event. Input events are located in the
+    /// `sim_connect_rs::sim_input_events` module.
+    ///
+    /// `callback` is invoked whenever the event is triggered, by the user or any client. Events
+    /// are handed to clients in order of `priority`, and a `mask`ed event is not handed on to
+    /// anyone after this client. Masking at `GroupPriority::HighestMaskable` intercepts the
+    /// event before the simulator acts on it. `GroupPriority::Highest` cannot mask events, so
+    /// asking it to returns `HResult::InvalidArg`.
+    ///
+    /// Subscribing to an event again replaces the callback, priority and masking.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sim_connect_rs::{
+    ///     sim_event_args::GroupPriority, sim_input_events::GearAndBrakes, SimConnect,
+    /// };
+    ///
+    /// let mut sc = SimConnect::open("My Awesome Application", None).unwrap();
+    /// // Keep the simulator from applying the brakes, and do something else instead
+    /// sc.subscribe_to_input_event(
+    ///     GearAndBrakes::Brakes,
+    ///     GroupPriority::HighestMaskable,
+    ///     true,
+    ///     |_| println!("brakes!"),
+    /// )
+    /// .unwrap();
+    /// ```
+    pub fn subscribe_to_input_event(
+        &mut self,
+        input_event: impl InputEvent,
+        priority: GroupPriority,
+        mask: bool,
+        callback: impl Fn(RecvClientEvent) + Send + Sync + 'static,
+    ) -> SimConnectResult<()> {
+        if mask && priority == GroupPriority::Highest {
+            return Err(HResult::InvalidArg.into());
+        }
+        let event_name = input_event.sc_string().to_string_lossy().into_owned();
+        let event_id = self.map_client_event(input_event)?;
+        let group_id = self.notification_group(priority);
+
+        self.register(Registration::ClientCallback(
+            event_id,
+            Some(Box::new(callback)),
+        ))?;
+
+        let subscribed = self.get_backend_lock().and_then(|mut backend| {
+            if let Some(old_group_id) = self.input_event_groups.get(&event_id) {
+                backend.remove_client_event(*old_group_id, event_id)?;
+                self.record_sent(
+                    &mut backend,
+                    format!("RemoveClientEvent({event_name})"),
+                    Waiter::None,
+                )?;
+            }
+            backend.add_client_event_to_notification_group(group_id, event_id, mask)?;
+            self.record_sent(
+                &mut backend,
+                format!("AddClientEventToNotificationGroup({event_name}, {priority:?})"),
+                Waiter::None,
+            )?;
+            backend.set_notification_group_priority(group_id, priority.into())?;
+            self.record_sent(
+                &mut backend,
+                format!("SetNotificationGroupPriority({priority:?})"),
+                Waiter::None,
+            )
+        });
+
+        match subscribed {
+            Ok(()) => {
+                self.input_event_groups.insert(event_id, group_id);
+            }
+            Err(_) => {
+                self.input_event_groups.remove(&event_id);
+                let _ = self.register(Registration::ClientCallback(event_id, None));
+            }
+        }
+        subscribed
+    }
+
+    /// Stops invoking the callback registered by `subscribe_to_input_event`
+    pub fn unsubscribe_from_input_event(
+        &mut self,
+        input_event: impl InputEvent,
+    ) -> SimConnectResult<()> {
+        let event_name = input_event.sc_string().to_string_lossy().into_owned();
+        let Some(event_id) = self.client_events.get(&event_name).copied() else {
+            return Ok(());
+        };
+        let Some(group_id) = self.input_event_groups.remove(&event_id) else {
+            return Ok(());
+        };
+
+        {
+            let mut backend = self.get_backend_lock()?;
+            backend.remove_client_event(group_id, event_id)?;
+            self.record_sent(
+                &mut backend,
+                format!("RemoveClientEvent({event_name})"),
+                Waiter::None,
+            )?;
+        }
+
+        self.register(Registration::ClientCallback(event_id, None))
+    }
+    /* #endregion */
+
+    /* #region aircraft_input_event */
+    #[cfg(feature = "async")]
+    /// Enumerates the input events of the loaded aircraft, such as the knobs and switches in
+    /// its cockpit. MSFS only. The future is driven by the listener thread, so it works on any
+    /// executor without blocking it.
+    pub async fn enumerate_aircraft_input_events(&mut self) -> SimConnectResult<InputEventCatalog> {
+        self.send_input_event_enumeration()?.await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Enumerates the input events of the loaded aircraft, such as the knobs and switches in
+    /// its cockpit. MSFS only.
+    ///
+    /// Input events are read, set and subscribed to by the hash in their descriptor. They
+    /// differ between aircraft, so should be enumerated again once another aircraft is loaded.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sim_connect_rs::SimConnect;
+    ///
+    /// let mut sc = SimConnect::open("My Awesome Application", None).unwrap();
+    /// let catalog = sc.enumerate_aircraft_input_events().unwrap();
+    /// if let Some(hash) = catalog.hash_of("LANDING_LIGHTS_SWITCH") {
+    ///     println!("landing lights at {:?}", sc.get_aircraft_input_event(hash).unwrap());
+    ///     sc.set_aircraft_input_event(hash, 1.0).unwrap();
+    /// }
+    /// ```
+    pub fn enumerate_aircraft_input_events(&mut self) -> SimConnectResult<InputEventCatalog> {
+        self.send_input_event_enumeration()?.recv()
+    }
+
+    #[cfg(feature = "async")]
+    /// Reads the value of an aircraft input event
+    pub async fn get_aircraft_input_event(
+        &mut self,
+        hash: u64,
+    ) -> SimConnectResult<InputEventValue> {
+        self.send_input_event_request(hash)?.await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Reads the value of an aircraft input event
+    pub fn get_aircraft_input_event(&mut self, hash: u64) -> SimConnectResult<InputEventValue> {
+        self.send_input_event_request(hash)?.recv()
+    }
+
+    #[cfg(feature = "async")]
+    /// Lists the types of the parameters an aircraft input event takes
+    pub async fn enumerate_aircraft_input_event_params(
+        &mut self,
+        hash: u64,
+    ) -> SimConnectResult<Vec<String>> {
+        self.send_input_event_params_request(hash)?.await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Lists the types of the parameters an aircraft input event takes
+    pub fn enumerate_aircraft_input_event_params(
+        &mut self,
+        hash: u64,
+    ) -> SimConnectResult<Vec<String>> {
+        self.send_input_event_params_request(hash)?.recv()
+    }
+
+    /// Sets the value of an aircraft input event. The value must be of the type the event
+    /// was enumerated with, otherwise the simulator raises `SetInputEventFailed`.
+    pub fn set_aircraft_input_event(
+        &mut self,
+        hash: u64,
+        value: impl Into<InputEventValue>,
+    ) -> SimConnectResult<()> {
+        let value = value.into();
+        let bytes = match &value {
+            InputEventValue::Double(value) => value.to_le_bytes().to_vec(),
+            InputEventValue::String(value) => CString::new(value.as_str())?.into_bytes_with_nul(),
+        };
+
+        let mut backend = self.get_backend_lock()?;
+        backend.set_input_event(hash, &bytes)?;
+        self.record_sent(
+            &mut backend,
+            format!("SetInputEvent({hash:#x}, {value:?})"),
+            Waiter::None,
+        )
+    }
+
+    /// Invokes `callback` with the new value whenever an aircraft input event changes.
+    /// Subscribing to an event again replaces the callback.
+    pub fn subscribe_to_aircraft_input_event(
+        &mut self,
+        hash: u64,
+        callback: impl Fn(InputEventValue) + Send + Sync + 'static,
+    ) -> SimConnectResult<()> {
+        self.register(Registration::InputEventCallback(
+            hash,
+            Some(Box::new(callback)),
+        ))?;
+
+        let subscribed = self.get_backend_lock().and_then(|mut backend| {
+            backend.subscribe_input_event(hash)?;
+            self.record_sent(
+                &mut backend,
+                format!("SubscribeInputEvent({hash:#x})"),
+                Waiter::None,
+            )
+        });
+        if subscribed.is_err() {
+            let _ = self.register(Registration::InputEventCallback(hash, None));
+        }
+        subscribed
+    }
+
+    /// Stops invoking the callback registered by `subscribe_to_aircraft_input_event`
+    pub fn unsubscribe_from_aircraft_input_event(&mut self, hash: u64) -> SimConnectResult<()> {
+        {
+            let mut backend = self.get_backend_lock()?;
+            backend.unsubscribe_input_event(hash)?;
+            self.record_sent(
+                &mut backend,
+                format!("UnsubscribeInputEvent({hash:#x})"),
+                Waiter::None,
+            )?;
+        }
+        self.register(Registration::InputEventCallback(hash, None))
+    }
+    /* #endregion */
+
+    /* #region input_group */
+    /// Maps a keyboard chord or joystick input to a sim event, which the simulator then acts
+    /// on whenever the input is used while `group_id` is on. Input groups are created by
+    /// mapping their first input, and start off until `set_input_group_state` turns them on.
+    ///
+    /// `definition` follows SimConnect's grammar, e.g. `"shift+ctrl+g"`, `"joystick:0:button:3"`
+    /// or `"joystick:0:XAxis"`, and is validated before anything is sent. See
+    /// `input_definition::InputDefinition`. An axis sends its position as the event's parameter,
+    /// so it suits `_SET` events.
+    ///
+    /// A `mask`ed input is not handed on to input groups of a lower priority, including the
+    /// simulator's own bindings if the group is above `GroupPriority::Standard`.
+    pub fn map_input_to_event(
+        &mut self,
+        group_id: u32,
+        definition: &str,
+        event: impl InputEvent,
+        mask: bool,
+    ) -> SimConnectResult<()> {
+        let definition: InputDefinition = definition.parse()?;
+        let event_id = self.map_client_event(event)?;
+        self.send_input_mapping(group_id, definition, event_id, None, mask)
+    }
+
+    /// Maps a keyboard chord or joystick input to `callback`, which is invoked whenever the
+    /// input is used while `group_id` is on. See `map_input_to_event` for the input groups and
+    /// the definition grammar.
+    ///
+    /// The `data` handed to `callback` is `1` when a key or button is pressed and `0` when it
+    /// is released. Axes, sliders and POVs report their position instead, which is an `i32`
+    /// sent as a `u32`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sim_connect_rs::SimConnect;
+    ///
+    /// const COCKPIT_INPUTS: u32 = 1;
+    ///
+    /// let mut sc = SimConnect::open("My Awesome Application", None).unwrap();
+    /// sc.map_input(COCKPIT_INPUTS, "shift+ctrl+g", true, |event| {
+    ///     println!("gear chord {}", if event.data == 1 { "down" } else { "up" })
+    /// })
+    /// .unwrap();
+    /// sc.map_input(COCKPIT_INPUTS, "joystick:0:XAxis", false, |event| {
+    ///     println!("x axis at {}", event.data as i32)
+    /// })
+    /// .unwrap();
+    /// sc.set_input_group_state(COCKPIT_INPUTS, true).unwrap();
+    /// ```
+    pub fn map_input(
+        &mut self,
+        group_id: u32,
+        definition: &str,
+        mask: bool,
+        callback: impl Fn(RecvClientEvent) + Send + Sync + 'static,
+    ) -> SimConnectResult<()> {
+        let definition: InputDefinition = definition.parse()?;
+        // The input is mapped to a private client event, which is only delivered to the client
+        // through a notification group
+        let input_callback = InputCallback {
+            notification_group: self.notification_group(GroupPriority::Highest),
+            event_id: self.new_client_event_id(),
+        };
+        let InputCallback {
+            notification_group,
+            event_id,
+        } = input_callback;
+
+        let mut backend = self.get_backend_lock()?;
+        backend.map_client_event_to_sim_event(event_id, &CString::default())?;
+        self.record_sent(
+            &mut backend,
+            format!("MapClientEventToSimEvent() for {definition}"),
+            Waiter::None,
+        )?;
+        backend.add_client_event_to_notification_group(notification_group, event_id, false)?;
+        // From here on the event is in the notification group, and is removed again if the
+        // input can't be mapped to it
+        let added = self
+            .record_sent(
+                &mut backend,
+                format!("AddClientEventToNotificationGroup({definition}, {notification_group})"),
+                Waiter::None,
+            )
+            .and_then(|()| {
+                backend.set_notification_group_priority(
+                    notification_group,
+                    GroupPriority::Highest.into(),
+                )?;
+                self.record_sent(
+                    &mut backend,
+                    format!("SetNotificationGroupPriority({:?})", GroupPriority::Highest),
+                    Waiter::None,
+                )
+            });
+        drop(backend);
+
+        let mapped = added
+            .and_then(|()| {
+                self.register(Registration::ClientCallback(
+                    event_id,
+                    Some(Box::new(callback)),
+                ))
+            })
+            .and_then(|()| {
+                self.send_input_mapping(group_id, definition, event_id, Some(input_callback), mask)
             });
+        if mapped.is_err() {
+            if let Ok(mut backend) = self.get_backend_lock() {
+                let _ = self.release_input_callback(&mut backend, input_callback);
+            }
+        }
+        mapped
+    }
+
+    /// Removes an input mapped with `map_input` or `map_input_to_event` from `group_id`
+    pub fn remove_input_event(&mut self, group_id: u32, definition: &str) -> SimConnectResult<()> {
+        let definition: InputDefinition = definition.parse()?;
+        self.unmap_input(group_id, &definition)
+    }
+
+    /// Removes every input mapped to `group_id`
+    pub fn clear_input_group(&mut self, group_id: u32) -> SimConnectResult<()> {
+        let Some(mappings) = self.input_groups.remove(&group_id) else {
+            return Ok(());
+        };
+
+        let mut backend = self.get_backend_lock()?;
+        backend.clear_input_group(group_id)?;
+        self.record_sent(
+            &mut backend,
+            format!("ClearInputGroup({group_id})"),
+            Waiter::None,
+        )?;
+        for callback in mappings.into_iter().filter_map(|(_, callback)| callback) {
+            self.release_input_callback(&mut backend, callback)?;
+        }
+        Ok(())
+    }
+
+    /// Sets the priority of an input group. When several clients map the same input, the
+    /// groups are handed it in order of priority.
+    pub fn set_input_group_priority(
+        &mut self,
+        group_id: u32,
+        priority: GroupPriority,
+    ) -> SimConnectResult<()> {
+        let mut backend = self.get_backend_lock()?;
+        backend.set_input_group_priority(group_id, priority.into())?;
+        self.record_sent(
+            &mut backend,
+            format!("SetInputGroupPriority({group_id}, {priority:?})"),
+            Waiter::None,
+        )
+    }
+
+    /// Turns an input group on or off. Inputs of a group which is off are left to the
+    /// simulator.
+    pub fn set_input_group_state(&mut self, group_id: u32, enabled: bool) -> SimConnectResult<()> {
+        let state = if enabled {
+            bindings::SIMCONNECT_STATE_SIMCONNECT_STATE_ON
+        } else {
+            bindings::SIMCONNECT_STATE_SIMCONNECT_STATE_OFF
+        };
+        let mut backend = self.get_backend_lock()?;
+        backend.set_input_group_state(group_id, state)?;
+        self.record_sent(
+            &mut backend,
+            format!("SetInputGroupState({group_id}, {enabled})"),
+            Waiter::None,
+        )
+    }
+    /* #endregion */
+
+    /* #region get_latest_data */
+    #[cfg(feature = "async")]
+    /// Gets data on a sim object. Calls `register_struct` if it hasn't already been called.
+    /// The future is driven by the listener thread, so it works on any executor without
+    /// blocking it.
+    ///
+    /// Cancellation safe: if the future is dropped before the simulator answers, the answer is
+    /// discarded when it arrives.
+    pub async fn get_latest_data<T: SimConnectToStruct>(
+        &mut self,
+    ) -> SimConnectResult<T::ReturnType> {
+        self.register_struct::<T>()?;
+        let data = self.request_data_on_self_object::<T>()?.await?;
+        DataParser::<T>::new(false).parse(&data)
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Gets data on a sim object. Calls `register_struct` if it hasn't already been called.
+    /// Blocks until the simulator answers.
+    pub fn get_latest_data<T: SimConnectToStruct>(&mut self) -> SimConnectResult<T::ReturnType> {
+        self.register_struct::<T>()?;
+        let data = self.request_data_on_self_object::<T>()?.recv()?;
+        DataParser::<T>::new(false).parse(&data)
+    }
+
+    /* #endregion */
+
+    /* #region get_data_by_type */
+    #[cfg(feature = "async")]
+    /// Gets data on every object of `object_type` within `radius_meters` of the user's
+    /// aircraft, as `(object_id, data)` pairs. A radius of `0` only returns the user's aircraft.
+    /// Calls `register_struct` if it hasn't already been called.
+    ///
+    /// The simulator sends each object separately; they are collected into one snapshot
+    /// before the future resolves.
+    pub async fn get_data_by_type<T>(
+        &mut self,
+        object_type: SimObjectType,
+        radius_meters: u32,
+    ) -> SimConnectResult<Vec<(u32, T::ReturnType)>>
+    where
+        T: SimConnectToStruct + 'static,
+        T::ReturnType: Send + 'static,
+    {
+        self.send_data_by_type_request::<T>(object_type, radius_meters)?
+            .await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Gets data on every object of `object_type` within `radius_meters` of the user's
+    /// aircraft, as `(object_id, data)` pairs. A radius of `0` only returns the user's aircraft.
+    /// Calls `register_struct` if it hasn't already been called.
+    ///
+    /// The simulator sends each object separately; this blocks until all of them arrived.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sim_connect_rs::{SimConnect, SimConnectToStruct, StructToSimConnect, SimConnectDatum};
+    /// # use sim_connect_rs::{sim_data::DataWriter, SimConnectResult};
+    /// # #[derive(Clone)]
+    /// # struct Position;
+    /// # impl StructToSimConnect for Position {
+    /// #     fn get_fields() -> Vec<SimConnectDatum> { vec![] }
+    /// #     fn write_data(&self, _: &mut DataWriter) -> SimConnectResult<()> { Ok(()) }
+    /// # }
+    /// # impl SimConnectToStruct for Position {
+    /// #     type Error = ();
+    /// #     type ReturnType = Position;
+    /// #     unsafe fn parse_struct(_: std::ptr::NonNull<u32>) -> Result<Position, ()> { Ok(Position) }
+    /// # }
+    /// use sim_connect_rs::sim_event_args::SimObjectType;
+    ///
+    /// let mut sc = SimConnect::open("My Awesome Application", None).unwrap();
+    /// // Every aircraft within 50km
+    /// for (object_id, position) in sc
+    ///     .get_data_by_type::<Position>(SimObjectType::Aircraft, 50_000)
+    ///     .unwrap()
+    /// {
+    ///     println!("{object_id}");
+    /// }
+    /// ```
+    pub fn get_data_by_type<T>(
+        &mut self,
+        object_type: SimObjectType,
+        radius_meters: u32,
+    ) -> SimConnectResult<Vec<(u32, T::ReturnType)>>
+    where
+        T: SimConnectToStruct + 'static,
+        T::ReturnType: Send + 'static,
+    {
+        self.send_data_by_type_request::<T>(object_type, radius_meters)?
+            .recv()
+    }
+
+    /* #endregion */
+
+    /* #region get_data_on_object */
+    #[cfg(feature = "async")]
+    /// Gets data on the object `object_id`, such as one returned by `get_data_by_type` or
+    /// created by the client. Calls `register_struct` if it hasn't already been called.
+    /// Returns `SimConnectError::ObjectNotFound` if the simulator sends no data for it.
+    pub async fn get_data_on_object<T>(
+        &mut self,
+        object_id: impl Into<SimObjectId>,
+    ) -> SimConnectResult<T::ReturnType>
+    where
+        T: SimConnectToStruct + 'static,
+        T::ReturnType: Send + 'static,
+    {
+        let object_id = object_id.into().0;
+        let snapshot = self.send_data_on_object_request::<T>(object_id)?.await?;
+        single_object(snapshot, object_id)
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Gets data on the object `object_id`, such as one returned by `get_data_by_type` or
+    /// created by the client. Calls `register_struct` if it hasn't already been called.
+    /// Returns `SimConnectError::ObjectNotFound` if the simulator sends no data for it.
+    pub fn get_data_on_object<T>(
+        &mut self,
+        object_id: impl Into<SimObjectId>,
+    ) -> SimConnectResult<T::ReturnType>
+    where
+        T: SimConnectToStruct + 'static,
+        T::ReturnType: Send + 'static,
+    {
+        let object_id = object_id.into().0;
+        let snapshot = self.send_data_on_object_request::<T>(object_id)?.recv()?;
+        single_object(snapshot, object_id)
+    }
+
+    /* #endregion */
+
+    /* #region subscribe_data */
+
+    /// Subscribes to data on the user's aircraft, which the simulator sends every `request`
+    /// period until the returned `DataSubscription` is dropped. Calls `register_struct` if it
+    /// hasn't already been called.
+    ///
+    /// Values are iterated over, or streamed with the `async` feature.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sim_connect_rs::{SimConnect, SimConnectToStruct, StructToSimConnect, SimConnectDatum};
+    /// # use sim_connect_rs::{sim_data::DataWriter, SimConnectResult};
+    /// # #[derive(Clone)]
+    /// # struct Position;
+    /// # impl StructToSimConnect for Position {
+    /// #     fn get_fields() -> Vec<SimConnectDatum> { vec![] }
+    /// #     fn write_data(&self, _: &mut DataWriter) -> SimConnectResult<()> { Ok(()) }
+    /// # }
+    /// # impl SimConnectToStruct for Position {
+    /// #     type Error = ();
+    /// #     type ReturnType = Position;
+    /// #     unsafe fn parse_struct(_: std::ptr::NonNull<u32>) -> Result<Position, ()> { Ok(Position) }
+    /// # }
+    /// use sim_connect_rs::subscription::{DataRequest, Period};
+    ///
+    /// let mut sc = SimConnect::open("My Awesome Application", None).unwrap();
+    /// let positions = sc
+    ///     .subscribe_data::<Position>(DataRequest::new(Period::Second).changed())
+    ///     .unwrap();
+    /// # #[cfg(not(feature = "async"))]
+    /// for position in positions {
+    ///     let position = position.unwrap();
+    /// }
+    /// ```
+    pub fn subscribe_data<T>(
+        &mut self,
+        request: impl Into<DataRequest>,
+    ) -> SimConnectResult<DataSubscription<T>>
+    where
+        T: SimConnectToStruct + 'static,
+        T::ReturnType: Send + 'static,
+    {
+        let request: DataRequest = request.into();
+        self.register_struct::<T>()?;
+        let define_id = self.define_id::<T>()?;
+        let mut parser = DataParser::<T>::new(request.tagged);
+        let (feed, unsubscribe) = self.send_data_subscription(
+            std::any::type_name::<T>().to_owned(),
+            define_id,
+            request,
+            move |data| parser.parse(data),
+        )?;
+        Ok(DataSubscription::new(feed, unsubscribe))
+    }
+
+    /// Subscribes to the definition `define_id` of `data_name` on the user's aircraft, feeding
+    /// every answer read with `parse` to the returned `Feed` until the returned `Unsubscribe`
+    /// is called
+    fn send_data_subscription<V: Send + 'static>(
+        &mut self,
+        data_name: String,
+        define_id: u32,
+        request: DataRequest,
+        mut parse: impl FnMut(&RecvSimData) -> SimConnectResult<V> + Send + 'static,
+    ) -> SimConnectResult<(Feed<SimConnectResult<V>>, Unsubscribe)> {
+        let request_id = self.new_request_id();
+
+        let (feeder, feed) = queue::channel();
+        let mut remaining = request.answer_limit();
+        self.register(Registration::Subscription(
+            request_id,
+            Box::new(move |answer| {
+                let value = answer.and_then(|data| parse(&data));
+                if value.is_ok() {
+                    remaining = remaining.map(|remaining| remaining - 1);
+                }
+                feeder.send(value) && remaining != Some(0)
+            }),
+        ))?;
+
+        let requested = self.get_backend_lock().and_then(|mut backend| {
+            backend.request_data_on_sim_object(
+                request_id,
+                define_id,
+                bindings::SIMCONNECT_OBJECT_ID_USER,
+                request.period.into(),
+                request.flags(),
+                request.origin,
+                request.interval,
+                request.limit,
+            )?;
+            self.record_sent(
+                &mut backend,
+                format!(
+                    "RequestDataOnSimObject({:?}) for {data_name}",
+                    request.period
+                ),
+                Waiter::Data(request_id),
+            )
+        });
+        if let Err(e) = requested {
+            let _ = self.register(Registration::Unsubscribe(request_id));
+            return Err(e);
+        }
+
+        let backend = self.backend.clone();
+        let registrations = self.registration_sender.clone();
+        Ok((
+            feed,
+            Box::new(move || {
+                let _ = registrations.send(Registration::Unsubscribe(request_id));
+                backend
+                    .lock()
+                    .map_err(|_| SimConnectError::Poisoned)?
+                    .request_data_on_sim_object(
+                        request_id,
+                        define_id,
+                        bindings::SIMCONNECT_OBJECT_ID_USER,
+                        bindings::SIMCONNECT_PERIOD_SIMCONNECT_PERIOD_NEVER,
+                        bindings::SIMCONNECT_DATA_REQUEST_FLAG_DEFAULT,
+                        0,
+                        0,
+                        0,
+                    )
+            }),
+        ))
+    }
+
+    /* #endregion */
+
+    /* #region set_data_on_object */
+
+    /// Sets the simvars of `T` on the user's aircraft
+    pub fn set_data_on_self<T: StructToSimConnect>(&mut self, data: T) -> SimConnectResult<()> {
+        self.set_data_on_object(SimObjectId::USER, data)
+    }
+
+    /// Sets the simvars of `T` on `object_id`, such as an object created by the client and
+    /// released from the simulator's AI with `release_control`. Calls `register_struct` if it
+    /// hasn't already been called.
+    ///
+    /// The fields are packed back to back as the data types of their datums, whatever the
+    /// layout of `T` in memory.
+    pub fn set_data_on_object<T: StructToSimConnect>(
+        &mut self,
+        object_id: impl Into<SimObjectId>,
+        data: T,
+    ) -> SimConnectResult<()> {
+        let object_id = object_id.into();
+        let mut writer = DataWriter::new::<T>();
+        data.write_data(&mut writer)?;
+        let data = writer.into_data()?;
+
+        self.send_data_set::<T>(
+            format!(
+                "SetDataOnSimObject({object_id}) for {}",
+                std::any::type_name::<T>()
+            ),
+            object_id,
+            bindings::SIMCONNECT_DATA_SET_FLAG_DEFAULT,
+            data.len() as u32,
+            data,
+        )
+    }
+
+    /// Sets an array of `T` on `object_id` in one call, for the definitions SimConnect accepts
+    /// arrays for, such as `SIMCONNECT_DATA_WAYPOINT`s. Every element must pack to the same
+    /// number of bytes, so `T` can't contain variable length strings of differing lengths.
+    pub fn set_data_array_on_object<T: StructToSimConnect>(
+        &mut self,
+        object_id: impl Into<SimObjectId>,
+        data: &[T],
+    ) -> SimConnectResult<()> {
+        let object_id = object_id.into();
+        let mut unit_size = None;
+        let mut array = Vec::new();
+        for element in data {
+            let mut writer = DataWriter::new::<T>();
+            element.write_data(&mut writer)?;
+            let element = writer.into_data()?;
+            if *unit_size.get_or_insert(element.len()) != element.len() {
+                return Err(SimConnectError::InvalidData(format!(
+                    "The elements of an array of {} are not all the same size",
+                    std::any::type_name::<T>()
+                )));
+            }
+            array.extend(element);
+        }
+        let Some(unit_size) = unit_size else {
+            return Err(SimConnectError::InvalidData(format!(
+                "An array of {} has no elements",
+                std::any::type_name::<T>()
+            )));
+        };
+
+        let array_count = data.len() as u32;
+        let call = format!(
+            "SetDataOnSimObject({object_id}) for [{}; {array_count}]",
+            std::any::type_name::<T>()
+        );
+        self.register_struct::<T>()?;
+        let define_id = self.define_id::<T>()?;
+        let mut backend = self.get_backend_lock()?;
+        backend.set_data_on_sim_object(
+            define_id,
+            object_id.0,
+            bindings::SIMCONNECT_DATA_SET_FLAG_DEFAULT,
+            array_count,
+            unit_size as u32,
+            &array,
+        )?;
+        self.record_sent(&mut backend, call, Waiter::None)
+    }
+
+    /// Sets only the datums of `T` with `datum_ids` on `object_id`, leaving the other simvars
+    /// of the definition as they are. With the derive, the id of a datum is the index of its
+    /// field.
+    pub fn set_tagged_data_on_object<T: StructToSimConnect>(
+        &mut self,
+        object_id: impl Into<SimObjectId>,
+        data: &T,
+        datum_ids: &[u32],
+    ) -> SimConnectResult<()> {
+        let object_id = object_id.into();
+        let mut writer = DataWriter::new::<T>();
+        data.write_data(&mut writer)?;
+        let data = writer.into_tagged(datum_ids)?;
+
+        self.send_data_set::<T>(
+            format!(
+                "SetDataOnSimObject({object_id}, tagged {datum_ids:?}) for {}",
+                std::any::type_name::<T>()
+            ),
+            object_id,
+            bindings::SIMCONNECT_DATA_SET_FLAG_TAGGED,
+            data.len() as u32,
+            data,
+        )
+    }
+
+    /// Sends a single, non array, `SetDataOnSimObject` for the definition of `T`
+    fn send_data_set<T: StructToSimConnect>(
+        &mut self,
+        call: String,
+        object_id: SimObjectId,
+        flags: u32,
+        unit_size: u32,
+        data: Vec<u8>,
+    ) -> SimConnectResult<()> {
+        self.register_struct::<T>()?;
+        let define_id = self.define_id::<T>()?;
+
+        let mut backend = self.get_backend_lock()?;
+        backend.set_data_on_sim_object(define_id, object_id.0, flags, 0, unit_size, &data)?;
+        self.record_sent(&mut backend, call, Waiter::None)
+    }
+
+    /// The definition id `register_struct` gave `T`
+    fn define_id<T: StructToSimConnect>(&self) -> SimConnectResult<u32> {
+        self.type_map
+            .get(&self.get_struct_name::<T>())
+            .copied()
+            .ok_or_else(|| SimConnectError::Unregistered(std::any::type_name::<T>().to_owned()))
+    }
+
+    /* #endregion */
+
+    /* #region data_definition */
+    #[cfg(feature = "async")]
+    /// Gets the values of `definition`, one per datum, on the object `object_id`. Registers the
+    /// definition if it hasn't been already. Returns `SimConnectError::ObjectNotFound` if the
+    /// simulator sends no data for it.
+    pub async fn get_values_on_object(
+        &mut self,
+        definition: &DataDefinition,
+        object_id: impl Into<SimObjectId>,
+    ) -> SimConnectResult<Vec<SimValue>> {
+        let object_id = object_id.into().0;
+        let snapshot = self
+            .send_values_on_object_request(definition, object_id)?
+            .await?;
+        single_object(snapshot, object_id)
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Gets the values of `definition`, one per datum, on the object `object_id`. Registers the
+    /// definition if it hasn't been already. Returns `SimConnectError::ObjectNotFound` if the
+    /// simulator sends no data for it.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sim_connect_rs::{
+    ///     data_definition::DataDefinition, sim_objects::SimObjectId, sim_var_types::SimVarType,
+    ///     SimConnect,
+    /// };
+    ///
+    /// let mut sc = SimConnect::open("My Awesome Application", None).unwrap();
+    /// // Picked by the user at runtime
+    /// let definition = DataDefinition::new()
+    ///     .add_named("GENERAL ENG RPM:1", "rpm", SimVarType::F64)
+    ///     .add_named("ATC ID", "", SimVarType::String64);
+    /// let values = sc
+    ///     .get_values_on_object(&definition, SimObjectId::USER)
+    ///     .unwrap();
+    /// for (name, value) in definition.to_map(values) {
+    ///     println!("{name}: {value:?}");
+    /// }
+    /// ```
+    pub fn get_values_on_object(
+        &mut self,
+        definition: &DataDefinition,
+        object_id: impl Into<SimObjectId>,
+    ) -> SimConnectResult<Vec<SimValue>> {
+        let object_id = object_id.into().0;
+        let snapshot = self
+            .send_values_on_object_request(definition, object_id)?
+            .recv()?;
+        single_object(snapshot, object_id)
+    }
+
+    #[cfg(feature = "async")]
+    /// Gets the values of `definition` on every object of `object_type` within
+    /// `radius_meters` of the user's aircraft, as `(object_id, values)` pairs, like
+    /// `get_data_by_type`
+    pub async fn get_values_by_type(
+        &mut self,
+        definition: &DataDefinition,
+        object_type: SimObjectType,
+        radius_meters: u32,
+    ) -> SimConnectResult<Vec<(u32, Vec<SimValue>)>> {
+        self.send_values_by_type_request(definition, object_type, radius_meters)?
+            .await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Gets the values of `definition` on every object of `object_type` within
+    /// `radius_meters` of the user's aircraft, as `(object_id, values)` pairs, like
+    /// `get_data_by_type`
+    pub fn get_values_by_type(
+        &mut self,
+        definition: &DataDefinition,
+        object_type: SimObjectType,
+        radius_meters: u32,
+    ) -> SimConnectResult<Vec<(u32, Vec<SimValue>)>> {
+        self.send_values_by_type_request(definition, object_type, radius_meters)?
+            .recv()
+    }
+
+    /// Subscribes to the values of `definition` on the user's aircraft, like `subscribe_data`.
+    /// Registers the definition if it hasn't been already.
+    pub fn subscribe_values(
+        &mut self,
+        definition: &DataDefinition,
+        request: impl Into<DataRequest>,
+    ) -> SimConnectResult<ValuesSubscription> {
+        let request: DataRequest = request.into();
+        let define_id = self.definition_id(definition)?;
+        let parser = definition.clone();
+        let mut values = Vec::new();
+        let (feed, unsubscribe) =
+            self.send_data_subscription(definition.to_string(), define_id, request, move |data| {
+                match request.tagged {
+                    true => {
+                        parser.read_tagged(data, &mut values)?;
+                        Ok(values.clone())
+                    }
+                    false => parser.read(data),
+                }
+            })?;
+        Ok(ValuesSubscription::new(feed, unsubscribe))
+    }
+
+    /// Sets `values`, one for each datum of `definition`, on `object_id`. Registers the
+    /// definition if it hasn't been already.
+    pub fn set_values_on_object(
+        &mut self,
+        definition: &DataDefinition,
+        object_id: impl Into<SimObjectId>,
+        values: &[SimValue],
+    ) -> SimConnectResult<()> {
+        let object_id = object_id.into();
+        let data = definition.write(values)?;
+        let define_id = self.definition_id(definition)?;
+
+        let mut backend = self.get_backend_lock()?;
+        backend.set_data_on_sim_object(
+            define_id,
+            object_id.0,
+            bindings::SIMCONNECT_DATA_SET_FLAG_DEFAULT,
+            0,
+            data.len() as u32,
+            &data,
+        )?;
+        self.record_sent(
+            &mut backend,
+            format!("SetDataOnSimObject({object_id}) for {definition}"),
+            Waiter::None,
+        )
+    }
+
+    /* #endregion */
+
+    /* #region ai_objects */
+    #[cfg(feature = "async")]
+    /// Creates an aircraft parked at `airport` (e.g. `"KSEA"`), under the simulator's ATC.
+    /// `container_title` is the `title` of the aircraft in its `aircraft.cfg`.
+    pub async fn create_parked_atc_aircraft(
+        &mut self,
+        container_title: &str,
+        tail_number: &str,
+        airport: &str,
+    ) -> SimConnectResult<SimObjectId> {
+        self.send_parked_atc_aircraft(container_title, tail_number, airport)?
+            .await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Creates an aircraft parked at `airport` (e.g. `"KSEA"`), under the simulator's ATC.
+    /// `container_title` is the `title` of the aircraft in its `aircraft.cfg`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sim_connect_rs::SimConnect;
+    ///
+    /// let mut sc = SimConnect::open("My Awesome Application", None).unwrap();
+    /// let object_id = sc
+    ///     .create_parked_atc_aircraft("Cessna Skyhawk Asobo", "N172SP", "KSEA")
+    ///     .unwrap();
+    /// sc.set_aircraft_flight_plan(object_id, "C:\\Flight Plans\\KSEA-KPAE")
+    ///     .unwrap();
+    /// ```
+    pub fn create_parked_atc_aircraft(
+        &mut self,
+        container_title: &str,
+        tail_number: &str,
+        airport: &str,
+    ) -> SimConnectResult<SimObjectId> {
+        self.send_parked_atc_aircraft(container_title, tail_number, airport)?
+            .recv()
+    }
+
+    #[cfg(feature = "async")]
+    /// Creates an aircraft flying the `.PLN` flight plan at `flight_plan`, given without its
+    /// extension, under the simulator's ATC. `flight_plan_position` is the waypoint it starts
+    /// at, with the fraction of the leg to the next waypoint already flown, and `touch_and_go`
+    /// has it land and take off again at the destination.
+    pub async fn create_enroute_atc_aircraft(
+        &mut self,
+        container_title: &str,
+        tail_number: &str,
+        flight_number: i32,
+        flight_plan: &str,
+        flight_plan_position: f64,
+        touch_and_go: bool,
+    ) -> SimConnectResult<SimObjectId> {
+        self.send_enroute_atc_aircraft(
+            container_title,
+            tail_number,
+            flight_number,
+            flight_plan,
+            flight_plan_position,
+            touch_and_go,
+        )?
+        .await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Creates an aircraft flying the `.PLN` flight plan at `flight_plan`, given without its
+    /// extension, under the simulator's ATC. `flight_plan_position` is the waypoint it starts
+    /// at, with the fraction of the leg to the next waypoint already flown, and `touch_and_go`
+    /// has it land and take off again at the destination.
+    pub fn create_enroute_atc_aircraft(
+        &mut self,
+        container_title: &str,
+        tail_number: &str,
+        flight_number: i32,
+        flight_plan: &str,
+        flight_plan_position: f64,
+        touch_and_go: bool,
+    ) -> SimConnectResult<SimObjectId> {
+        self.send_enroute_atc_aircraft(
+            container_title,
+            tail_number,
+            flight_number,
+            flight_plan,
+            flight_plan_position,
+            touch_and_go,
+        )?
+        .recv()
+    }
+
+    #[cfg(feature = "async")]
+    /// Creates an aircraft at `position`, outside of the simulator's ATC
+    pub async fn create_non_atc_aircraft(
+        &mut self,
+        container_title: &str,
+        tail_number: &str,
+        position: InitPosition,
+    ) -> SimConnectResult<SimObjectId> {
+        self.send_non_atc_aircraft(container_title, tail_number, position)?
+            .await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Creates an aircraft at `position`, outside of the simulator's ATC
+    pub fn create_non_atc_aircraft(
+        &mut self,
+        container_title: &str,
+        tail_number: &str,
+        position: InitPosition,
+    ) -> SimConnectResult<SimObjectId> {
+        self.send_non_atc_aircraft(container_title, tail_number, position)?
+            .recv()
+    }
+
+    #[cfg(feature = "async")]
+    /// Creates an object which is not an aircraft, such as a boat, ground vehicle or animal, at
+    /// `position`
+    pub async fn create_simulated_object(
+        &mut self,
+        container_title: &str,
+        position: InitPosition,
+    ) -> SimConnectResult<SimObjectId> {
+        self.send_simulated_object(container_title, position)?.await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Creates an object which is not an aircraft, such as a boat, ground vehicle or animal, at
+    /// `position`
+    pub fn create_simulated_object(
+        &mut self,
+        container_title: &str,
+        position: InitPosition,
+    ) -> SimConnectResult<SimObjectId> {
+        self.send_simulated_object(container_title, position)?
+            .recv()
+    }
+
+    /// Removes an object the client created. The simulator removes them all once the client
+    /// disconnects.
+    pub fn remove_object(&mut self, object_id: SimObjectId) -> SimConnectResult<()> {
+        self.send_ai_call(
+            format!("AIRemoveObject({object_id})"),
+            |backend, request_id| backend.ai_remove_object(object_id.0, request_id),
+        )
+    }
+
+    /// Gives an aircraft the client created the `.PLN` flight plan at `flight_plan`, given
+    /// without its extension
+    pub fn set_aircraft_flight_plan(
+        &mut self,
+        object_id: SimObjectId,
+        flight_plan: &str,
+    ) -> SimConnectResult<()> {
+        let path = CString::new(flight_plan)?;
+        self.send_ai_call(
+            format!("AISetAircraftFlightPlan({object_id}, {flight_plan})"),
+            |backend, request_id| {
+                backend.ai_set_aircraft_flight_plan(object_id.0, &path, request_id)
+            },
+        )
+    }
+
+    /// Stops the simulator's AI from controlling an object the client created, so that its
+    /// position and state can be driven with `set_data_on_object` instead
+    pub fn release_control(&mut self, object_id: SimObjectId) -> SimConnectResult<()> {
+        self.send_ai_call(
+            format!("AIReleaseControl({object_id})"),
+            |backend, request_id| backend.ai_release_control(object_id.0, request_id),
+        )
+    }
+
+    /* #endregion */
+
+    /* #region flights */
+    #[cfg(feature = "async")]
+    /// Loads the `.FLT` flight at `file_name`, resolving with the file name the simulator
+    /// reports once it has loaded. `file_name` can be given with or without its extension.
+    ///
+    /// The simulator fires `FlightLoaded` for flights loaded any other way too. Events are
+    /// matched to calls by file name, or by the file's stem when the simulator reports it by
+    /// another path, so this keeps waiting if no event names the flight. Unsubscribing from
+    /// `FlightLoaded` while this is waiting fails it.
+    pub async fn flight_load(&mut self, file_name: &str) -> SimConnectResult<String> {
+        self.send_flight_load(file_name)?.await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Loads the `.FLT` flight at `file_name`, returning the file name the simulator reports
+    /// once it has loaded. `file_name` can be given with or without its extension.
+    ///
+    /// The simulator fires `FlightLoaded` for flights loaded any other way too. Events are
+    /// matched to calls by file name, or by the file's stem when the simulator reports it by
+    /// another path, so this keeps waiting if no event names the flight. Unsubscribing from
+    /// `FlightLoaded` while this is waiting fails it.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn main() -> Result<(), sim_connect_rs::SimConnectError> {
+    /// let mut sc = sim_connect_rs::SimConnect::open("Instructor Station", None)?;
+    ///
+    /// let scenario = sc.flight_save("scenarios/engine_failure", "Engine failure", "")?;
+    /// // ... the student flies, then is reset to the saved scenario
+    /// sc.flight_load(&scenario)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn flight_load(&mut self, file_name: &str) -> SimConnectResult<String> {
+        self.send_flight_load(file_name)?.recv()
+    }
+
+    #[cfg(feature = "async")]
+    /// Saves the current flight to `file_name` with a title and description, resolving with
+    /// the file name the simulator reports once it has been saved
+    pub async fn flight_save(
+        &mut self,
+        file_name: &str,
+        title: &str,
+        description: &str,
+    ) -> SimConnectResult<String> {
+        self.send_flight_save(file_name, title, description)?.await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Saves the current flight to `file_name` with a title and description, returning the
+    /// file name the simulator reports once it has been saved
+    pub fn flight_save(
+        &mut self,
+        file_name: &str,
+        title: &str,
+        description: &str,
+    ) -> SimConnectResult<String> {
+        self.send_flight_save(file_name, title, description)?.recv()
+    }
+
+    #[cfg(feature = "async")]
+    /// Loads and activates the `.PLN` flight plan at `file_name`, given without its extension,
+    /// resolving with the file name the simulator reports once it is active
+    pub async fn flight_plan_load(&mut self, file_name: &str) -> SimConnectResult<String> {
+        self.send_flight_plan_load(file_name)?.await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Loads and activates the `.PLN` flight plan at `file_name`, given without its extension,
+    /// returning the file name the simulator reports once it is active
+    pub fn flight_plan_load(&mut self, file_name: &str) -> SimConnectResult<String> {
+        self.send_flight_plan_load(file_name)?.recv()
+    }
+
+    /* #endregion */
+
+    /* #region client_data */
+
+    /// Creates a client data area holding a `T`, which other clients, such as a WASM gauge,
+    /// can map to share the data. The area is named `name` prefixed with the program name,
+    /// which `ClientDataArea::name` returns for the other clients to map.
+    ///
+    /// A `read_only` area can only be written to by this client. Creating an area which
+    /// already exists raises `SimConnectException::AlreadyCreated`, reported by
+    /// `take_exceptions`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sim_connect_rs::{client_data::ClientDataField, ClientData, SimConnect};
+    ///
+    /// #[derive(Clone, Copy)]
+    /// #[repr(C)]
+    /// struct Lights {
+    ///     beacon: i32,
+    /// }
+    /// # unsafe impl ClientData for Lights {
+    /// #     fn fields() -> Vec<ClientDataField> { vec![] }
+    /// # }
+    ///
+    /// let mut sc = SimConnect::open("My Awesome Application", None).unwrap();
+    /// let lights = sc.create_client_data::<Lights>(".Lights", false).unwrap();
+    /// sc.set_client_data(&lights, &Lights { beacon: 1 }).unwrap();
+    /// ```
+    pub fn create_client_data<T: ClientData>(
+        &mut self,
+        name: &str,
+        read_only: bool,
+    ) -> SimConnectResult<ClientDataArea<T>> {
+        let size = std::mem::size_of::<T>();
+        if size > bindings::SIMCONNECT_CLIENTDATA_MAX_SIZE as usize {
+            return Err(HResult::InvalidArg.into());
         }
-        Ok(())
+
+        let name = self.get_client_data_name(name);
+        let client_data_id = self.client_data_id(&name)?;
+        let flags = match read_only {
+            true => bindings::SIMCONNECT_CREATE_CLIENT_DATA_FLAG_READ_ONLY,
+            false => bindings::SIMCONNECT_CREATE_CLIENT_DATA_FLAG_DEFAULT,
+        };
+        {
+            let mut backend = self.get_backend_lock()?;
+            backend.create_client_data(client_data_id, size as u32, flags)?;
+            self.record_sent(
+                &mut backend,
+                format!("CreateClientData({name}, {size} bytes)"),
+                Waiter::None,
+            )?;
+        }
+
+        let define_id = self.client_data_definition::<T>()?;
+        Ok(ClientDataArea::new(name, client_data_id, define_id))
+    }
+
+    /// Maps a client data area holding a `T` which another client created, by its full name
+    pub fn map_client_data<T: ClientData>(
+        &mut self,
+        name: &str,
+    ) -> SimConnectResult<ClientDataArea<T>> {
+        let client_data_id = self.client_data_id(name)?;
+        let define_id = self.client_data_definition::<T>()?;
+        Ok(ClientDataArea::new(
+            name.to_owned(),
+            client_data_id,
+            define_id,
+        ))
+    }
+
+    /// Writes `value` to a client data area. Writing to a read-only area created by another
+    /// client raises an exception, reported by `take_exceptions`.
+    pub fn set_client_data<T: ClientData>(
+        &mut self,
+        area: &ClientDataArea<T>,
+        value: &T,
+    ) -> SimConnectResult<()> {
+        let mut backend = self.get_backend_lock()?;
+        backend.set_client_data(
+            area.client_data_id(),
+            area.define_id(),
+            bindings::SIMCONNECT_CLIENT_DATA_SET_FLAG_DEFAULT,
+            &value.to_bytes(),
+        )?;
+        self.record_sent(
+            &mut backend,
+            format!(
+                "SetClientData({}) for {}",
+                area.name(),
+                std::any::type_name::<T>()
+            ),
+            Waiter::None,
+        )
+    }
+
+    #[cfg(feature = "async")]
+    /// Reads the current contents of a client data area
+    pub async fn get_client_data<T: ClientData>(
+        &mut self,
+        area: &ClientDataArea<T>,
+    ) -> SimConnectResult<T> {
+        let call = client_data_call(area);
+        let answer =
+            self.send_client_data_request(area.client_data_id(), area.define_id(), call)?;
+        client_data_from(&answer.await?)
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Reads the current contents of a client data area. Blocks until the simulator answers.
+    pub fn get_client_data<T: ClientData>(
+        &mut self,
+        area: &ClientDataArea<T>,
+    ) -> SimConnectResult<T> {
+        let call = client_data_call(area);
+        let answer =
+            self.send_client_data_request(area.client_data_id(), area.define_id(), call)?;
+        client_data_from(&answer.recv()?)
+    }
+
+    /// Subscribes to the contents of a client data area, which the simulator sends every
+    /// `request` period until the returned `ClientDataSubscription` is dropped.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sim_connect_rs::{client_data::ClientDataField, ClientData, SimConnect};
+    /// # #[derive(Clone, Copy)]
+    /// # #[repr(C)]
+    /// # struct Lights {
+    /// #     beacon: i32,
+    /// # }
+    /// # unsafe impl ClientData for Lights {
+    /// #     fn fields() -> Vec<ClientDataField> { vec![] }
+    /// # }
+    /// use sim_connect_rs::subscription::{ClientDataPeriod, ClientDataRequest};
+    ///
+    /// let mut sc = SimConnect::open("My Awesome Application", None).unwrap();
+    /// let area = sc.map_client_data::<Lights>("MyGauge.Lights").unwrap();
+    /// let changes = sc
+    ///     .subscribe_client_data(&area, ClientDataRequest::new(ClientDataPeriod::OnSet).changed())
+    ///     .unwrap();
+    /// # #[cfg(not(feature = "async"))]
+    /// for lights in changes {
+    ///     println!("beacon: {}", lights.unwrap().beacon);
+    /// }
+    /// ```
+    pub fn subscribe_client_data<T: ClientData>(
+        &mut self,
+        area: &ClientDataArea<T>,
+        request: impl Into<ClientDataRequest>,
+    ) -> SimConnectResult<ClientDataSubscription<T>> {
+        let request: ClientDataRequest = request.into();
+        let (client_data_id, define_id) = (area.client_data_id(), area.define_id());
+
+        let (feeder, feed) = queue::channel();
+        let mut remaining = request.answer_limit();
+        let request_id = self.send_client_data_subscription(
+            client_data_id,
+            define_id,
+            &request,
+            format!(
+                "RequestClientData({}, {:?}) for {}",
+                area.name(),
+                request.period,
+                std::any::type_name::<T>()
+            ),
+            Box::new(move |answer| {
+                let value = answer.and_then(|data| client_data_from(&data));
+                if value.is_ok() {
+                    remaining = remaining.map(|remaining| remaining - 1);
+                }
+                feeder.send(value) && remaining != Some(0)
+            }),
+        )?;
+
+        let backend = self.backend.clone();
+        let registrations = self.registration_sender.clone();
+        Ok(ClientDataSubscription::new(
+            feed,
+            Box::new(move || {
+                let _ = registrations.send(Registration::Unsubscribe(request_id));
+                backend
+                    .lock()
+                    .map_err(|_| SimConnectError::Poisoned)?
+                    .request_client_data(
+                        client_data_id,
+                        request_id,
+                        define_id,
+                        bindings::SIMCONNECT_CLIENT_DATA_PERIOD_SIMCONNECT_CLIENT_DATA_PERIOD_NEVER,
+                        bindings::SIMCONNECT_CLIENT_DATA_REQUEST_FLAG_DEFAULT,
+                        0,
+                        0,
+                        0,
+                    )
+            }),
+        ))
+    }
+
+    /* #endregion */
+
+    /* #region wasm_bridge */
+    #[cfg(feature = "async")]
+    /// Resolves once the WASM module has answered a ping, which tells whether it is installed
+    /// and running. Fails with the exception raised if its client data areas don't exist.
+    ///
+    /// Local (`L:`) variables and `H:` events can't be reached through SimConnect itself, so
+    /// `read_lvar`, `set_lvar` and `fire_hevent` go through a WASM module running in the
+    /// simulator, which speaks the protocol of MobiFlight's module described in `wasm_bridge`.
+    /// The first of them asks the module for a channel named after the program, which it
+    /// answers on from then on.
+    pub async fn ping_wasm_bridge(&mut self) -> SimConnectResult<()> {
+        self.connect_wasm_bridge().await?;
+        self.send_bridge_ping()?.await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Blocks until the WASM module has answered a ping, which tells whether it is installed
+    /// and running. Fails with the exception raised if its client data areas don't exist.
+    ///
+    /// Local (`L:`) variables and `H:` events can't be reached through SimConnect itself, so
+    /// `read_lvar`, `set_lvar` and `fire_hevent` go through a WASM module running in the
+    /// simulator, which speaks the protocol of MobiFlight's module described in `wasm_bridge`.
+    /// The first of them asks the module for a channel named after the program, which it
+    /// answers on from then on.
+    pub fn ping_wasm_bridge(&mut self) -> SimConnectResult<()> {
+        self.connect_wasm_bridge()?;
+        self.send_bridge_ping()?.recv()
+    }
+
+    #[cfg(feature = "async")]
+    /// Reads a local variable, e.g. `"A32NX_AUTOPILOT_1_ACTIVE"`, through the WASM bridge.
+    ///
+    /// The variable is registered with the module the first time it is read, after which the
+    /// module keeps its slot up to date every frame. The module only holds `f32` values.
+    pub async fn read_lvar(&mut self, name: &str) -> SimConnectResult<f64> {
+        self.connect_wasm_bridge().await?;
+        let variable = format!("(L:{name})");
+        let (define_id, registered) = self.bridge_variable(&variable)?;
+        if let Some(registered) = registered {
+            registered.await?;
+        }
+        bridge_value_from(
+            &self
+                .send_bridge_value_request(&variable, define_id)?
+                .await?,
+        )
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Reads a local variable, e.g. `"A32NX_AUTOPILOT_1_ACTIVE"`, through the WASM bridge.
+    /// Blocks until the module answers.
+    ///
+    /// The variable is registered with the module the first time it is read, after which the
+    /// module keeps its slot up to date every frame. The module only holds `f32` values.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sim_connect_rs::SimConnect;
+    ///
+    /// let mut sc = SimConnect::open("My Awesome Application", None).unwrap();
+    /// if sc.read_lvar("A32NX_AUTOPILOT_1_ACTIVE").unwrap() != 0.0 {
+    ///     sc.fire_hevent("A320_Neo_CDU_1_BTN_INIT").unwrap();
+    /// }
+    /// ```
+    pub fn read_lvar(&mut self, name: &str) -> SimConnectResult<f64> {
+        self.connect_wasm_bridge()?;
+        let variable = format!("(L:{name})");
+        let (define_id, registered) = self.bridge_variable(&variable)?;
+        if let Some(registered) = registered {
+            registered.recv()?;
+        }
+        bridge_value_from(
+            &self
+                .send_bridge_value_request(&variable, define_id)?
+                .recv()?,
+        )
+    }
+
+    #[cfg(feature = "async")]
+    /// Sets a local variable through the WASM bridge. Returns `HResult::InvalidArg` if `value`
+    /// isn't finite or `name` can't be written into calculator code.
+    pub async fn set_lvar(&mut self, name: &str, value: f64) -> SimConnectResult<()> {
+        self.execute_calculator_code(&set_lvar_code(name, value)?)
+            .await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Sets a local variable through the WASM bridge. Returns `HResult::InvalidArg` if `value`
+    /// isn't finite or `name` can't be written into calculator code.
+    pub fn set_lvar(&mut self, name: &str, value: f64) -> SimConnectResult<()> {
+        self.execute_calculator_code(&set_lvar_code(name, value)?)
+    }
+
+    #[cfg(feature = "async")]
+    /// Fires an `H:` event, e.g. `"A320_Neo_CDU_1_BTN_INIT"`, through the WASM bridge
+    pub async fn fire_hevent(&mut self, name: &str) -> SimConnectResult<()> {
+        self.execute_calculator_code(&format!("(>H:{name})")).await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Fires an `H:` event, e.g. `"A320_Neo_CDU_1_BTN_INIT"`, through the WASM bridge
+    pub fn fire_hevent(&mut self, name: &str) -> SimConnectResult<()> {
+        self.execute_calculator_code(&format!("(>H:{name})"))
+    }
+
+    #[cfg(feature = "async")]
+    /// Has the WASM module execute calculator code, e.g. `"1 (>L:MY_VAR) (>H:MY_EVENT)"`.
+    /// Commands are at most 255 bytes long, anything longer is an invalid argument.
+    pub async fn execute_calculator_code(&mut self, code: &str) -> SimConnectResult<()> {
+        self.connect_wasm_bridge().await?;
+        self.send_bridge_command(BridgeCommand::Execute(code.to_owned()))
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Has the WASM module execute calculator code, e.g. `"1 (>L:MY_VAR) (>H:MY_EVENT)"`.
+    /// Commands are at most 255 bytes long, anything longer is an invalid argument.
+    pub fn execute_calculator_code(&mut self, code: &str) -> SimConnectResult<()> {
+        self.connect_wasm_bridge()?;
+        self.send_bridge_command(BridgeCommand::Execute(code.to_owned()))
+    }
+
+    /* #endregion */
+
+    /* #region facility_data */
+    #[cfg(feature = "async")]
+    /// Reads the facility with the ICAO code `icao` from the simulator's navigation data, with
+    /// everything nested in it, such as the runways and procedures of an `Airport`. `region`
+    /// tells apart facilities sharing a code, and is needed for most navaids. Resolves to `None`
+    /// if there is no such facility. MSFS only.
+    pub async fn get_facility<F: Facility>(
+        &mut self,
+        icao: &str,
+        region: Option<&str>,
+    ) -> SimConnectResult<Option<F>> {
+        let record = self.send_facility_request::<F>(icao, region)?.await?;
+        record.as_ref().map(F::from_record).transpose()
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Reads the facility with the ICAO code `icao` from the simulator's navigation data, with
+    /// everything nested in it, such as the runways and procedures of an `Airport`. `region`
+    /// tells apart facilities sharing a code, and is needed for most navaids. Returns `None` if
+    /// there is no such facility. MSFS only.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sim_connect_rs::{facilities::Airport, SimConnect};
+    ///
+    /// let mut sc = SimConnect::open("My Awesome Application", None).unwrap();
+    /// if let Some(airport) = sc.get_facility::<Airport>("KSEA", None).unwrap() {
+    ///     for runway in &airport.runways {
+    ///         println!("{}/{}", runway.primary.ident(), runway.secondary.ident());
+    ///     }
+    /// }
+    /// ```
+    pub fn get_facility<F: Facility>(
+        &mut self,
+        icao: &str,
+        region: Option<&str>,
+    ) -> SimConnectResult<Option<F>> {
+        let record = self.send_facility_request::<F>(icao, region)?.recv()?;
+        record.as_ref().map(F::from_record).transpose()
+    }
+
+    #[cfg(feature = "async")]
+    /// Lists the facilities of `T`'s type in the simulator's cache, which holds those within
+    /// range of the user's aircraft. The answer may span several messages, which are merged
+    /// into one list.
+    pub async fn request_facilities_list<T: FacilityListEntry>(
+        &mut self,
+    ) -> SimConnectResult<Vec<T>> {
+        self.send_facilities_list_request::<T>()?.await
+    }
+
+    #[cfg(not(feature = "async"))]
+    /// Lists the facilities of `T`'s type in the simulator's cache, which holds those within
+    /// range of the user's aircraft. The answer may span several messages, which are merged
+    /// into one list.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sim_connect_rs::{facilities::AirportEntry, SimConnect};
+    ///
+    /// let mut sc = SimConnect::open("My Awesome Application", None).unwrap();
+    /// for airport in sc.request_facilities_list::<AirportEntry>().unwrap() {
+    ///     println!("{} at {}, {}", airport.icao, airport.latitude, airport.longitude);
+    /// }
+    /// ```
+    pub fn request_facilities_list<T: FacilityListEntry>(&mut self) -> SimConnectResult<Vec<T>> {
+        self.send_facilities_list_request::<T>()?.recv()
+    }
+
+    /// Follows the facilities of `T`'s type in the simulator's cache. Everything in the cache
+    /// is delivered as added first, then facilities are added and removed as the user's
+    /// aircraft moves. The simulator only keeps one subscription per type of facility.
+    ///
+    /// SimConnect only reports facilities added to the cache, so whenever some are, the whole
+    /// cache is listed again to find out which were dropped from it.
+    pub fn subscribe_to_facilities<T: FacilityListEntry>(
+        &mut self,
+    ) -> SimConnectResult<FacilitySubscription<T>> {
+        let list_type = T::LIST_TYPE;
+        let added_id = self.new_request_id();
+        let listed_id = self.new_request_id();
+
+        let (feeder, feed) = queue::channel();
+        let tracker = Arc::new(Mutex::new(FacilityTracker::<T>::new(feeder)));
+
+        let mut merger = FacilityListMerger::<T>::new();
+        let listed_tracker = tracker.clone();
+        self.register(Registration::FacilitiesList(
+            listed_id,
+            Box::new(move |answer| {
+                let mut tracker = listed_tracker.lock().unwrap_or_else(|e| e.into_inner());
+                match answer.and_then(|list| merger.merge(list)) {
+                    Ok(None) => true,
+                    Ok(Some(entries)) => tracker.replace(entries),
+                    Err(e) => tracker.fail(e),
+                }
+            }),
+        ))?;
+
+        let mut merger = FacilityListMerger::<T>::new();
+        let mut first = true;
+        let backend = self.backend.clone();
+        let packet_log = self.packet_log.clone();
+        let relist = move || -> SimConnectResult<()> {
+            let mut backend = backend.lock().map_err(|_| SimConnectError::Poisoned)?;
+            backend.request_facilities_list(list_type.into(), listed_id)?;
+            let send_id = backend.last_sent_packet_id()?;
+            packet_log
+                .lock()
+                .map_err(|_| SimConnectError::Poisoned)?
+                .record(
+                    send_id,
+                    format!("RequestFacilitiesList({list_type:?})"),
+                    Waiter::FacilitiesList(listed_id),
+                );
+            Ok(())
+        };
+        self.register(Registration::FacilitiesList(
+            added_id,
+            Box::new(move |answer| {
+                let mut tracker = tracker.lock().unwrap_or_else(|e| e.into_inner());
+                match answer.and_then(|list| merger.merge(list)) {
+                    Ok(None) => true,
+                    Ok(Some(entries)) => {
+                        let open = tracker.add(entries);
+                        // The first list is the whole cache, so nothing can have left it yet
+                        if std::mem::replace(&mut first, false) {
+                            return open;
+                        }
+                        match relist() {
+                            Ok(()) => open,
+                            Err(e) => open && tracker.fail(e),
+                        }
+                    }
+                    Err(e) => tracker.fail(e),
+                }
+            }),
+        ))?;
+
+        let requested = self.get_backend_lock().and_then(|mut backend| {
+            backend.subscribe_to_facilities(list_type.into(), added_id)?;
+            self.record_sent(
+                &mut backend,
+                format!("SubscribeToFacilities({list_type:?})"),
+                Waiter::FacilitiesList(added_id),
+            )
+        });
+        let registrations = self.registration_sender.clone();
+        let unregister = move || {
+            let _ = registrations.send(Registration::Unsubscribe(added_id));
+            let _ = registrations.send(Registration::Unsubscribe(listed_id));
+        };
+        if let Err(e) = requested {
+            unregister();
+            return Err(e);
+        }
+
+        let backend = self.backend.clone();
+        Ok(FacilitySubscription::new(
+            feed,
+            Box::new(move || {
+                unregister();
+                backend
+                    .lock()
+                    .map_err(|_| SimConnectError::Poisoned)?
+                    .unsubscribe_to_facilities(list_type.into())
+            }),
+        ))
+    }
+
+    /* #endregion */
+
+    /* #region exceptions */
+
+    /// Takes the exceptions raised by calls which nothing was waiting on the answer to, such as
+    /// `set_data_on_self` or `subscribe_to_system_event`. Exceptions raised by a data or system
+    /// state request are returned from that request instead. Messages from the simulator which
+    /// couldn't be read are reported here as `SimConnectError::Parse`.
+    pub fn take_exceptions(&self) -> Vec<SimConnectError> {
+        self.exception_receiver.try_iter().collect()
     }
 
     /* #endregion */
 }
 
-impl Drop for SimConnect {
+/// Takes the data out of the snapshot answering a request on a single object
+fn single_object<T>(snapshot: Vec<(u32, T)>, object_id: u32) -> SimConnectResult<T> {
+    snapshot
+        .into_iter()
+        .next()
+        .map(|(_, data)| data)
+        .ok_or(SimConnectError::ObjectNotFound(object_id))
+}
+
+/// Describes a `RequestClientData` call for a single answer, for exceptions it raises
+fn client_data_call<T: ClientData>(area: &ClientDataArea<T>) -> String {
+    format!(
+        "RequestClientData({}) for {}",
+        area.name(),
+        std::any::type_name::<T>()
+    )
+}
+
+/// Rebuilds a `ClientData` struct from the data answering a client data request
+fn client_data_from<T: ClientData>(data: &RecvSimData) -> SimConnectResult<T> {
+    T::from_bytes(data.get_data()).ok_or_else(|| SimConnectError::Parse {
+        recv_id: data.get_recv_id(),
+        reason: format!(
+            "{} bytes of data is too short for {}",
+            data.get_data().len(),
+            std::any::type_name::<T>()
+        ),
+    })
+}
+
+/// The calculator code setting the local variable `name` to `value`. Anything which would
+/// change the meaning of the code, such as a name with spaces or parentheses, or a value
+/// formatted as `NaN` or `inf`, is rejected.
+fn set_lvar_code(name: &str, value: f64) -> SimConnectResult<String> {
+    let invalid_name =
+        name.is_empty() || name.contains(|c: char| c.is_whitespace() || c == '(' || c == ')');
+    if invalid_name || !value.is_finite() {
+        return Err(HResult::InvalidArg.into());
+    }
+    Ok(format!("{value} (>L:{name})"))
+}
+
+/// Reads the `f32` in a slot of the WASM bridge's value area
+fn bridge_value_from(data: &RecvSimData) -> SimConnectResult<f64> {
+    let value = data
+        .get_data()
+        .get(..wasm_bridge::VALUE_SIZE)
+        .ok_or_else(|| SimConnectError::Parse {
+            recv_id: data.get_recv_id(),
+            reason: format!(
+                "{} bytes of data is too short for a bridge value",
+                data.get_data().len()
+            ),
+        })?;
+    Ok(f32::from_le_bytes(value.try_into().expect("slice is the size of an f32")) as f64)
+}
+
+impl<B: SimConnectBackend> Drop for SimConnect<B> {
     fn drop(&mut self) {
-        let mut should_quit = self.should_quit.write().unwrap();
-        *should_quit = true;
+        {
+            let mut should_quit = self.should_quit.write().unwrap();
+            *should_quit = true;
+        }
         if let Some(join_handle) = self.listen_handle.take() {
             let _ = join_handle.join();
         }
-        let handle = self.get_handle_lock().unwrap();
 
-        unsafe {
-            bindings::SimConnect_Close(handle.as_ptr());
+        if let Ok(mut backend) = self.get_backend_lock() {
+            let _ = backend.close();
         }
     }
 }
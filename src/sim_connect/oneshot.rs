@@ -0,0 +1,147 @@
+use sim_connect_data::error::{SimConnectError, SimConnectResult};
+#[cfg(feature = "async")]
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use std::{
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    task::Waker,
+};
+
+/// Creates a single use channel the listener answers a request through. The answer can be
+/// waited on from a thread, or awaited from any executor as the listener wakes the task itself.
+pub(crate) fn channel<T>() -> (Responder<T>, Answer<T>) {
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            value: None,
+            waker: None,
+            closed: false,
+        }),
+        ready: Condvar::new(),
+    });
+    (
+        Responder {
+            shared: shared.clone(),
+        },
+        Answer { shared },
+    )
+}
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    ready: Condvar,
+}
+
+struct State<T> {
+    value: Option<SimConnectResult<T>>,
+    waker: Option<Waker>,
+    /// Set once either half is dropped
+    closed: bool,
+}
+
+impl<T> State<T> {
+    fn take(&mut self) -> Option<SimConnectResult<T>> {
+        match self.value.take() {
+            Some(value) => Some(value),
+            None if self.closed => Some(Err(SimConnectError::ListenerStopped)),
+            None => None,
+        }
+    }
+}
+
+impl<T> Shared<T> {
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        // Nothing can panic while the lock is held, but don't lose an answer if it ever does
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn close(&self) {
+        let mut state = self.lock();
+        state.closed = true;
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+        self.ready.notify_all();
+    }
+}
+
+/// The listener's half of the channel
+pub(crate) struct Responder<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Responder<T> {
+    /// Answers the request. Fails with the value if nothing is waiting on the answer anymore.
+    pub fn send(self, value: SimConnectResult<T>) -> Result<(), SimConnectResult<T>> {
+        let mut state = self.shared.lock();
+        if state.closed {
+            return Err(value);
+        }
+        state.value = Some(value);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+        self.shared.ready.notify_all();
+        Ok(())
+    }
+
+    /// Returns `true` if the `Answer` has been dropped, e.g. because the future waiting on it
+    /// was cancelled
+    pub fn is_closed(&self) -> bool {
+        self.shared.lock().closed
+    }
+}
+
+impl<T> Drop for Responder<T> {
+    fn drop(&mut self) {
+        self.shared.close();
+    }
+}
+
+/// The caller's half of the channel. Resolves to `SimConnectError::ListenerStopped` if the
+/// listener goes away without answering.
+pub(crate) struct Answer<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Answer<T> {
+    /// Blocks the current thread until the request is answered
+    #[cfg(not(feature = "async"))]
+    pub fn recv(self) -> SimConnectResult<T> {
+        let mut state = self.shared.lock();
+        loop {
+            if let Some(value) = state.take() {
+                return value;
+            }
+            state = self
+                .shared
+                .ready
+                .wait(state)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T> Future for Answer<T> {
+    type Output = SimConnectResult<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.shared.lock();
+        match state.take() {
+            Some(value) => Poll::Ready(value),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T> Drop for Answer<T> {
+    fn drop(&mut self) {
+        self.shared.close();
+    }
+}
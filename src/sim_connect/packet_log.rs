@@ -0,0 +1,62 @@
+use sim_connect_data::sim_events::SystemEvent;
+use std::collections::VecDeque;
+
+/// How many sent packets are remembered. Exceptions arrive shortly after the call which caused
+/// them, so only the most recent packets need to be kept around.
+const CAPACITY: usize = 256;
+
+/// Who is waiting on the answer to a packet, and should therefore be handed any exception the
+/// packet raises
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Waiter {
+    /// Nobody is waiting. The exception is kept for `SimConnect::take_exceptions`
+    None,
+    /// Every later request using the data definition
+    Definition(u32),
+    /// The data request with the request id
+    Data(u32),
+    /// The oldest unanswered `request_system_state` for the state
+    SystemState(u32),
+    /// The input event enumeration or `GetInputEvent` call with the request id
+    InputEvent(u32),
+    /// The oldest unanswered `EnumerateInputEventParams` call for the input event hash
+    InputEventParams(u64),
+    /// The facility data request with the request id
+    FacilityData(u32),
+    /// The facility list request or subscription with the request id
+    FacilitiesList(u32),
+    /// The `AICreate*` call with the request id
+    CreateObject(u32),
+    /// The flight command waiting on the system event, with the request id
+    FlightFile(SystemEvent, u32),
+}
+
+pub(crate) struct SentPacket {
+    pub call: String,
+    pub waiter: Waiter,
+}
+
+/// The most recent packets sent to the simulator, keyed by the id returned from
+/// `SimConnect_GetLastSentPacketID`
+#[derive(Default)]
+pub(crate) struct PacketLog {
+    packets: VecDeque<(u32, SentPacket)>,
+}
+
+impl PacketLog {
+    pub fn record(&mut self, send_id: u32, call: String, waiter: Waiter) {
+        if self.packets.len() == CAPACITY {
+            self.packets.pop_front();
+        }
+        self.packets
+            .push_back((send_id, SentPacket { call, waiter }));
+    }
+
+    pub fn find(&self, send_id: u32) -> Option<&SentPacket> {
+        self.packets
+            .iter()
+            .rev()
+            .find(|(id, _)| *id == send_id)
+            .map(|(_, packet)| packet)
+    }
+}
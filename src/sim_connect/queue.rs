@@ -0,0 +1,131 @@
+#[cfg(feature = "async")]
+use std::task::{Context, Poll};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+    task::Waker,
+};
+
+/// Creates an unbounded channel the listener feeds a subscription through. Like
+/// `oneshot::channel`, the receiving half can block a thread or be polled from any executor.
+pub(crate) fn channel<T>() -> (Feeder<T>, Feed<T>) {
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            values: VecDeque::new(),
+            waker: None,
+            closed: false,
+        }),
+        ready: Condvar::new(),
+    });
+    (
+        Feeder {
+            shared: shared.clone(),
+        },
+        Feed { shared },
+    )
+}
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+    ready: Condvar,
+}
+
+struct State<T> {
+    values: VecDeque<T>,
+    waker: Option<Waker>,
+    /// Set once either half is dropped
+    closed: bool,
+}
+
+impl<T> Shared<T> {
+    fn lock(&self) -> MutexGuard<'_, State<T>> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn notify(&self, state: &mut State<T>) {
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+        self.ready.notify_all();
+    }
+
+    fn close(&self) {
+        let mut state = self.lock();
+        state.closed = true;
+        self.notify(&mut state);
+    }
+}
+
+/// The listener's half of the channel
+pub(crate) struct Feeder<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Feeder<T> {
+    /// Queues a value. Returns `false` if the `Feed` has been dropped.
+    pub fn send(&self, value: T) -> bool {
+        let mut state = self.shared.lock();
+        if state.closed {
+            return false;
+        }
+        state.values.push_back(value);
+        self.shared.notify(&mut state);
+        true
+    }
+}
+
+impl<T> Drop for Feeder<T> {
+    fn drop(&mut self) {
+        self.shared.close();
+    }
+}
+
+/// The subscriber's half of the channel. Ends once the listener drops the `Feeder`.
+pub(crate) struct Feed<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> Feed<T> {
+    /// Blocks until a value is queued, or returns `None` once the feed has ended
+    #[cfg(not(feature = "async"))]
+    pub fn recv(&self) -> Option<T> {
+        let mut state = self.shared.lock();
+        loop {
+            if let Some(value) = state.values.pop_front() {
+                return Some(value);
+            }
+            if state.closed {
+                return None;
+            }
+            state = self
+                .shared
+                .ready
+                .wait(state)
+                .unwrap_or_else(|e| e.into_inner());
+        }
+    }
+
+    /// Takes the next queued value without blocking
+    pub fn try_recv(&self) -> Option<T> {
+        self.shared.lock().values.pop_front()
+    }
+
+    #[cfg(feature = "async")]
+    pub fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let mut state = self.shared.lock();
+        match state.values.pop_front() {
+            Some(value) => Poll::Ready(Some(value)),
+            None if state.closed => Poll::Ready(None),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T> Drop for Feed<T> {
+    fn drop(&mut self) {
+        self.shared.close();
+    }
+}
@@ -0,0 +1,673 @@
+use super::queue::{Feed, Feeder};
+use sim_connect_data::{
+    client_data::ClientData,
+    data_definition::SimValue,
+    error::{SimConnectError, SimConnectResult},
+    facilities::{FacilityChange, FacilityListEntry},
+    recv_data::{RecvFacilitiesList, RecvSimData},
+    sim_var_types::SimVarType,
+    SimConnectToStruct,
+};
+use sim_connect_sys::bindings;
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    ptr::NonNull,
+};
+#[cfg(feature = "async")]
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// How often the simulator sends the data of a subscription
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    /// A single answer, after which the subscription ends
+    Once,
+    /// Every visual (rendered) frame
+    VisualFrame,
+    /// Every simulated frame, whether or not it is rendered
+    SimFrame,
+    /// Every second
+    Second,
+}
+
+impl From<Period> for bindings::SIMCONNECT_PERIOD {
+    fn from(period: Period) -> Self {
+        match period {
+            Period::Once => bindings::SIMCONNECT_PERIOD_SIMCONNECT_PERIOD_ONCE,
+            Period::VisualFrame => bindings::SIMCONNECT_PERIOD_SIMCONNECT_PERIOD_VISUAL_FRAME,
+            Period::SimFrame => bindings::SIMCONNECT_PERIOD_SIMCONNECT_PERIOD_SIM_FRAME,
+            Period::Second => bindings::SIMCONNECT_PERIOD_SIMCONNECT_PERIOD_SECOND,
+        }
+    }
+}
+
+/// Describes when the simulator answers a data subscription. A `Period` on its own converts
+/// into a request for every period.
+///
+/// # Example
+///
+/// ```
+/// use sim_connect_rs::subscription::{DataRequest, Period};
+///
+/// // Every 5th second, starting after a minute, and only when something changed
+/// let request = DataRequest::new(Period::Second)
+///     .origin(60)
+///     .interval(4)
+///     .changed();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DataRequest {
+    pub(crate) period: Period,
+    pub(crate) origin: u32,
+    pub(crate) interval: u32,
+    pub(crate) limit: u32,
+    pub(crate) changed: bool,
+    pub(crate) tagged: bool,
+}
+
+impl DataRequest {
+    pub fn new(period: Period) -> Self {
+        Self {
+            period,
+            origin: 0,
+            interval: 0,
+            limit: 0,
+            changed: false,
+            tagged: false,
+        }
+    }
+
+    /// Number of periods to let pass before the first answer
+    pub fn origin(mut self, origin: u32) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Number of periods to let pass between answers
+    pub fn interval(mut self, interval: u32) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Ends the subscription after `limit` answers. `0`, the default, never ends it.
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Only answer when a value changed since the last answer (`SIMCONNECT_DATA_REQUEST_FLAG_CHANGED`)
+    pub fn changed(mut self) -> Self {
+        self.changed = true;
+        self
+    }
+
+    /// Have the simulator send the data in tagged format (`SIMCONNECT_DATA_REQUEST_FLAG_TAGGED`).
+    /// Combined with `changed`, only the values which changed are sent, and the rest are
+    /// filled in from the previous answer.
+    pub fn tagged(mut self) -> Self {
+        self.tagged = true;
+        self
+    }
+
+    pub(crate) fn flags(&self) -> bindings::SIMCONNECT_DATA_REQUEST_FLAG {
+        let mut flags = bindings::SIMCONNECT_DATA_REQUEST_FLAG_DEFAULT;
+        if self.changed {
+            flags |= bindings::SIMCONNECT_DATA_REQUEST_FLAG_CHANGED;
+        }
+        if self.tagged {
+            flags |= bindings::SIMCONNECT_DATA_REQUEST_FLAG_TAGGED;
+        }
+        flags
+    }
+
+    /// Number of answers after which the simulator stops sending, or `None` if it never does
+    pub(crate) fn answer_limit(&self) -> Option<u32> {
+        match (self.period, self.limit) {
+            (Period::Once, _) => Some(1),
+            (_, 0) => None,
+            (_, limit) => Some(limit),
+        }
+    }
+}
+
+impl From<Period> for DataRequest {
+    fn from(period: Period) -> Self {
+        Self::new(period)
+    }
+}
+
+/// How often the simulator sends the data of a client data subscription
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientDataPeriod {
+    /// A single answer, after which the subscription ends
+    Once,
+    /// Every visual (rendered) frame
+    VisualFrame,
+    /// Whenever any client sets the data
+    OnSet,
+    /// Every second
+    Second,
+}
+
+impl From<ClientDataPeriod> for bindings::SIMCONNECT_CLIENT_DATA_PERIOD {
+    fn from(period: ClientDataPeriod) -> Self {
+        match period {
+            ClientDataPeriod::Once => {
+                bindings::SIMCONNECT_CLIENT_DATA_PERIOD_SIMCONNECT_CLIENT_DATA_PERIOD_ONCE
+            }
+            ClientDataPeriod::VisualFrame => {
+                bindings::SIMCONNECT_CLIENT_DATA_PERIOD_SIMCONNECT_CLIENT_DATA_PERIOD_VISUAL_FRAME
+            }
+            ClientDataPeriod::OnSet => {
+                bindings::SIMCONNECT_CLIENT_DATA_PERIOD_SIMCONNECT_CLIENT_DATA_PERIOD_ON_SET
+            }
+            ClientDataPeriod::Second => {
+                bindings::SIMCONNECT_CLIENT_DATA_PERIOD_SIMCONNECT_CLIENT_DATA_PERIOD_SECOND
+            }
+        }
+    }
+}
+
+/// Describes when the simulator answers a client data subscription. A `ClientDataPeriod` on
+/// its own converts into a request for every period.
+///
+/// # Example
+///
+/// ```
+/// use sim_connect_rs::subscription::{ClientDataPeriod, ClientDataRequest};
+///
+/// // Whenever the data is set, but only if it actually changed
+/// let request = ClientDataRequest::new(ClientDataPeriod::OnSet).changed();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientDataRequest {
+    pub(crate) period: ClientDataPeriod,
+    pub(crate) origin: u32,
+    pub(crate) interval: u32,
+    pub(crate) limit: u32,
+    pub(crate) changed: bool,
+}
+
+impl ClientDataRequest {
+    pub fn new(period: ClientDataPeriod) -> Self {
+        Self {
+            period,
+            origin: 0,
+            interval: 0,
+            limit: 0,
+            changed: false,
+        }
+    }
+
+    /// Number of periods to let pass before the first answer
+    pub fn origin(mut self, origin: u32) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Number of periods to let pass between answers
+    pub fn interval(mut self, interval: u32) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Ends the subscription after `limit` answers. `0`, the default, never ends it.
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    /// Only answer when a field changed by more than its epsilon since the last answer
+    /// (`SIMCONNECT_CLIENT_DATA_REQUEST_FLAG_CHANGED`)
+    pub fn changed(mut self) -> Self {
+        self.changed = true;
+        self
+    }
+
+    pub(crate) fn flags(&self) -> bindings::SIMCONNECT_CLIENT_DATA_REQUEST_FLAG {
+        match self.changed {
+            true => bindings::SIMCONNECT_CLIENT_DATA_REQUEST_FLAG_CHANGED,
+            false => bindings::SIMCONNECT_CLIENT_DATA_REQUEST_FLAG_DEFAULT,
+        }
+    }
+
+    /// Number of answers after which the simulator stops sending, or `None` if it never does
+    pub(crate) fn answer_limit(&self) -> Option<u32> {
+        match (self.period, self.limit) {
+            (ClientDataPeriod::Once, _) => Some(1),
+            (_, 0) => None,
+            (_, limit) => Some(limit),
+        }
+    }
+}
+
+impl From<ClientDataPeriod> for ClientDataRequest {
+    fn from(period: ClientDataPeriod) -> Self {
+        Self::new(period)
+    }
+}
+
+/// Parses the answers to a data request, filling tagged data in from earlier answers
+pub(crate) struct DataParser<T: SimConnectToStruct> {
+    /// The datum id and type of every field, in definition order. Only needed for tagged data.
+    fields: Vec<(u32, SimVarType)>,
+    /// The latest value of every field, filled in as tagged answers arrive
+    values: Vec<Vec<u8>>,
+    tagged: bool,
+    _struct: std::marker::PhantomData<fn() -> T>,
+}
+
+impl<T: SimConnectToStruct> DataParser<T> {
+    pub fn new(tagged: bool) -> Self {
+        let fields: Vec<(u32, SimVarType)> = T::get_fields()
+            .into_iter()
+            .map(|field| (field.id, field.data_type))
+            .collect();
+        let values = fields
+            .iter()
+            .map(|(_, data_type)| vec![0; data_type.size().unwrap_or(4)])
+            .collect();
+        Self {
+            fields,
+            values,
+            tagged,
+            _struct: std::marker::PhantomData,
+        }
+    }
+
+    pub fn parse(&mut self, data: &RecvSimData) -> SimConnectResult<T::ReturnType> {
+        let parse_error = || SimConnectError::Parse {
+            recv_id: data.get_recv_id(),
+            reason: format!("Unable to parse {}", std::any::type_name::<T>()),
+        };
+
+        if !self.tagged {
+            return unsafe { T::parse_struct(data.get_pointer()) }.map_err(|_| parse_error());
+        }
+
+        self.untag(data.get_data(), data.get_define_count())
+            .map_err(|reason| SimConnectError::Parse {
+                recv_id: data.get_recv_id(),
+                reason,
+            })?;
+        let mut untagged = super::backend::aligned_copy(&self.values.concat());
+        let pointer = NonNull::new(untagged.as_mut_ptr() as *mut u32).ok_or_else(parse_error)?;
+        unsafe { T::parse_struct(pointer) }.map_err(|_| parse_error())
+    }
+
+    /// Reads `count` tagged datums from `data` into `values`
+    fn untag(&mut self, data: &[u8], count: u32) -> Result<(), String> {
+        let mut offset = 0;
+        for _ in 0..count {
+            let datum_id = data
+                .get(offset..offset + 4)
+                .map(|id| u32::from_le_bytes(id.try_into().unwrap()))
+                .ok_or("Tagged data ended early")?;
+            offset += 4;
+
+            let index = self
+                .fields
+                .iter()
+                .position(|(id, _)| *id == datum_id)
+                .ok_or_else(|| format!("Unknown datum id {datum_id}"))?;
+            let size = match self.fields[index].1.size() {
+                Some(size) => size,
+                // Variable length strings are padded to a multiple of 4 bytes
+                None => {
+                    let length = data[offset.min(data.len())..]
+                        .iter()
+                        .position(|b| *b == 0)
+                        .ok_or("Unterminated string")?;
+                    (length + 1 + 3) & !3
+                }
+            };
+
+            let value = data
+                .get(offset..offset + size)
+                .ok_or("Tagged data ended early")?;
+            self.values[index] = value.to_vec();
+            offset += size;
+        }
+        Ok(())
+    }
+}
+
+/// Stops a subscription with the simulator
+pub(crate) type Unsubscribe = Box<dyn FnOnce() -> SimConnectResult<()> + Send>;
+
+/// A data subscription created by `SimConnect::subscribe_data`.
+///
+/// This is an `Iterator`, or a `Stream` with the `async` feature. Iterating blocks until the
+/// simulator sends the next value, and ends once the subscription has delivered its last
+/// value (for `Period::Once` or a `limit`), or the connection closes. Dropping the
+/// subscription tells the simulator to stop sending.
+pub struct DataSubscription<T: SimConnectToStruct> {
+    feed: Feed<SimConnectResult<T::ReturnType>>,
+    unsubscribe: Option<Unsubscribe>,
+}
+
+impl<T: SimConnectToStruct> DataSubscription<T> {
+    pub(crate) fn new(
+        feed: Feed<SimConnectResult<T::ReturnType>>,
+        unsubscribe: Unsubscribe,
+    ) -> Self {
+        Self {
+            feed,
+            unsubscribe: Some(unsubscribe),
+        }
+    }
+
+    /// Takes the next value if the simulator already sent one, without waiting
+    pub fn try_next(&mut self) -> Option<SimConnectResult<T::ReturnType>> {
+        self.feed.try_recv()
+    }
+
+    /// Tells the simulator to stop sending. Unlike dropping the subscription, this reports
+    /// whether that worked.
+    pub fn unsubscribe(mut self) -> SimConnectResult<()> {
+        match self.unsubscribe.take() {
+            Some(unsubscribe) => unsubscribe(),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(not(feature = "async"))]
+impl<T: SimConnectToStruct> Iterator for DataSubscription<T> {
+    type Item = SimConnectResult<T::ReturnType>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.feed.recv()
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T: SimConnectToStruct> futures_core::Stream for DataSubscription<T> {
+    type Item = SimConnectResult<T::ReturnType>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.feed.poll_recv(cx)
+    }
+}
+
+impl<T: SimConnectToStruct> Drop for DataSubscription<T> {
+    fn drop(&mut self) {
+        if let Some(unsubscribe) = self.unsubscribe.take() {
+            let _ = unsubscribe();
+        }
+    }
+}
+
+/// A subscription to a runtime `DataDefinition` created by `SimConnect::subscribe_values`,
+/// yielding a `SimValue` for each datum of the definition.
+///
+/// Like `DataSubscription`, this is an `Iterator`, or a `Stream` with the `async` feature, and
+/// dropping it tells the simulator to stop sending.
+pub struct ValuesSubscription {
+    feed: Feed<SimConnectResult<Vec<SimValue>>>,
+    unsubscribe: Option<Unsubscribe>,
+}
+
+impl ValuesSubscription {
+    pub(crate) fn new(
+        feed: Feed<SimConnectResult<Vec<SimValue>>>,
+        unsubscribe: Unsubscribe,
+    ) -> Self {
+        Self {
+            feed,
+            unsubscribe: Some(unsubscribe),
+        }
+    }
+
+    /// Takes the next values if the simulator already sent them, without waiting
+    pub fn try_next(&mut self) -> Option<SimConnectResult<Vec<SimValue>>> {
+        self.feed.try_recv()
+    }
+
+    /// Tells the simulator to stop sending. Unlike dropping the subscription, this reports
+    /// whether that worked.
+    pub fn unsubscribe(mut self) -> SimConnectResult<()> {
+        match self.unsubscribe.take() {
+            Some(unsubscribe) => unsubscribe(),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(not(feature = "async"))]
+impl Iterator for ValuesSubscription {
+    type Item = SimConnectResult<Vec<SimValue>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.feed.recv()
+    }
+}
+
+#[cfg(feature = "async")]
+impl futures_core::Stream for ValuesSubscription {
+    type Item = SimConnectResult<Vec<SimValue>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.feed.poll_recv(cx)
+    }
+}
+
+impl Drop for ValuesSubscription {
+    fn drop(&mut self) {
+        if let Some(unsubscribe) = self.unsubscribe.take() {
+            let _ = unsubscribe();
+        }
+    }
+}
+
+/// A client data subscription created by `SimConnect::subscribe_client_data`.
+///
+/// Like `DataSubscription`, this is an `Iterator`, or a `Stream` with the `async` feature, and
+/// dropping it tells the simulator to stop sending.
+pub struct ClientDataSubscription<T: ClientData> {
+    feed: Feed<SimConnectResult<T>>,
+    unsubscribe: Option<Unsubscribe>,
+}
+
+impl<T: ClientData> ClientDataSubscription<T> {
+    pub(crate) fn new(feed: Feed<SimConnectResult<T>>, unsubscribe: Unsubscribe) -> Self {
+        Self {
+            feed,
+            unsubscribe: Some(unsubscribe),
+        }
+    }
+
+    /// Takes the next value if the simulator already sent one, without waiting
+    pub fn try_next(&mut self) -> Option<SimConnectResult<T>> {
+        self.feed.try_recv()
+    }
+
+    /// Tells the simulator to stop sending. Unlike dropping the subscription, this reports
+    /// whether that worked.
+    pub fn unsubscribe(mut self) -> SimConnectResult<()> {
+        match self.unsubscribe.take() {
+            Some(unsubscribe) => unsubscribe(),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(not(feature = "async"))]
+impl<T: ClientData> Iterator for ClientDataSubscription<T> {
+    type Item = SimConnectResult<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.feed.recv()
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T: ClientData> futures_core::Stream for ClientDataSubscription<T> {
+    type Item = SimConnectResult<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.feed.poll_recv(cx)
+    }
+}
+
+impl<T: ClientData> Drop for ClientDataSubscription<T> {
+    fn drop(&mut self) {
+        if let Some(unsubscribe) = self.unsubscribe.take() {
+            let _ = unsubscribe();
+        }
+    }
+}
+
+/// Merges the messages of a facility list, which are numbered from `0` up to `out_of - 1`
+pub(crate) struct FacilityListMerger<T: FacilityListEntry> {
+    entries: Vec<T>,
+}
+
+impl<T: FacilityListEntry> FacilityListMerger<T> {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    /// Adds the entries of a message, returning the whole list once its last message arrived
+    pub fn merge(&mut self, list: RecvFacilitiesList) -> SimConnectResult<Option<Vec<T>>> {
+        self.entries.extend(list.entries::<T>()?);
+        Ok((list.entry_number + 1 >= list.out_of).then(|| std::mem::take(&mut self.entries)))
+    }
+}
+
+/// Keeps track of the facilities in the simulator's cache for a `FacilitySubscription`, and
+/// feeds it every change
+pub(crate) struct FacilityTracker<T: FacilityListEntry> {
+    in_range: HashMap<(String, String), T>,
+    feeder: Feeder<SimConnectResult<FacilityChange<T>>>,
+}
+
+impl<T: FacilityListEntry> FacilityTracker<T> {
+    pub fn new(feeder: Feeder<SimConnectResult<FacilityChange<T>>>) -> Self {
+        Self {
+            in_range: HashMap::new(),
+            feeder,
+        }
+    }
+
+    /// Reports the entries not in range yet as added. Returns `false` once the subscription
+    /// has been dropped.
+    pub fn add(&mut self, entries: Vec<T>) -> bool {
+        for entry in entries {
+            let (icao, region) = entry.ident();
+            let key = (icao.to_owned(), region.to_owned());
+            if let Entry::Vacant(vacant) = self.in_range.entry(key) {
+                vacant.insert(entry.clone());
+                if !self.feeder.send(Ok(FacilityChange::Added(entry))) {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Takes a list of everything in the cache, reporting whatever is missing from it as
+    /// removed and anything new as added
+    pub fn replace(&mut self, entries: Vec<T>) -> bool {
+        let listed: HashMap<(String, String), T> = entries
+            .into_iter()
+            .map(|entry| {
+                let (icao, region) = entry.ident();
+                ((icao.to_owned(), region.to_owned()), entry)
+            })
+            .collect();
+
+        let mut removed: Vec<(String, String)> = self
+            .in_range
+            .keys()
+            .filter(|key| !listed.contains_key(*key))
+            .cloned()
+            .collect();
+        removed.sort_unstable();
+        for key in removed {
+            let entry = self.in_range.remove(&key).expect("key was just listed");
+            if !self.feeder.send(Ok(FacilityChange::Removed(entry))) {
+                return false;
+            }
+        }
+
+        let mut added: Vec<T> = listed
+            .into_iter()
+            .filter(|(key, _)| !self.in_range.contains_key(key))
+            .map(|(_, entry)| entry)
+            .collect();
+        added.sort_unstable_by(|a, b| a.ident().cmp(&b.ident()));
+        self.add(added)
+    }
+
+    pub fn fail(&self, error: SimConnectError) -> bool {
+        self.feeder.send(Err(error))
+    }
+}
+
+/// A facility subscription created by `SimConnect::subscribe_to_facilities`, which delivers
+/// every facility in the simulator's cache as `FacilityChange::Added`, then each change to the
+/// cache as the user's aircraft moves.
+///
+/// Like `DataSubscription`, this is an `Iterator`, or a `Stream` with the `async` feature, and
+/// dropping it tells the simulator to stop sending.
+pub struct FacilitySubscription<T: FacilityListEntry> {
+    feed: Feed<SimConnectResult<FacilityChange<T>>>,
+    unsubscribe: Option<Unsubscribe>,
+}
+
+impl<T: FacilityListEntry> FacilitySubscription<T> {
+    pub(crate) fn new(
+        feed: Feed<SimConnectResult<FacilityChange<T>>>,
+        unsubscribe: Unsubscribe,
+    ) -> Self {
+        Self {
+            feed,
+            unsubscribe: Some(unsubscribe),
+        }
+    }
+
+    /// Takes the next change if the simulator already sent one, without waiting
+    pub fn try_next(&mut self) -> Option<SimConnectResult<FacilityChange<T>>> {
+        self.feed.try_recv()
+    }
+
+    /// Tells the simulator to stop sending. Unlike dropping the subscription, this reports
+    /// whether that worked.
+    pub fn unsubscribe(mut self) -> SimConnectResult<()> {
+        match self.unsubscribe.take() {
+            Some(unsubscribe) => unsubscribe(),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(not(feature = "async"))]
+impl<T: FacilityListEntry> Iterator for FacilitySubscription<T> {
+    type Item = SimConnectResult<FacilityChange<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.feed.recv()
+    }
+}
+
+#[cfg(feature = "async")]
+impl<T: FacilityListEntry> futures_core::Stream for FacilitySubscription<T> {
+    type Item = SimConnectResult<FacilityChange<T>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.feed.poll_recv(cx)
+    }
+}
+
+impl<T: FacilityListEntry> Drop for FacilitySubscription<T> {
+    fn drop(&mut self) {
+        if let Some(unsubscribe) = self.unsubscribe.take() {
+            let _ = unsubscribe();
+        }
+    }
+}
@@ -0,0 +1,534 @@
+#![cfg(feature = "async")]
+
+use sim_connect_rs::{
+    backend::{MockBackend, MockSim},
+    client_data::{ClientDataField, ClientDataValue},
+    sim_data::DataWriter,
+    sim_event_args::SimStateArgs,
+    sim_events::SystemEvent,
+    sim_units::GPS,
+    sim_var_types::SimVarType,
+    sim_vars::SimVar,
+    ClientData, SimConnect, SimConnectDatum, SimConnectResult, SimConnectToStruct,
+    StructToSimConnect,
+};
+use std::{
+    ptr::NonNull,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Debug, PartialEq)]
+#[repr(C)]
+struct Position {
+    latitude: f64,
+    longitude: f64,
+}
+
+impl StructToSimConnect for Position {
+    fn get_fields() -> Vec<SimConnectDatum> {
+        vec![
+            SimConnectDatum {
+                id: 0,
+                sim_var: SimVar::PlaneLat,
+                sim_unit: Some(Box::new(GPS::DegLat)),
+                data_type: SimVarType::F64,
+            },
+            SimConnectDatum {
+                id: 1,
+                sim_var: SimVar::PlaneLong,
+                sim_unit: Some(Box::new(GPS::DegLon)),
+                data_type: SimVarType::F64,
+            },
+        ]
+    }
+
+    fn write_data(&self, writer: &mut DataWriter) -> SimConnectResult<()> {
+        writer.write(&self.latitude)?;
+        writer.write(&self.longitude)
+    }
+}
+
+impl SimConnectToStruct for Position {
+    type Error = ();
+    type ReturnType = Position;
+
+    unsafe fn parse_struct(pointer: NonNull<u32>) -> Result<Self::ReturnType, Self::Error> {
+        let pointer = pointer.as_ptr() as *const f64;
+        Ok(Self {
+            latitude: pointer.read_unaligned(),
+            longitude: pointer.add(1).read_unaligned(),
+        })
+    }
+}
+
+fn open(sim: &MockSim) -> SimConnect<MockBackend> {
+    SimConnect::open_with_backend(sim.backend(), "Async Test", Some(Duration::from_millis(1)))
+        .expect("mock backend should always open")
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn get_latest_data_on_tokio() {
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    sim.set_sim_var("Plane Latitude", 47.45);
+    sim.set_sim_var("Plane Longitude", -122.31);
+    assert_eq!(
+        sc.get_latest_data::<Position>().await.unwrap(),
+        Position {
+            latitude: 47.45,
+            longitude: -122.31
+        }
+    );
+}
+
+#[test]
+fn request_system_state_on_another_executor() {
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    sim.set_system_state(SimStateArgs::Sim, true);
+    let state = futures_executor::block_on(sc.request_system_state(SimStateArgs::Sim)).unwrap();
+    assert_eq!(state.sim, Some(true));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn waiting_does_not_block_the_executor() {
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+    sim.pause();
+
+    // Only the task below can resume the sim in time, and it shares the executor's only thread
+    // with the request
+    let resumed_by = Arc::new(Mutex::new(None));
+    let (done, finished) = std::sync::mpsc::channel::<()>();
+    let watchdog = {
+        let sim = sim.clone();
+        let resumed_by = resumed_by.clone();
+        std::thread::spawn(move || {
+            if finished.recv_timeout(TIMEOUT).is_err() {
+                resumed_by.lock().unwrap().get_or_insert("watchdog");
+                sim.resume();
+            }
+        })
+    };
+
+    let (position, _) = tokio::join!(sc.get_latest_data::<Position>(), async {
+        tokio::task::yield_now().await;
+        resumed_by.lock().unwrap().get_or_insert("task");
+        sim.resume();
+    });
+
+    done.send(()).unwrap();
+    watchdog.join().unwrap();
+    assert!(position.is_ok());
+    assert_eq!(*resumed_by.lock().unwrap(), Some("task"));
+}
+
+#[tokio::test]
+async fn cancelled_requests_do_not_answer_later_ones() {
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    sim.set_sim_var("Plane Latitude", 1.0);
+    sim.set_system_state(SimStateArgs::Sim, false);
+    sim.pause();
+
+    let cancelled =
+        tokio::time::timeout(Duration::from_millis(10), sc.get_latest_data::<Position>()).await;
+    assert!(cancelled.is_err());
+    let cancelled = tokio::time::timeout(
+        Duration::from_millis(10),
+        sc.request_system_state(SimStateArgs::Sim),
+    )
+    .await;
+    assert!(cancelled.is_err());
+
+    // The answers to the cancelled requests are still on their way, and carry the old values
+    sim.set_sim_var("Plane Latitude", 2.0);
+    sim.set_system_state(SimStateArgs::Sim, true);
+    sim.resume();
+
+    let position = tokio::time::timeout(TIMEOUT, sc.get_latest_data::<Position>())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(position.latitude, 2.0);
+
+    let state = tokio::time::timeout(TIMEOUT, sc.request_system_state(SimStateArgs::Sim))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(state.sim, Some(true));
+}
+
+#[tokio::test]
+async fn data_subscriptions_are_streams() {
+    use futures_util::StreamExt;
+    use sim_connect_rs::subscription::{DataRequest, Period};
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    let positions = sc
+        .subscribe_data::<Position>(DataRequest::new(Period::SimFrame).limit(3))
+        .unwrap();
+    for frame in 0..5 {
+        sim.set_sim_var("Plane Latitude", frame as f64);
+        sim.next_frame();
+    }
+
+    let latitudes: Vec<f64> = tokio::time::timeout(TIMEOUT, positions.collect::<Vec<_>>())
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|p| p.unwrap().latitude)
+        .collect();
+    assert_eq!(latitudes, vec![0.0, 1.0, 2.0]);
+}
+
+#[tokio::test]
+async fn data_by_type_is_collected_on_any_executor() {
+    use sim_connect_rs::sim_event_args::SimObjectType;
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    let first = sim.add_object(SimObjectType::Boat, 100);
+    let second = sim.add_object(SimObjectType::Boat, 200);
+    sim.set_object_var(second, "Plane Longitude", 3.5);
+
+    let boats = tokio::time::timeout(
+        TIMEOUT,
+        sc.get_data_by_type::<Position>(SimObjectType::Boat, 1_000),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    let object_ids: Vec<u32> = boats.iter().map(|(object_id, _)| *object_id).collect();
+    assert_eq!(object_ids, vec![first, second]);
+
+    let position = tokio::time::timeout(TIMEOUT, sc.get_data_on_object::<Position>(second))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(position.longitude, 3.5);
+}
+
+#[tokio::test]
+async fn runtime_definitions_are_read_on_any_executor() {
+    use sim_connect_rs::{
+        data_definition::{DataDefinition, SimValue},
+        sim_event_args::SimObjectType,
+    };
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+    let definition = DataDefinition::new()
+        .add(SimVar::PlaneLong, Some(&GPS::DegLon), SimVarType::F64)
+        .add_named("ATC ID", "", SimVarType::String32);
+
+    let boat = sim.add_object(SimObjectType::Boat, 100);
+    sim.set_object_var(boat, "Plane Longitude", -8.5);
+    sim.set_object_var(boat, "ATC ID", "FERRY");
+
+    let values = tokio::time::timeout(TIMEOUT, sc.get_values_on_object(&definition, boat))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(values, [SimValue::F64(-8.5), SimValue::from("FERRY")]);
+
+    let boats = tokio::time::timeout(
+        TIMEOUT,
+        sc.get_values_by_type(&definition, SimObjectType::Boat, 1_000),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    assert_eq!(boats, [(boat, values)]);
+}
+
+#[tokio::test]
+async fn aircraft_input_events_are_read_on_any_executor() {
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+    sim.add_input_event("BEACON_SWITCH", 1.0);
+
+    let catalog = tokio::time::timeout(TIMEOUT, sc.enumerate_aircraft_input_events())
+        .await
+        .unwrap()
+        .unwrap();
+    let beacon = catalog.hash_of("BEACON_SWITCH").unwrap();
+    let value = tokio::time::timeout(TIMEOUT, sc.get_aircraft_input_event(beacon))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(value.as_f64(), Some(1.0));
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+struct Counter {
+    count: i32,
+}
+
+unsafe impl ClientData for Counter {
+    fn fields() -> Vec<ClientDataField> {
+        vec![ClientDataField {
+            offset: 0,
+            size_or_type: i32::SIZE_OR_TYPE,
+            epsilon: 0.0,
+        }]
+    }
+}
+
+#[tokio::test]
+async fn client_data_is_read_on_any_executor() {
+    use futures_util::StreamExt;
+    use sim_connect_rs::subscription::ClientDataPeriod;
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    let area = sc.create_client_data::<Counter>(".Counter", false).unwrap();
+    sc.set_client_data(&area, &Counter { count: 41 }).unwrap();
+    let count = tokio::time::timeout(TIMEOUT, sc.get_client_data(&area))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(count, Counter { count: 41 });
+
+    let mut counts = sc
+        .subscribe_client_data(&area, ClientDataPeriod::Second)
+        .unwrap();
+    sim.next_second();
+    let count = tokio::time::timeout(TIMEOUT, counts.next()).await.unwrap();
+    assert_eq!(count.unwrap().unwrap(), Counter { count: 41 });
+}
+
+#[tokio::test]
+async fn lvars_are_read_on_any_executor() {
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+    sim.install_wasm_bridge();
+    sim.set_lvar("A32NX_AUTOPILOT_1_ACTIVE", 1.0);
+
+    tokio::time::timeout(TIMEOUT, sc.ping_wasm_bridge())
+        .await
+        .unwrap()
+        .unwrap();
+    let active = tokio::time::timeout(TIMEOUT, sc.read_lvar("A32NX_AUTOPILOT_1_ACTIVE"))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(active, 1.0);
+
+    sc.set_lvar("XMLVAR_Baro1_Mode", 2.0).await.unwrap();
+    sc.fire_hevent("A320_Neo_CDU_1_BTN_INIT").await.unwrap();
+    assert_eq!(sim.lvar("XMLVAR_Baro1_Mode"), Some(2.0));
+    assert_eq!(sim.take_fired_hevents(), ["A320_Neo_CDU_1_BTN_INIT"]);
+}
+
+#[tokio::test]
+async fn the_wasm_bridge_is_written_to_before_anything_is_read() {
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+    sim.install_wasm_bridge();
+
+    tokio::time::timeout(TIMEOUT, sc.set_lvar("XMLVAR_Baro1_Mode", 2.0))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(sim.lvar("XMLVAR_Baro1_Mode"), Some(2.0));
+}
+
+#[tokio::test]
+async fn facilities_are_read_on_any_executor() {
+    use sim_connect_rs::{
+        backend::MockFacility,
+        facilities::{FacilityDataType, Vor},
+    };
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+    sim.add_facility(
+        "SEA",
+        "K1",
+        MockFacility::new(FacilityDataType::Vor)
+            .with("NAME", "SEATTLE")
+            .with("FREQUENCY", 116_800_000)
+            .with("HAS_DME", true),
+    );
+
+    let vor = tokio::time::timeout(TIMEOUT, sc.get_facility::<Vor>("SEA", Some("K1")))
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(vor.name, "SEATTLE");
+    assert_eq!(vor.frequency, 116_800_000);
+    assert!(vor.has_dme);
+    assert_eq!(vor.localizer, None);
+}
+
+#[tokio::test]
+async fn facility_lists_are_read_on_any_executor() {
+    use sim_connect_rs::{
+        backend::MockFacility,
+        facilities::{FacilityDataType, WaypointEntry},
+    };
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+    sim.add_facility(
+        "SUMMA",
+        "K1",
+        MockFacility::new(FacilityDataType::Waypoint).with("MAGVAR", 15.5),
+    );
+    sim.set_facilities_in_range(&["SUMMA"]);
+
+    let waypoints = tokio::time::timeout(TIMEOUT, sc.request_facilities_list::<WaypointEntry>())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(waypoints.len(), 1);
+    assert_eq!(waypoints[0].icao, "SUMMA");
+    assert_eq!(waypoints[0].magvar, 15.5);
+}
+
+#[tokio::test]
+async fn ai_objects_are_created_on_any_executor() {
+    use sim_connect_rs::{sim_event_args::SimObjectType, sim_objects::InitPosition};
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+    sim.add_container("Yacht", SimObjectType::Boat);
+
+    let yacht = tokio::time::timeout(
+        TIMEOUT,
+        sc.create_simulated_object("Yacht", InitPosition::new(47.6, -122.4, 0.0).on_ground()),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    let boats = tokio::time::timeout(
+        TIMEOUT,
+        sc.get_data_by_type::<Position>(SimObjectType::Boat, 1_000),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    assert_eq!(
+        boats,
+        vec![(
+            yacht.0,
+            Position {
+                latitude: 47.6,
+                longitude: -122.4
+            }
+        )]
+    );
+}
+
+#[tokio::test]
+async fn flight_commands_resolve_on_their_system_event() {
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+    sim.add_flight("Scenarios\\Pattern Work", "Pattern work");
+
+    let loaded = tokio::time::timeout(TIMEOUT, sc.flight_load("Scenarios\\Pattern Work"))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(loaded, "Scenarios\\Pattern Work");
+
+    let missing = tokio::time::timeout(TIMEOUT, sc.flight_plan_load("Plans\\Missing"))
+        .await
+        .unwrap();
+    assert!(missing.is_err());
+}
+
+#[tokio::test]
+async fn cancelled_flight_commands_do_not_answer_later_ones() {
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+    sim.add_flight("Scenarios\\Pattern Work", "Pattern work");
+    sim.add_flight("Scenarios\\Cold And Dark", "Cold and dark");
+
+    // The load is sent, but given up on before the simulator answers
+    sim.pause();
+    let cancelled = tokio::time::timeout(
+        Duration::from_millis(50),
+        sc.flight_load("Scenarios\\Pattern Work"),
+    )
+    .await;
+    assert!(cancelled.is_err());
+
+    let loaded = sc.flight_load("scenarios/cold and dark.FLT");
+    sim.resume();
+    assert_eq!(
+        tokio::time::timeout(TIMEOUT, loaded)
+            .await
+            .unwrap()
+            .unwrap(),
+        "scenarios/cold and dark.FLT"
+    );
+    assert_eq!(
+        sim.loaded_flight().as_deref(),
+        Some("scenarios/cold and dark.FLT")
+    );
+}
+
+#[tokio::test]
+async fn flight_commands_ignore_other_flights_being_loaded() {
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+    sim.add_flight("Scenarios\\Pattern Work", "Pattern work");
+
+    // A flight loaded from the menus is reported before the simulator answers the call
+    sc.subscribe_to_system_event(SystemEvent::FlightLoaded, |_| {})
+        .unwrap();
+    sim.pause();
+    assert!(sim.fire_filename_event(SystemEvent::FlightLoaded, "Scenarios\\Cold And Dark"));
+    let loaded = sc.flight_load("Scenarios\\Pattern Work");
+    tokio::pin!(loaded);
+    assert!(tokio::time::timeout(Duration::from_millis(50), &mut loaded)
+        .await
+        .is_err());
+
+    sim.resume();
+    assert_eq!(
+        tokio::time::timeout(TIMEOUT, loaded)
+            .await
+            .unwrap()
+            .unwrap(),
+        "Scenarios\\Pattern Work"
+    );
+}
+
+#[tokio::test]
+async fn flight_commands_match_files_reported_by_another_path() {
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+    sim.add_flight("Pattern Work", "Pattern work");
+
+    sc.subscribe_to_system_event(SystemEvent::FlightLoaded, |_| {})
+        .unwrap();
+    sim.pause();
+    assert!(sim.fire_filename_event(
+        SystemEvent::FlightLoaded,
+        "C:\\Users\\Pilot\\Flights\\Pattern Work.FLT",
+    ));
+    let loaded = sc.flight_load("Pattern Work");
+    sim.resume();
+    assert_eq!(
+        tokio::time::timeout(TIMEOUT, loaded)
+            .await
+            .unwrap()
+            .unwrap(),
+        "C:\\Users\\Pilot\\Flights\\Pattern Work.FLT"
+    );
+}
@@ -0,0 +1,65 @@
+#![cfg(feature = "derive")]
+
+use sim_connect_rs::{
+    client_data::{ClientDataField, ClientDataValue},
+    ClientData,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, ClientData)]
+#[repr(C)]
+struct GaugeState {
+    #[client_data(epsilon = 0.5)]
+    needle: f64,
+    mode: i16,
+    lights: [u8; 4],
+    counter: u32,
+}
+
+#[test]
+fn derived_fields_follow_the_struct_layout() {
+    assert_eq!(
+        GaugeState::fields(),
+        vec![
+            ClientDataField {
+                offset: 0,
+                size_or_type: f64::SIZE_OR_TYPE,
+                epsilon: 0.5,
+            },
+            ClientDataField {
+                offset: 8,
+                size_or_type: i16::SIZE_OR_TYPE,
+                epsilon: 0.0,
+            },
+            ClientDataField {
+                offset: 10,
+                size_or_type: 4,
+                epsilon: 0.0,
+            },
+            ClientDataField {
+                offset: 16,
+                size_or_type: 4,
+                epsilon: 0.0,
+            },
+        ]
+    );
+}
+
+#[test]
+fn fields_are_packed_without_padding() {
+    let state = GaugeState {
+        needle: 2.5,
+        mode: -1,
+        lights: [1, 2, 3, 4],
+        counter: 99,
+    };
+
+    let bytes = state.to_bytes();
+    let mut expected = 2.5f64.to_le_bytes().to_vec();
+    expected.extend((-1i16).to_le_bytes());
+    expected.extend([1, 2, 3, 4]);
+    expected.extend(99u32.to_le_bytes());
+    assert_eq!(bytes, expected);
+
+    assert_eq!(GaugeState::from_bytes(&bytes), Some(state));
+    assert_eq!(GaugeState::from_bytes(&bytes[..bytes.len() - 1]), None);
+}
@@ -0,0 +1,53 @@
+use sim_connect_rs::{
+    error::HResult, recv_data::RecvDataEvent, sim_events::SystemEvent, SimConnectError,
+};
+use sim_connect_sys::bindings;
+use std::ptr::NonNull;
+
+#[test]
+fn hresults_are_decoded() {
+    assert_eq!(HResult::from(0x8000_4005u32 as i32), HResult::Fail);
+    assert_eq!(HResult::from(0x8007_0057u32 as i32), HResult::InvalidArg);
+    assert_eq!(HResult::from(-1), HResult::Other(-1));
+    assert_eq!(HResult::InvalidArg.code(), 0x8007_0057u32 as i32);
+
+    let error: SimConnectError = HResult::Fail.into();
+    assert_eq!(
+        error.to_string(),
+        "SimConnect call failed with E_FAIL (0x80004005)"
+    );
+}
+
+#[test]
+fn unknown_names_are_reported() {
+    assert!(matches!(
+        "NotAnEvent".parse::<SystemEvent>(),
+        Err(SimConnectError::UnknownName(name)) if name == "NotAnEvent"
+    ));
+    assert!(matches!(
+        "paused".parse::<SystemEvent>(),
+        Ok(SystemEvent::Paused)
+    ));
+}
+
+#[test]
+fn parse_errors_carry_the_recv_id() {
+    // dwSize, dwVersion, dwID, uGroupID, uEventID, dwData
+    let mut event: [u32; 6] = [
+        24,
+        4,
+        bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT as u32,
+        u32::MAX,
+        u32::MAX,
+        0,
+    ];
+    let pointer = NonNull::from(&mut event).cast::<bindings::SIMCONNECT_RECV>();
+
+    match RecvDataEvent::from_pointer(pointer) {
+        Err(SimConnectError::Parse { recv_id, .. }) => assert_eq!(
+            recv_id,
+            bindings::SIMCONNECT_RECV_ID_SIMCONNECT_RECV_ID_EVENT as u32
+        ),
+        other => panic!("unexpected result {other:?}"),
+    }
+}
@@ -0,0 +1,189 @@
+use sim_connect_rs::{
+    flight::{Flight, FlightTime},
+    sim_objects::InitPosition,
+    SimConnectError,
+};
+
+/// Trimmed from a flight saved by MSFS
+const KSEA_RAMP: &str = "\u{feff}[Main]\r
+Title=KSEA Ramp\r
+Description=Parked at the north satellite\r
+MissionType=FreeFlight\r
+\r
+; The user's aircraft\r
+[Sim.0]\r
+Sim=Cessna Skyhawk G1000 Asobo\r
+Pilot=Pilot_Female_Uniform\r
+\r
+[SimVars.0]\r
+Latitude=N47° 26' 56.94\"\r
+Longitude=W122° 18' 33.51\"\r
+Altitude=+000433.00\r
+Pitch=-0.25\r
+Bank=0\r
+Heading=178.5\r
+SimOnGround=True\r
+\r
+[Fuel.0]\r
+LeftMain=75.000000,26.000000\r
+RightMain=50.000000,26.000000\r
+\r
+[DateTimeSeason]\r
+Season=Summer\r
+Year=2023\r
+Day=182\r
+Hours=14\r
+Minutes=30\r
+Seconds=0\r
+\r
+[ATC_Aircraft.0]\r
+ActiveFlightPlan=False\r
+RequestedFlightPlan=False\r
+";
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-6,
+        "{actual} is not close to {expected}"
+    );
+}
+
+#[test]
+fn msfs_flights_are_parsed() {
+    let flight: Flight = KSEA_RAMP.parse().unwrap();
+
+    let names: Vec<_> = flight.sections().map(|s| s.name()).collect();
+    assert_eq!(
+        names,
+        [
+            "Main",
+            "Sim.0",
+            "SimVars.0",
+            "Fuel.0",
+            "DateTimeSeason",
+            "ATC_Aircraft.0"
+        ]
+    );
+    assert_eq!(flight.title(), Some("KSEA Ramp"));
+    assert_eq!(flight.description(), Some("Parked at the north satellite"));
+    assert_eq!(flight.aircraft_title(), Some("Cessna Skyhawk G1000 Asobo"));
+    assert_eq!(
+        flight.get("atc_aircraft.0", "activeflightplan"),
+        Some("False")
+    );
+
+    let position = flight.position().unwrap().unwrap();
+    assert_close(position.latitude, 47.0 + 26.0 / 60.0 + 56.94 / 3600.0);
+    assert_close(position.longitude, -(122.0 + 18.0 / 60.0 + 33.51 / 3600.0));
+    assert_eq!(position.altitude, 433.0);
+    assert_eq!(position.pitch, -0.25);
+    assert_eq!(position.heading, 178.5);
+    assert!(position.on_ground);
+
+    assert_eq!(
+        flight.fuel().unwrap(),
+        [
+            ("LeftMain".to_owned(), 75.0),
+            ("RightMain".to_owned(), 50.0)
+        ]
+    );
+    assert_eq!(
+        flight.date_time().unwrap(),
+        Some(FlightTime {
+            year: 2023,
+            day: 182,
+            hours: 14,
+            minutes: 30,
+            seconds: 0,
+        })
+    );
+}
+
+#[test]
+fn flights_survive_a_round_trip() {
+    let mut flight: Flight = KSEA_RAMP.parse().unwrap();
+    flight.set_fuel("LeftMain", 100.0);
+    let written = flight.to_string();
+
+    assert!(written.starts_with("[Main]\r\nTitle=KSEA Ramp\r\n"));
+    assert!(written.contains("LeftMain=100.000000,26.000000\r\n"));
+    assert!(written.contains("Latitude=N47° 26' 56.94\"\r\n"));
+    assert_eq!(written.parse::<Flight>().unwrap(), flight);
+}
+
+#[test]
+fn built_flights_are_written_as_flt() {
+    let mut flight = Flight::default();
+    flight.set_title("Go around");
+    flight.set_aircraft_title("Cessna Skyhawk G1000 Asobo");
+    flight.set_position(
+        &InitPosition::new(47.625, -122.25, 1500.0)
+            .heading(90.0)
+            .attitude(2.5, 0.0),
+    );
+    flight.set_fuel("Center1", 40.0);
+    flight.set_date_time(FlightTime {
+        year: 2024,
+        day: 1,
+        hours: 6,
+        minutes: 0,
+        seconds: 0,
+    });
+
+    let written = flight.to_string();
+    assert!(written.contains("Latitude=N47° 37' 30.00\"\r\n"));
+    assert!(written.contains("Longitude=W122° 15' 0.00\"\r\n"));
+    assert!(written.contains("Altitude=+001500.00\r\n"));
+    assert!(written.contains("SimOnGround=False\r\n"));
+    assert!(written.contains("Center1=40.000000\r\n"));
+
+    let read: Flight = written.parse().unwrap();
+    assert_eq!(read, flight);
+    let position = read.position().unwrap().unwrap();
+    assert_close(position.latitude, 47.625);
+    assert_eq!(position.pitch, 2.5);
+    assert!(!position.on_ground);
+}
+
+#[test]
+fn flights_are_read_from_and_saved_to_files() {
+    let path = std::env::temp_dir().join(format!("sim_connect_rs_{}.flt", std::process::id()));
+    let flight: Flight = KSEA_RAMP.parse().unwrap();
+
+    flight.save(&path).unwrap();
+    let read = Flight::from_file(&path);
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(read.unwrap(), flight);
+
+    assert!(matches!(
+        Flight::from_file(&path),
+        Err(SimConnectError::Io(_))
+    ));
+}
+
+#[test]
+fn malformed_flights_are_rejected() {
+    fn invalid<T>(result: Result<T, SimConnectError>) -> bool {
+        matches!(
+            result,
+            Err(SimConnectError::InvalidFile {
+                file_type: ".FLT",
+                ..
+            })
+        )
+    }
+
+    assert!(invalid("Title=No section".parse::<Flight>()));
+    assert!(invalid("[Main]\nTitle".parse::<Flight>()));
+
+    let flight: Flight = "[SimVars.0]\nLatitude=47.4\nLongitude=W122° 18' 33.51\""
+        .parse()
+        .unwrap();
+    assert!(invalid(flight.position()));
+    let flight: Flight = "[Fuel.0]\nLeftMain=full".parse().unwrap();
+    assert!(invalid(flight.fuel()));
+    let flight: Flight = "[DateTimeSeason]\nYear=2023\nDay=366th".parse().unwrap();
+    assert!(invalid(flight.date_time()));
+
+    assert_eq!(Flight::default().position().unwrap(), None);
+}
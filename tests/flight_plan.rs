@@ -0,0 +1,179 @@
+use sim_connect_rs::{
+    flight_plan::{
+        AtcWaypoint, FlightPlan, FlightPlanType, RouteType, WaypointType, WorldPosition,
+    },
+    SimConnectError,
+};
+
+/// As saved by MSFS, including elements the parser doesn't read
+const KSEA_KPDX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+
+<SimBase.Document Type="AceXML" version="1,0">
+    <Descr>AceXML Document</Descr>
+    <FlightPlan.FlightPlan>
+        <Title>KSEA to KPDX</Title>
+        <FPType>IFR</FPType>
+        <RouteType>HighAlt</RouteType>
+        <CruisingAlt>11000.000</CruisingAlt>
+        <DepartureID>KSEA</DepartureID>
+        <DepartureLLA>N47° 26' 56.94",W122° 18' 33.51",+000433.00</DepartureLLA>
+        <DestinationID>KPDX</DestinationID>
+        <DestinationLLA>N45° 35' 19.57",W122° 35' 49.22",+000030.00</DestinationLLA>
+        <Descr>KSEA, KPDX</Descr>
+        <DeparturePosition>16L</DeparturePosition>
+        <DepartureName>Seattle-Tacoma Intl</DepartureName>
+        <DestinationName>Portland Intl</DestinationName>
+        <AppVersion>
+            <AppVersionMajor>11</AppVersionMajor>
+            <AppVersionBuild>282174</AppVersionBuild>
+        </AppVersion>
+        <ATCWaypoint id="KSEA">
+            <ATCWaypointType>Airport</ATCWaypointType>
+            <WorldPosition>N47° 26' 56.94",W122° 18' 33.51",+000433.00</WorldPosition>
+            <RunwayNumberFP>16</RunwayNumberFP>
+            <ICAO>
+                <ICAOIdent>KSEA</ICAOIdent>
+            </ICAO>
+        </ATCWaypoint>
+        <ATCWaypoint id="SUMMA">
+            <ATCWaypointType>Intersection</ATCWaypointType>
+            <WorldPosition>N46° 37' 0.87",W122° 59' 0.00",+011000.00</WorldPosition>
+            <DepartureFP>SUMMA1</DepartureFP>
+            <ICAO>
+                <ICAORegion>K1</ICAORegion>
+                <ICAOIdent>SUMMA</ICAOIdent>
+            </ICAO>
+        </ATCWaypoint>
+        <ATCWaypoint id="BTG">
+            <ATCWaypointType>VOR</ATCWaypointType>
+            <WorldPosition>N45° 44' 52.36",W122° 35' 22.51",+011000.00</WorldPosition>
+            <ATCAirway>J70</ATCAirway>
+            <ArrivalFP>WHAMY4</ArrivalFP>
+            <ICAO>
+                <ICAORegion>K1</ICAORegion>
+                <ICAOIdent>BTG</ICAOIdent>
+            </ICAO>
+        </ATCWaypoint>
+        <ATCWaypoint id="KPDX">
+            <ATCWaypointType>Airport</ATCWaypointType>
+            <WorldPosition>N45° 35' 19.57",W122° 35' 49.22",+000030.00</WorldPosition>
+            <ICAO>
+                <ICAOIdent>KPDX</ICAOIdent>
+            </ICAO>
+        </ATCWaypoint>
+    </FlightPlan.FlightPlan>
+</SimBase.Document>
+"#;
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-6,
+        "{actual} is not close to {expected}"
+    );
+}
+
+#[test]
+fn msfs_plans_are_parsed() {
+    let plan: FlightPlan = KSEA_KPDX.parse().unwrap();
+
+    assert_eq!(plan.title, "KSEA to KPDX");
+    assert_eq!(plan.description, "KSEA, KPDX");
+    assert_eq!(plan.plan_type, FlightPlanType::Ifr);
+    assert_eq!(plan.route_type, RouteType::HighAltitude);
+    assert_eq!(plan.cruising_altitude, 11000.0);
+    assert_eq!(plan.departure.icao, "KSEA");
+    assert_eq!(plan.departure.name.as_deref(), Some("Seattle-Tacoma Intl"));
+    assert_eq!(plan.departure_position.as_deref(), Some("16L"));
+    assert_eq!(plan.destination.icao, "KPDX");
+    assert_eq!(plan.app_version.unwrap().build, 282174);
+
+    let departure = plan.departure.position.unwrap();
+    assert_close(departure.latitude, 47.0 + 26.0 / 60.0 + 56.94 / 3600.0);
+    assert_close(departure.longitude, -(122.0 + 18.0 / 60.0 + 33.51 / 3600.0));
+    assert_eq!(departure.altitude, 433.0);
+
+    let ids: Vec<_> = plan.waypoints.iter().map(|w| w.id.as_str()).collect();
+    assert_eq!(ids, ["KSEA", "SUMMA", "BTG", "KPDX"]);
+    assert_eq!(plan.waypoints[2].waypoint_type, WaypointType::Vor);
+    assert_eq!(plan.waypoints[2].airway.as_deref(), Some("J70"));
+    let icao = plan.waypoints[1].icao.as_ref().unwrap();
+    assert_eq!(icao.region.as_deref(), Some("K1"));
+    assert_eq!(icao.ident, "SUMMA");
+    assert_eq!(plan.sid(), Some("SUMMA1"));
+    assert_eq!(plan.star(), Some("WHAMY4"));
+}
+
+#[test]
+fn plans_survive_a_round_trip() {
+    let plan: FlightPlan = KSEA_KPDX.parse().unwrap();
+    let written = plan.to_string();
+
+    assert!(written.contains(r#"<DepartureLLA>N47° 26' 56.94",W122° 18' 33.51",+000433.00"#));
+    assert!(written.contains(r#"<ATCWaypoint id="SUMMA">"#));
+    assert_eq!(written.parse::<FlightPlan>().unwrap(), plan);
+}
+
+#[test]
+fn built_plans_are_written_as_pln() {
+    let mut plan = FlightPlan::new("KBFI", "KPAE", 3500.0);
+    plan.plan_type = FlightPlanType::Vfr;
+    let mut lake = AtcWaypoint::new("LAKE <WA>", WaypointType::User);
+    lake.position = WorldPosition {
+        latitude: 47.625,
+        longitude: -122.25,
+        altitude: 3500.0,
+    };
+    plan.waypoints.push(lake);
+
+    let written = plan.to_string();
+    assert!(written.contains("<FPType>VFR</FPType>"));
+    assert!(written.contains(r#"<ATCWaypoint id="LAKE &lt;WA&gt;">"#));
+    assert!(written.contains(r#"<WorldPosition>N47° 37' 30.00",W122° 15' 0.00",+003500.00"#));
+
+    let read: FlightPlan = written.parse().unwrap();
+    assert_eq!(read.waypoints[0].id, "LAKE <WA>");
+    assert_eq!(read, plan);
+}
+
+#[test]
+fn plans_are_read_from_and_saved_to_files() {
+    let path = std::env::temp_dir().join(format!("sim_connect_rs_{}.pln", std::process::id()));
+    let plan: FlightPlan = KSEA_KPDX.parse().unwrap();
+
+    plan.save(&path).unwrap();
+    let read = FlightPlan::from_file(&path);
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(read.unwrap(), plan);
+
+    assert!(matches!(
+        FlightPlan::from_file(&path),
+        Err(SimConnectError::Io(_))
+    ));
+}
+
+#[test]
+fn malformed_plans_are_rejected() {
+    let invalid = |xml: &str| {
+        matches!(
+            xml.parse::<FlightPlan>(),
+            Err(SimConnectError::InvalidFile {
+                file_type: ".PLN",
+                ..
+            })
+        )
+    };
+
+    assert!(invalid("<SimBase.Document></SimBase.Document>"));
+    assert!(invalid(
+        "<FlightPlan.FlightPlan><RouteType>Scenic</RouteType></FlightPlan.FlightPlan>"
+    ));
+    assert!(invalid(
+        "<FlightPlan.FlightPlan><DepartureLLA>47.4,-122.3,433</DepartureLLA></FlightPlan.FlightPlan>"
+    ));
+    assert!(invalid(
+        "<FlightPlan.FlightPlan><CruisingAlt>FL350</CruisingAlt></FlightPlan.FlightPlan>"
+    ));
+    assert!(invalid(
+        "<FlightPlan.FlightPlan><Title>Unclosed</FlightPlan.FlightPlan>"
+    ));
+}
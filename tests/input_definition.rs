@@ -0,0 +1,80 @@
+use sim_connect_rs::{
+    input_definition::{InputDefinition, JoystickInput, Modifier},
+    SimConnectError,
+};
+
+fn parse(definition: &str) -> InputDefinition {
+    definition.parse().unwrap()
+}
+
+#[test]
+fn key_chords_are_written_the_way_simconnect_expects() {
+    let chord = parse("shift+ctrl+g");
+    assert_eq!(
+        chord,
+        InputDefinition::Keys {
+            modifiers: vec![Modifier::Shift, Modifier::Ctrl],
+            key: "G".to_owned(),
+        }
+    );
+    assert_eq!(chord.to_string(), "Shift+Ctrl+G");
+    assert_eq!(parse(" Control + SHIFT + g "), chord);
+
+    assert_eq!(parse("f12").to_string(), "F12");
+    assert_eq!(parse("alt+num_5").to_string(), "Alt+Num_5");
+    assert_eq!(parse("tab+u").to_string(), "Tab+U");
+    assert_eq!(parse("tab").to_string(), "Tab");
+    assert_eq!(parse("ctrl+/").to_string(), "Ctrl+/");
+    assert!(!chord.is_analog());
+}
+
+#[test]
+fn joystick_inputs_are_written_the_way_simconnect_expects() {
+    let button = parse("joystick:0:button:3");
+    assert_eq!(
+        button,
+        InputDefinition::Joystick {
+            joystick: 0,
+            input: JoystickInput::Button(3),
+        }
+    );
+    assert_eq!(button.to_string(), "joystick:0:button:3");
+    assert!(!button.is_analog());
+
+    let axis = parse("JOYSTICK:1:xaxis");
+    assert_eq!(axis.to_string(), "joystick:1:XAxis");
+    assert!(axis.is_analog());
+    assert_eq!(parse("joystick:2:pov").to_string(), "joystick:2:POV");
+}
+
+#[test]
+fn invalid_definitions_are_rejected() {
+    for definition in [
+        "",
+        "shift+",
+        "hyper+g",
+        "shift+shift+g",
+        "ctrl+shift",
+        "f25",
+        "gear",
+        "joystick:a:button:1",
+        "joystick:0:button:",
+        "joystick:0:button:+1",
+        "joystick:0:wheel",
+        "joystick:0:button:1:2",
+    ] {
+        assert!(
+            matches!(
+                definition.parse::<InputDefinition>(),
+                Err(SimConnectError::InvalidInputDefinition { .. })
+            ),
+            "{definition:?} was accepted"
+        );
+    }
+
+    let error = "hyper+g".parse::<InputDefinition>().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Invalid input definition \"hyper+g\": \"hyper\" is not a modifier, expected Shift, Ctrl, Alt or Tab"
+    );
+}
@@ -0,0 +1,1993 @@
+use sim_connect_rs::{
+    backend::{MockSim, MockValue},
+    client_data::{ClientDataField, ClientDataValue},
+    sim_data::DataWriter,
+    sim_event_args::SimObjectType,
+    sim_events::{SystemEvent, SystemEventData},
+    sim_units::GPS,
+    sim_var_types::SimVarType,
+    sim_vars::SimVar,
+    ClientData, SimConnect, SimConnectDatum, SimConnectResult, SimConnectToStruct,
+    StructToSimConnect,
+};
+use std::{
+    ptr::NonNull,
+    sync::mpsc::{channel, Receiver},
+    time::Duration,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Clone, Debug, PartialEq)]
+#[repr(C)]
+struct Position {
+    latitude: f64,
+    longitude: f64,
+}
+
+impl StructToSimConnect for Position {
+    fn get_fields() -> Vec<SimConnectDatum> {
+        vec![
+            SimConnectDatum {
+                id: 0,
+                sim_var: SimVar::PlaneLat,
+                sim_unit: Some(Box::new(GPS::DegLat)),
+                data_type: SimVarType::F64,
+            },
+            SimConnectDatum {
+                id: 1,
+                sim_var: SimVar::PlaneLong,
+                sim_unit: Some(Box::new(GPS::DegLon)),
+                data_type: SimVarType::F64,
+            },
+        ]
+    }
+
+    fn write_data(&self, writer: &mut DataWriter) -> SimConnectResult<()> {
+        writer.write(&self.latitude)?;
+        writer.write(&self.longitude)
+    }
+}
+
+impl SimConnectToStruct for Position {
+    type Error = ();
+    type ReturnType = Position;
+
+    unsafe fn parse_struct(pointer: NonNull<u32>) -> Result<Self::ReturnType, Self::Error> {
+        let pointer = pointer.as_ptr() as *const f64;
+        Ok(Self {
+            latitude: pointer.read_unaligned(),
+            longitude: pointer.add(1).read_unaligned(),
+        })
+    }
+}
+
+/// Laid out with padding after `lights`, which the simulator never sends
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(C)]
+struct GaugeState {
+    needle: f64,
+    mode: i16,
+    lights: [u8; 4],
+}
+
+unsafe impl ClientData for GaugeState {
+    fn fields() -> Vec<ClientDataField> {
+        vec![
+            ClientDataField {
+                offset: 0,
+                size_or_type: f64::SIZE_OR_TYPE,
+                epsilon: 0.5,
+            },
+            ClientDataField {
+                offset: 8,
+                size_or_type: i16::SIZE_OR_TYPE,
+                epsilon: 0.0,
+            },
+            ClientDataField {
+                offset: 10,
+                size_or_type: <[u8; 4]>::SIZE_OR_TYPE,
+                epsilon: 0.0,
+            },
+        ]
+    }
+}
+
+fn open(sim: &MockSim) -> SimConnect<sim_connect_rs::backend::MockBackend> {
+    SimConnect::open_with_backend(sim.backend(), "Mock Test", Some(Duration::from_millis(1)))
+        .expect("mock backend should always open")
+}
+
+fn subscribe(
+    sc: &mut SimConnect<sim_connect_rs::backend::MockBackend>,
+    event: SystemEvent,
+) -> Receiver<SystemEventData> {
+    let (sender, receiver) = channel();
+    let sender = std::sync::Mutex::new(sender);
+    sc.subscribe_to_system_event(event, move |data| {
+        let _ = sender.lock().unwrap().send(data.event_data);
+    })
+    .unwrap();
+    receiver
+}
+
+#[test]
+fn open_and_close_register_the_client() {
+    let sim = MockSim::new();
+    let sc = open(&sim);
+    assert_eq!(sim.program_name().as_deref(), Some("Mock Test"));
+
+    drop(sc);
+    assert!(!sim.is_open());
+}
+
+#[test]
+fn system_event_callbacks_fire() {
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    let crashed = subscribe(&mut sc, SystemEvent::Crashed);
+    let paused = subscribe(&mut sc, SystemEvent::Paused);
+
+    assert!(sim.fire_system_event(SystemEvent::Crashed));
+    assert!(matches!(
+        crashed.recv_timeout(TIMEOUT),
+        Ok(SystemEventData::Crashed)
+    ));
+
+    assert!(sim.fire_system_event(SystemEvent::Paused));
+    assert!(matches!(
+        paused.recv_timeout(TIMEOUT),
+        Ok(SystemEventData::Paused)
+    ));
+}
+
+#[test]
+fn system_events_with_data_are_parsed() {
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    let flight_loaded = subscribe(&mut sc, SystemEvent::FlightLoaded);
+    let object_added = subscribe(&mut sc, SystemEvent::ObjectAdded);
+    let sim_state = subscribe(&mut sc, SystemEvent::Sim);
+
+    sim.fire_filename_event(SystemEvent::FlightLoaded, "flights\\lesson1.FLT");
+    sim.fire_object_event(SystemEvent::ObjectAdded, SimObjectType::Boat);
+    sim.fire_system_event_with_data(SystemEvent::Sim, 1);
+
+    match flight_loaded.recv_timeout(TIMEOUT) {
+        Ok(SystemEventData::FlightLoaded(name)) => assert_eq!(name, "flights\\lesson1.FLT"),
+        other => panic!("unexpected event {other:?}"),
+    }
+    assert!(matches!(
+        object_added.recv_timeout(TIMEOUT),
+        Ok(SystemEventData::ObjectAdded(SimObjectType::Boat))
+    ));
+    assert!(matches!(
+        sim_state.recv_timeout(TIMEOUT),
+        Ok(SystemEventData::Sim(true))
+    ));
+}
+
+#[test]
+fn malformed_messages_are_reported_without_stopping_the_listener() {
+    use sim_connect_rs::SimConnectError;
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+    let flight_loaded = subscribe(&mut sc, SystemEvent::FlightLoaded);
+
+    // Cut off in the middle of a character, so the file name isn't valid UTF-8
+    sim.fire_filename_event(SystemEvent::FlightLoaded, &"é".repeat(200));
+    sim.fire_filename_event(SystemEvent::FlightLoaded, "flights\\lesson1.FLT");
+
+    match flight_loaded.recv_timeout(TIMEOUT) {
+        Ok(SystemEventData::FlightLoaded(name)) => assert_eq!(name, "flights\\lesson1.FLT"),
+        other => panic!("unexpected event {other:?}"),
+    }
+    assert!(matches!(
+        sc.take_exceptions().as_slice(),
+        [SimConnectError::Parse { .. }]
+    ));
+}
+
+#[test]
+fn unsubscribed_events_are_not_delivered() {
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    let crashed = subscribe(&mut sc, SystemEvent::Crashed);
+    sc.unsubscribe_from_system_event(SystemEvent::Crashed)
+        .unwrap();
+
+    assert!(!sim.is_subscribed(SystemEvent::Crashed));
+    assert!(!sim.fire_system_event(SystemEvent::Crashed));
+    assert!(crashed.recv_timeout(Duration::from_millis(50)).is_err());
+}
+
+#[test]
+fn set_data_on_self_updates_the_sim_vars() {
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    sc.set_data_on_self(Position {
+        latitude: 12.5,
+        longitude: -3.25,
+    })
+    .unwrap();
+
+    assert_eq!(sim.sim_var("Plane Latitude"), Some(MockValue::Number(12.5)));
+    assert_eq!(
+        sim.sim_var("plane longitude"),
+        Some(MockValue::Number(-3.25))
+    );
+}
+
+/// Laid out with padding after `on_ground`, which must not be sent
+#[derive(Clone, Debug, PartialEq)]
+#[repr(C)]
+struct Touchdown {
+    on_ground: i32,
+    altitude: f64,
+}
+
+impl StructToSimConnect for Touchdown {
+    fn get_fields() -> Vec<SimConnectDatum> {
+        vec![
+            SimConnectDatum {
+                id: 0,
+                sim_var: SimVar::SimOnGround,
+                sim_unit: None,
+                data_type: SimVarType::I32,
+            },
+            SimConnectDatum {
+                id: 1,
+                sim_var: SimVar::IndicatedAlt,
+                sim_unit: None,
+                data_type: SimVarType::F64,
+            },
+        ]
+    }
+
+    fn write_data(&self, writer: &mut DataWriter) -> SimConnectResult<()> {
+        // Safety: `#[repr(C)]`, with a field of the right type for each datum
+        unsafe { writer.write_repr_c(self) }
+    }
+}
+
+#[test]
+fn set_data_skips_the_padding_of_repr_c_structs() {
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    sc.set_data_on_self(Touchdown {
+        on_ground: 1,
+        altitude: 433.0,
+    })
+    .unwrap();
+
+    assert_eq!(sim.sim_var("SIM ON GROUND"), Some(MockValue::Number(1.0)));
+    assert_eq!(
+        sim.sim_var("INDICATED ALTITUDE"),
+        Some(MockValue::Number(433.0))
+    );
+    assert!(sc.take_exceptions().is_empty());
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn derived_structs_are_packed_field_by_field() {
+    use sim_connect_rs::backend::MockDataSet;
+
+    #[derive(Clone, StructToSimConnect)]
+    struct Spawned {
+        #[datum(sim_var = "SimVar::SimOnGround")]
+        on_ground: i32,
+        #[datum(sim_var = "SimVar::Title")]
+        title: String,
+        #[datum(sim_var = "SimVar::IndicatedAlt")]
+        altitude: f64,
+    }
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    sc.set_data_on_self(Spawned {
+        on_ground: 0,
+        title: "Cessna Skyhawk".to_owned(),
+        altitude: 2500.0,
+    })
+    .unwrap();
+
+    assert_eq!(
+        sim.take_data_sets(),
+        vec![MockDataSet {
+            object_id: 0,
+            elements: vec![vec![
+                ("SIM ON GROUND".to_owned(), MockValue::Number(0.0)),
+                ("TITLE".to_owned(), MockValue::from("Cessna Skyhawk")),
+                ("INDICATED ALTITUDE".to_owned(), MockValue::Number(2500.0)),
+            ]],
+            tagged: false,
+        }]
+    );
+    assert_eq!(
+        sim.sim_var("INDICATED ALTITUDE"),
+        Some(MockValue::Number(2500.0))
+    );
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn derived_structs_read_indexed_and_custom_sim_vars() {
+    use std::collections::HashSet;
+
+    #[derive(Clone, Debug, PartialEq, StructToSimConnect)]
+    #[repr(C)]
+    struct Engines {
+        #[datum(sim_var = "SimVar::GeneralEngRpm", index = 1)]
+        engine_1_rpm: f64,
+        #[datum(sim_var = "SimVar::GeneralEngRpm", index = 2)]
+        engine_2_rpm: f64,
+        #[datum(sim_var = "SimVar::Custom(\"L:MY_VAR\".into())")]
+        my_var: f64,
+    }
+
+    let fields: HashSet<SimVar> = Engines::get_fields()
+        .into_iter()
+        .map(|field| field.sim_var)
+        .collect();
+    assert!(fields.contains(&SimVar::GeneralEngRpm.index(2)));
+    assert!(fields.contains(&SimVar::Custom("L:MY_VAR".to_owned())));
+    assert!(!fields.contains(&SimVar::GeneralEngRpm));
+    // Simvars are told apart by the name SimConnect knows them by
+    assert!(fields.contains(&SimVar::Custom("General Eng Rpm:1".to_owned())));
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    sc.set_data_on_self(Engines {
+        engine_1_rpm: 2100.0,
+        engine_2_rpm: 2300.0,
+        my_var: 1.0,
+    })
+    .unwrap();
+    assert_eq!(
+        sim.sim_var("GENERAL ENG RPM:2"),
+        Some(MockValue::Number(2300.0))
+    );
+    assert_eq!(sim.sim_var("L:MY_VAR"), Some(MockValue::Number(1.0)));
+    assert_eq!(sim.sim_var("GENERAL ENG RPM"), None);
+}
+
+#[test]
+fn arrays_of_data_are_set_element_by_element() {
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+    let boat = sim.add_object(SimObjectType::Boat, 1_000);
+
+    let path: Vec<_> = (0..3)
+        .map(|i| Position {
+            latitude: 47.0 + i as f64,
+            longitude: -122.0,
+        })
+        .collect();
+    sc.set_data_array_on_object(boat, &path).unwrap();
+
+    let sets = sim.take_data_sets();
+    assert_eq!(sets.len(), 1);
+    assert_eq!(sets[0].object_id, boat);
+    assert_eq!(sets[0].elements.len(), 3);
+    assert_eq!(
+        sets[0].elements[2][0],
+        ("PLANE LATITUDE".to_owned(), MockValue::Number(49.0))
+    );
+    assert_eq!(
+        sim.object_var(boat, "PLANE LATITUDE"),
+        Some(MockValue::Number(49.0))
+    );
+
+    assert!(sc.set_data_array_on_object::<Position>(boat, &[]).is_err());
+    assert!(sc.take_exceptions().is_empty());
+}
+
+#[test]
+fn tagged_data_only_sets_the_sent_datums() {
+    use sim_connect_rs::SimConnectError;
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+    sim.set_sim_var("PLANE LONGITUDE", -122.0);
+
+    let position = Position {
+        latitude: 47.5,
+        longitude: 0.0,
+    };
+    sc.set_tagged_data_on_object(0, &position, &[0]).unwrap();
+
+    assert_eq!(sim.sim_var("PLANE LATITUDE"), Some(MockValue::Number(47.5)));
+    assert_eq!(
+        sim.sim_var("PLANE LONGITUDE"),
+        Some(MockValue::Number(-122.0))
+    );
+    let sets = sim.take_data_sets();
+    assert!(sets[0].tagged);
+    assert_eq!(sets[0].elements[0].len(), 1);
+
+    assert!(matches!(
+        sc.set_tagged_data_on_object(0, &position, &[7]),
+        Err(SimConnectError::InvalidData(_))
+    ));
+    assert!(sc.take_exceptions().is_empty());
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn get_latest_data_reads_the_sim_vars() {
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    sim.set_sim_var("Plane Latitude", 47.45);
+    sim.set_sim_var("Plane Longitude", -122.31);
+    assert_eq!(
+        sc.get_latest_data::<Position>().unwrap(),
+        Position {
+            latitude: 47.45,
+            longitude: -122.31
+        }
+    );
+
+    sim.set_sim_var("Plane Latitude", 48.0);
+    assert_eq!(sc.get_latest_data::<Position>().unwrap().latitude, 48.0);
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn request_system_state_answers_from_the_table() {
+    use sim_connect_rs::sim_event_args::SimStateArgs;
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    sim.set_system_state(SimStateArgs::FlightLoaded, "flights\\lesson1.FLT");
+    sim.set_system_state(SimStateArgs::Sim, true);
+
+    let state = sc.request_system_state(SimStateArgs::FlightLoaded).unwrap();
+    assert_eq!(state.flight_loaded.as_deref(), Some("flights\\lesson1.FLT"));
+
+    let state = sc.request_system_state(SimStateArgs::Sim).unwrap();
+    assert_eq!(state.sim, Some(true));
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn exceptions_are_returned_to_the_waiting_request() {
+    use sim_connect_rs::{error::SimConnectException, SimConnectError};
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    sim.reject_name("Plane Longitude");
+    match sc.get_latest_data::<Position>() {
+        Err(SimConnectError::Exception {
+            exception: SimConnectException::NameUnrecognized,
+            index: 2,
+            call: Some(call),
+            ..
+        }) => assert!(
+            call.contains("AddToDataDefinition(Plane Longitude"),
+            "{call}"
+        ),
+        other => panic!("unexpected result {other:?}"),
+    }
+    assert!(sc.take_exceptions().is_empty());
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn data_by_type_is_collected_into_one_snapshot() {
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    sim.set_sim_var("Plane Latitude", 1.0);
+    let near = sim.add_object(SimObjectType::Aircraft, 1_000);
+    let far = sim.add_object(SimObjectType::Aircraft, 9_000);
+    let out_of_range = sim.add_object(SimObjectType::Aircraft, 100_000);
+    let boat = sim.add_object(SimObjectType::Boat, 500);
+    for (object_id, latitude) in [(near, 2.0), (far, 3.0), (out_of_range, 4.0), (boat, 5.0)] {
+        sim.set_object_var(object_id, "Plane Latitude", latitude);
+    }
+
+    let latitudes: Vec<(u32, f64)> = sc
+        .get_data_by_type::<Position>(SimObjectType::Aircraft, 10_000)
+        .unwrap()
+        .into_iter()
+        .map(|(object_id, position)| (object_id, position.latitude))
+        .collect();
+    assert_eq!(latitudes, vec![(0, 1.0), (near, 2.0), (far, 3.0)]);
+
+    let boats = sc
+        .get_data_by_type::<Position>(SimObjectType::Boat, 10_000)
+        .unwrap();
+    assert_eq!(boats.len(), 1);
+    assert_eq!(boats[0].0, boat);
+
+    // A radius of 0 only ever returns the user's aircraft
+    let user = sc
+        .get_data_by_type::<Position>(SimObjectType::All, 0)
+        .unwrap();
+    assert_eq!(user.len(), 1);
+    assert_eq!(user[0].0, 0);
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn data_by_type_with_nothing_in_range_is_empty() {
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    sim.add_object(SimObjectType::Ground, 20_000);
+    assert!(sc
+        .get_data_by_type::<Position>(SimObjectType::Ground, 10_000)
+        .unwrap()
+        .is_empty());
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn data_on_object_reads_that_object() {
+    use sim_connect_rs::{error::SimConnectException, SimConnectError};
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    let helicopter = sim.add_object(SimObjectType::Helicopter, 2_000);
+    sim.set_object_var(helicopter, "Plane Latitude", 12.5);
+    sim.set_object_var(helicopter, "Plane Longitude", 7.25);
+    assert_eq!(
+        sc.get_data_on_object::<Position>(helicopter).unwrap(),
+        Position {
+            latitude: 12.5,
+            longitude: 7.25
+        }
+    );
+
+    sim.remove_object(helicopter);
+    match sc.get_data_on_object::<Position>(helicopter) {
+        Err(SimConnectError::Exception {
+            exception: SimConnectException::UnrecognizedId,
+            index: 3,
+            ..
+        }) => {}
+        other => panic!("unexpected result {other:?}"),
+    }
+}
+
+#[test]
+fn client_events_are_mapped_once_and_transmitted() {
+    use sim_connect_rs::{
+        backend::TransmittedEvent,
+        sim_input_events::{Failures, GearAndBrakes},
+    };
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    let gear_down = sc.map_client_event(GearAndBrakes::GearDown).unwrap();
+    assert_eq!(
+        sc.map_client_event(GearAndBrakes::GearDown).unwrap(),
+        gear_down
+    );
+    assert_ne!(
+        sc.map_client_event(GearAndBrakes::ParkingBrakes).unwrap(),
+        gear_down
+    );
+
+    sc.send(GearAndBrakes::GearDown).unwrap();
+    sc.transmit_client_event(GearAndBrakes::ParkingBrakes, 1)
+        .unwrap();
+    sc.transmit_client_event_ex1(Failures::MasterCautionSet, &[1, 2])
+        .unwrap();
+    assert!(sc
+        .transmit_client_event_ex1(Failures::MasterCautionSet, &[0; 6])
+        .is_err());
+
+    let event = |name: &str, data: Vec<u32>| TransmittedEvent {
+        name: name.to_owned(),
+        object_id: 0,
+        data,
+    };
+    assert_eq!(
+        sim.take_transmitted_events(),
+        vec![
+            event("GEAR_DOWN", vec![0]),
+            event("PARKING_BRAKES", vec![1]),
+            event("MASTER_CAUTION_SET", vec![1, 2, 0, 0, 0]),
+        ]
+    );
+    assert!(sc.take_exceptions().is_empty());
+}
+
+#[test]
+fn unknown_client_events_raise_an_exception() {
+    use sim_connect_rs::{
+        error::SimConnectException, sim_input_events::GearAndBrakes, SimConnectError,
+    };
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    sim.reject_name("GEAR_PUMP");
+    sc.send(GearAndBrakes::GearPump).unwrap();
+
+    let deadline = std::time::Instant::now() + TIMEOUT;
+    let exceptions = loop {
+        let exceptions = sc.take_exceptions();
+        if exceptions.len() >= 2 || std::time::Instant::now() > deadline {
+            break exceptions;
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    };
+
+    let calls: Vec<(SimConnectException, &str)> = exceptions
+        .iter()
+        .map(|exception| match exception {
+            SimConnectError::Exception {
+                exception,
+                call: Some(call),
+                ..
+            } => (*exception, call.as_str()),
+            other => panic!("unexpected exception {other:?}"),
+        })
+        .collect();
+    assert_eq!(
+        calls,
+        vec![
+            (
+                SimConnectException::NameUnrecognized,
+                "MapClientEventToSimEvent(GEAR_PUMP)"
+            ),
+            (
+                SimConnectException::UnrecognizedId,
+                "TransmitClientEvent(GEAR_PUMP, 0)"
+            ),
+        ]
+    );
+    assert!(sim.take_transmitted_events().is_empty());
+}
+
+fn subscribe_input(
+    sc: &mut SimConnect<sim_connect_rs::backend::MockBackend>,
+    event: sim_connect_rs::sim_input_events::GearAndBrakes,
+    priority: sim_connect_rs::sim_event_args::GroupPriority,
+    mask: bool,
+) -> Receiver<u32> {
+    let (sender, receiver) = channel();
+    let sender = std::sync::Mutex::new(sender);
+    sc.subscribe_to_input_event(event, priority, mask, move |event| {
+        let _ = sender.lock().unwrap().send(event.data);
+    })
+    .unwrap();
+    receiver
+}
+
+#[test]
+fn input_events_are_delivered_to_the_callback() {
+    use sim_connect_rs::{sim_event_args::GroupPriority, sim_input_events::GearAndBrakes};
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    let gear_down = subscribe_input(
+        &mut sc,
+        GearAndBrakes::GearDown,
+        GroupPriority::Default,
+        false,
+    );
+    assert!(sim.trigger_event("GEAR_DOWN", 3));
+    assert_eq!(gear_down.recv_timeout(TIMEOUT), Ok(3));
+
+    // Subscribing doesn't stop the client from transmitting the event itself
+    sc.send(GearAndBrakes::GearDown).unwrap();
+    assert_eq!(sim.take_transmitted_events().len(), 1);
+
+    sc.unsubscribe_from_input_event(GearAndBrakes::GearDown)
+        .unwrap();
+    assert!(sim.trigger_event("GEAR_DOWN", 4));
+    assert!(gear_down.recv_timeout(Duration::from_millis(50)).is_err());
+    assert!(sc.take_exceptions().is_empty());
+}
+
+#[test]
+fn input_events_share_a_notification_group_per_priority() {
+    use sim_connect_rs::{sim_event_args::GroupPriority, sim_input_events::GearAndBrakes};
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    let (sender, groups) = channel();
+    for (event, priority) in [
+        (GearAndBrakes::GearDown, GroupPriority::Default),
+        (GearAndBrakes::GearUp, GroupPriority::Default),
+        (GearAndBrakes::Brakes, GroupPriority::Lowest),
+    ] {
+        let sender = std::sync::Mutex::new(sender.clone());
+        sc.subscribe_to_input_event(event, priority, false, move |event| {
+            let _ = sender.lock().unwrap().send(event.group_id);
+        })
+        .unwrap();
+    }
+
+    let group_of = |name| {
+        assert!(sim.trigger_event(name, 0));
+        groups.recv_timeout(TIMEOUT).unwrap()
+    };
+    let default_group = group_of("GEAR_DOWN");
+    assert_eq!(group_of("GEAR_UP"), default_group);
+    let lowest_group = group_of("BRAKES");
+    assert_ne!(lowest_group, default_group);
+    // Group ids are allocated by the client rather than taken from the priority
+    assert_ne!(default_group, u32::from(GroupPriority::Default));
+    assert!(sc.take_exceptions().is_empty());
+}
+
+#[test]
+fn masked_input_events_are_kept_from_the_sim() {
+    use sim_connect_rs::{
+        error::HResult, sim_event_args::GroupPriority, sim_input_events::GearAndBrakes,
+        SimConnectError,
+    };
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    assert!(matches!(
+        sc.subscribe_to_input_event(GearAndBrakes::Brakes, GroupPriority::Highest, true, |_| {}),
+        Err(SimConnectError::HResult(HResult::InvalidArg))
+    ));
+
+    let brakes = subscribe_input(
+        &mut sc,
+        GearAndBrakes::Brakes,
+        GroupPriority::HighestMaskable,
+        true,
+    );
+    assert!(!sim.trigger_event("BRAKES", 0));
+    assert_eq!(brakes.recv_timeout(TIMEOUT), Ok(0));
+
+    // Masking after the simulator only keeps the event from later clients
+    let brakes = subscribe_input(&mut sc, GearAndBrakes::Brakes, GroupPriority::Lowest, true);
+    assert!(sim.trigger_event("BRAKES", 1));
+    assert_eq!(brakes.recv_timeout(TIMEOUT), Ok(1));
+    assert!(sc.take_exceptions().is_empty());
+}
+
+#[test]
+fn mapped_inputs_report_presses_and_releases() {
+    const GROUP: u32 = 1;
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    let (sender, chord) = channel();
+    let sender = std::sync::Mutex::new(sender);
+    sc.map_input(GROUP, "shift+ctrl+g", false, move |event| {
+        let _ = sender.lock().unwrap().send(event.data);
+    })
+    .unwrap();
+    let (sender, axis) = channel();
+    let sender = std::sync::Mutex::new(sender);
+    sc.map_input(GROUP, "joystick:0:XAxis", false, move |event| {
+        let _ = sender.lock().unwrap().send(event.data as i32);
+    })
+    .unwrap();
+
+    // Input groups start off
+    assert!(sim.press_input("ctrl+shift+g"));
+    assert!(chord.recv_timeout(Duration::from_millis(50)).is_err());
+
+    sc.set_input_group_state(GROUP, true).unwrap();
+    assert!(sim.press_input("Ctrl+Shift+G"));
+    assert_eq!(chord.recv_timeout(TIMEOUT), Ok(1));
+    assert!(sim.release_input("ctrl+shift+g"));
+    assert_eq!(chord.recv_timeout(TIMEOUT), Ok(0));
+    assert!(sim.move_input("joystick:0:xaxis", -500));
+    assert_eq!(axis.recv_timeout(TIMEOUT), Ok(-500));
+
+    sc.remove_input_event(GROUP, "ctrl+shift+g").unwrap();
+    assert!(sim.press_input("ctrl+shift+g"));
+    assert!(chord.recv_timeout(Duration::from_millis(50)).is_err());
+    assert!(sc.take_exceptions().is_empty());
+}
+
+#[test]
+fn inputs_mapped_to_sim_events_can_be_masked() {
+    use sim_connect_rs::{
+        sim_event_args::GroupPriority, sim_input_events::GearAndBrakes, SimConnectError,
+    };
+    const GROUP: u32 = 2;
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    assert!(matches!(
+        sc.map_input_to_event(GROUP, "hyper+g", GearAndBrakes::GearDown, false),
+        Err(SimConnectError::InvalidInputDefinition { .. })
+    ));
+
+    let gear_down = subscribe_input(
+        &mut sc,
+        GearAndBrakes::GearDown,
+        GroupPriority::Default,
+        false,
+    );
+    sc.map_input_to_event(GROUP, "joystick:0:button:3", GearAndBrakes::GearDown, true)
+        .unwrap();
+    sc.set_input_group_priority(GROUP, GroupPriority::HighestMaskable)
+        .unwrap();
+    sc.set_input_group_state(GROUP, true).unwrap();
+    assert!(sim.is_input_group_enabled(GROUP));
+
+    // The button triggers the sim event, and keeps the simulator's own binding from seeing it
+    assert!(!sim.press_input("joystick:0:button:3"));
+    assert_eq!(gear_down.recv_timeout(TIMEOUT), Ok(0));
+    // Only presses are mapped
+    assert!(sim.release_input("joystick:0:button:3"));
+    assert!(gear_down.recv_timeout(Duration::from_millis(50)).is_err());
+
+    sc.clear_input_group(GROUP).unwrap();
+    assert!(sim.press_input("joystick:0:button:3"));
+    assert!(gear_down.recv_timeout(Duration::from_millis(50)).is_err());
+    assert!(sc.take_exceptions().is_empty());
+}
+
+#[test]
+fn received_data_outlives_the_dispatch_buffer() {
+    use sim_connect_rs::{backend::SimConnectBackend, recv_data::RecvDataEvent};
+
+    const RECV_ID_SIMOBJECT_DATA: u32 = 8;
+    let message = |latitude: f64| {
+        let mut message = Vec::new();
+        for dword in [0, 4, RECV_ID_SIMOBJECT_DATA, 7, 0, 3, 0, 1, 1, 2] {
+            message.extend_from_slice(&u32::to_le_bytes(dword));
+        }
+        message.extend_from_slice(&latitude.to_le_bytes());
+        message.extend_from_slice(&(-latitude).to_le_bytes());
+        let size = message.len() as u32;
+        message[..4].copy_from_slice(&size.to_le_bytes());
+        message
+    };
+
+    let sim = MockSim::new();
+    let mut backend = sim.backend();
+    backend.open(c"Owned Data").unwrap();
+    backend.get_next_dispatch().unwrap();
+
+    sim.push_message(message(1.0));
+    sim.push_message(message(2.0));
+    let first = backend.get_next_dispatch().unwrap().unwrap();
+    let Ok(RecvDataEvent::Data(first)) = RecvDataEvent::from_pointer(first) else {
+        panic!("expected sim object data");
+    };
+    // Hands out the second message in place of the first one's buffer
+    backend.get_next_dispatch().unwrap().unwrap();
+
+    assert_eq!(first.get_request_id(), 7);
+    assert_eq!(first.get_id(), 3);
+    assert_eq!(first.get_data().len(), 16);
+    let position =
+        std::thread::spawn(move || unsafe { Position::parse_struct(first.get_pointer()) })
+            .join()
+            .unwrap()
+            .unwrap();
+    assert_eq!(
+        position,
+        Position {
+            latitude: 1.0,
+            longitude: -1.0
+        }
+    );
+}
+
+#[test]
+fn unclaimed_exceptions_are_kept_for_later() {
+    use sim_connect_rs::{error::SimConnectException, SimConnectError};
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    sim.reject_name("Paused");
+    let _paused = subscribe(&mut sc, SystemEvent::Paused);
+    assert!(!sim.is_subscribed(SystemEvent::Paused));
+
+    let deadline = std::time::Instant::now() + TIMEOUT;
+    let exceptions = loop {
+        let exceptions = sc.take_exceptions();
+        if !exceptions.is_empty() || std::time::Instant::now() > deadline {
+            break exceptions;
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    };
+
+    match exceptions.as_slice() {
+        [SimConnectError::Exception {
+            exception: SimConnectException::NameUnrecognized,
+            call: Some(call),
+            ..
+        }] => assert_eq!(call, "SubscribeToSystemEvent(Paused)"),
+        other => panic!("unexpected exceptions {other:?}"),
+    }
+}
+
+/// Waits for the next value of a subscription, failing the test if none arrives in time
+fn next_value(
+    subscription: &mut sim_connect_rs::subscription::DataSubscription<Position>,
+) -> Position {
+    let deadline = std::time::Instant::now() + TIMEOUT;
+    loop {
+        if let Some(value) = subscription.try_next() {
+            return value.unwrap();
+        }
+        assert!(std::time::Instant::now() < deadline, "no data received");
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn data_subscriptions_are_answered_every_period() {
+    use sim_connect_rs::subscription::Period;
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    let mut positions = sc.subscribe_data::<Position>(Period::SimFrame).unwrap();
+    for frame in 0..3 {
+        sim.set_sim_var("Plane Latitude", frame as f64);
+        sim.next_frame();
+        assert_eq!(next_value(&mut positions).latitude, frame as f64);
+    }
+
+    // Seconds are not frames
+    sim.next_second();
+    sim.next_frame();
+    next_value(&mut positions);
+    assert!(positions.try_next().is_none());
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn data_subscriptions_honour_origin_interval_and_limit() {
+    use sim_connect_rs::subscription::{DataRequest, Period};
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    let positions = sc
+        .subscribe_data::<Position>(
+            DataRequest::new(Period::Second)
+                .origin(1)
+                .interval(1)
+                .limit(2),
+        )
+        .unwrap();
+    for second in 1..=6 {
+        sim.set_sim_var("Plane Latitude", second as f64);
+        sim.next_second();
+    }
+
+    let latitudes: Vec<f64> = positions.map(|p| p.unwrap().latitude).collect();
+    assert_eq!(latitudes, vec![2.0, 4.0]);
+    assert_eq!(sim.data_request_count(), 0);
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn once_subscriptions_end_after_one_value() {
+    use sim_connect_rs::subscription::Period;
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    sim.set_sim_var("Plane Longitude", -3.5);
+    let positions: Vec<Position> = sc
+        .subscribe_data::<Position>(Period::Once)
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(
+        positions,
+        vec![Position {
+            latitude: 0.0,
+            longitude: -3.5
+        }]
+    );
+}
+
+#[test]
+fn changed_tagged_data_is_filled_in_from_earlier_values() {
+    use sim_connect_rs::subscription::{DataRequest, Period};
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    let mut positions = sc
+        .subscribe_data::<Position>(DataRequest::new(Period::SimFrame).changed().tagged())
+        .unwrap();
+
+    sim.set_sim_var("Plane Latitude", 1.0);
+    sim.set_sim_var("Plane Longitude", 2.0);
+    sim.next_frame();
+    assert_eq!(
+        next_value(&mut positions),
+        Position {
+            latitude: 1.0,
+            longitude: 2.0
+        }
+    );
+
+    // Nothing changed, so nothing is sent
+    sim.next_frame();
+    sim.set_sim_var("Plane Longitude", 3.0);
+    sim.next_frame();
+    assert_eq!(
+        next_value(&mut positions),
+        Position {
+            latitude: 1.0,
+            longitude: 3.0
+        }
+    );
+    assert!(positions.try_next().is_none());
+}
+
+#[test]
+fn dropping_a_subscription_stops_it() {
+    use sim_connect_rs::subscription::Period;
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    let positions = sc.subscribe_data::<Position>(Period::VisualFrame).unwrap();
+    let seconds = sc.subscribe_data::<Position>(Period::Second).unwrap();
+    assert_eq!(sim.data_request_count(), 2);
+
+    drop(positions);
+    assert_eq!(sim.data_request_count(), 1);
+    seconds.unsubscribe().unwrap();
+    assert_eq!(sim.data_request_count(), 0);
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn aircraft_input_events_are_enumerated_read_and_set() {
+    use sim_connect_rs::{
+        aircraft_input_events::{InputEventType, InputEventValue},
+        error::SimConnectException,
+        SimConnectError,
+    };
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    // More events than fit in one message, so the catalog is collected from several
+    for index in 0..5 {
+        sim.add_input_event(&format!("FUEL_PUMP_{index}"), 0.0);
+    }
+    let radio = sim.add_input_event("COM1_IDENT", "KSEA TWR");
+
+    let catalog = sc.enumerate_aircraft_input_events().unwrap();
+    assert_eq!(catalog.len(), 6);
+    let names: Vec<&str> = catalog.iter().map(|event| event.name.as_str()).collect();
+    assert_eq!(names[..2], ["FUEL_PUMP_0", "FUEL_PUMP_1"]);
+    let pump = catalog.get("FUEL_PUMP_3").unwrap();
+    assert_eq!(pump.event_type, InputEventType::Double);
+    assert_eq!(catalog.hash_of("COM1_IDENT"), Some(radio));
+    assert_eq!(
+        catalog.by_hash(radio).unwrap().event_type,
+        InputEventType::String
+    );
+
+    sc.set_aircraft_input_event(pump.hash, 1.0).unwrap();
+    assert_eq!(
+        sc.get_aircraft_input_event(pump.hash).unwrap(),
+        InputEventValue::Double(1.0)
+    );
+    assert_eq!(sim.input_event("FUEL_PUMP_3"), Some(MockValue::Number(1.0)));
+    sc.set_aircraft_input_event(radio, "KSEA GND").unwrap();
+    assert_eq!(
+        sc.get_aircraft_input_event(radio).unwrap().as_str(),
+        Some("KSEA GND")
+    );
+    assert_eq!(
+        sc.enumerate_aircraft_input_event_params(radio).unwrap(),
+        vec!["STRING".to_owned()]
+    );
+
+    assert!(matches!(
+        sc.get_aircraft_input_event(0xDEAD),
+        Err(SimConnectError::Exception {
+            exception: SimConnectException::GetInputEventFailed,
+            ..
+        })
+    ));
+    assert!(sc.take_exceptions().is_empty());
+}
+
+#[test]
+fn aircraft_input_event_changes_reach_the_callback() {
+    use sim_connect_rs::aircraft_input_events::InputEventValue;
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+    let beacon = sim.add_input_event("BEACON_SWITCH", 0.0);
+
+    let (sender, changes) = channel();
+    let sender = std::sync::Mutex::new(sender);
+    sc.subscribe_to_aircraft_input_event(beacon, move |value| {
+        let _ = sender.lock().unwrap().send(value);
+    })
+    .unwrap();
+
+    assert!(sim.set_input_event("BEACON_SWITCH", 1.0));
+    assert_eq!(
+        changes.recv_timeout(TIMEOUT),
+        Ok(InputEventValue::Double(1.0))
+    );
+    // Changes made by the client are reported too
+    sc.set_aircraft_input_event(beacon, 0.0).unwrap();
+    assert_eq!(
+        changes.recv_timeout(TIMEOUT),
+        Ok(InputEventValue::Double(0.0))
+    );
+
+    sc.unsubscribe_from_aircraft_input_event(beacon).unwrap();
+    assert!(!sim.set_input_event("BEACON_SWITCH", 1.0));
+    assert!(changes.recv_timeout(Duration::from_millis(50)).is_err());
+    assert!(sc.take_exceptions().is_empty());
+}
+
+/// `GaugeState` as it is laid out in a client data area
+fn gauge_bytes(needle: f64, mode: i16, lights: [u8; 4]) -> Vec<u8> {
+    let mut bytes = needle.to_le_bytes().to_vec();
+    bytes.extend(mode.to_le_bytes());
+    bytes.extend(lights);
+    bytes.resize(std::mem::size_of::<GaugeState>(), 0);
+    bytes
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn client_data_is_shared_with_other_clients() {
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    assert!(sim.create_client_data("Gauge.State", std::mem::size_of::<GaugeState>(), false));
+    let area = sc.map_client_data::<GaugeState>("Gauge.State").unwrap();
+
+    sim.set_client_data("Gauge.State", 0, &gauge_bytes(12.5, -2, [1, 0, 1, 0]));
+    assert_eq!(
+        sc.get_client_data(&area).unwrap(),
+        GaugeState {
+            needle: 12.5,
+            mode: -2,
+            lights: [1, 0, 1, 0]
+        }
+    );
+
+    sc.set_client_data(
+        &area,
+        &GaugeState {
+            needle: 3.0,
+            mode: 7,
+            lights: [0, 0, 0, 1],
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        sc.get_client_data(&area).unwrap(),
+        GaugeState {
+            needle: 3.0,
+            mode: 7,
+            lights: [0, 0, 0, 1]
+        }
+    );
+    assert_eq!(
+        sim.client_data("Gauge.State").unwrap(),
+        gauge_bytes(3.0, 7, [0, 0, 0, 1])
+    );
+}
+
+#[test]
+fn read_only_client_data_can_only_be_written_by_its_creator() {
+    use sim_connect_rs::{error::SimConnectException, SimConnectError};
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    // Created areas are prefixed with the program name
+    let own = sc.create_client_data::<GaugeState>(".Own", true).unwrap();
+    assert_eq!(own.name(), "Mock Test.Own");
+    let state = GaugeState {
+        needle: 1.0,
+        mode: 1,
+        lights: [1; 4],
+    };
+    sc.set_client_data(&own, &state).unwrap();
+    assert_eq!(
+        sim.client_data("Mock Test.Own").unwrap(),
+        gauge_bytes(1.0, 1, [1; 4])
+    );
+
+    sim.create_client_data("Gauge.Locked", std::mem::size_of::<GaugeState>(), true);
+    let locked = sc.map_client_data::<GaugeState>("Gauge.Locked").unwrap();
+    sc.set_client_data(&locked, &state).unwrap();
+
+    let deadline = std::time::Instant::now() + TIMEOUT;
+    let exceptions = loop {
+        let exceptions = sc.take_exceptions();
+        if !exceptions.is_empty() || std::time::Instant::now() > deadline {
+            break exceptions;
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    };
+    match exceptions.as_slice() {
+        [SimConnectError::Exception {
+            exception: SimConnectException::IllegalOperation,
+            call: Some(call),
+            ..
+        }] => assert!(call.starts_with("SetClientData(Gauge.Locked)"), "{call}"),
+        other => panic!("unexpected exceptions {other:?}"),
+    }
+    assert_eq!(sim.client_data("Gauge.Locked").unwrap(), vec![0; 16]);
+}
+
+#[test]
+fn client_data_subscriptions_only_send_changes_beyond_epsilon() {
+    use sim_connect_rs::subscription::{ClientDataPeriod, ClientDataRequest};
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    sim.create_client_data("Gauge.State", std::mem::size_of::<GaugeState>(), false);
+    let area = sc.map_client_data::<GaugeState>("Gauge.State").unwrap();
+    let mut changes = sc
+        .subscribe_client_data(
+            &area,
+            ClientDataRequest::new(ClientDataPeriod::OnSet).changed(),
+        )
+        .unwrap();
+
+    let next = |changes: &mut sim_connect_rs::subscription::ClientDataSubscription<GaugeState>| {
+        let deadline = std::time::Instant::now() + TIMEOUT;
+        loop {
+            if let Some(value) = changes.try_next() {
+                return value.unwrap();
+            }
+            assert!(std::time::Instant::now() < deadline, "no data received");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    };
+
+    sim.set_client_data("Gauge.State", 0, &gauge_bytes(10.0, 0, [0; 4]));
+    assert_eq!(next(&mut changes).needle, 10.0);
+
+    // Within the needle's epsilon, so not a change
+    sim.set_client_data("Gauge.State", 0, &gauge_bytes(10.25, 0, [0; 4]));
+    // Any change to a field without an epsilon counts
+    sim.set_client_data("Gauge.State", 0, &gauge_bytes(10.25, 0, [0, 1, 0, 0]));
+    assert_eq!(
+        next(&mut changes),
+        GaugeState {
+            needle: 10.25,
+            mode: 0,
+            lights: [0, 1, 0, 0]
+        }
+    );
+    assert!(changes.try_next().is_none());
+
+    // Frames and seconds don't answer requests for whenever the area is set
+    sim.next_frame();
+    sim.next_second();
+    assert_eq!(sim.client_data_request_count(), 1);
+    drop(changes);
+    assert_eq!(sim.client_data_request_count(), 0);
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn lvars_are_read_through_the_wasm_bridge() {
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+    sim.install_wasm_bridge();
+    sim.set_lvar("A32NX_AUTOPILOT_1_ACTIVE", 1.0);
+
+    sc.ping_wasm_bridge().unwrap();
+    assert_eq!(sc.read_lvar("A32NX_AUTOPILOT_1_ACTIVE").unwrap(), 1.0);
+    assert_eq!(sc.read_lvar("A32NX_UNSET").unwrap(), 0.0);
+
+    // The module keeps registered variables up to date
+    sim.set_lvar("A32NX_AUTOPILOT_1_ACTIVE", 0.0);
+    assert_eq!(sc.read_lvar("A32NX_AUTOPILOT_1_ACTIVE").unwrap(), 0.0);
+
+    sc.set_lvar("A32NX_UNSET", 2.5).unwrap();
+    assert_eq!(sc.read_lvar("A32NX_UNSET").unwrap(), 2.5);
+    assert!(sc.take_exceptions().is_empty());
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn lvars_which_cannot_be_written_as_calculator_code_are_rejected() {
+    use sim_connect_rs::{error::HResult, SimConnectError};
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+    sim.install_wasm_bridge();
+
+    for (name, value) in [
+        ("A32NX_UNSET", f64::NAN),
+        ("A32NX_UNSET", f64::INFINITY),
+        ("A32NX UNSET", 1.0),
+        ("A32NX_UNSET) (>H:A320_Neo_CDU_1_BTN_INIT", 1.0),
+    ] {
+        assert!(matches!(
+            sc.set_lvar(name, value),
+            Err(SimConnectError::HResult(HResult::InvalidArg))
+        ));
+    }
+    assert!(sim.take_fired_hevents().is_empty());
+    assert_eq!(sc.read_lvar("A32NX_UNSET").unwrap(), 0.0);
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn the_wasm_bridge_fails_without_the_module() {
+    use sim_connect_rs::{error::SimConnectException, SimConnectError};
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    match sc.read_lvar("A32NX_AUTOPILOT_1_ACTIVE") {
+        Err(SimConnectError::Exception {
+            exception, call, ..
+        }) => {
+            assert_eq!(exception, SimConnectException::UnrecognizedId);
+            assert!(call.unwrap().contains("MobiFlight.Response"));
+        }
+        other => panic!("expected an exception, got {other:?}"),
+    }
+
+    // Installing the module later sets the bridge up again
+    sim.install_wasm_bridge();
+    sim.set_lvar("A32NX_AUTOPILOT_1_ACTIVE", 1.0);
+    assert_eq!(sc.read_lvar("A32NX_AUTOPILOT_1_ACTIVE").unwrap(), 1.0);
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn lvars_and_hevents_are_written_through_the_wasm_bridge() {
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+    sim.install_wasm_bridge();
+
+    sc.set_lvar("XMLVAR_Baro1_Mode", 2.0).unwrap();
+    sc.fire_hevent("A320_Neo_CDU_1_BTN_INIT").unwrap();
+    sc.execute_calculator_code(
+        "(L:XMLVAR_Baro1_Mode) (>L:XMLVAR_Baro2_Mode) (>H:A320_Neo_CDU_1_BTN_DIR)",
+    )
+    .unwrap();
+
+    assert_eq!(sim.lvar("XMLVAR_Baro1_Mode"), Some(2.0));
+    assert_eq!(sim.lvar("XMLVAR_Baro2_Mode"), Some(2.0));
+    assert_eq!(
+        sim.take_fired_hevents(),
+        ["A320_Neo_CDU_1_BTN_INIT", "A320_Neo_CDU_1_BTN_DIR"]
+    );
+
+    // Commands have to fit in the command area
+    assert!(sc.execute_calculator_code(&"1 ".repeat(128)).is_err());
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn the_wasm_bridge_uses_a_channel_of_its_own() {
+    use sim_connect_rs::wasm_bridge::{self, BridgeMessage};
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+    sim.install_wasm_bridge();
+
+    // Another client of the module, such as the MobiFlight Connector, on the default channel
+    let connector = sc
+        .map_client_data::<BridgeMessage>(wasm_bridge::COMMAND_AREA)
+        .unwrap();
+    let command = BridgeMessage::new("MF.SimVars.Add.(L:CONNECTOR_VAR)").unwrap();
+    sc.set_client_data(&connector, &command).unwrap();
+
+    sim.set_lvar("A32NX_AUTOPILOT_1_ACTIVE", 1.0);
+    assert_eq!(sc.read_lvar("A32NX_AUTOPILOT_1_ACTIVE").unwrap(), 1.0);
+
+    assert_eq!(
+        sim.bridge_variables(wasm_bridge::DEFAULT_CLIENT).unwrap(),
+        ["(L:CONNECTOR_VAR)"]
+    );
+    assert_eq!(
+        sim.bridge_variables("Mock Test").unwrap(),
+        ["(L:A32NX_AUTOPILOT_1_ACTIVE)"]
+    );
+    assert!(sc.take_exceptions().is_empty());
+}
+
+#[cfg(not(feature = "async"))]
+fn mock_airport() -> sim_connect_rs::backend::MockFacility {
+    use sim_connect_rs::{backend::MockFacility, facilities::FacilityDataType};
+
+    MockFacility::new(FacilityDataType::Airport)
+        .with("ICAO", "KSEA")
+        .with("REGION", "K1")
+        .with("NAME", "Seattle-Tacoma Intl")
+        .with("LATITUDE", 47.449)
+        .with("LONGITUDE", -122.309)
+        .with("ALTITUDE", 131.978)
+        .child(
+            MockFacility::new(FacilityDataType::Runway)
+                .with("LENGTH", 3627.0)
+                .with("PRIMARY_NUMBER", 16)
+                .with("PRIMARY_DESIGNATOR", 1)
+                .with("PRIMARY_ILS_ICAO", "ISNQ")
+                .with("SECONDARY_NUMBER", 34)
+                .with("SECONDARY_DESIGNATOR", 2),
+        )
+        .child(
+            MockFacility::new(FacilityDataType::Runway)
+                .with("PRIMARY_NUMBER", 16)
+                .with("PRIMARY_DESIGNATOR", 3)
+                .with("SECONDARY_NUMBER", 34)
+                .with("SECONDARY_DESIGNATOR", 3),
+        )
+        .child(
+            MockFacility::new(FacilityDataType::Frequency)
+                .with("TYPE", 1)
+                .with("FREQUENCY", 118_000_000)
+                .with("NAME", "ATIS"),
+        )
+        .child(
+            MockFacility::new(FacilityDataType::Approach)
+                .with("TYPE", 4)
+                .with("RUNWAY_NUMBER", 16)
+                .with("RUNWAY_DESIGNATOR", 1)
+                .with("FAF_ICAO", "JOMPO")
+                .child(
+                    MockFacility::new(FacilityDataType::ApproachTransition)
+                        .with("IAF_ICAO", "SUMMA"),
+                ),
+        )
+        .child(
+            MockFacility::new(FacilityDataType::Departure)
+                .with("NAME", "SUMMA2")
+                .child(
+                    MockFacility::new(FacilityDataType::RunwayTransition)
+                        .with("RUNWAY_NUMBER", 34)
+                        .with("RUNWAY_DESIGNATOR", 3),
+                )
+                .child(MockFacility::new(FacilityDataType::EnrouteTransition).with("NAME", "EPH")),
+        )
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn facilities_are_read_into_a_tree() {
+    use sim_connect_rs::facilities::{Airport, ApproachType, FrequencyType, Vor};
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+    sim.add_facility("KSEA", "K1", mock_airport());
+
+    let airport = sc.get_facility::<Airport>("KSEA", None).unwrap().unwrap();
+    assert_eq!(airport.icao, "KSEA");
+    assert_eq!(airport.name, "Seattle-Tacoma Intl");
+    assert_eq!(airport.latitude, 47.449);
+
+    assert_eq!(airport.runways.len(), 2);
+    let runway = airport.runway("34R").unwrap();
+    assert_eq!(runway.primary.ident(), "16L");
+    assert_eq!(runway.length, 3627.0);
+    assert_eq!(runway.primary.ils, Some(("ISNQ".to_owned(), String::new())));
+    assert_eq!(runway.secondary.ils, None);
+    assert_eq!(airport.runways[1].primary.ident(), "16C");
+
+    assert_eq!(airport.frequencies[0].frequency_type, FrequencyType::Atis);
+    assert_eq!(airport.frequencies[0].mhz(), 118.0);
+
+    let approach = &airport.approaches[0];
+    assert_eq!(approach.approach_type, ApproachType::Ils);
+    assert_eq!(approach.runway(), "16L");
+    assert_eq!(approach.transitions[0].iaf.as_ref().unwrap().0, "SUMMA");
+
+    let departure = &airport.departures[0];
+    assert_eq!(departure.name, "SUMMA2");
+    assert_eq!(departure.runway_transitions[0].runway(), "34C");
+    assert_eq!(departure.enroute_transitions[0].name, "EPH");
+    assert!(airport.arrivals.is_empty());
+
+    // The definition is only added once, and unknown or mismatched facilities are `None`
+    assert!(sc
+        .get_facility::<Airport>("KSEA", Some("K2"))
+        .unwrap()
+        .is_none());
+    assert!(sc.get_facility::<Airport>("XXXX", None).unwrap().is_none());
+    assert!(sc.get_facility::<Vor>("KSEA", None).unwrap().is_none());
+    assert!(sc.take_exceptions().is_empty());
+}
+
+#[cfg(not(feature = "async"))]
+fn add_mock_airports(sim: &MockSim, icaos: &[&str]) {
+    use sim_connect_rs::{backend::MockFacility, facilities::FacilityDataType};
+
+    for (i, icao) in icaos.iter().enumerate() {
+        sim.add_facility(
+            icao,
+            "K1",
+            MockFacility::new(FacilityDataType::Airport)
+                .with("LATITUDE", 47.0 + i as f64)
+                .with("LONGITUDE", -122.0),
+        );
+    }
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn facility_lists_are_merged_across_messages() {
+    use sim_connect_rs::{
+        backend::MockFacility,
+        facilities::{AirportEntry, FacilityDataType, NdbEntry, VorEntry},
+    };
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+    let airports = ["KSEA", "KBFI", "KRNT", "KPAE", "KTIW", "KOLM"];
+    add_mock_airports(&sim, &airports);
+    sim.add_facility(
+        "SEA",
+        "K1",
+        MockFacility::new(FacilityDataType::Vor)
+            .with("VOR_LATITUDE", 47.435)
+            .with("FREQUENCY", 116_800_000)
+            .with("MAGVAR", 16.0)
+            .with("HAS_DME", true),
+    );
+    sim.set_facilities_in_range(&["KSEA", "KBFI", "KRNT", "KPAE", "KTIW", "KOLM", "SEA"]);
+
+    // Six airports take two messages
+    let listed = sc.request_facilities_list::<AirportEntry>().unwrap();
+    assert_eq!(
+        listed.iter().map(|a| a.icao.as_str()).collect::<Vec<_>>(),
+        airports
+    );
+    assert_eq!(listed[1].region, "K1");
+    assert_eq!(listed[1].latitude, 48.0);
+
+    let vors = sc.request_facilities_list::<VorEntry>().unwrap();
+    assert_eq!(vors.len(), 1);
+    assert_eq!(vors[0].icao, "SEA");
+    assert_eq!(vors[0].latitude, 47.435);
+    assert_eq!(vors[0].frequency, 116_800_000);
+    assert_eq!(vors[0].magvar, 16.0);
+    assert!(vors[0].has_dme() && !vors[0].has_localizer());
+
+    assert!(sc.request_facilities_list::<NdbEntry>().unwrap().is_empty());
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn facility_subscriptions_report_additions_and_removals() {
+    use sim_connect_rs::facilities::{AirportEntry, FacilityChange};
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+    add_mock_airports(&sim, &["KSEA", "KBFI", "KRNT", "KPAE"]);
+    sim.set_facilities_in_range(&["KSEA", "KBFI"]);
+
+    let mut changes = sc.subscribe_to_facilities::<AirportEntry>().unwrap();
+    let mut next = || {
+        let deadline = std::time::Instant::now() + TIMEOUT;
+        loop {
+            if let Some(change) = changes.try_next() {
+                return match change.unwrap() {
+                    FacilityChange::Added(airport) => format!("+{}", airport.icao),
+                    FacilityChange::Removed(airport) => format!("-{}", airport.icao),
+                };
+            }
+            assert!(std::time::Instant::now() < deadline, "no change received");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    };
+    assert_eq!([next(), next()], ["+KSEA", "+KBFI"]);
+
+    // Flying north brings KPAE into range and leaves KSEA behind
+    sim.set_facilities_in_range(&["KBFI", "KRNT", "KPAE"]);
+    assert_eq!([next(), next(), next()], ["+KRNT", "+KPAE", "-KSEA"]);
+
+    drop(changes);
+    sim.set_facilities_in_range(&["KSEA"]);
+    assert!(sc.take_exceptions().is_empty());
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn ai_objects_are_created_driven_and_removed() {
+    use sim_connect_rs::sim_objects::InitPosition;
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+    sim.add_container("Cessna Skyhawk Asobo", SimObjectType::Aircraft);
+
+    let parked = sc
+        .create_parked_atc_aircraft("Cessna Skyhawk Asobo", "N172SP", "KSEA")
+        .unwrap();
+    let parked_object = sim.ai_object(parked.0).unwrap();
+    assert_eq!(parked_object.tail_number.as_deref(), Some("N172SP"));
+    assert_eq!(parked_object.airport.as_deref(), Some("KSEA"));
+    sc.set_aircraft_flight_plan(parked, "KSEA-KPAE").unwrap();
+    assert_eq!(
+        sim.ai_object(parked.0).unwrap().flight_plan.as_deref(),
+        Some("KSEA-KPAE")
+    );
+
+    let flying = sc
+        .create_non_atc_aircraft(
+            "Cessna Skyhawk Asobo",
+            "N1234",
+            InitPosition::new(47.5, -122.25, 3_000.0).heading(90.0),
+        )
+        .unwrap();
+    assert_ne!(flying, parked);
+    assert_eq!(
+        sc.get_data_on_object::<Position>(flying).unwrap(),
+        Position {
+            latitude: 47.5,
+            longitude: -122.25
+        }
+    );
+
+    // Once released, the object is driven by the client
+    sc.release_control(flying).unwrap();
+    assert!(sim.ai_object(flying.0).unwrap().released);
+    sc.set_data_on_object(
+        flying,
+        Position {
+            latitude: 47.75,
+            longitude: -122.5,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        sim.object_var(flying.0, "Plane Latitude"),
+        Some(MockValue::Number(47.75))
+    );
+    assert_eq!(sim.sim_var("Plane Latitude"), None);
+
+    sc.remove_object(flying).unwrap();
+    assert!(sim.ai_object(flying.0).is_none());
+    assert!(sc.take_exceptions().is_empty());
+
+    // Objects created by a client are removed once it disconnects
+    drop(sc);
+    assert!(sim.ai_object(parked.0).is_none());
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn ai_object_failures_raise_exceptions() {
+    use sim_connect_rs::{error::SimConnectException, sim_objects::SimObjectId, SimConnectError};
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    match sc.create_parked_atc_aircraft("Not Installed", "N1", "KSEA") {
+        Err(SimConnectError::Exception {
+            exception: SimConnectException::CreateObjectFailed,
+            call: Some(call),
+            ..
+        }) => assert_eq!(call, "AICreateParkedATCAircraft(Not Installed, KSEA)"),
+        other => panic!("unexpected result {other:?}"),
+    }
+
+    // Only objects the client created can be removed
+    let traffic = sim.add_object(SimObjectType::Aircraft, 100);
+    sc.remove_object(SimObjectId(traffic)).unwrap();
+
+    let deadline = std::time::Instant::now() + TIMEOUT;
+    let exceptions = loop {
+        let exceptions = sc.take_exceptions();
+        if !exceptions.is_empty() || std::time::Instant::now() > deadline {
+            break exceptions;
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    };
+    match exceptions.as_slice() {
+        [SimConnectError::Exception {
+            exception: SimConnectException::UnrecognizedId,
+            call: Some(call),
+            ..
+        }] => assert_eq!(call, &format!("AIRemoveObject({traffic})")),
+        other => panic!("unexpected exceptions {other:?}"),
+    }
+    assert!(sim.remove_object(traffic));
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn flights_are_saved_and_loaded() {
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+    let loaded = subscribe(&mut sc, SystemEvent::FlightLoaded);
+
+    let saved = sc
+        .flight_save(
+            "Scenarios\\Engine Failure",
+            "Engine failure",
+            "Engine 1 fails on the climb out",
+        )
+        .unwrap();
+    assert_eq!(saved, "Scenarios\\Engine Failure");
+    assert_eq!(
+        sim.flight("scenarios/engine failure.FLT").unwrap().title,
+        "Engine failure"
+    );
+
+    assert_eq!(sc.flight_load(&saved).unwrap(), saved);
+    assert_eq!(sim.loaded_flight(), Some(saved.clone()));
+    // Subscribers to the event are still told about the flight
+    assert!(matches!(
+        loaded.recv_timeout(TIMEOUT),
+        Ok(SystemEventData::FlightLoaded(file_name)) if file_name == saved
+    ));
+
+    sim.add_flight_plan("Plans\\KSEA-KPDX.pln");
+    assert_eq!(
+        sc.flight_plan_load("Plans\\KSEA-KPDX").unwrap(),
+        "Plans\\KSEA-KPDX"
+    );
+    assert_eq!(
+        sim.loaded_flight_plan().as_deref(),
+        Some("Plans\\KSEA-KPDX")
+    );
+    assert!(sc.take_exceptions().is_empty());
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn missing_flights_fail_with_an_exception() {
+    use sim_connect_rs::{error::SimConnectException, SimConnectError};
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    match sc.flight_load("Scenarios\\Missing") {
+        Err(SimConnectError::Exception {
+            exception: SimConnectException::Error,
+            call: Some(call),
+            ..
+        }) => assert_eq!(call, "FlightLoad(Scenarios\\Missing)"),
+        other => panic!("unexpected result {other:?}"),
+    }
+    assert!(sc.flight_plan_load("Plans\\Missing").is_err());
+    assert_eq!(sim.loaded_flight(), None);
+
+    // A failed load doesn't answer the next one
+    sim.add_flight("Scenarios\\Cold And Dark", "Cold and dark");
+    assert_eq!(
+        sc.flight_load("Scenarios\\Cold And Dark.flt").unwrap(),
+        "Scenarios\\Cold And Dark.flt"
+    );
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn custom_sim_vars_with_a_null_character_are_rejected() {
+    use sim_connect_rs::{
+        data_definition::DataDefinition, sim_objects::SimObjectId, SimConnectError,
+    };
+
+    #[derive(Clone)]
+    struct Broken;
+
+    impl StructToSimConnect for Broken {
+        fn get_fields() -> Vec<SimConnectDatum> {
+            vec![SimConnectDatum {
+                id: 0,
+                sim_var: SimVar::Custom("L:MY\0VAR".to_owned()),
+                sim_unit: None,
+                data_type: SimVarType::F64,
+            }]
+        }
+
+        fn write_data(&self, writer: &mut DataWriter) -> SimConnectResult<()> {
+            writer.write(&0.0)
+        }
+    }
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    assert!(matches!(
+        sc.register_struct::<Broken>(),
+        Err(SimConnectError::InvalidString(_))
+    ));
+    let definition = DataDefinition::new().add(
+        SimVar::Custom("L:MY\0VAR".to_owned()),
+        None,
+        SimVarType::F64,
+    );
+    assert!(matches!(
+        sc.get_values_on_object(&definition, SimObjectId::USER),
+        Err(SimConnectError::InvalidString(_))
+    ));
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn runtime_definitions_read_and_write_values() {
+    use sim_connect_rs::{
+        data_definition::{DataDefinition, SimValue},
+        sim_objects::SimObjectId,
+    };
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+    let definition = DataDefinition::new()
+        .add(SimVar::PlaneLat, Some(&GPS::DegLat), SimVarType::F64)
+        .add_named("GENERAL ENG RPM:1", "rpm", SimVarType::I32)
+        .add_named("TITLE", "", SimVarType::String64)
+        .add_named("ATC ID", "", SimVarType::StringV);
+
+    sim.set_sim_var("Plane Latitude", 47.5);
+    sim.set_sim_var("General Eng Rpm:1", 2400);
+    sim.set_sim_var("Title", "Cessna Skyhawk G1000 Asobo");
+    sim.set_sim_var("ATC ID", "N172SP");
+    let values = sc
+        .get_values_on_object(&definition, SimObjectId::USER)
+        .unwrap();
+    assert_eq!(
+        values,
+        [
+            SimValue::F64(47.5),
+            SimValue::I32(2400),
+            SimValue::from("Cessna Skyhawk G1000 Asobo"),
+            SimValue::from("N172SP"),
+        ]
+    );
+    let named = definition.to_map(values);
+    assert_eq!(named["GENERAL ENG RPM:1"].as_f64(), Some(2400.0));
+    assert_eq!(named["ATC ID"].as_str(), Some("N172SP"));
+
+    sc.set_values_on_object(
+        &definition,
+        SimObjectId::USER,
+        &[
+            SimValue::F64(-12.25),
+            SimValue::I32(1800),
+            SimValue::from("Cessna Skyhawk G1000 Asobo"),
+            SimValue::from("N12345"),
+        ],
+    )
+    .unwrap();
+    assert_eq!(
+        sim.sim_var("plane latitude"),
+        Some(MockValue::Number(-12.25))
+    );
+    assert_eq!(
+        sim.sim_var("ATC ID"),
+        Some(MockValue::Text("N12345".to_owned()))
+    );
+
+    let boat = sim.add_object(SimObjectType::Boat, 500);
+    sim.set_object_var(boat, "General Eng Rpm:1", 900);
+    let boats = sc
+        .get_values_by_type(&definition, SimObjectType::Boat, 1_000)
+        .unwrap();
+    assert_eq!(boats.len(), 1);
+    assert_eq!(boats[0].0, boat);
+    assert_eq!(boats[0].1[1], SimValue::I32(900));
+    assert!(sc.take_exceptions().is_empty());
+}
+
+#[test]
+fn runtime_definition_subscriptions_fill_in_tagged_values() {
+    use sim_connect_rs::{
+        data_definition::{DataDefinition, SimValue},
+        subscription::{DataRequest, Period},
+    };
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+    let definition = DataDefinition::new()
+        .add_named("PLANE ALTITUDE", "feet", SimVarType::F64)
+        .add_named("GENERAL ENG RPM:2", "rpm", SimVarType::F32);
+
+    let mut values = sc
+        .subscribe_values(
+            &definition,
+            DataRequest::new(Period::SimFrame).changed().tagged(),
+        )
+        .unwrap();
+    let mut next = || {
+        let deadline = std::time::Instant::now() + TIMEOUT;
+        loop {
+            if let Some(value) = values.try_next() {
+                return value.unwrap();
+            }
+            assert!(std::time::Instant::now() < deadline, "no data received");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    };
+
+    sim.set_sim_var("Plane Altitude", 1500.0);
+    sim.next_frame();
+    assert_eq!(next(), [SimValue::F64(1500.0), SimValue::F32(0.0)]);
+
+    sim.set_sim_var("General Eng Rpm:2", 2200.0);
+    sim.next_frame();
+    assert_eq!(next(), [SimValue::F64(1500.0), SimValue::F32(2200.0)]);
+}
+
+#[test]
+fn invalid_runtime_definitions_are_rejected() {
+    use sim_connect_rs::{
+        data_definition::{DataDefinition, SimValue},
+        sim_objects::SimObjectId,
+        SimConnectError,
+    };
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+
+    assert!(matches!(
+        sc.register_definition(&DataDefinition::new()),
+        Err(SimConnectError::InvalidData(_))
+    ));
+    let waypoints = DataDefinition::new().add_named("AI WAYPOINT LIST", "", SimVarType::Waypoint);
+    assert!(matches!(
+        sc.register_definition(&waypoints),
+        Err(SimConnectError::InvalidData(_))
+    ));
+
+    let definition = DataDefinition::new().add_named("PLANE ALTITUDE", "feet", SimVarType::F64);
+    assert!(matches!(
+        sc.set_values_on_object(&definition, SimObjectId::USER, &[]),
+        Err(SimConnectError::InvalidData(_))
+    ));
+    assert!(matches!(
+        sc.set_values_on_object(&definition, SimObjectId::USER, &[SimValue::from("high")]),
+        Err(SimConnectError::InvalidData(_))
+    ));
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn indexed_sim_vars_are_read_by_instance() {
+    use sim_connect_rs::{
+        data_definition::{DataDefinition, SimValue},
+        sim_objects::SimObjectId,
+    };
+
+    let sim = MockSim::new();
+    let mut sc = open(&sim);
+    let throttle = SimVar::GeneralEngThrottleLeverPosition;
+    let definition = DataDefinition::new()
+        .add(throttle.clone().index(1), None, SimVarType::F64)
+        .add(throttle.index(2), None, SimVarType::F64)
+        .add(
+            SimVar::Custom("L:FLAPS_HANDLE".to_owned()),
+            None,
+            SimVarType::I32,
+        );
+
+    sim.set_sim_var("GENERAL ENG THROTTLE LEVER POSITION:1", 40.0);
+    sim.set_sim_var("GENERAL ENG THROTTLE LEVER POSITION:2", 85.0);
+    sim.set_sim_var("L:FLAPS_HANDLE", 2);
+    assert_eq!(
+        sc.get_values_on_object(&definition, SimObjectId::USER)
+            .unwrap(),
+        [SimValue::F64(40.0), SimValue::F64(85.0), SimValue::I32(2)]
+    );
+}
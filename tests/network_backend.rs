@@ -0,0 +1,383 @@
+use sim_connect_rs::{
+    backend::{NetworkBackend, SimConnectBackend},
+    error::SimConnectException,
+    sim_events::{SystemEvent, SystemEventData},
+    SimConnect, SimConnectError,
+};
+use std::{
+    collections::HashMap,
+    ffi::CString,
+    io::{Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        mpsc::{channel, Receiver},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+const RECV_ID_EXCEPTION: u32 = 1;
+const RECV_ID_OPEN: u32 = 2;
+const RECV_ID_EVENT: u32 = 4;
+const RECV_ID_SIMOBJECT_DATA_BYTYPE: u32 = 9;
+const RECV_ID_SYSTEM_STATE: u32 = 15;
+
+/// A packet received by the fake server
+#[derive(Debug, Clone)]
+struct Sent {
+    version: u32,
+    packet_type: u32,
+    send_id: u32,
+    body: Vec<u8>,
+}
+
+impl Sent {
+    fn dword(&self, offset: usize) -> u32 {
+        u32::from_le_bytes(self.body[offset..offset + 4].try_into().unwrap())
+    }
+
+    fn string256(&self, offset: usize) -> String {
+        let raw = &self.body[offset..offset + 256];
+        let end = raw.iter().position(|b| *b == 0).unwrap_or(raw.len());
+        String::from_utf8_lossy(&raw[..end]).into_owned()
+    }
+}
+
+fn packet(recv_id: u32, body: &[u8]) -> Vec<u8> {
+    let mut packet = Vec::new();
+    packet.extend_from_slice(&(12 + body.len() as u32).to_le_bytes());
+    packet.extend_from_slice(&4u32.to_le_bytes());
+    packet.extend_from_slice(&recv_id.to_le_bytes());
+    packet.extend_from_slice(body);
+    packet
+}
+
+fn fixed_string(value: &str, len: usize) -> Vec<u8> {
+    let mut bytes = value.as_bytes().to_vec();
+    bytes.resize(len, 0);
+    bytes
+}
+
+fn dwords(values: &[u32]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn read_packet(stream: &mut TcpStream) -> Option<Sent> {
+    let mut header = [0u8; 16];
+    stream.read_exact(&mut header).ok()?;
+    let dword = |i: usize| u32::from_le_bytes(header[i * 4..i * 4 + 4].try_into().unwrap());
+
+    let mut body = vec![0u8; dword(0) as usize - 16];
+    stream.read_exact(&mut body).ok()?;
+    Some(Sent {
+        version: dword(1),
+        packet_type: dword(2) & !0xF000_0000,
+        send_id: dword(3),
+        body,
+    })
+}
+
+/// A single client simulator speaking just enough of the protocol for the tests
+struct FakeSim {
+    address: SocketAddr,
+    received: Arc<Mutex<Vec<Sent>>>,
+    handle: JoinHandle<()>,
+}
+
+impl FakeSim {
+    fn start(refuse: bool) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let received = Arc::new(Mutex::new(Vec::new()));
+
+        let log = received.clone();
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut definitions: HashMap<u32, u32> = HashMap::new();
+
+            while let Some(sent) = read_packet(&mut stream) {
+                log.lock().unwrap().push(sent.clone());
+
+                let reply = match sent.packet_type {
+                    0x01 if refuse => Some(packet(RECV_ID_EXCEPTION, &dwords(&[31, 1, 0]))),
+                    0x01 => {
+                        let mut body = fixed_string("Fake Sim", 256);
+                        body.extend(dwords(&[11, 0, 282174, 999, 11, 0, 62651, 3, 0, 0]));
+                        Some(packet(RECV_ID_OPEN, &body))
+                    }
+                    0x0C => {
+                        *definitions.entry(sent.dword(0)).or_default() += 1;
+                        None
+                    }
+                    0x0F => {
+                        let define_id = sent.dword(4);
+                        let count = definitions.get(&define_id).copied().unwrap_or_default();
+                        let mut body = dwords(&[sent.dword(0), 0, define_id, 0, 1, 1, count]);
+                        body.extend(47.5f64.to_le_bytes());
+                        body.extend((-122.25f64).to_le_bytes());
+                        Some(packet(RECV_ID_SIMOBJECT_DATA_BYTYPE, &body))
+                    }
+                    // Subscribing immediately fires the event back
+                    0x17 => Some(packet(
+                        RECV_ID_EVENT,
+                        &dwords(&[u32::MAX, sent.dword(0), 0]),
+                    )),
+                    0x35 => {
+                        let mut body = dwords(&[sent.dword(0), 1]);
+                        body.extend(1f32.to_le_bytes());
+                        body.extend(fixed_string("", 260));
+                        Some(packet(RECV_ID_SYSTEM_STATE, &body))
+                    }
+                    _ => None,
+                };
+
+                if let Some(reply) = reply {
+                    if stream.write_all(&reply).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        Self {
+            address,
+            received,
+            handle,
+        }
+    }
+
+    fn received(&self) -> Vec<Sent> {
+        self.received.lock().unwrap().clone()
+    }
+}
+
+fn subscribe(sc: &mut SimConnect<NetworkBackend>, event: SystemEvent) -> Receiver<SystemEventData> {
+    let (sender, receiver) = channel();
+    let sender = Mutex::new(sender);
+    sc.subscribe_to_system_event(event, move |data| {
+        let _ = sender.lock().unwrap().send(data.event_data);
+    })
+    .unwrap();
+    receiver
+}
+
+#[test]
+fn open_performs_the_handshake() {
+    let sim = FakeSim::start(false);
+    let mut backend = NetworkBackend::new(sim.address).unwrap();
+    backend.open(&CString::new("Net Test").unwrap()).unwrap();
+
+    let info = backend.server_info().expect("server info after open");
+    assert_eq!(info.application_name, "Fake Sim");
+    assert_eq!(backend.last_sent_packet_id().unwrap(), 1);
+
+    let open = &sim.received()[0];
+    assert_eq!(open.packet_type, 0x01);
+    assert_eq!(open.version, 4);
+    assert_eq!(open.send_id, 1);
+    assert_eq!(open.string256(0), "Net Test");
+
+    // The open message is still delivered to the client
+    assert!(backend.get_next_dispatch().unwrap().is_some());
+    assert!(backend.get_next_dispatch().unwrap().is_none());
+
+    backend.close().unwrap();
+    sim.handle.join().unwrap();
+}
+
+#[test]
+fn refused_connections_fail_to_open() {
+    let sim = FakeSim::start(true);
+    let mut backend = NetworkBackend::new(sim.address).unwrap();
+    assert!(matches!(
+        backend.open(&CString::new("Net Test").unwrap()),
+        Err(SimConnectError::Exception {
+            exception: SimConnectException::OutOfBounds,
+            send_id: 1,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn system_events_round_trip() {
+    let sim = FakeSim::start(false);
+    let backend = NetworkBackend::new(sim.address).unwrap();
+    let mut sc =
+        SimConnect::open_with_backend(backend, "Net Test", Some(Duration::from_millis(1))).unwrap();
+
+    let paused = subscribe(&mut sc, SystemEvent::Paused);
+    assert!(matches!(
+        paused.recv_timeout(TIMEOUT),
+        Ok(SystemEventData::Paused)
+    ));
+
+    let subscribe = sim
+        .received()
+        .into_iter()
+        .find(|sent| sent.packet_type == 0x17)
+        .unwrap();
+    assert_eq!(subscribe.string256(4), "Paused");
+}
+
+#[test]
+fn send_ids_increase_with_every_packet() {
+    let sim = FakeSim::start(false);
+    let backend = NetworkBackend::new(sim.address).unwrap();
+    let mut sc =
+        SimConnect::open_with_backend(backend, "Net Test", Some(Duration::from_millis(1))).unwrap();
+
+    sc.unsubscribe_from_system_event(SystemEvent::Crashed)
+        .unwrap();
+    sc.unsubscribe_from_system_event(SystemEvent::Paused)
+        .unwrap();
+    drop(sc);
+    sim.handle.join().unwrap();
+
+    let send_ids: Vec<u32> = sim
+        .received
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|s| s.send_id)
+        .collect();
+    assert_eq!(send_ids, vec![1, 2, 3]);
+}
+
+#[test]
+fn client_events_are_sent_over_the_network() {
+    use sim_connect_rs::sim_input_events::GearAndBrakes;
+
+    let sim = FakeSim::start(false);
+    let backend = NetworkBackend::new(sim.address).unwrap();
+    let mut sc =
+        SimConnect::open_with_backend(backend, "Net Test", Some(Duration::from_millis(1))).unwrap();
+
+    sc.send(GearAndBrakes::GearDown).unwrap();
+    sc.transmit_client_event(GearAndBrakes::GearDown, 1)
+        .unwrap();
+    drop(sc);
+    sim.handle.join().unwrap();
+
+    let received = sim.received.lock().unwrap().clone();
+    let mapped: Vec<&Sent> = received.iter().filter(|s| s.packet_type == 0x04).collect();
+    assert_eq!(mapped.len(), 1);
+    assert_eq!(mapped[0].string256(4), "GEAR_DOWN");
+
+    let event_id = mapped[0].dword(0);
+    let transmitted: Vec<Vec<u32>> = received
+        .iter()
+        .filter(|s| s.packet_type == 0x05)
+        .map(|s| (0..5).map(|i| s.dword(i * 4)).collect())
+        .collect();
+    assert_eq!(
+        transmitted,
+        vec![vec![0, event_id, 0, 1, 16], vec![0, event_id, 1, 1, 16]]
+    );
+}
+
+#[cfg(not(feature = "async"))]
+#[test]
+fn requests_are_answered_over_the_network() {
+    use sim_connect_rs::{
+        sim_data::DataWriter, sim_event_args::SimStateArgs, sim_units::GPS,
+        sim_var_types::SimVarType, sim_vars::SimVar, SimConnectDatum, SimConnectResult,
+        SimConnectToStruct, StructToSimConnect,
+    };
+    use std::ptr::NonNull;
+
+    #[derive(Clone, Debug, PartialEq)]
+    #[repr(C)]
+    struct Position {
+        latitude: f64,
+        longitude: f64,
+    }
+
+    impl StructToSimConnect for Position {
+        fn get_fields() -> Vec<SimConnectDatum> {
+            vec![
+                SimConnectDatum {
+                    id: 0,
+                    sim_var: SimVar::PlaneLat,
+                    sim_unit: Some(Box::new(GPS::DegLat)),
+                    data_type: SimVarType::F64,
+                },
+                SimConnectDatum {
+                    id: 1,
+                    sim_var: SimVar::PlaneLong,
+                    sim_unit: Some(Box::new(GPS::DegLon)),
+                    data_type: SimVarType::F64,
+                },
+            ]
+        }
+
+        fn write_data(&self, writer: &mut DataWriter) -> SimConnectResult<()> {
+            writer.write(&self.latitude)?;
+            writer.write(&self.longitude)
+        }
+    }
+
+    impl SimConnectToStruct for Position {
+        type Error = ();
+        type ReturnType = Position;
+
+        unsafe fn parse_struct(pointer: NonNull<u32>) -> Result<Self::ReturnType, Self::Error> {
+            let pointer = pointer.as_ptr() as *const f64;
+            Ok(Self {
+                latitude: pointer.read_unaligned(),
+                longitude: pointer.add(1).read_unaligned(),
+            })
+        }
+    }
+
+    let sim = FakeSim::start(false);
+    let backend = NetworkBackend::new(sim.address).unwrap();
+    let mut sc =
+        SimConnect::open_with_backend(backend, "Net Test", Some(Duration::from_millis(1))).unwrap();
+
+    assert_eq!(
+        sc.get_latest_data::<Position>().unwrap(),
+        Position {
+            latitude: 47.5,
+            longitude: -122.25
+        }
+    );
+
+    let definitions: Vec<String> = sim
+        .received()
+        .into_iter()
+        .filter(|sent| sent.packet_type == 0x0C)
+        .map(|sent| sent.string256(4))
+        .collect();
+    assert_eq!(definitions, vec!["Plane Latitude", "Plane Longitude"]);
+
+    let state = sc.request_system_state(SimStateArgs::Sim).unwrap();
+    assert_eq!(state.sim, Some(true));
+}
+
+#[test]
+fn config_files_are_read() {
+    let sim = FakeSim::start(false);
+    let path = std::env::temp_dir().join(format!("SimConnect-{}.cfg", sim.address.port()));
+    std::fs::write(
+        &path,
+        format!(
+            "[SimConnect]\nProtocol=Pipe\nAddress=.\nPort=Custom/SimConnect\n\n\
+             [SimConnect.1]\nProtocol=IPv4\nAddress={}\nPort={}\n",
+            sim.address.ip(),
+            sim.address.port()
+        ),
+    )
+    .unwrap();
+
+    assert!(NetworkBackend::from_config(&path, 0).is_err());
+    assert!(NetworkBackend::from_config(&path, 2).is_err());
+
+    let mut backend = NetworkBackend::from_config(&path, 1).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    backend.open(&CString::new("Net Test").unwrap()).unwrap();
+    assert!(backend.server_info().is_some());
+}